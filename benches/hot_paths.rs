@@ -0,0 +1,121 @@
+//! # Hot path benchmarks
+//! Criterion baselines for the encode, decode, URI validation and subscription matching paths,
+//! only going through the public API.
+//!
+//! ## Running
+//! ```text
+//! cargo bench --bench hot_paths
+//! cargo bench --bench hot_paths -- decode_small_call
+//! ```
+//!
+//! To compare a change against the current tree, save a baseline first and compare to it
+//! afterwards:
+//!
+//! ```text
+//! cargo bench --bench hot_paths -- --save-baseline before
+//! cargo bench --bench hot_paths -- --baseline before
+//! ```
+//!
+//! ## Benchmarks
+//! | Name | Measures |
+//! |------|----------|
+//! | `decode_small_call` | [`Messages::decode`] of a CALL with a short payload |
+//! | `decode_large_event` | [`Messages::decode`] of an EVENT carrying 1 MB of kwargs |
+//! | `encode_publish_reused_buffer` | [`Messages::serialize_into`] of a PUBLISH into one buffer |
+//! | `uri_validate_strict` | [`UriMode::is_valid`] of a URI in strict mode |
+//! | `trie_match_10k_patterns` | [`SubscriptionTrie::matches`] against 10k subscriptions |
+//!
+//! Keep the names stable, saved baselines are looked up by them.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, to_vec};
+use wamp_core::{
+    messages::{Messages, Publish},
+    pubsub::SubscriptionTrie,
+    transport::Encoding,
+    uri::{uri_rules::WampRules, MatchPolicy, UriMode},
+};
+
+const SMALL_CALL: &[u8] =
+    br#"[48,7814135,{},"com.myapp.user.new",["johnny"],{"firstname":"John","surname":"Doe"}]"#;
+
+const PATTERNS: u64 = 10_000;
+
+fn decode_small_call(c: &mut Criterion) {
+    c.bench_function("decode_small_call", |b| {
+        b.iter(|| Messages::decode(black_box(SMALL_CALL), Encoding::Json).unwrap())
+    });
+}
+
+fn decode_large_event(c: &mut Criterion) {
+    let frame = to_vec(&json!([
+        36,
+        5512315355u64,
+        4429313566u64,
+        {},
+        [],
+        { "blob": "x".repeat(1 << 20) }
+    ]))
+    .unwrap();
+
+    let mut group = c.benchmark_group("large frames");
+    group.sample_size(20);
+    group.bench_function("decode_large_event", |b| {
+        b.iter(|| Messages::decode(black_box(&frame), Encoding::Json).unwrap())
+    });
+    group.finish();
+}
+
+fn encode_publish_reused_buffer(c: &mut Criterion) {
+    let publish = Messages::from(Publish {
+        request_id: 239714735,
+        options: json!({ "acknowledge": true }),
+        topic: "com.myapp.mytopic1".into(),
+        args: json!(["Hello, world!"]),
+        kwargs: json!({ "color": "orange", "sizes": [23, 42, 7] }),
+    });
+    let mut buf = String::new();
+
+    c.bench_function("encode_publish_reused_buffer", |b| {
+        b.iter(|| {
+            black_box(&publish).serialize_into(&mut buf).unwrap();
+            black_box(buf.len())
+        })
+    });
+}
+
+fn uri_validate_strict(c: &mut Criterion) {
+    c.bench_function("uri_validate_strict", |b| {
+        b.iter(|| UriMode::Strict.is_valid(WampRules::URI, black_box("com.myapp.user.new")))
+    });
+}
+
+fn trie_match_10k_patterns(c: &mut Criterion) {
+    let mut trie = SubscriptionTrie::new();
+    for id in 1..=PATTERNS {
+        let (pattern, policy) = match id % 3 {
+            0 => (format!("com.myapp.topic{id}"), MatchPolicy::Exact),
+            1 => (format!("com.myapp.topic{id}"), MatchPolicy::Prefix),
+            _ => (
+                format!("com.myapp.topic{id}..update"),
+                MatchPolicy::Wildcard,
+            ),
+        };
+        trie.insert(&pattern, policy, id);
+    }
+    let topic = "com.myapp.topic5000.user.update";
+    assert!(!trie.matches(topic).is_empty());
+
+    c.bench_function("trie_match_10k_patterns", |b| {
+        b.iter(|| black_box(trie.matches(black_box(topic))))
+    });
+}
+
+criterion_group!(
+    benches,
+    decode_small_call,
+    decode_large_event,
+    encode_publish_reused_buffer,
+    uri_validate_strict,
+    trie_match_10k_patterns,
+);
+criterion_main!(benches);
@@ -0,0 +1,129 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+use wamp_core::{
+    messages::{Event, Publish, SharedEvent},
+    pubsub::{plan_shared_fanout, MatchedSubscription, ReceiverIdentity},
+    uri::MatchPolicy,
+};
+
+const CLONES: usize = 10_000;
+const PAYLOAD: usize = 1 << 20;
+const SUBSCRIBERS: u64 = 1_000;
+
+/// Counts the bytes allocated by the current thread, so a fan-out that copies the payload per
+/// subscriber shows up next to its time.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|bytes| bytes.set(bytes.get() + layout.size()));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of bytes `f` allocates on the current thread.
+fn allocated<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.with(Cell::get);
+    black_box(f());
+    ALLOCATED.with(Cell::get) - before
+}
+
+fn event() -> Event {
+    Event {
+        subscription: 1,
+        publication: 2,
+        details: json!({}),
+        args: json!(["x".repeat(PAYLOAD)]),
+        kwargs: Value::Null,
+    }
+}
+
+fn clone_event(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone 1 MB event 10k times");
+    group.sample_size(10);
+
+    let owned = event();
+    group.bench_function("Event", |b| {
+        b.iter(|| {
+            for _ in 0..CLONES {
+                black_box(owned.clone());
+            }
+        })
+    });
+
+    let shared = SharedEvent::from(event());
+    group.bench_function("SharedEvent", |b| {
+        b.iter(|| {
+            for _ in 0..CLONES {
+                black_box(shared.clone());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn publish() -> Publish {
+    Publish {
+        request_id: 1,
+        options: json!({}),
+        topic: "com.myapp.topic1".into(),
+        args: json!(["x".repeat(PAYLOAD)]),
+        kwargs: Value::Null,
+    }
+}
+
+fn matches(subscribers: u64) -> [MatchedSubscription; 1] {
+    [MatchedSubscription {
+        subscription: 1,
+        policy: MatchPolicy::Exact,
+        subscribers: (1..=subscribers).collect(),
+    }]
+}
+
+fn identity(session: u64) -> Option<ReceiverIdentity> {
+    Some(ReceiverIdentity {
+        session,
+        ..Default::default()
+    })
+}
+
+fn fanout(c: &mut Criterion) {
+    let publish = publish();
+    let (one, many) = (matches(1), matches(SUBSCRIBERS));
+
+    let single = allocated(|| plan_shared_fanout(&publish, 2, &one, &identity));
+    let fanned = allocated(|| plan_shared_fanout(&publish, 2, &many, &identity));
+    assert!(
+        fanned - single < PAYLOAD,
+        "fan-out to {SUBSCRIBERS} subscribers copied the payload ({fanned} bytes against {single})"
+    );
+
+    let mut group = c.benchmark_group("fan out 1 MB event");
+    group.sample_size(10);
+
+    group.bench_function(format!("{SUBSCRIBERS} subscribers"), |b| {
+        b.iter(|| black_box(plan_shared_fanout(&publish, 2, &many, &identity)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, clone_event, fanout);
+criterion_main!(benches);
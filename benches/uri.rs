@@ -0,0 +1,72 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    borrow::Cow,
+    cell::Cell,
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_json::{json, Value};
+use wamp_core::{call, messages::Call};
+
+const PROCEDURE: &str = "com.myapp.user.new";
+
+/// Counts the allocations of the current thread, so the saving of a borrowed procedure is
+/// measured next to its time.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of allocations `f` makes on the current thread.
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    black_box(f());
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn call(procedure: Cow<'static, str>) -> Call {
+    Call {
+        request_id: 1,
+        options: json!({}),
+        procedure,
+        args: Value::Null,
+        kwargs: Value::Null,
+    }
+}
+
+fn build_call(c: &mut Criterion) {
+    let borrowed = allocations(|| call!(black_box(PROCEDURE)));
+    let owned = allocations(|| call!(black_box(PROCEDURE).to_string()));
+    assert_eq!(borrowed, 0, "call! with a 'static procedure allocated");
+    assert_eq!(owned, 1);
+
+    let mut group = c.benchmark_group("build CALL");
+
+    group.bench_function("borrowed procedure", |b| {
+        b.iter(|| black_box(call(black_box(PROCEDURE).into())))
+    });
+
+    group.bench_function("owned procedure", |b| {
+        b.iter(|| black_box(call(black_box(PROCEDURE).to_string().into())))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, build_call);
+criterion_main!(benches);
@@ -0,0 +1,310 @@
+//! # Authentication - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-authentication)
+//! Helpers for announcing authentication methods in HELLO and inspecting the outcome in WELCOME.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    error::Error,
+    messages::{Hello, Welcome},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # Auth Method
+/// An authentication method, as announced in `HELLO.Details.authmethods` and reported in
+/// `WELCOME.Details.authmethod`.
+/// ## Examples
+/// ```
+/// use wamp_core::auth::AuthMethod;
+///
+/// assert_eq!(AuthMethod::from("wampcra"), AuthMethod::WampCra);
+/// assert_eq!(AuthMethod::Cookie.as_str(), "cookie");
+/// assert!(AuthMethod::Tls.is_transport_level());
+/// assert!(AuthMethod::Ticket.is_challenge_based());
+/// ```
+pub enum AuthMethod {
+    /// No authentication, the router assigns an anonymous identity.
+    Anonymous,
+    /// A previously issued cookie sent with the WebSocket opening handshake.
+    Cookie,
+    /// A TLS client certificate.
+    Tls,
+    /// A static or dynamic ticket answered to a CHALLENGE.
+    Ticket,
+    /// WAMP challenge response authentication.
+    WampCra,
+    /// Salted challenge response authentication.
+    Scram,
+    /// Ed25519 public key authentication.
+    Cryptosign,
+    /// A method unknown to this crate.
+    Other(String),
+}
+
+impl AuthMethod {
+    /// The method as spelled in HELLO and WELCOME details.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AuthMethod::Anonymous => "anonymous",
+            AuthMethod::Cookie => "cookie",
+            AuthMethod::Tls => "tls",
+            AuthMethod::Ticket => "ticket",
+            AuthMethod::WampCra => "wampcra",
+            AuthMethod::Scram => "scram",
+            AuthMethod::Cryptosign => "cryptosign",
+            AuthMethod::Other(method) => method,
+        }
+    }
+
+    /// Whether the method authenticates using the transport, without a CHALLENGE.
+    pub fn is_transport_level(&self) -> bool {
+        matches!(self, AuthMethod::Cookie | AuthMethod::Tls)
+    }
+
+    /// Whether the router authenticates the method with a CHALLENGE / AUTHENTICATE exchange.
+    pub fn is_challenge_based(&self) -> bool {
+        matches!(
+            self,
+            AuthMethod::Ticket | AuthMethod::WampCra | AuthMethod::Scram | AuthMethod::Cryptosign
+        )
+    }
+}
+
+impl From<&str> for AuthMethod {
+    fn from(value: &str) -> Self {
+        match value {
+            "anonymous" => AuthMethod::Anonymous,
+            "cookie" => AuthMethod::Cookie,
+            "tls" => AuthMethod::Tls,
+            "ticket" => AuthMethod::Ticket,
+            "wampcra" => AuthMethod::WampCra,
+            "scram" => AuthMethod::Scram,
+            "cryptosign" => AuthMethod::Cryptosign,
+            other => AuthMethod::Other(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for AuthMethod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for AuthMethod {
+    fn schema_name() -> String {
+        "AuthMethod".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
+impl<'de> Deserialize<'de> for AuthMethod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(AuthMethod::from(
+            String::deserialize(deserializer)?.as_str(),
+        ))
+    }
+}
+
+/// # Hello with methods
+/// Builds a HELLO announcing every client role and the authentication `methods`, in order of
+/// preference, with an optional `authid`.
+///
+/// Announce [`AuthMethod::Cookie`] first to re-authenticate with a cookie set by an earlier
+/// session, and a challenge based method after it to fall back on when the cookie is unknown.
+/// ## Examples
+/// ```
+/// use wamp_core::auth::{hello_with_methods, AuthMethod};
+/// use serde_json::json;
+///
+/// let hello = hello_with_methods("realm1", &[AuthMethod::Ticket], Some("joe"));
+///
+/// assert_eq!(hello.details["authmethods"], json!(["ticket"]));
+/// assert_eq!(hello.details["authid"], json!("joe"));
+/// ```
+pub fn hello_with_methods(realm: &str, methods: &[AuthMethod], authid: Option<&str>) -> Hello {
+    let mut details = Map::new();
+    details.insert(
+        "roles".to_string(),
+        json!({ "caller": {}, "callee": {}, "publisher": {}, "subscriber": {} }),
+    );
+    details.insert("authmethods".to_string(), json!(methods));
+    if let Some(authid) = authid {
+        details.insert("authid".to_string(), json!(authid));
+    }
+    Hello {
+        realm: realm.to_string().into(),
+        details: Value::Object(details),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Auth Kind
+/// How the session of a WELCOME was authenticated.
+pub enum AuthKind {
+    /// The session was not authenticated.
+    Anonymous,
+    /// The session was authenticated by the transport, such as a cookie or TLS certificate.
+    Transport,
+    /// The session was authenticated with a CHALLENGE / AUTHENTICATE exchange.
+    Challenge,
+    /// The method is unknown to this crate.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Auth Summary
+/// The authentication outcome reported by a WELCOME, see [`WelcomeDetails::auth_summary`].
+pub struct AuthSummary {
+    /// The method the session was authenticated with.
+    pub method: AuthMethod,
+    /// Whether the method is transport level or challenge based.
+    pub kind: AuthKind,
+    /// The authenticated identity.
+    pub authid: Option<String>,
+    /// The role assigned to the session.
+    pub authrole: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Welcome Details
+/// The roles, authentication and agent keys of `WELCOME.Details`.
+/// ## Examples
+/// ```
+/// use wamp_core::auth::{AuthKind, AuthMethod, WelcomeDetails};
+/// use wamp_core::messages::Welcome;
+/// use serde_json::from_str;
+///
+/// let welcome: Welcome = from_str(r#"[2,1,{"authid":"joe","authrole":"user","authmethod":"cookie","authprovider":"cookie","roles":{}}]"#).unwrap();
+///
+/// let summary = WelcomeDetails::from_welcome(&welcome).unwrap().auth_summary().unwrap();
+/// assert_eq!(summary.method, AuthMethod::Cookie);
+/// assert_eq!(summary.kind, AuthKind::Transport);
+/// assert_eq!(summary.authid.as_deref(), Some("joe"));
+/// ```
+pub struct WelcomeDetails {
+    /// The router roles and the features they announce, keyed by role name.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub roles: Map<String, Value>,
+    /// The authenticated identity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authid: Option<String>,
+    /// The role assigned to the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authrole: Option<String>,
+    /// The method the session was authenticated with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authmethod: Option<AuthMethod>,
+    /// The component that authenticated the session, for example `static` or `dynamic`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authprovider: Option<String>,
+    /// Additional authentication information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authextra: Option<Value>,
+    /// The router implementation, for example `"crossbar-22.1.1"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+}
+
+impl WelcomeDetails {
+    /// Parse the roles, authentication and agent keys of a WELCOME, ignoring every other key.
+    pub fn from_welcome(welcome: &Welcome) -> Result<Self, Error> {
+        match &welcome.details {
+            Value::Null => Ok(Self::default()),
+            details => Ok(Self::deserialize(details)?),
+        }
+    }
+
+    /// Sets the agent string the router announces, see
+    /// [`default_agent`](crate::messages::default_agent).
+    pub fn with_agent<T: Into<String>>(mut self, agent: T) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+
+    /// The method the router authenticated the session with, `None` when it is not reported.
+    pub fn auth_summary(&self) -> Option<AuthSummary> {
+        let method = self.authmethod.clone()?;
+        let kind = if method == AuthMethod::Anonymous {
+            AuthKind::Anonymous
+        } else if method.is_transport_level() {
+            AuthKind::Transport
+        } else if method.is_challenge_based() {
+            AuthKind::Challenge
+        } else {
+            AuthKind::Unknown
+        };
+        Some(AuthSummary {
+            method,
+            kind,
+            authid: self.authid.clone(),
+            authrole: self.authrole.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json, to_string};
+
+    use super::*;
+
+    #[test]
+    fn cookie_reauthentication() {
+        let hello = hello_with_methods("realm1", &[AuthMethod::Cookie, AuthMethod::Ticket], None);
+        assert_eq!(
+            to_string(&hello).unwrap(),
+            r#"[1,"realm1",{"authmethods":["cookie","ticket"],"roles":{"callee":{},"caller":{},"publisher":{},"subscriber":{}}}]"#
+        );
+
+        let welcome: Welcome = from_str(r#"[2,3251278072152162,{"authid":"FGRP-PXUC-3WFG-6PQT-3KVQ-HQPF","authrole":"anonymous","authmethod":"cookie","authprovider":"cookie","authextra":{},"roles":{"broker":{},"dealer":{}}}]"#).unwrap();
+        let summary = WelcomeDetails::from_welcome(&welcome)
+            .unwrap()
+            .auth_summary()
+            .unwrap();
+        assert_eq!(summary.kind, AuthKind::Transport);
+        assert_eq!(summary.authrole.as_deref(), Some("anonymous"));
+    }
+
+    #[test]
+    fn summaries() {
+        for (method, kind) in [
+            ("anonymous", AuthKind::Anonymous),
+            ("tls", AuthKind::Transport),
+            ("wampcra", AuthKind::Challenge),
+            ("x_custom", AuthKind::Unknown),
+        ] {
+            let welcome = Welcome {
+                session: 1,
+                details: json!({ "authmethod": method }),
+            };
+            let details = WelcomeDetails::from_welcome(&welcome).unwrap();
+            assert_eq!(details.auth_summary().unwrap().kind, kind);
+        }
+        let welcome = Welcome {
+            session: 1,
+            details: Value::Null,
+        };
+        let details = WelcomeDetails::from_welcome(&welcome).unwrap();
+        assert!(details.auth_summary().is_none());
+    }
+
+    #[test]
+    fn agent_round_trip() {
+        let details = WelcomeDetails {
+            authrole: Some("user".to_string()),
+            ..Default::default()
+        }
+        .with_agent("crossbar-22.1.1");
+        let welcome = Welcome {
+            session: 1,
+            details: serde_json::to_value(&details).unwrap(),
+        };
+        assert_eq!(welcome.router_agent(), Some("crossbar-22.1.1"));
+        assert_eq!(WelcomeDetails::from_welcome(&welcome).unwrap(), details);
+    }
+}
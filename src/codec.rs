@@ -0,0 +1,391 @@
+//! # Line delimited codec
+//! Reads and writes streams of WAMP frames stored one JSON message per line, such as traffic
+//! captures. Frames may also be prefixed with an ASCII record separator (`0x1E`), as in
+//! [RFC 7464](https://www.rfc-editor.org/rfc/rfc7464) JSON text sequences.
+use std::io::{BufRead, Write};
+
+use serde::Serialize;
+
+use crate::{error::Error, messages::Messages};
+
+const RECORD_SEPARATOR: u8 = 0x1E;
+
+/// # JSON Lines Decoder
+/// Iterates over the WAMP messages of a line delimited stream without loading it in memory.
+///
+/// Each line yields one item. A line that fails to parse yields an [`Error::AtLine`] carrying
+/// its line number, and decoding continues with the next line. Blank lines are skipped.
+/// Decoding stops after the first read error of the underlying stream.
+///
+/// With the `tracing` feature, every decoded line is reported, see [`JsonLinesDecoder::instrument`]
+/// to group them under a span.
+/// ## Examples
+/// ```
+/// use wamp_core::codec::JsonLinesDecoder;
+/// use wamp_core::messages::Messages;
+///
+/// let capture = "[33,3,5512315355]\nnot json\n[6,{},\"wamp.close.normal\"]\n";
+/// let mut decoder = JsonLinesDecoder::new(capture.as_bytes());
+///
+/// assert!(matches!(decoder.next(), Some(Ok(Messages::Subscribed(_)))));
+/// assert!(decoder.next().unwrap().is_err());
+/// assert!(matches!(decoder.next(), Some(Ok(Messages::Goodbye(_)))));
+/// assert!(decoder.next().is_none());
+/// assert_eq!(decoder.line(), 3);
+/// ```
+pub struct JsonLinesDecoder<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    line: usize,
+    done: bool,
+    #[cfg(feature = "tracing")]
+    span: Option<tracing::Span>,
+}
+
+impl<R: BufRead> JsonLinesDecoder<R> {
+    /// Create a decoder reading from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            line: 0,
+            done: false,
+            #[cfg(feature = "tracing")]
+            span: None,
+        }
+    }
+
+    /// Enter `span` while decoding, so the events of a batch are grouped under it.
+    #[cfg(feature = "tracing")]
+    pub fn instrument(mut self, span: tracing::Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Number of lines read so far.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Consume the decoder, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLinesDecoder<R> {
+    type Item = Result<Messages, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.as_ref().map(tracing::Span::enter);
+        while !self.done {
+            self.buffer.clear();
+            match self.reader.read_until(b'\n', &mut self.buffer) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.line += 1;
+                    let frame = self.buffer.trim_ascii();
+                    let frame = frame.strip_prefix(&[RECORD_SEPARATOR]).unwrap_or(frame);
+                    if frame.is_empty() {
+                        continue;
+                    }
+                    let message = serde_json::from_slice(frame);
+                    crate::trace::decoded(&message, frame.len());
+                    return Some(message.map_err(|error| Error::AtLine {
+                        line: self.line,
+                        error: Box::new(error.into()),
+                    }));
+                }
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(Error::AtLine {
+                        line: self.line + 1,
+                        error: Box::new(error.into()),
+                    }));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// # JSON Lines Encoder
+/// Writes WAMP messages one per line, each followed by a newline.
+/// ## Examples
+/// ```
+/// use wamp_core::codec::JsonLinesEncoder;
+/// use wamp_core::messages::{Messages, Subscribed};
+///
+/// let mut encoder = JsonLinesEncoder::new(Vec::new());
+/// encoder.encode(&Subscribed { request_id: 3, subscription: 5512315355 }).unwrap();
+/// encoder.encode(&Messages::from(Subscribed { request_id: 4, subscription: 1 })).unwrap();
+///
+/// assert_eq!(encoder.into_inner(), b"[33,3,5512315355]\n[33,4,1]\n");
+/// ```
+pub struct JsonLinesEncoder<W> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesEncoder<W> {
+    /// Create an encoder writing to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write a single message followed by a newline.
+    pub fn encode<T: Serialize>(&mut self, message: &T) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, message)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Consume the encoder, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::tokio::WampCodec;
+
+#[cfg(feature = "tokio")]
+mod tokio {
+    use bytes::{Buf, BytesMut};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    use crate::{
+        error::Error,
+        messages::Messages,
+        rawsocket::{self, FrameType, MAX_FRAME_LEN},
+        transport::Encoding,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    /// # WAMP Codec
+    /// A `tokio_util` codec reading and writing WAMP messages as raw socket frames, to use with
+    /// `Framed` once the raw socket handshake is done. Requires the `tokio` feature.
+    ///
+    /// Frames are buffered until complete. Ping and pong frames carry no WAMP message and are
+    /// skipped by the decoder.
+    ///
+    /// The encoder rejects encoded messages longer than `max_len`, or than [`MAX_FRAME_LEN`]
+    /// whatever `max_len` is, since longer payloads do not fit the length of a frame.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::codec::WampCodec;
+    /// use wamp_core::messages::{Messages, Subscribed};
+    /// use wamp_core::transport::Encoding;
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::{Decoder, Encoder};
+    ///
+    /// let mut codec = WampCodec::new(Encoding::Json);
+    /// let mut buffer = BytesMut::new();
+    /// let message = Messages::from(Subscribed { request_id: 3, subscription: 5512315355 });
+    ///
+    /// codec.encode(message.clone(), &mut buffer).unwrap();
+    /// assert_eq!(codec.decode(&mut buffer).unwrap(), Some(message));
+    /// ```
+    pub struct WampCodec {
+        /// The encoding negotiated in the handshake.
+        pub encoding: Encoding,
+        /// The maximum message length announced in the handshake, longer frames are rejected.
+        pub max_len: u32,
+    }
+
+    impl WampCodec {
+        /// A codec for `encoding`, accepting frames up to [`MAX_FRAME_LEN`].
+        pub fn new(encoding: Encoding) -> Self {
+            Self {
+                encoding,
+                max_len: MAX_FRAME_LEN as u32,
+            }
+        }
+    }
+
+    impl Decoder for WampCodec {
+        type Item = Messages;
+        type Error = Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Messages>, Error> {
+            loop {
+                let Some((frame_type, payload, consumed)) =
+                    rawsocket::decode_frame(src, self.max_len)?
+                else {
+                    return Ok(None);
+                };
+                let message = (frame_type == FrameType::Regular)
+                    .then(|| Messages::decode(payload, self.encoding));
+                src.advance(consumed);
+                if let Some(message) = message {
+                    return message.map(Some);
+                }
+            }
+        }
+    }
+
+    impl Encoder<Messages> for WampCodec {
+        type Error = Error;
+
+        fn encode(&mut self, item: Messages, dst: &mut BytesMut) -> Result<(), Error> {
+            let payload = item.encode(self.encoding)?;
+            let max = (self.max_len as usize).min(MAX_FRAME_LEN);
+            if payload.len() > max {
+                return Err(Error::PayloadSizeExceeded {
+                    size: payload.len(),
+                    max,
+                });
+            }
+            dst.extend_from_slice(&rawsocket::encode_frame(&payload)?);
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bytes::BufMut;
+
+        use super::*;
+        use crate::rawsocket::encode_frame_with;
+
+        #[test]
+        fn partial_frames() {
+            let mut codec = WampCodec::new(Encoding::Json);
+            let mut encoded = BytesMut::new();
+            let goodbye: Messages = serde_json::from_str(r#"[6,{},"wamp.close.normal"]"#).unwrap();
+            codec.encode(goodbye.clone(), &mut encoded).unwrap();
+            let mut stream = encode_frame_with(FrameType::Ping, b"ping").unwrap();
+            stream.extend_from_slice(&encoded);
+            stream.extend_from_slice(&encoded);
+
+            let mut buffer = BytesMut::new();
+            let mut decoded = Vec::new();
+            for byte in stream {
+                buffer.put_u8(byte);
+                if let Some(message) = codec.decode(&mut buffer).unwrap() {
+                    decoded.push(message);
+                }
+            }
+            assert_eq!(decoded, vec![goodbye.clone(), goodbye]);
+            assert!(buffer.is_empty());
+        }
+
+        #[test]
+        fn over_length() {
+            let mut codec = WampCodec {
+                encoding: Encoding::Json,
+                max_len: 512,
+            };
+            let mut buffer = BytesMut::from(&[0u8, 0x00, 0x02, 0x01][..]);
+            assert!(matches!(
+                codec.decode(&mut buffer),
+                Err(Error::PayloadSizeExceeded {
+                    size: 513,
+                    max: 512
+                })
+            ));
+        }
+
+        #[test]
+        fn over_length_encode() {
+            let mut codec = WampCodec {
+                encoding: Encoding::Json,
+                max_len: 512,
+            };
+            let frame = format!(r#"[16,1,{{}},"com.myapp.topic1",["{}"]]"#, "a".repeat(500));
+            let publish: Messages = serde_json::from_str(&frame).unwrap();
+            assert_eq!(publish.serialized_size().unwrap(), frame.len());
+
+            let mut buffer = BytesMut::new();
+            assert!(matches!(
+                codec.encode(publish, &mut buffer),
+                Err(Error::PayloadSizeExceeded { size, max: 512 }) if size == frame.len()
+            ));
+            assert!(buffer.is_empty());
+        }
+
+        #[test]
+        fn frame_length_boundary() {
+            let publish = |len: usize| -> Messages {
+                let empty = r#"[16,1,{},"com.myapp.topic1",[""]]"#;
+                let padding = "a".repeat(len - empty.len());
+                serde_json::from_str(&empty.replace(r#""""#, &format!(r#""{padding}""#))).unwrap()
+            };
+            let mut codec = WampCodec {
+                encoding: Encoding::Json,
+                max_len: rawsocket::MAX_MAX_LEN,
+            };
+
+            let largest = publish(MAX_FRAME_LEN);
+            let mut buffer = BytesMut::new();
+            codec.encode(largest.clone(), &mut buffer).unwrap();
+            assert_eq!(buffer[..4], [0, 0xFF, 0xFF, 0xFF]);
+            assert_eq!(codec.decode(&mut buffer).unwrap(), Some(largest));
+
+            let mut buffer = BytesMut::new();
+            assert!(matches!(
+                codec.encode(publish(MAX_FRAME_LEN + 1), &mut buffer),
+                Err(Error::PayloadSizeExceeded { size, max: MAX_FRAME_LEN })
+                    if size == MAX_FRAME_LEN + 1
+            ));
+            assert!(buffer.is_empty());
+            assert_eq!(
+                WampCodec::new(Encoding::Json).max_len as usize,
+                MAX_FRAME_LEN
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAPTURE: &[u8] = include_bytes!("../tests/fixtures/capture.jsonl");
+
+    #[test]
+    fn mixed_capture() {
+        let items: Vec<_> = JsonLinesDecoder::new(CAPTURE).collect();
+        assert_eq!(items.len(), 10);
+        let failed: Vec<usize> = items
+            .iter()
+            .filter_map(|item| match item {
+                Err(Error::AtLine { line, .. }) => Some(*line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failed, vec![5, 6]);
+        assert!(matches!(items[0], Ok(Messages::Hello(_))));
+        assert!(matches!(items[9], Ok(Messages::Goodbye(_))));
+    }
+
+    #[test]
+    fn round_trip() {
+        let messages: Vec<Messages> = JsonLinesDecoder::new(CAPTURE)
+            .filter_map(Result::ok)
+            .collect();
+        let mut encoder = JsonLinesEncoder::new(Vec::new());
+        for message in &messages {
+            encoder.encode(message).unwrap();
+        }
+        let encoded = encoder.into_inner();
+        let decoded: Vec<Messages> = JsonLinesDecoder::new(&encoded[..])
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, messages);
+    }
+
+    #[test]
+    fn record_separator() {
+        let data = b"\x1e[33,3,5512315355]\n\x1e[33,4,1]";
+        let decoded: Vec<Messages> = JsonLinesDecoder::new(&data[..])
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded.len(), 2);
+    }
+}
@@ -3,22 +3,95 @@ use crate::messages::{
     Messages, Publish, Published, Register, Registered, Subscribe, Subscribed, Unregister,
     Unregistered, Unsubscribe, Unsubscribed, WampError, WampResult, Welcome, Yield,
 };
-use tungstenite::http::header::{InvalidHeaderValue, ToStrError};
+use crate::transport::{Encoding, FrameKind};
+use crate::uri::constants;
+use tungstenite::http::{
+    header::{InvalidHeaderValue, ToStrError},
+    StatusCode,
+};
+
+/// The non standard status nginx uses for requests canceled by the client.
+const CLIENT_CLOSED_REQUEST: u16 = 499;
+
+/// The WebSocket close code of a normal closure.
+const CLOSE_NORMAL: u16 = 1000;
+
+/// The WebSocket close code of an endpoint going away.
+const CLOSE_AWAY: u16 = 1001;
+
+/// Prefix of the URIs [`WampErrorUri::from_http_status`] creates for unmapped statuses.
+const HTTP_STATUS_PREFIX: &str = "http.status.";
 
 #[derive(Debug)]
 pub enum Error {
-    InvalidURI,
+    /// A URI does not follow a WAMP URI rule.
+    InvalidURI {
+        /// The offending URI.
+        uri: String,
+        /// Name of the rule the URI was checked against.
+        rule: &'static str,
+    },
     ToStrError(ToStrError),
     InvalidHeaderValue(InvalidHeaderValue),
-    TungsteniteError(tungstenite::Error),
+    TungsteniteError(Box<tungstenite::Error>),
     SerdeJsonError(serde_json::Error),
     InvalidMessageEnumMember,
     Error(&'static str),
-    InvalidFrameReceived(Messages),
-    Close,
+    InvalidFrameReceived(Box<Messages>),
+    /// A received message was a protocol violation, holds the ABORT queued in answer.
     Abort(Abort),
-    NoSuchWampErrorType(Messages),
+    NoSuchWampErrorType(Box<Messages>),
     NoSuchMessage,
+    /// The serialized message is larger than the allowed maximum.
+    PayloadSizeExceeded {
+        /// Size of the serialized message in bytes.
+        size: usize,
+        /// Maximum allowed size in bytes.
+        max: usize,
+    },
+    /// The message carries more positional arguments than allowed.
+    InvalidArgument {
+        /// Number of positional arguments in the message.
+        len: usize,
+        /// Maximum allowed number of positional arguments.
+        max: usize,
+    },
+    /// A WebSocket ping, pong or raw frame was received where a WAMP message was expected.
+    ControlFrame(FrameKind),
+    /// The peer sent a WebSocket close frame.
+    ConnectionClosed {
+        /// The close code, `None` when the frame carried none.
+        code: Option<u16>,
+        /// The close reason, empty when the frame carried none.
+        reason: String,
+    },
+    /// The message is serialized with an encoding this crate can not decode.
+    UnsupportedEncoding(Encoding),
+    /// An id is outside of the WAMP id range `[1, 2^53]`.
+    InvalidId(u64),
+    /// Reading or writing the underlying stream failed.
+    IoError(std::io::Error),
+    /// An error tied to a line of a line delimited capture.
+    AtLine {
+        /// One based line number the error occurred at.
+        line: usize,
+        /// The underlying error.
+        error: Box<Error>,
+    },
+    /// The session is closing, only GOODBYE and ABORT may still be sent. Holds the rejected
+    /// message.
+    SessionClosing(Box<Messages>),
+    /// Every id in the id space is taken.
+    IdSpaceExhausted,
+    /// An EVENT was received for a subscription no handler is registered for.
+    UnknownSubscription(u64),
+    /// An extension message was given the id of a standard message.
+    ReservedMessageId(u64),
+    /// A value is not binary data in the form of the JSON encoding, a string holding `\0`
+    /// followed by base64. Holds what is wrong with it.
+    InvalidBinary(&'static str),
+    /// The details of a HELLO or WELCOME carry no `roles`.
+    MissingRoles,
 }
 
 macro_rules! message_to_from {
@@ -67,21 +140,82 @@ message_to_from!(Yield);
 //    }
 //}
 
+impl Error {
+    /// # Is close
+    /// Whether the error reports an orderly close of the connection rather than a failure:
+    /// [`Error::ConnectionClosed`] without a close code or with the code `1000` (normal) or
+    /// `1001` (going away), or tungstenite reporting the connection closed. Close frames with
+    /// any other code report a failure.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Messages;
+    /// use wamp_core::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+    /// use wamp_core::tungstenite::Message;
+    ///
+    /// let close = Message::Close(Some(CloseFrame { code: CloseCode::Normal, reason: "bye".into() }));
+    /// assert!(Messages::try_from(close).unwrap_err().is_close());
+    ///
+    /// let error = Message::Close(Some(CloseFrame { code: CloseCode::Error, reason: "".into() }));
+    /// assert!(!Messages::try_from(error).unwrap_err().is_close());
+    ///
+    /// let invalid = Message::Text("[33,3]".to_string());
+    /// assert!(!Messages::try_from(invalid).unwrap_err().is_close());
+    /// ```
+    pub fn is_close(&self) -> bool {
+        match self {
+            Error::ConnectionClosed { code, .. } => {
+                matches!(code, None | Some(CLOSE_NORMAL | CLOSE_AWAY))
+            }
+            Error::TungsteniteError(error) => matches!(
+                **error,
+                tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed
+            ),
+            _ => false,
+        }
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(value: serde_json::Error) -> Self {
         Self::SerdeJsonError(value)
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
+
+impl From<InvalidHeaderValue> for Error {
+    fn from(value: InvalidHeaderValue) -> Self {
+        Self::InvalidHeaderValue(value)
+    }
+}
+
 impl From<tungstenite::Error> for Error {
     fn from(value: tungstenite::Error) -> Self {
-        Self::TungsteniteError(value)
+        Self::TungsteniteError(Box::new(value))
     }
 }
 
-#[derive(Debug)]
-/// # [TODO]: WampErrorUri
-/// Unimplemented, unfortunately this does absolutely nothing in the current moment. The reasons are described below.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # WampErrorUri
+/// The error URIs predefined by the WAMP protocol. These can be turned into their URI with
+/// [`WampErrorUri::as_str`] or `Display`, and parsed back with `FromStr`. Parsing compares the
+/// whole string against the predefined URIs, any other URI is kept verbatim in
+/// [`WampErrorUri::Other`], the reasons for not matching URI patterns are described below.
+/// ## Examples
+/// ```
+/// use wamp_core::error::WampErrorUri;
+///
+/// let uri: WampErrorUri = "wamp.error.no_such_procedure".parse().unwrap();
+/// assert_eq!(uri, WampErrorUri::NoSuchProcedure);
+///
+/// let uri: WampErrorUri = "com.vendor.error.custom".parse().unwrap();
+/// assert_eq!(uri, WampErrorUri::Other("com.vendor.error.custom".to_string()));
+/// assert_eq!(uri.to_string(), "com.vendor.error.custom");
+/// ```
 /// 
 /// ## The Problem
 /// Wamp URI's have a variable amount of error URIs that get sent with different enabled features on wamp routers.
@@ -125,11 +259,371 @@ pub enum WampErrorUri {
     AuthorizationRequired,
     NetworkFailure,
     OptionNotAllowed,
+    /// Any URI that is not predefined, such as router or application specific errors.
+    Other(String),
+}
+
+impl WampErrorUri {
+    /// Every predefined error URI.
+    pub const KNOWN: [WampErrorUri; 28] = [
+        WampErrorUri::NotAuthorized,
+        WampErrorUri::ProcedureAlreadyExists,
+        WampErrorUri::NoSuchRealm,
+        WampErrorUri::ProtocolViolation,
+        WampErrorUri::NoSuchSubscription,
+        WampErrorUri::NoSuchRegistration,
+        WampErrorUri::InvalidUri,
+        WampErrorUri::NoSuchProcedure,
+        WampErrorUri::InvalidArgument,
+        WampErrorUri::Canceled,
+        WampErrorUri::PayloadSizeExceeded,
+        WampErrorUri::FeatureNotSupported,
+        WampErrorUri::Timeout,
+        WampErrorUri::Unavailable,
+        WampErrorUri::NoAvailableCallee,
+        WampErrorUri::DiscloseMeNotAllowed,
+        WampErrorUri::OptionDisallowedDiscloseMe,
+        WampErrorUri::NoMatchingAuthMethod,
+        WampErrorUri::NoSuchRole,
+        WampErrorUri::NoSuchPrincipal,
+        WampErrorUri::AuthenticationDenied,
+        WampErrorUri::AuthenticationFailed,
+        WampErrorUri::AuthenticationRequired,
+        WampErrorUri::AuthorizationDenied,
+        WampErrorUri::AuthorizationFailed,
+        WampErrorUri::AuthorizationRequired,
+        WampErrorUri::NetworkFailure,
+        WampErrorUri::OptionNotAllowed,
+    ];
+
+    /// The URI of the error, for example `wamp.error.no_such_procedure`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            WampErrorUri::NotAuthorized => constants::ERROR_NOT_AUTHORIZED,
+            WampErrorUri::ProcedureAlreadyExists => constants::ERROR_PROCEDURE_ALREADY_EXISTS,
+            WampErrorUri::NoSuchRealm => constants::ERROR_NO_SUCH_REALM,
+            WampErrorUri::ProtocolViolation => constants::ERROR_PROTOCOL_VIOLATION,
+            WampErrorUri::NoSuchSubscription => constants::ERROR_NO_SUCH_SUBSCRIPTION,
+            WampErrorUri::NoSuchRegistration => constants::ERROR_NO_SUCH_REGISTRATION,
+            WampErrorUri::InvalidUri => constants::ERROR_INVALID_URI,
+            WampErrorUri::NoSuchProcedure => constants::ERROR_NO_SUCH_PROCEDURE,
+            WampErrorUri::InvalidArgument => constants::ERROR_INVALID_ARGUMENT,
+            WampErrorUri::Canceled => constants::ERROR_CANCELED,
+            WampErrorUri::PayloadSizeExceeded => constants::ERROR_PAYLOAD_SIZE_EXCEEDED,
+            WampErrorUri::FeatureNotSupported => constants::ERROR_FEATURE_NOT_SUPPORTED,
+            WampErrorUri::Timeout => constants::ERROR_TIMEOUT,
+            WampErrorUri::Unavailable => constants::ERROR_UNAVAILABLE,
+            WampErrorUri::NoAvailableCallee => constants::ERROR_NO_AVAILABLE_CALLEE,
+            WampErrorUri::DiscloseMeNotAllowed => constants::ERROR_DISCLOSE_ME_NOT_ALLOWED,
+            WampErrorUri::OptionDisallowedDiscloseMe => {
+                constants::ERROR_OPTION_DISALLOWED_DISCLOSE_ME
+            }
+            WampErrorUri::NoMatchingAuthMethod => constants::ERROR_NO_MATCHING_AUTH_METHOD,
+            WampErrorUri::NoSuchRole => constants::ERROR_NO_SUCH_ROLE,
+            WampErrorUri::NoSuchPrincipal => constants::ERROR_NO_SUCH_PRINCIPAL,
+            WampErrorUri::AuthenticationDenied => constants::ERROR_AUTHENTICATION_DENIED,
+            WampErrorUri::AuthenticationFailed => constants::ERROR_AUTHENTICATION_FAILED,
+            WampErrorUri::AuthenticationRequired => constants::ERROR_AUTHENTICATION_REQUIRED,
+            WampErrorUri::AuthorizationDenied => constants::ERROR_AUTHORIZATION_DENIED,
+            WampErrorUri::AuthorizationFailed => constants::ERROR_AUTHORIZATION_FAILED,
+            WampErrorUri::AuthorizationRequired => constants::ERROR_AUTHORIZATION_REQUIRED,
+            WampErrorUri::NetworkFailure => constants::ERROR_NETWORK_FAILURE,
+            WampErrorUri::OptionNotAllowed => constants::ERROR_OPTION_NOT_ALLOWED,
+            WampErrorUri::Other(uri) => uri,
+        }
+    }
+}
+
+impl WampErrorUri {
+    /// # HTTP status
+    /// The HTTP status a gateway answers with when a call fails with this error.
+    ///
+    /// | Error | Status |
+    /// |---|---|
+    /// | `invalid_argument`, `invalid_uri`, `protocol_violation` | 400 Bad Request |
+    /// | `authentication_*`, `authorization_required`, `no_matching_auth_method` | 401 Unauthorized |
+    /// | `not_authorized`, `authorization_denied`, `option_not_allowed`, `disclose_me` errors | 403 Forbidden |
+    /// | `no_such_*` | 404 Not Found |
+    /// | `procedure_already_exists` | 409 Conflict |
+    /// | `payload_size_exceeded` | 413 Payload Too Large |
+    /// | `canceled` | 499 Client Closed Request |
+    /// | `authorization_failed` | 500 Internal Server Error |
+    /// | `feature_not_supported` | 501 Not Implemented |
+    /// | `network_failure` | 502 Bad Gateway |
+    /// | `unavailable`, `no_available_callee` | 503 Service Unavailable |
+    /// | `timeout` | 504 Gateway Timeout |
+    ///
+    /// [`WampErrorUri::Other`] maps to 500, except for the `http.status.<code>` URIs created by
+    /// [`WampErrorUri::from_http_status`], which map back to their code.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::error::WampErrorUri;
+    /// use wamp_core::http::StatusCode;
+    ///
+    /// assert_eq!(WampErrorUri::NoSuchProcedure.http_status(), StatusCode::NOT_FOUND);
+    /// assert_eq!(WampErrorUri::Other("com.myapp.error".to_string()).http_status(), StatusCode::INTERNAL_SERVER_ERROR);
+    /// ```
+    pub fn http_status(&self) -> StatusCode {
+        match self {
+            WampErrorUri::InvalidArgument
+            | WampErrorUri::InvalidUri
+            | WampErrorUri::ProtocolViolation => StatusCode::BAD_REQUEST,
+            WampErrorUri::AuthenticationDenied
+            | WampErrorUri::AuthenticationFailed
+            | WampErrorUri::AuthenticationRequired
+            | WampErrorUri::AuthorizationRequired
+            | WampErrorUri::NoMatchingAuthMethod => StatusCode::UNAUTHORIZED,
+            WampErrorUri::NotAuthorized
+            | WampErrorUri::AuthorizationDenied
+            | WampErrorUri::OptionNotAllowed
+            | WampErrorUri::DiscloseMeNotAllowed
+            | WampErrorUri::OptionDisallowedDiscloseMe => StatusCode::FORBIDDEN,
+            WampErrorUri::NoSuchProcedure
+            | WampErrorUri::NoSuchRealm
+            | WampErrorUri::NoSuchSubscription
+            | WampErrorUri::NoSuchRegistration
+            | WampErrorUri::NoSuchRole
+            | WampErrorUri::NoSuchPrincipal => StatusCode::NOT_FOUND,
+            WampErrorUri::ProcedureAlreadyExists => StatusCode::CONFLICT,
+            WampErrorUri::PayloadSizeExceeded => StatusCode::PAYLOAD_TOO_LARGE,
+            WampErrorUri::Canceled => {
+                StatusCode::from_u16(CLIENT_CLOSED_REQUEST).expect("499 is a valid status code")
+            }
+            WampErrorUri::AuthorizationFailed => StatusCode::INTERNAL_SERVER_ERROR,
+            WampErrorUri::FeatureNotSupported => StatusCode::NOT_IMPLEMENTED,
+            WampErrorUri::NetworkFailure => StatusCode::BAD_GATEWAY,
+            WampErrorUri::Unavailable | WampErrorUri::NoAvailableCallee => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            WampErrorUri::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            WampErrorUri::Other(uri) => uri
+                .strip_prefix(HTTP_STATUS_PREFIX)
+                .and_then(|code| code.parse().ok())
+                .and_then(|code| StatusCode::from_u16(code).ok())
+                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+
+    /// # From HTTP status
+    /// The error a gateway reports when the HTTP backend of a procedure answers with `status`.
+    ///
+    /// Each status of the [`WampErrorUri::http_status`] table maps to the first error listed
+    /// for it (`invalid_argument`, `authentication_required`, `not_authorized`,
+    /// `no_such_procedure`, ...), 408 maps to `timeout` and 429 to `unavailable`. Any other
+    /// status becomes `Other("http.status.<code>")`, so no information is lost.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::error::WampErrorUri;
+    /// use wamp_core::http::StatusCode;
+    ///
+    /// assert_eq!(WampErrorUri::from_http_status(StatusCode::NOT_FOUND), WampErrorUri::NoSuchProcedure);
+    /// assert_eq!(WampErrorUri::from_http_status(StatusCode::IM_A_TEAPOT).to_string(), "http.status.418");
+    /// ```
+    pub fn from_http_status(status: StatusCode) -> WampErrorUri {
+        match status {
+            StatusCode::BAD_REQUEST => WampErrorUri::InvalidArgument,
+            StatusCode::UNAUTHORIZED => WampErrorUri::AuthenticationRequired,
+            StatusCode::FORBIDDEN => WampErrorUri::NotAuthorized,
+            StatusCode::NOT_FOUND => WampErrorUri::NoSuchProcedure,
+            StatusCode::REQUEST_TIMEOUT => WampErrorUri::Timeout,
+            StatusCode::CONFLICT => WampErrorUri::ProcedureAlreadyExists,
+            StatusCode::PAYLOAD_TOO_LARGE => WampErrorUri::PayloadSizeExceeded,
+            StatusCode::TOO_MANY_REQUESTS => WampErrorUri::Unavailable,
+            status if status.as_u16() == CLIENT_CLOSED_REQUEST => WampErrorUri::Canceled,
+            StatusCode::NOT_IMPLEMENTED => WampErrorUri::FeatureNotSupported,
+            StatusCode::BAD_GATEWAY => WampErrorUri::NetworkFailure,
+            StatusCode::SERVICE_UNAVAILABLE => WampErrorUri::Unavailable,
+            StatusCode::GATEWAY_TIMEOUT => WampErrorUri::Timeout,
+            status => WampErrorUri::Other(format!("{HTTP_STATUS_PREFIX}{}", status.as_u16())),
+        }
+    }
+}
+
+impl std::str::FromStr for WampErrorUri {
+    type Err = std::convert::Infallible;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        Ok(WampErrorUri::KNOWN
+            .into_iter()
+            .find(|known| known.as_str() == uri)
+            .unwrap_or_else(|| WampErrorUri::Other(uri.to_string())))
+    }
+}
+
+impl std::fmt::Display for WampErrorUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
-/// [TODO]: See WampErrorUri Structure for more details.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Close URI
+/// The predefined reasons of GOODBYE and ABORT messages closing a session.
+/// ## Examples
+/// ```
+/// use wamp_core::error::CloseUri;
+/// use wamp_core::uri::constants::CLOSE_GOODBYE_AND_OUT;
+///
+/// assert_eq!(CloseUri::GoodbyeAndOut.to_string(), CLOSE_GOODBYE_AND_OUT);
+/// assert_eq!("wamp.close.killed".parse::<CloseUri>().unwrap(), CloseUri::Killed);
+/// ```
 pub enum CloseUri {
+    /// The session closed normally.
+    Normal,
+    /// The peer is shutting down.
     SystemShutdown,
+    /// The router closes the realm.
     CloseRealm,
+    /// The reply to a GOODBYE.
     GoodbyeAndOut,
+    /// The session was killed through the session meta API.
     Killed,
 }
+
+impl CloseUri {
+    /// Every predefined close URI.
+    pub const KNOWN: [CloseUri; 5] = [
+        CloseUri::Normal,
+        CloseUri::SystemShutdown,
+        CloseUri::CloseRealm,
+        CloseUri::GoodbyeAndOut,
+        CloseUri::Killed,
+    ];
+
+    /// The URI of the reason, for example `wamp.close.system_shutdown`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CloseUri::Normal => constants::CLOSE_NORMAL,
+            CloseUri::SystemShutdown => constants::CLOSE_SYSTEM_SHUTDOWN,
+            CloseUri::CloseRealm => constants::CLOSE_CLOSE_REALM,
+            CloseUri::GoodbyeAndOut => constants::CLOSE_GOODBYE_AND_OUT,
+            CloseUri::Killed => constants::CLOSE_KILLED,
+        }
+    }
+}
+
+impl std::str::FromStr for CloseUri {
+    type Err = Error;
+
+    /// Parse a predefined close URI, failing with [`Error::InvalidURI`] for any other URI.
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        CloseUri::KNOWN
+            .into_iter()
+            .find(|known| known.as_str() == uri)
+            .ok_or_else(|| Error::InvalidURI {
+                uri: uri.to_string(),
+                rule: "close",
+            })
+    }
+}
+
+impl std::fmt::Display for CloseUri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_status_mapping() {
+        let table = [
+            (WampErrorUri::NotAuthorized, 403),
+            (WampErrorUri::ProcedureAlreadyExists, 409),
+            (WampErrorUri::NoSuchRealm, 404),
+            (WampErrorUri::ProtocolViolation, 400),
+            (WampErrorUri::NoSuchSubscription, 404),
+            (WampErrorUri::NoSuchRegistration, 404),
+            (WampErrorUri::InvalidUri, 400),
+            (WampErrorUri::NoSuchProcedure, 404),
+            (WampErrorUri::InvalidArgument, 400),
+            (WampErrorUri::Canceled, 499),
+            (WampErrorUri::PayloadSizeExceeded, 413),
+            (WampErrorUri::FeatureNotSupported, 501),
+            (WampErrorUri::Timeout, 504),
+            (WampErrorUri::Unavailable, 503),
+            (WampErrorUri::NoAvailableCallee, 503),
+            (WampErrorUri::DiscloseMeNotAllowed, 403),
+            (WampErrorUri::OptionDisallowedDiscloseMe, 403),
+            (WampErrorUri::NoMatchingAuthMethod, 401),
+            (WampErrorUri::NoSuchRole, 404),
+            (WampErrorUri::NoSuchPrincipal, 404),
+            (WampErrorUri::AuthenticationDenied, 401),
+            (WampErrorUri::AuthenticationFailed, 401),
+            (WampErrorUri::AuthenticationRequired, 401),
+            (WampErrorUri::AuthorizationDenied, 403),
+            (WampErrorUri::AuthorizationFailed, 500),
+            (WampErrorUri::AuthorizationRequired, 401),
+            (WampErrorUri::NetworkFailure, 502),
+            (WampErrorUri::OptionNotAllowed, 403),
+        ];
+        assert_eq!(table.len(), WampErrorUri::KNOWN.len());
+        for (uri, status) in table {
+            assert_eq!(uri.http_status().as_u16(), status, "{uri}");
+        }
+
+        let reverse = [
+            (400, WampErrorUri::InvalidArgument),
+            (401, WampErrorUri::AuthenticationRequired),
+            (403, WampErrorUri::NotAuthorized),
+            (404, WampErrorUri::NoSuchProcedure),
+            (408, WampErrorUri::Timeout),
+            (409, WampErrorUri::ProcedureAlreadyExists),
+            (413, WampErrorUri::PayloadSizeExceeded),
+            (429, WampErrorUri::Unavailable),
+            (499, WampErrorUri::Canceled),
+            (501, WampErrorUri::FeatureNotSupported),
+            (502, WampErrorUri::NetworkFailure),
+            (503, WampErrorUri::Unavailable),
+            (504, WampErrorUri::Timeout),
+        ];
+        for (status, uri) in reverse {
+            let status = StatusCode::from_u16(status).unwrap();
+            assert_eq!(WampErrorUri::from_http_status(status), uri);
+        }
+
+        let teapot = WampErrorUri::from_http_status(StatusCode::IM_A_TEAPOT);
+        assert_eq!(teapot, WampErrorUri::Other("http.status.418".to_string()));
+        assert_eq!(teapot.http_status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(
+            WampErrorUri::Other("http.status.x".to_string()).http_status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn error_uri_round_trip() {
+        for known in WampErrorUri::KNOWN {
+            assert_eq!(known.to_string().parse::<WampErrorUri>(), Ok(known));
+        }
+
+        let custom: WampErrorUri = "com.vendor.error.custom".parse().unwrap();
+        assert_eq!(
+            custom,
+            WampErrorUri::Other("com.vendor.error.custom".to_string())
+        );
+        assert_eq!(custom.to_string(), "com.vendor.error.custom");
+        assert_eq!(custom.to_string().parse::<WampErrorUri>(), Ok(custom));
+
+        let odd = WampErrorUri::Other("Wamp.Error.Not Authorized ".to_string());
+        assert_eq!(odd.to_string().parse::<WampErrorUri>(), Ok(odd));
+    }
+
+    #[test]
+    fn close_uri_constants() {
+        assert_eq!(
+            CloseUri::SystemShutdown.to_string(),
+            constants::CLOSE_SYSTEM_SHUTDOWN
+        );
+        for known in CloseUri::KNOWN {
+            assert_eq!(known.to_string().parse::<CloseUri>().unwrap(), known);
+        }
+        assert!("wamp.close.unknown".parse::<CloseUri>().is_err());
+        assert_eq!(
+            WampErrorUri::NotAuthorized.as_str(),
+            constants::ERROR_NOT_AUTHORIZED
+        );
+    }
+}
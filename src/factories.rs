@@ -1,43 +1,300 @@
-use std::sync::RwLock;
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    sync::{Mutex, RwLock},
+};
 
 use lazy_static::lazy_static;
 
+use crate::{error::Error, messages::MAX_ID, pending::PendingRequests};
+
 lazy_static! {
     static ref NUMBER: RwLock<u64> = RwLock::new(0);
     static ref TOPICS: RwLock<Vec<String>> = RwLock::new(vec![]);
 }
 
+/// The id following `previous`, wrapping around to 1 after [`MAX_ID`].
+fn next_id(previous: u64) -> u64 {
+    if previous >= MAX_ID {
+        1
+    } else {
+        previous + 1
+    }
+}
+
 /// # Auto incrementer
 /// Thread safe Auto Incrementing method that adds 1, and returns the number.
-/// 
+///
+/// Ids stay in the WAMP id range `[1, 2^53]`: after [`MAX_ID`] the counter wraps around to 1
+/// instead of overflowing.
+///
 /// Here is the source code for that particular snippet, as its usage is obvious
 /// and this space is used so people can audit it for its "thread safety".
 /// ```
 /// use lazy_static::lazy_static;
 /// use std::sync::RwLock;
-/// 
+/// use wamp_core::messages::MAX_ID;
+///
 /// lazy_static! {
 ///     static ref NUMBER: RwLock<u64> = RwLock::new(0);
 /// }
-/// 
+///
 /// pub fn increment() -> u64 {
-///     let previous = *NUMBER.read().unwrap();
 ///     let mut num = NUMBER.write().unwrap();
-///     *num = previous + 1;
+///     *num = if *num >= MAX_ID { 1 } else { *num + 1 };
 ///     *num
 /// }
-/// 
+///
 /// for i in 1..10 {
 ///     assert_eq!(i, increment());
 /// }
 /// ```
 pub fn increment() -> u64 {
-    let previous = *NUMBER.read().unwrap();
     let mut num = NUMBER.write().unwrap();
-    *num = previous + 1;
+    *num = next_id(*num);
     *num
 }
 
+#[derive(Debug, Clone)]
+/// # Correlator
+/// Routes responses back to whoever sent the request, keyed by request id.
+///
+/// `T` is whatever the awaiting side needs, a channel sender, a callback or the request itself.
+/// ## Examples
+/// ```
+/// use wamp_core::factories::{increment, Correlator};
+///
+/// let mut correlator = Correlator::new();
+/// let request_id = increment();
+/// correlator.register(request_id, "add two numbers");
+///
+/// assert_eq!(correlator.pending_ids(), vec![request_id]);
+/// assert_eq!(correlator.take(request_id), Some("add two numbers"));
+/// assert_eq!(correlator.take(request_id), None);
+/// ```
+pub struct Correlator<T> {
+    pending: HashMap<u64, T>,
+}
+
+impl<T> Default for Correlator<T> {
+    fn default() -> Self {
+        Correlator {
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Correlator<T> {
+    /// Create an empty correlator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Await a response to request `id`, returning the value it replaces if `id` was pending.
+    pub fn register(&mut self, id: u64, value: T) -> Option<T> {
+        self.pending.insert(id, value)
+    }
+
+    /// Stop awaiting request `id`, returning its value, `None` when it is not pending.
+    pub fn take(&mut self, id: u64) -> Option<T> {
+        self.pending.remove(&id)
+    }
+
+    /// Whether request `id` is pending.
+    pub fn contains(&self, id: u64) -> bool {
+        self.pending.contains_key(&id)
+    }
+
+    /// Ids of the pending requests, in ascending order.
+    pub fn pending_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.pending.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Number of pending requests.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no request is pending.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// # Id Policy
+/// What an [`IdGenerator`] does once its sequential ids run out.
+pub enum IdPolicy {
+    /// Start over at 1, skipping ids that are still pending.
+    #[default]
+    Wrap,
+    /// Fail with [`Error::IdSpaceExhausted`].
+    Error,
+    /// Pick random ids that are not pending.
+    Random,
+}
+
+#[derive(Debug, Clone)]
+/// # Id Generator
+/// Sequential request ids in `[1, max]`, `max` being `2^53` unless configured otherwise, with an
+/// [`IdPolicy`] for when the sequence runs out.
+/// ## Examples
+/// ```
+/// use wamp_core::factories::{IdGenerator, IdPolicy};
+/// use wamp_core::pending::PendingRequests;
+/// use wamp_core::messages::{Call, Messages};
+/// use serde_json::{json, Value};
+///
+/// let mut ids = IdGenerator::new().max(2);
+/// let mut pending = PendingRequests::new();
+///
+/// let first = ids.next_checked(&pending).unwrap();
+/// pending.track(&Messages::from(Call {
+///     request_id: first,
+///     options: json!({}),
+///     procedure: "com.myapp.slow".into(),
+///     args: Value::Null,
+///     kwargs: Value::Null
+/// }));
+///
+/// assert_eq!(ids.next_checked(&pending).unwrap(), 2);
+/// // 1 is still pending, so the sequence wraps around to 2 again
+/// assert_eq!(ids.next_checked(&pending).unwrap(), 2);
+///
+/// let mut ids = IdGenerator::new().max(1).policy(IdPolicy::Error);
+/// assert_eq!(ids.next_checked(&PendingRequests::new()).unwrap(), 1);
+/// assert!(ids.next_checked(&PendingRequests::new()).is_err());
+/// ```
+pub struct IdGenerator {
+    next: u64,
+    max: u64,
+    policy: IdPolicy,
+    random: RandomState,
+    draws: u64,
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        IdGenerator {
+            next: 1,
+            max: MAX_ID,
+            policy: IdPolicy::default(),
+            random: RandomState::new(),
+            draws: 0,
+        }
+    }
+}
+
+impl IdGenerator {
+    /// Create a generator starting at 1 with the [`IdPolicy::Wrap`] policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the policy applied once the sequential ids run out.
+    pub fn policy(mut self, policy: IdPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the largest id handed out, at most `2^53`.
+    pub fn max(mut self, max: u64) -> Self {
+        self.max = max.clamp(1, MAX_ID);
+        self
+    }
+
+    /// The first free id scanning up from `start`, wrapping around to 1.
+    fn scan(&self, start: u64, pending: &PendingRequests) -> Result<u64, Error> {
+        let mut id = start;
+        for _ in 0..self.max {
+            if id > self.max {
+                id = 1;
+            }
+            if !pending.contains(id) {
+                return Ok(id);
+            }
+            id += 1;
+        }
+        Err(Error::IdSpaceExhausted)
+    }
+
+    /// # Next checked
+    /// The next id, never one that is still pending in `pending`.
+    ///
+    /// Fails with [`Error::IdSpaceExhausted`] when every id is pending, or once the sequence
+    /// ran out with the [`IdPolicy::Error`] policy.
+    pub fn next_checked(&mut self, pending: &PendingRequests) -> Result<u64, Error> {
+        while self.next <= self.max && pending.contains(self.next) {
+            self.next += 1;
+        }
+        if self.next <= self.max {
+            self.next += 1;
+            return Ok(self.next - 1);
+        }
+        match self.policy {
+            IdPolicy::Error => Err(Error::IdSpaceExhausted),
+            IdPolicy::Wrap => {
+                let id = self.scan(1, pending)?;
+                self.next = id + 1;
+                Ok(id)
+            }
+            IdPolicy::Random => {
+                let mut hasher = self.random.build_hasher();
+                hasher.write_u64(self.draws);
+                self.draws += 1;
+                self.scan(hasher.finish() % self.max + 1, pending)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+/// # Realm Id Generators
+/// An independent [`IdGenerator`] per realm, created on first use, for routers serving several
+/// realms.
+///
+/// Ids are unique within a realm. Every realm has its own lock, so realms do not wait on each
+/// other once their generator exists.
+/// ## Examples
+/// ```
+/// use wamp_core::factories::RealmIdGenerators;
+///
+/// let ids = RealmIdGenerators::new();
+///
+/// assert_eq!(ids.next("realm1"), 1);
+/// assert_eq!(ids.next("realm1"), 2);
+/// assert_eq!(ids.next("realm2"), 1);
+/// ```
+pub struct RealmIdGenerators {
+    realms: RwLock<HashMap<String, Mutex<IdGenerator>>>,
+}
+
+impl RealmIdGenerators {
+    /// Create an empty map, generators start at 1 with the [`IdPolicy::Wrap`] policy.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The next id of `realm`.
+    pub fn next(&self, realm: &str) -> u64 {
+        if let Some(ids) = self.realms.read().unwrap().get(realm) {
+            return draw(ids);
+        }
+        let mut realms = self.realms.write().unwrap();
+        draw(realms.entry(realm.to_string()).or_default())
+    }
+}
+
+/// The next id of a wrapping generator, which never runs out as no id is pending.
+fn draw(ids: &Mutex<IdGenerator>) -> u64 {
+    ids.lock()
+        .unwrap()
+        .next_checked(&PendingRequests::new())
+        .expect("wrapping generators without pending ids never run out")
+}
+
 pub fn add_associated_subscription() {
     
 }
@@ -54,4 +311,128 @@ pub fn unsubscribe<T: ToString>(topic: &T) {
 
 pub fn subscription_contains<T: ToString>(topic: &T) -> bool {
     TOPICS.read().unwrap().contains(&topic.to_string())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::{next_id, Correlator, IdGenerator, IdPolicy, RealmIdGenerators};
+    use crate::{
+        error::Error,
+        messages::{Call, Messages, MAX_ID},
+        pending::PendingRequests,
+    };
+
+    fn track(pending: &mut PendingRequests, request_id: u64) {
+        pending.track(&Messages::from(Call {
+            request_id,
+            options: json!({}),
+            procedure: "com.myapp.slow".into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        }));
+    }
+
+    #[test]
+    fn wrap_skips_pending_ids() {
+        let mut pending = PendingRequests::new();
+        let mut ids = IdGenerator::new().max(4);
+        for expected in 1..=4 {
+            let id = ids.next_checked(&pending).unwrap();
+            assert_eq!(id, expected);
+            if id % 2 == 1 {
+                track(&mut pending, id);
+            }
+        }
+        assert_eq!(ids.next_checked(&pending).unwrap(), 2);
+        assert_eq!(ids.next_checked(&pending).unwrap(), 4);
+        assert_eq!(ids.next_checked(&pending).unwrap(), 2);
+
+        track(&mut pending, 2);
+        track(&mut pending, 4);
+        assert!(matches!(
+            ids.next_checked(&pending),
+            Err(Error::IdSpaceExhausted)
+        ));
+    }
+
+    #[test]
+    fn increment_wraps_at_max_id() {
+        assert_eq!(next_id(0), 1);
+        assert_eq!(next_id(MAX_ID - 1), MAX_ID);
+        assert_eq!(next_id(MAX_ID), 1);
+        assert_eq!(next_id(u64::MAX), 1);
+
+        let mut ids = IdGenerator::new();
+        ids.next = MAX_ID - 1;
+        let pending = PendingRequests::new();
+        assert_eq!(ids.next_checked(&pending).unwrap(), MAX_ID - 1);
+        assert_eq!(ids.next_checked(&pending).unwrap(), MAX_ID);
+        assert_eq!(ids.next_checked(&pending).unwrap(), 1);
+    }
+
+    #[test]
+    fn realms_have_independent_sequences() {
+        let ids = RealmIdGenerators::new();
+        assert_eq!(ids.next("realm1"), 1);
+        assert_eq!(ids.next("realm2"), 1);
+        assert_eq!(ids.next("realm1"), 2);
+
+        let drawn: Vec<Vec<u64>> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| (0..100).map(|_| ids.next("realm3")).collect()))
+                .collect();
+            threads.into_iter().map(|t| t.join().unwrap()).collect()
+        });
+        let mut drawn: Vec<u64> = drawn.into_iter().flatten().collect();
+        drawn.sort_unstable();
+        assert_eq!(drawn, (1..=400).collect::<Vec<u64>>());
+        assert_eq!(ids.next("realm2"), 2);
+    }
+
+    #[test]
+    fn error_policy() {
+        let pending = PendingRequests::new();
+        let mut ids = IdGenerator::new().max(2).policy(IdPolicy::Error);
+        assert_eq!(ids.next_checked(&pending).unwrap(), 1);
+        assert_eq!(ids.next_checked(&pending).unwrap(), 2);
+        assert!(matches!(
+            ids.next_checked(&pending),
+            Err(Error::IdSpaceExhausted)
+        ));
+    }
+
+    #[test]
+    fn random_policy() {
+        let mut pending = PendingRequests::new();
+        let mut ids = IdGenerator::new().max(8).policy(IdPolicy::Random);
+        for _ in 1..=8 {
+            ids.next_checked(&pending).unwrap();
+        }
+        for id in [1, 2, 3, 5, 6, 8] {
+            track(&mut pending, id);
+        }
+        for _ in 0..32 {
+            let id = ids.next_checked(&pending).unwrap();
+            assert!(id == 4 || id == 7, "{id} is pending");
+        }
+        track(&mut pending, 4);
+        track(&mut pending, 7);
+        assert!(ids.next_checked(&pending).is_err());
+    }
+
+    #[test]
+    fn correlator() {
+        let mut correlator = Correlator::new();
+        assert_eq!(correlator.take(1), None);
+        assert_eq!(correlator.register(2, "b"), None);
+        assert_eq!(correlator.register(1, "a"), None);
+        assert_eq!(correlator.register(2, "c"), Some("b"));
+        assert_eq!(correlator.pending_ids(), vec![1, 2]);
+        assert_eq!(correlator.take(2), Some("c"));
+        assert!(!correlator.contains(2));
+        assert_eq!(correlator.take(2), None);
+        assert_eq!(correlator.len(), 1);
+    }
+}
@@ -0,0 +1,621 @@
+//! # Advanced profile features - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-feature-announcement)
+//! Helpers for working with the `roles.<role>.features` objects announced in HELLO and WELCOME.
+use std::fmt;
+
+use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    auth::AuthMethod,
+    messages::{Abort, Hello, Messages, Welcome},
+    roles::Roles,
+};
+
+macro_rules! router_features {
+    ($($(#[$doc:meta])* $feature:ident,)*) => {
+        #[derive(Debug, Clone, Default, PartialEq)]
+        /// # Router Features
+        /// The advanced features a router announced for its broker and dealer roles in WELCOME.
+        ///
+        /// Features announced by both roles (such as `session_meta_api`) are set when either role
+        /// announces them. Feature keys this crate does not know are kept in `unknown`.
+        /// ## Examples
+        /// ```
+        /// use wamp_core::messages::Welcome;
+        /// use serde_json::json;
+        ///
+        /// let welcome = Welcome {
+        ///     session: 1,
+        ///     details: json!({ "roles": {
+        ///         "broker": { "features": { "pattern_based_subscription": true } },
+        ///         "dealer": { "features": { "progressive_call_results": true, "x_acme_batching": true } }
+        ///     }})
+        /// };
+        ///
+        /// let features = welcome.features();
+        /// assert!(features.pattern_based_subscription);
+        /// assert!(features.progressive_call_results);
+        /// assert!(!features.call_canceling);
+        /// assert_eq!(features.unknown["x_acme_batching"], json!(true));
+        /// ```
+        pub struct RouterFeatures {
+            $($(#[$doc])* pub $feature: bool,)*
+            /// Announced feature keys that are not known to this crate, with their announced value.
+            pub unknown: Map<String, Value>,
+        }
+
+        impl RouterFeatures {
+            fn insert(&mut self, feature: &str, value: &Value) {
+                match feature {
+                    $(stringify!($feature) => self.$feature |= value.as_bool() == Some(true),)*
+                    _ => {
+                        self.unknown.insert(feature.to_string(), value.clone());
+                    }
+                }
+            }
+        }
+    };
+}
+
+router_features! {
+    /// Broker: events carry the publisher's session id on request.
+    publisher_identification,
+    /// Broker: events carry a trust level.
+    publication_trustlevels,
+    /// Broker: prefix and wildcard subscriptions.
+    pattern_based_subscription,
+    /// Broker: `wamp.subscription.*` meta procedures.
+    subscription_meta_api,
+    /// Broker: `eligible` / `exclude` publish options.
+    subscriber_blackwhite_listing,
+    /// Broker: publishers may receive their own events.
+    publisher_exclusion,
+    /// Broker: `wamp.topic.history.*` procedures.
+    event_history,
+    /// Broker: retained events delivered on subscribe.
+    event_retention,
+    /// Broker: sharded subscriptions.
+    sharded_subscription,
+    /// Broker: subscriptions may be revoked by the router.
+    subscription_revocation,
+    /// Dealer: progressive results for calls.
+    progressive_call_results,
+    /// Dealer: progressive invocations.
+    progressive_call_invocations,
+    /// Dealer: `timeout` call option.
+    call_timeout,
+    /// Dealer: CANCEL and INTERRUPT.
+    call_canceling,
+    /// Dealer: invocations carry the caller's session id on request.
+    caller_identification,
+    /// Dealer: invocations carry a trust level.
+    call_trustlevels,
+    /// Dealer: prefix and wildcard registrations.
+    pattern_based_registration,
+    /// Dealer: several callees per registration.
+    shared_registration,
+    /// Dealer: sharded registrations.
+    sharded_registration,
+    /// Dealer: registrations may be revoked by the router.
+    registration_revocation,
+    /// Dealer: `wamp.registration.*` meta procedures.
+    registration_meta_api,
+    /// Broker or dealer: `wamp.session.*` meta procedures.
+    session_meta_api,
+    /// Broker or dealer: testament meta procedures.
+    testament_meta_api,
+    /// Broker or dealer: payload passthrough mode.
+    payload_passthru_mode,
+}
+
+impl RouterFeatures {
+    /// Parse the features of the `broker` and `dealer` roles out of WELCOME details.
+    pub fn from_details(details: &Value) -> Self {
+        let mut features = RouterFeatures::default();
+        for role in ["broker", "dealer"] {
+            let announced = details["roles"][role]["features"].as_object();
+            for (feature, value) in announced.into_iter().flatten() {
+                features.insert(feature, value);
+            }
+        }
+        features
+    }
+}
+
+macro_rules! role_features {
+    ($($(#[$doc:meta])* $name:ident { $($feature:ident,)* })*) => {
+        $(
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+            $(#[$doc])*
+            pub struct $name {
+                $(
+                    #[doc = concat!("The `", stringify!($feature), "` feature.")]
+                    pub $feature: bool,
+                )*
+            }
+
+            impl $name {
+                /// Every feature of the role, as spelled in the announcement.
+                pub const FEATURES: &'static [&'static str] = &[$(stringify!($feature),)*];
+
+                /// The enabled features, in the order of [`FEATURES`](Self::FEATURES).
+                pub fn enabled(&self) -> Vec<&'static str> {
+                    let mut enabled = Vec::new();
+                    $(
+                        if self.$feature {
+                            enabled.push(stringify!($feature));
+                        }
+                    )*
+                    enabled
+                }
+            }
+
+            impl Serialize for $name {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    let enabled = self.enabled();
+                    let mut map = serializer.serialize_map(Some(enabled.len()))?;
+                    for feature in enabled {
+                        map.serialize_entry(feature, &true)?;
+                    }
+                    map.end()
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $name {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let announced = Map::<String, Value>::deserialize(deserializer)?;
+                    let enabled = |feature| announced.get(feature) == Some(&Value::Bool(true));
+                    Ok($name {
+                        $($feature: enabled(stringify!($feature)),)*
+                    })
+                }
+            }
+        )*
+    };
+}
+
+role_features! {
+    /// # Broker Features
+    /// The advanced features of a broker, the `roles.broker.features` of a WELCOME.
+    ///
+    /// Only enabled features are serialized, as `"feature": true`. Deserializing ignores
+    /// features this crate does not know.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::features::BrokerFeatures;
+    /// use serde_json::{json, to_value};
+    ///
+    /// let features = BrokerFeatures { pattern_based_subscription: true, ..Default::default() };
+    ///
+    /// assert_eq!(to_value(features).unwrap(), json!({ "pattern_based_subscription": true }));
+    /// ```
+    BrokerFeatures {
+        publisher_identification,
+        publication_trustlevels,
+        pattern_based_subscription,
+        subscription_meta_api,
+        subscriber_blackwhite_listing,
+        publisher_exclusion,
+        event_history,
+        event_retention,
+        sharded_subscription,
+        subscription_revocation,
+        session_meta_api,
+        testament_meta_api,
+        payload_passthru_mode,
+    }
+
+    /// # Dealer Features
+    /// The advanced features of a dealer, the `roles.dealer.features` of a WELCOME, see
+    /// [`BrokerFeatures`].
+    DealerFeatures {
+        progressive_call_results,
+        progressive_call_invocations,
+        call_timeout,
+        call_canceling,
+        caller_identification,
+        call_trustlevels,
+        pattern_based_registration,
+        shared_registration,
+        sharded_registration,
+        registration_revocation,
+        registration_meta_api,
+        session_meta_api,
+        testament_meta_api,
+        payload_passthru_mode,
+    }
+
+    /// # Caller Features
+    /// The advanced features of a caller, the `roles.caller.features` of a HELLO, see
+    /// [`BrokerFeatures`].
+    CallerFeatures {
+        progressive_call_results,
+        progressive_call_invocations,
+        call_timeout,
+        call_canceling,
+        caller_identification,
+        payload_passthru_mode,
+    }
+
+    /// # Callee Features
+    /// The advanced features of a callee, the `roles.callee.features` of a HELLO, see
+    /// [`BrokerFeatures`].
+    CalleeFeatures {
+        progressive_call_results,
+        progressive_call_invocations,
+        call_timeout,
+        call_canceling,
+        caller_identification,
+        call_trustlevels,
+        pattern_based_registration,
+        shared_registration,
+        sharded_registration,
+        payload_passthru_mode,
+    }
+
+    /// # Publisher Features
+    /// The advanced features of a publisher, the `roles.publisher.features` of a HELLO, see
+    /// [`BrokerFeatures`].
+    PublisherFeatures {
+        publisher_identification,
+        subscriber_blackwhite_listing,
+        publisher_exclusion,
+        payload_passthru_mode,
+    }
+
+    /// # Subscriber Features
+    /// The advanced features of a subscriber, the `roles.subscriber.features` of a HELLO, see
+    /// [`BrokerFeatures`].
+    SubscriberFeatures {
+        publisher_identification,
+        publication_trustlevels,
+        pattern_based_subscription,
+        event_history,
+        sharded_subscription,
+        payload_passthru_mode,
+    }
+}
+
+/// The router role a client role talks to.
+fn peer_role(role: &str) -> Option<&'static str> {
+    match role {
+        "caller" | "callee" => Some("dealer"),
+        "publisher" | "subscriber" => Some("broker"),
+        _ => None,
+    }
+}
+
+fn enabled_features<'a>(details: &'a Value, role: &str) -> impl Iterator<Item = &'a String> {
+    details["roles"][role]["features"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, enabled)| enabled.as_bool() == Some(true))
+        .map(|(feature, _)| feature)
+}
+
+/// # Negotiated features
+/// Computes the advanced features both peers of a session announced.
+///
+/// For every client role announced in the HELLO, the features enabled by the client are
+/// intersected with the features of the router role it talks to (the dealer for callers and
+/// callees, the broker for publishers and subscribers) announced in the WELCOME.
+///
+/// The result is an object keyed by client role, holding `"feature": true` pairs. Roles the
+/// router does not implement are left out.
+/// ## Examples
+/// ```
+/// use wamp_core::features::negotiated_features;
+/// use wamp_core::messages::{Hello, Welcome};
+/// use serde_json::json;
+///
+/// let hello = Hello {
+///     realm: "realm1".into(),
+///     details: json!({ "roles": { "caller": { "features": {
+///         "progressive_call_results": true,
+///         "call_canceling": true
+///     }}}})
+/// };
+///
+/// let welcome = Welcome {
+///     session: 1,
+///     details: json!({ "roles": { "dealer": { "features": {
+///         "progressive_call_results": true
+///     }}}})
+/// };
+///
+/// assert_eq!(negotiated_features(&hello, &welcome), json!({
+///     "caller": { "progressive_call_results": true }
+/// }));
+/// ```
+pub fn negotiated_features(hello: &Hello, welcome: &Welcome) -> Value {
+    let mut negotiated = Map::new();
+    let client_roles = hello.details["roles"].as_object().into_iter().flatten();
+    for (role, _) in client_roles {
+        let Some(peer) = peer_role(role) else {
+            continue;
+        };
+        if welcome.details["roles"].get(peer).is_none() {
+            continue;
+        }
+        let router: Vec<&String> = enabled_features(&welcome.details, peer).collect();
+        let features: Map<String, Value> = enabled_features(&hello.details, role)
+            .filter(|feature| router.contains(feature))
+            .map(|feature| (feature.clone(), json!(true)))
+            .collect();
+        negotiated.insert(role.clone(), Value::Object(features));
+    }
+    Value::Object(negotiated)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Feature
+/// What a session must have negotiated for a message to be legal, see
+/// [`MessageDescriptor::required_feature`](crate::messages::MessageDescriptor::required_feature).
+pub enum Feature {
+    /// An advanced feature of a client role, announced by the client and by the router role it
+    /// talks to, such as `call_canceling` of the caller.
+    Role(Roles, &'static str),
+    /// A challenge based authentication method, offered by the client in its HELLO.
+    ChallengeAuth,
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Feature::Role(role, feature) => {
+                write!(f, "the {feature} feature of the {}", role.name())
+            }
+            Feature::ChallengeAuth => f.write_str("a challenge based authentication method"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// # Negotiated Features
+/// The [`Feature`]s of a session, deciding which messages it may exchange.
+///
+/// Messages of the basic profile are always permitted. CANCEL and INTERRUPT need
+/// `call_canceling` to be negotiated for the caller and callee, CHALLENGE and AUTHENTICATE a
+/// challenge based authentication method offered in the HELLO.
+/// ## Examples
+/// ```
+/// use wamp_core::features::NegotiatedFeatures;
+/// use wamp_core::messages::{Cancel, Hello, Messages, Welcome};
+/// use serde_json::json;
+///
+/// let hello = Hello {
+///     realm: "realm1".into(),
+///     details: json!({ "roles": { "caller": { "features": { "call_canceling": true } } } })
+/// };
+/// let cancel = Messages::from(Cancel { request_id: 7, options: json!({}) });
+///
+/// let welcome = Welcome { session: 1, details: json!({ "roles": { "dealer": {} } }) };
+/// assert!(!NegotiatedFeatures::new(&hello, &welcome).permits(&cancel));
+///
+/// let welcome = Welcome {
+///     session: 1,
+///     details: json!({ "roles": { "dealer": { "features": { "call_canceling": true } } } })
+/// };
+/// assert!(NegotiatedFeatures::new(&hello, &welcome).permits(&cancel));
+/// ```
+pub struct NegotiatedFeatures {
+    roles: Value,
+    challenge_auth: bool,
+}
+
+impl NegotiatedFeatures {
+    /// The features known once the client sent `hello`, before the router answered.
+    ///
+    /// No role feature is negotiated yet, only the authentication methods are known.
+    pub fn from_hello(hello: &Hello) -> Self {
+        let offered: Vec<AuthMethod> =
+            serde_json::from_value(hello.details["authmethods"].clone()).unwrap_or_default();
+        NegotiatedFeatures {
+            roles: json!({}),
+            challenge_auth: offered.iter().any(AuthMethod::is_challenge_based),
+        }
+    }
+
+    /// The features of a session opened with `hello` and `welcome`, see
+    /// [`negotiated_features`].
+    pub fn new(hello: &Hello, welcome: &Welcome) -> Self {
+        NegotiatedFeatures {
+            roles: negotiated_features(hello, welcome),
+            ..Self::from_hello(hello)
+        }
+    }
+
+    /// Whether `feature` was negotiated.
+    pub fn has(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Role(role, feature) => self.roles[role.name()][feature] == json!(true),
+            Feature::ChallengeAuth => self.challenge_auth,
+        }
+    }
+
+    /// Whether the session may exchange `message`, that is whether the feature it requires,
+    /// if any, was negotiated. Extension messages are always permitted.
+    pub fn permits(&self, message: &Messages) -> bool {
+        message
+            .kind()
+            .and_then(|kind| kind.required_feature())
+            .is_none_or(|feature| self.has(feature))
+    }
+
+    /// # Check
+    /// Like [`permits`](Self::permits), but fails with the ABORT to send, a
+    /// [`protocol_violation`](Abort::protocol_violation) naming the missing feature.
+    pub fn check(&self, message: &Messages) -> Result<(), Abort> {
+        let Some(kind) = message.kind() else {
+            return Ok(());
+        };
+        match kind.required_feature() {
+            Some(feature) if !self.has(feature) => Err(Abort::protocol_violation(Some(&format!(
+                "{} requires {feature}, which was not negotiated.",
+                kind.name()
+            )))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::messages::{Hello, Welcome};
+
+    fn hello() -> Hello {
+        Hello {
+            realm: "realm1".into(),
+            details: json!({"roles": {
+                "caller": {"features": {"progressive_call_results": true, "call_timeout": true, "caller_identification": false}},
+                "subscriber": {"features": {"pattern_based_subscription": true}},
+                "publisher": {}
+            }}),
+        }
+    }
+
+    #[test]
+    fn overlapping() {
+        let welcome = Welcome {
+            session: 1,
+            details: json!({"roles": {
+                "dealer": {"features": {"progressive_call_results": true, "caller_identification": true, "call_timeout": false}},
+                "broker": {"features": {"pattern_based_subscription": true, "publisher_exclusion": true}}
+            }}),
+        };
+        assert_eq!(
+            negotiated_features(&hello(), &welcome),
+            json!({
+                "caller": {"progressive_call_results": true},
+                "subscriber": {"pattern_based_subscription": true},
+                "publisher": {}
+            })
+        );
+    }
+
+    #[test]
+    fn disjoint() {
+        let welcome = Welcome {
+            session: 1,
+            details: json!({"roles": {
+                "dealer": {"features": {"shared_registration": true}}
+            }}),
+        };
+        assert_eq!(
+            negotiated_features(&hello(), &welcome),
+            json!({"caller": {}})
+        );
+    }
+
+    #[test]
+    fn crossbar_welcome() {
+        let d1 = r#"[2,3256245574451374,{"realm":"realm1","authprovider":"static","roles":{"broker":{"features":{"publisher_identification":true,"pattern_based_subscription":true,"session_meta_api":true,"subscription_meta_api":true,"subscriber_blackwhite_listing":true,"publisher_exclusion":true,"subscription_revocation":true,"event_retention":true,"payload_transparency":true,"payload_encryption_cryptobox":true}},"dealer":{"features":{"caller_identification":true,"pattern_based_registration":true,"session_meta_api":true,"registration_meta_api":true,"shared_registration":true,"progressive_call_results":true,"registration_revocation":true,"payload_transparency":true,"testament_meta_api":true,"payload_encryption_cryptobox":true,"call_canceling":true}}},"authid":"NHGP-VMF6-UHRE-6PS4-CK9W-7UKC","authrole":"anonymous","authmethod":"anonymous","x_cb_node":"intel-nuci7-27532","x_cb_worker":"worker001","x_cb_peer":"tcp4:127.0.0.1:53398","x_cb_pid":27564}]"#;
+        let welcome: Welcome = serde_json::from_str(d1).unwrap();
+        let features = welcome.features();
+        assert!(features.pattern_based_subscription);
+        assert!(features.subscriber_blackwhite_listing);
+        assert!(features.progressive_call_results);
+        assert!(features.call_canceling);
+        assert!(features.session_meta_api);
+        assert!(features.testament_meta_api);
+        assert!(!features.call_timeout);
+        assert!(!features.event_history);
+        assert!(!features.payload_passthru_mode);
+        assert_eq!(features.unknown.len(), 2);
+        assert_eq!(features.unknown["payload_transparency"], json!(true));
+        assert_eq!(
+            RouterFeatures::from_details(&json!(null)),
+            RouterFeatures::default()
+        );
+    }
+
+    #[test]
+    fn role_features_serialize_enabled_only() {
+        let broker = BrokerFeatures {
+            pattern_based_subscription: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_string(&broker).unwrap(),
+            r#"{"pattern_based_subscription":true}"#
+        );
+        assert_eq!(
+            serde_json::to_value(CallerFeatures::default()).unwrap(),
+            json!({})
+        );
+
+        let callee: CalleeFeatures = serde_json::from_value(json!({
+            "progressive_call_results": true,
+            "call_canceling": false,
+            "shared_registration": {},
+            "x_acme_batching": true
+        }))
+        .unwrap();
+        assert_eq!(
+            callee,
+            CalleeFeatures {
+                progressive_call_results: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(callee.enabled(), vec!["progressive_call_results"]);
+
+        let dealer = DealerFeatures {
+            call_canceling: true,
+            session_meta_api: true,
+            ..Default::default()
+        };
+        let round_trip: DealerFeatures =
+            serde_json::from_value(serde_json::to_value(dealer).unwrap()).unwrap();
+        assert_eq!(round_trip, dealer);
+        assert!(serde_json::from_value::<PublisherFeatures>(json!([])).is_err());
+        assert_eq!(SubscriberFeatures::FEATURES.len(), 6);
+    }
+
+    #[test]
+    fn cancel_needs_call_canceling() {
+        let cancel: Messages = serde_json::from_str(r#"[49,7,{"mode":"kill"}]"#).unwrap();
+        let interrupt: Messages = serde_json::from_str(r#"[69,8,{}]"#).unwrap();
+        let published: Messages = serde_json::from_str(r#"[17,1,2]"#).unwrap();
+        let dealer = Welcome {
+            session: 1,
+            details: json!({"roles": {"dealer": {"features": {"call_canceling": true}}}}),
+        };
+
+        let negotiated = NegotiatedFeatures::new(&hello(), &dealer);
+        assert!(!negotiated.has(Feature::Role(Roles::Caller, "call_canceling")));
+        assert!(!negotiated.permits(&cancel));
+        assert!(!negotiated.permits(&interrupt));
+        assert!(negotiated.permits(&published));
+
+        let hello = Hello {
+            realm: "realm1".into(),
+            details: json!({"roles": {
+                "caller": {"features": {"call_canceling": true}},
+                "callee": {}
+            }}),
+        };
+        let negotiated = NegotiatedFeatures::new(&hello, &dealer);
+        assert!(negotiated.permits(&cancel));
+        assert!(!negotiated.permits(&interrupt));
+        assert_eq!(
+            Feature::Role(Roles::Callee, "call_canceling").to_string(),
+            "the call_canceling feature of the callee"
+        );
+    }
+
+    #[test]
+    fn challenge_needs_offered_method() {
+        let challenge: Messages = serde_json::from_str(r#"[4,"ticket",{}]"#).unwrap();
+        assert!(!NegotiatedFeatures::from_hello(&hello()).permits(&challenge));
+
+        let hello = crate::auth::hello_with_methods("realm1", &[AuthMethod::Ticket], None);
+        assert!(NegotiatedFeatures::from_hello(&hello).permits(&challenge));
+        let anonymous = crate::auth::hello_with_methods("realm1", &[AuthMethod::Anonymous], None);
+        assert!(!NegotiatedFeatures::from_hello(&anonymous).permits(&challenge));
+    }
+}
@@ -19,6 +19,51 @@ pub mod error;
 pub mod factories;
 pub mod uri;
 
+/// Authentication method announcement and outcome.
+pub mod auth;
+
+/// Advanced profile feature announcement and negotiation.
+pub mod features;
+
+/// Line delimited encoding and decoding of WAMP message streams.
+pub mod codec;
+
+/// Transport encodings and WebSocket subprotocols.
+pub mod transport;
+
+/// Raw socket transport handshake and framing.
+pub mod rawsocket;
+
+/// Broker side publish and subscribe helpers.
+pub mod pubsub;
+
+/// Client side tracking of requests waiting for a response.
+pub mod pending;
+
+/// Routing state of an embedded router.
+pub mod router;
+
+/// Strict and lossy parsing of JSON frames.
+pub mod parse;
+
+/// Dealer side re-routing of calls to unavailable callees.
+pub mod rpc;
+
+/// Client session state and outgoing message ordering.
+pub mod session;
+
+/// Glob import of the commonly used types, traits and macros.
+pub mod prelude;
+
+/// JSON Schemas of the WAMP messages.
+#[cfg(feature = "schemars")]
+pub mod schema;
+
+mod trace;
+
+/// WAMP meta API procedure constructors and result parsers.
+pub mod meta;
+
 pub use regex;
 pub use serde;
 pub use serde_json;
@@ -30,4 +75,5 @@ pub use http;
 pub use messages::*;
 pub use error::*;
 pub use factories::*;
-pub use uri::*;
\ No newline at end of file
+pub use uri::*;
+pub use parse::{parse, parse_lossy, FieldPath, ParseWarning, WarningKind};
\ No newline at end of file
@@ -1,11 +1,7 @@
 use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::{
-    de::{self, Visitor},
-    Deserialize, Serialize,
-};
-use serde_json::Value;
-use std::marker::PhantomData;
+use crate::{auth::AuthMethod, error::WampErrorUri, roles::Roles};
+use serde::Serialize;
+use serde_json::{json, Value};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Abort - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-abort-2)
@@ -111,36 +107,92 @@ macro_rules! abort {
     };
 }
 
+impl Abort {
+    /// An ABORT with `reason`, and `message` as the human readable `details.message`.
+    fn with_message(reason: WampErrorUri, message: Option<&str>) -> Abort {
+        let details = match message {
+            Some(message) => json!({ "message": message }),
+            None => json!({}),
+        };
+        abort!(reason.as_str(), details)
+    }
+
+    /// # Authentication denied
+    /// The ABORT of a session whose credentials were rejected, with the reason
+    /// `wamp.error.authentication_denied`.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Abort;
+    /// use serde_json::json;
+    ///
+    /// let abort = Abort::auth_denied(Some("Unknown ticket."));
+    ///
+    /// assert_eq!(abort.reason, "wamp.error.authentication_denied");
+    /// assert_eq!(abort.details, json!({ "message": "Unknown ticket." }));
+    /// ```
+    pub fn auth_denied(message: Option<&str>) -> Abort {
+        Abort::with_message(WampErrorUri::AuthenticationDenied, message)
+    }
+
+    /// # Authentication failed
+    /// The ABORT of a session the router could not authenticate, for example because the
+    /// authenticator was unavailable, with the reason `wamp.error.authentication_failed`.
+    pub fn auth_failed(message: Option<&str>) -> Abort {
+        Abort::with_message(WampErrorUri::AuthenticationFailed, message)
+    }
+
+    /// # No matching auth method
+    /// The ABORT of a session that offered no authentication method the router accepts, with
+    /// the reason `wamp.error.no_matching_auth_method` and the `offered` methods as
+    /// `details.authmethods`.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Abort;
+    /// use wamp_core::auth::AuthMethod;
+    /// use serde_json::json;
+    ///
+    /// let abort = Abort::no_matching_auth_method(&[AuthMethod::Ticket, AuthMethod::WampCra]);
+    ///
+    /// assert_eq!(abort.reason, "wamp.error.no_matching_auth_method");
+    /// assert_eq!(abort.details["authmethods"], json!(["ticket", "wampcra"]));
+    /// ```
+    pub fn no_matching_auth_method(offered: &[AuthMethod]) -> Abort {
+        abort!(
+            WampErrorUri::NoMatchingAuthMethod.as_str(),
+            json!({ "authmethods": offered })
+        )
+    }
+
+    /// # Authorization denied
+    /// The ABORT of an authenticated session that may not join the realm, with the reason
+    /// `wamp.error.authorization_denied`.
+    pub fn authorization_denied(message: Option<&str>) -> Abort {
+        Abort::with_message(WampErrorUri::AuthorizationDenied, message)
+    }
+
+    /// # Protocol violation
+    /// The ABORT of a session whose peer broke the protocol, for example by sending a message
+    /// out of turn, with the reason `wamp.error.protocol_violation`.
+    pub fn protocol_violation(message: Option<&str>) -> Abort {
+        Abort::with_message(WampErrorUri::ProtocolViolation, message)
+    }
+}
+
 impl WampMessage for Abort {
     const ID: u64 = 3;
+    const NAME: &'static str = "ABORT";
+    const FIELDS: &'static [&'static str] = &["details", "reason"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &["message", "authmethods"];
 
-    fn direction(role: crate::roles::Roles) -> &'static super::MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+    fn direction(role: Roles) -> &'static MessageDirection {
+        direction_table!(role;
+            Callee: rx tx,
+            Caller: rx tx,
+            Publisher: rx tx,
+            Subscriber: rx tx,
+            Dealer: rx tx,
+            Broker: rx tx,
+        )
     }
 }
 
@@ -155,40 +207,20 @@ impl Serialize for Abort {
     }
 }
 
-impl<'de> Deserialize<'de> for Abort {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct AbortVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for AbortVisitor {
-            type Value = Abort;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("WAMP Abort frame, expressed as a sequence.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'vi>,
-            {
-                let message_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Message ID must be type u8.")?;
-                helpers::validate_id::<Abort, A, _>(&message_id, "Abort")?;
-                let details: Value =
-                    helpers::deser_seq_element(&mut seq, "Details must be a JSON value.")?;
-                let reason: String =
-                    helpers::deser_seq_element(&mut seq, "Reason must be a String.")?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                Ok(Abort { reason, details })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Abort",
-            &["reason", "details"],
-            AbortVisitor(PhantomData, PhantomData, PhantomData),
-        )
+deserialize_message!(
+    Abort,
+    expecting: "WAMP Abort frame, expressed as a sequence.",
+    id_error: "Message ID must be type u8.",
+    |seq| {
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(1, "details", "Details must be a JSON value."),
+        )?;
+        let reason: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "reason", "Reason must be a String."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+        Ok(Abort { reason, details })
     }
-}
+);
@@ -1,8 +1,7 @@
 use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::{de::Visitor, Deserialize, Serialize};
+use crate::{features::Feature, roles::Roles};
+use serde::Serialize;
 use serde_json::Value;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Authenticate - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-authenticate)
@@ -111,34 +110,19 @@ macro_rules! authenticate {
 
 impl WampMessage for Authenticate {
     const ID: u64 = 5;
+    const NAME: &'static str = "AUTHENTICATE";
+    const FIELDS: &'static [&'static str] = &["signature", "details"];
+    const REQUIRED_FEATURE: Option<Feature> = Some(Feature::ChallengeAuth);
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: tx,
+            Caller: tx,
+            Publisher: tx,
+            Subscriber: tx,
+            Dealer: rx,
+            Broker: rx,
+        )
     }
 }
 
@@ -153,43 +137,20 @@ impl Serialize for Authenticate {
     }
 }
 
-impl<'de> Deserialize<'de> for Authenticate {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct AuthenticateVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for AuthenticateVisitor {
-            type Value = Authenticate;
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("Wamp message containing authentication details")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Authenticate, A, _>(&message_id, "Authenticate")?;
-                let signature: String =
-                    helpers::deser_seq_element(&mut seq, "Signature must be type String.")?;
-                let details: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Details must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "Value must be object like")?;
-                Ok(Authenticate { signature, details })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Authenticate",
-            &["signature", "details"],
-            AuthenticateVisitor(PhantomData, PhantomData, PhantomData),
-        )
+deserialize_message!(
+    Authenticate,
+    expecting: "Wamp message containing authentication details",
+    id_error: "Message ID must be present and type u8.",
+    |seq| {
+        let signature: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(1, "signature", "Signature must be type String."),
+        )?;
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "details", "Details must be present and object like."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Value must be object like")?;
+        Ok(Authenticate { signature, details })
     }
-}
+);
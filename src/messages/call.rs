@@ -1,10 +1,8 @@
 use super::{helpers, MessageDirection, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Call - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-call-2)
@@ -18,7 +16,7 @@ use std::marker::PhantomData;
 /// let call = Call {
 ///     request_id: 1,
 ///     options: json!({ }),
-///     procedure: "procedure".to_string(),
+///     procedure: "procedure".into(),
 ///     args: Value::Null,
 ///     kwargs: Value::Null
 /// };
@@ -36,7 +34,7 @@ use std::marker::PhantomData;
 /// let call = Call {
 ///     request_id: 7814135,
 ///     options: json!({}),
-///     procedure: "com.myapp.user.new".to_string(),
+///     procedure: "com.myapp.user.new".into(),
 ///     args: json!(["johnny"]),
 ///     kwargs: json!({"firstname":"John","surname":"Doe"})
 /// };
@@ -55,7 +53,7 @@ use std::marker::PhantomData;
 /// let call = Call {
 ///     request_id: 7814135,
 ///     options: json!({}),
-///     procedure: "com.myapp.user.new".to_string(),
+///     procedure: "com.myapp.user.new".into(),
 ///     args: json!(["johnny"]),
 ///     kwargs: json!({"firstname":"John","surname":"Doe"})
 /// };
@@ -68,22 +66,48 @@ use std::marker::PhantomData;
 pub struct Call {
     pub request_id: u64,
     pub options: Value,
-    pub procedure: String,
+    pub procedure: Cow<'static, str>,
     pub args: Value,
     pub kwargs: Value,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Call Options
+/// Typed view of the `options` of a CALL, only the keys that are set are serialized.
+///
+/// Use it with the [`call!`](crate::call) macro as `call!(procedure, options: CallOptions { .. })`.
 pub struct CallOptions {
-    receive_progress: Option<bool>,
-    timeout: Option<u64>,
-    progress: Option<bool>,
-    disclose_me: Option<bool>,
-    ppt_cipher: Option<String>,
-    ppt_scheme: Option<String>,
-    ppt_serializer: Option<String>,
-    ppt_keyid: Option<String>,
-    rkey: Option<String>,
-    runmode: Option<String>
+    /// Asks the dealer for progressive results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receive_progress: Option<bool>,
+    /// Milliseconds after which the dealer cancels the call, `0` for no timeout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Marks the call as a progressive call invocation, more parts follow.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<bool>,
+    /// Asks the dealer to disclose the caller to the callee.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disclose_me: Option<bool>,
+    /// Cipher used to encrypt the payload, see [`PptOptions`](super::PptOptions).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_cipher: Option<String>,
+    /// Payload passthrough scheme, see [`PptOptions`](super::PptOptions).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_scheme: Option<String>,
+    /// Serializer of the inner payload, see [`PptOptions`](super::PptOptions).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_serializer: Option<String>,
+    /// Identifier of the encryption key, see [`PptOptions`](super::PptOptions).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_keyid: Option<String>,
+    /// Routing key of a sharded registration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rkey: Option<String>,
+    /// Run mode of a sharded registration, `"partition"` to route by `rkey`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runmode: Option<String>,
 }
 
 #[macro_export]
@@ -91,16 +115,14 @@ pub struct CallOptions {
 /// Call message builder with thread safe auto-incrementing request-ids.
 /// ### Examples
 /// ```
-/// use wamp_core::call;
-/// use wamp_core::messages::Call;
-/// use serde_json::{json, Value};
+/// use wamp_core::prelude::*;
 ///
 /// // Create a call message with default values
 /// let call = call!("procedure");
 ///
 /// // Which is the same as creating this:
 /// let call2 = Call {
-///     procedure: "procedure".to_string(),
+///     procedure: "procedure".into(),
 ///     request_id: 1,
 ///     options: json!({}),
 ///     args: Value::Null,
@@ -128,77 +150,111 @@ pub struct CallOptions {
 /// // Create a call with custom options, and both custom args and kwargs
 /// // Note that when you use all "required" arguments for the struuct, keyword arguments should not be used for args and kwargs
 /// let _ = call!("procedure", json!({}), json!([]), json!({}));
+///
+/// // Create a call with typed options, only the options that are set are sent
+/// let call = call!("procedure", options: CallOptions {
+///     timeout: Some(5000),
+///     receive_progress: Some(true),
+///     ..Default::default()
+/// }, args: json!([1, 2]));
+///
+/// assert_eq!(call.options, json!({ "timeout": 5000, "receive_progress": true }));
+/// assert_eq!(call.args, json!([1, 2]));
 /// ```
 macro_rules! call {
-    ($request_id:expr, $procedure:expr) => {
-        call! {$request_id, $procedure, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
+    ($procedure:expr) => {
+        $crate::call! {$procedure, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
+    };
+
+    ($procedure:expr, $options:expr) => {
+        $crate::call! {$procedure, $options, serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($request_id:expr, $procedure:expr, $options:expr) => {
-        call! {$request_id, $procedure, $options, serde_json::Value::Null, serde_json::Value::Null}
+    ($procedure:expr, args: $args:expr) => {
+        $crate::call! {$procedure, serde_json::json!({}), $args, serde_json::Value::Null}
     };
 
-    ($request_id:expr, $procedure:expr, args: $args:expr) => {
-        call! {$request_id, $procedure, serde_json::json!({}), $args, serde_json::Value::Null}
+    ($procedure:expr, kwargs: $kwargs:expr) => {
+        $crate::call! {$procedure, serde_json::json!({}), serde_json::Value::Null, $kwargs}
     };
 
-    ($request_id:expr, $procedure:expr, kwargs: $kwargs:expr) => {
-        call! {$request_id, $procedure, serde_json::json!({}), serde_json::Value::Null, $kwargs}
+    ($procedure:expr, args: $args:expr, kwargs: $kwargs:expr) => {
+        $crate::call! {$procedure, serde_json::json!({}), $args, $kwargs}
     };
 
-    ($request_id:expr, $procedure:expr, args: $args:expr, kwargs: $kwargs:expr) => {
-        call! {$request_id, $procedure, serde_json::json!({}), $args, $kwargs}
+    ($procedure:expr, options: $options:expr) => {
+        $crate::call! {$procedure, options: $options, args: serde_json::Value::Null, kwargs: serde_json::Value::Null}
     };
 
-    ($request_id:expr, $procedure:expr, $options:expr, args: $args:expr) => {
-        call! {$request_id, $procedure, $options, $args, serde_json::Value::Null}
+    ($procedure:expr, options: $options:expr, args: $args:expr) => {
+        $crate::call! {$procedure, options: $options, args: $args, kwargs: serde_json::Value::Null}
     };
 
-    ($request_id:expr, $procedure:expr, $options:expr, kwargs: $kwargs:expr) => {
-        call! {$request_id, $procedure, $options, serde_json::Value::Null, $kwargs}
+    ($procedure:expr, options: $options:expr, kwargs: $kwargs:expr) => {
+        $crate::call! {$procedure, options: $options, args: serde_json::Value::Null, kwargs: $kwargs}
     };
 
-    ($request_id:expr, $procedure:expr, $options:expr, $args:expr, $kwargs:expr) => {{
+    ($procedure:expr, options: $options:expr, args: $args:expr, kwargs: $kwargs:expr) => {{
+        let options: $crate::messages::CallOptions = $options;
+        let options = serde_json::to_value(options).expect("CallOptions serializes to an object");
+        $crate::call! {$procedure, options, $args, $kwargs}
+    }};
+
+    ($procedure:expr, $options:expr, args: $args:expr) => {
+        $crate::call! {$procedure, $options, $args, serde_json::Value::Null}
+    };
+
+    ($procedure:expr, $options:expr, kwargs: $kwargs:expr) => {
+        $crate::call! {$procedure, $options, serde_json::Value::Null, $kwargs}
+    };
+
+    ($procedure:expr, $options:expr, $args:expr, $kwargs:expr) => {{
         $crate::messages::Call {
             request_id: $crate::factories::increment(),
             options: $options,
-            procedure: $procedure.to_string(),
+            procedure: $procedure.into(),
             args: $args,
             kwargs: $kwargs,
         }
     }};
 }
 
+object_form!(Call {
+    #[serde(default)]
+    request_id: u64,
+    #[serde(default = "empty_object")]
+    options: Value,
+    procedure: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
 impl WampMessage for Call {
     const ID: u64 = 48;
+    const NAME: &'static str = "CALL";
+    const FIELDS: &'static [&'static str] =
+        &["request_id", "options", "procedure", "args", "kwargs"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &[
+        "receive_progress",
+        "progress",
+        "timeout",
+        "disclose_me",
+        "ppt_scheme",
+        "ppt_serializer",
+        "ppt_cipher",
+        "ppt_keyid",
+        "rkey",
+        "runmode",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Caller: tx,
+            Dealer: rx,
+            _: none,
+        )
     }
 }
 
@@ -247,84 +303,67 @@ impl Serialize for Call {
     }
 }
 
-impl<'de> Deserialize<'de> for Call {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct CallVisitor(
-            PhantomData<u8>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<String>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-        );
+deserialize_message!(Call, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Call",
+        helpers::FieldContext::new(1, "request_id", "Request ID must be present and type u64."),
+    )?;
+    let options: Value = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(2, "options", "Options must be present and object like."),
+    )?;
+    helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
+    let procedure: String = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(3, "procedure", "Procedure must be present and object like."),
+    )?;
+    let args: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+    let kwargs: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+    Ok(Call {
+        request_id,
+        options,
+        procedure: procedure.into(),
+        args,
+        kwargs,
+    })
+});
 
-        impl<'vi> Visitor<'vi> for CallVisitor {
-            type Value = Call;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Call components.")
-            }
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use serde_json::{from_str, from_value, json, to_value};
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Call, A, _>(&message_id, "Call")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64.",
-                )?;
-                let options: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Options must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
-                let procedure: String = helpers::deser_seq_element(
-                    &mut seq,
-                    "Procedure must be present and object like.",
-                )?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(Call {
-                    request_id,
-                    options,
-                    procedure,
-                    args,
-                    kwargs,
-                })
-            }
-        }
+    use super::{object_form, Call};
+
+    #[derive(Debug, Deserialize)]
+    struct Config {
+        #[serde(with = "object_form")]
+        startup: Call,
+    }
 
-        deserializer.deserialize_struct(
-            "Call",
-            &[
-                "request_id",
-                "message_id",
-                "options",
-                "procedure",
-                "args",
-                "kwargs",
-            ],
-            CallVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
+    #[test]
+    fn object_form_matches_wire_form() {
+        let config: Config = from_str(
+            r#"{"startup": {"procedure": "com.myapp.user.new", "args": ["johnny"], "kwargs": {"firstname": "John"}}}"#,
         )
+        .unwrap();
+        let wire: Call =
+            from_str(r#"[48,0,{},"com.myapp.user.new",["johnny"],{"firstname":"John"}]"#).unwrap();
+        assert_eq!(config.startup, wire);
+
+        let object = object_form::serialize(&wire, serde_json::value::Serializer).unwrap();
+        assert_eq!(
+            object,
+            json!({"request_id": 0, "options": {}, "procedure": "com.myapp.user.new", "args": ["johnny"], "kwargs": {"firstname": "John"}})
+        );
+        assert_eq!(object_form::deserialize(object).unwrap(), wire);
+        assert_eq!(to_value(&wire).unwrap()[0], json!(48));
+
+        let invalid = json!({"procedure": "com.myapp.user.new", "args": {"not": "an array"}});
+        assert!(object_form::deserialize(invalid).is_err());
+        assert!(from_value::<Config>(json!({"startup": {"topic": "x"}})).is_err());
     }
 }
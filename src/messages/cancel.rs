@@ -1,11 +1,7 @@
 use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::{
-    de::{self, Visitor},
-    Deserialize, Serialize,
-};
+use crate::{features::Feature, roles::Roles};
+use serde::Serialize;
 use serde_json::Value;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Cancel - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-cancel)
@@ -119,34 +115,17 @@ macro_rules! cancel {
 
 impl WampMessage for Cancel {
     const ID: u64 = 49;
+    const NAME: &'static str = "CANCEL";
+    const FIELDS: &'static [&'static str] = &["request_id", "options"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &["mode"];
+    const REQUIRED_FEATURE: Option<Feature> = Some(Feature::Role(Roles::Caller, "call_canceling"));
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Caller: tx,
+            Dealer: rx,
+            _: none,
+        )
     }
 }
 
@@ -161,43 +140,24 @@ impl Serialize for Cancel {
     }
 }
 
-impl<'de> Deserialize<'de> for Cancel {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct CancelVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for CancelVisitor {
-            type Value = Cancel;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("WAMP Cancel frame, expressed as a sequence.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'vi>,
-            {
-                let message_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Message ID must be type u64.")?;
-                helpers::validate_id::<Cancel, A, _>(&message_id, "Cancel")?;
-                let request_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Request ID must be a u64.")?;
-                let options: Value =
-                    helpers::deser_seq_element(&mut seq, "Options must be a JSON value.")?;
-                helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
-                Ok(Cancel {
-                    request_id,
-                    options,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
+deserialize_message!(
+    Cancel,
+    expecting: "WAMP Cancel frame, expressed as a sequence.",
+    id_error: "Message ID must be type u64.",
+    |seq| {
+        let request_id = helpers::deser_id_element(
+            &mut seq,
             "Cancel",
-            &["request_id", "options"],
-            CancelVisitor(PhantomData, PhantomData, PhantomData),
-        )
+            helpers::FieldContext::new(1, "request_id", "Request ID must be a u64."),
+        )?;
+        let options: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "options", "Options must be a JSON value."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
+        Ok(Cancel {
+            request_id,
+            options,
+        })
     }
-}
+);
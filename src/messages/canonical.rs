@@ -0,0 +1,157 @@
+//! # Canonical JSON
+//! [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) style serialization of JSON values, see
+//! [`Messages::canonical_bytes`](super::Messages::canonical_bytes).
+use std::io::Write;
+
+use serde_json::{Map, Number, Value};
+
+/// Drop empty trailing `kwargs`, then empty trailing `args`, so that empty and absent payloads
+/// serialize alike. `fields` are the field names after the message id.
+pub(crate) fn elide_empty_payload(elements: &mut Vec<Value>, fields: &[&str]) {
+    for (field, empty) in [
+        ("kwargs", Value::Object(Map::new())),
+        ("args", Value::Array(vec![])),
+    ] {
+        let position = fields.iter().position(|known| *known == field);
+        if position.is_some_and(|position| elements.len() == position + 2)
+            && elements.last() == Some(&empty)
+        {
+            elements.pop();
+        }
+    }
+}
+
+/// Write `value` with object keys sorted by their UTF-16 code units, no whitespace and
+/// numbers in their shortest ECMAScript form.
+pub(crate) fn write_canonical(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Array(elements) => {
+            out.push(b'[');
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_canonical(element, out);
+            }
+            out.push(b']');
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push(b'{');
+            for (index, (key, element)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_string(key, out);
+                out.push(b':');
+                write_canonical(element, out);
+            }
+            out.push(b'}');
+        }
+        Value::String(string) => write_string(string, out),
+        Value::Number(number) => write_number(number, out),
+        Value::Bool(true) => out.extend_from_slice(b"true"),
+        Value::Bool(false) => out.extend_from_slice(b"false"),
+        Value::Null => out.extend_from_slice(b"null"),
+    }
+}
+
+fn write_string(string: &str, out: &mut Vec<u8>) {
+    // serde_json escapes exactly like JSON.stringify: short escapes where they exist, lower
+    // case \u00xx for the other control characters, everything else verbatim.
+    serde_json::to_writer(out, string).expect("writing a string to a Vec can not fail");
+}
+
+fn write_number(number: &Number, out: &mut Vec<u8>) {
+    if number.is_i64() || number.is_u64() {
+        write!(out, "{number}").expect("writing to a Vec can not fail");
+        return;
+    }
+    let float = number.as_f64().unwrap_or_default();
+    if float == 0.0 {
+        out.push(b'0');
+        return;
+    }
+    if float < 0.0 {
+        out.push(b'-');
+    }
+    // `{:e}` gives the shortest digits that round trip, as ECMAScript requires.
+    let scientific = format!("{:e}", float.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("scientific notation has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("the exponent is an integer");
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+    let formatted = if k <= n && n <= 21 {
+        format!("{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat(-n as usize))
+    } else {
+        let sign = if n > 0 { "+" } else { "-" };
+        let exponent = (n - 1).abs();
+        match digits.split_at(1) {
+            (first, "") => format!("{first}e{sign}{exponent}"),
+            (first, rest) => format!("{first}.{rest}e{sign}{exponent}"),
+        }
+    };
+    out.extend_from_slice(formatted.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn canonical(value: Value) -> String {
+        let mut out = Vec::new();
+        write_canonical(&value, &mut out);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn rfc_8785_numbers() {
+        for (number, expected) in [
+            (json!(0.0), "0"),
+            (json!(-0.0), "0"),
+            (json!(1.0), "1"),
+            (json!(-1.5), "-1.5"),
+            (json!(1e21), "1e+21"),
+            (json!(1e20), "100000000000000000000"),
+            (json!(123456789.125), "123456789.125"),
+            (json!(0.000001), "0.000001"),
+            (json!(1e-7), "1e-7"),
+            (json!(4.5e-10), "4.5e-10"),
+            (json!(1e9 / 3.0), "333333333.3333333"),
+            (json!(9007199254740992u64), "9007199254740992"),
+            (json!(-42), "-42"),
+        ] {
+            assert_eq!(canonical(number), expected);
+        }
+    }
+
+    #[test]
+    fn rfc_8785_strings_and_keys() {
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{0080}": "Control\u{007f}",
+            "\u{00f6}": "Latin Small Letter O With Diaeresis"
+        });
+        assert_eq!(
+            canonical(value),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{0080}\":\"Control\u{007f}\",\
+             \"\u{00f6}\":\"Latin Small Letter O With Diaeresis\",\"\u{20ac}\":\"Euro Sign\",\
+             \"\u{1f600}\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+        );
+        assert_eq!(canonical(json!("\u{1}\t\"/")), r#""\u0001\t\"/""#);
+    }
+}
@@ -1,8 +1,7 @@
 use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::{de::Visitor, Deserialize, Serialize};
+use crate::{features::Feature, roles::Roles};
+use serde::Serialize;
 use serde_json::Value;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Challenge - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-challenge)
@@ -110,34 +109,19 @@ macro_rules! challenge {
 
 impl WampMessage for Challenge {
     const ID: u64 = 4;
+    const NAME: &'static str = "CHALLENGE";
+    const FIELDS: &'static [&'static str] = &["authmethod", "details"];
+    const REQUIRED_FEATURE: Option<Feature> = Some(Feature::ChallengeAuth);
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        direction_table!(role;
+            Callee: rx,
+            Caller: rx,
+            Publisher: rx,
+            Subscriber: rx,
+            Dealer: tx,
+            Broker: tx,
+        )
     }
 }
 
@@ -152,46 +136,23 @@ impl Serialize for Challenge {
     }
 }
 
-impl<'de> Deserialize<'de> for Challenge {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct ChallengeVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for ChallengeVisitor {
-            type Value = Challenge;
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("Wamp message containing authentication details")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Challenge, A, _>(&message_id, "Challenge")?;
-                let authmethod: String =
-                    helpers::deser_seq_element(&mut seq, "authmethod must be type String.")?;
-                let details: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Details must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "Value must be object like")?;
-                Ok(Challenge {
-                    authmethod,
-                    details,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Challenge",
-            &["authmethod", "details"],
-            ChallengeVisitor(PhantomData, PhantomData, PhantomData),
-        )
+deserialize_message!(
+    Challenge,
+    expecting: "Wamp message containing authentication details",
+    id_error: "Message ID must be present and type u8.",
+    |seq| {
+        let authmethod: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(1, "authmethod", "authmethod must be type String."),
+        )?;
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "details", "Details must be present and object like."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Value must be object like")?;
+        Ok(Challenge {
+            authmethod,
+            details,
+        })
     }
-}
+);
@@ -1,12 +1,16 @@
 use super::{
-    Call, Cancel, Invocation, MessageDirection, Publish, Register, Subscribe, Unregister,
+    Call, Cancel, Invocation, MessageDirection, Messages, Publish, Register, Subscribe, Unregister,
     Unsubscribe, WampMessage,
 };
-use crate::{messages::helpers, roles::Roles};
-use serde::{de::Visitor, Deserialize, Serialize};
+use crate::{
+    error::{Error, WampErrorUri},
+    messages::helpers,
+    roles::Roles,
+    uri::ErrorUriBuilder,
+};
+use serde::Serialize;
 use serde_json::{json, Value};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, Serialize_repr, Deserialize_repr, PartialEq, Eq)]
 #[repr(u64)]
@@ -21,6 +25,26 @@ pub enum WampErrorEvent {
     Call = Call::ID,
 }
 
+impl TryFrom<&Messages> for WampErrorEvent {
+    type Error = Error;
+
+    /// The error event replying to `message`, failing with [`Error::NoSuchWampErrorType`] for
+    /// messages that can not be answered with an ERROR.
+    fn try_from(message: &Messages) -> Result<Self, Error> {
+        match message {
+            Messages::Call(_) => Ok(WampErrorEvent::Call),
+            Messages::Cancel(_) => Ok(WampErrorEvent::Cancel),
+            Messages::Invocation(_) => Ok(WampErrorEvent::Invocation),
+            Messages::Publish(_) => Ok(WampErrorEvent::Publish),
+            Messages::Register(_) => Ok(WampErrorEvent::Register),
+            Messages::Unregister(_) => Ok(WampErrorEvent::Unregister),
+            Messages::Subscribe(_) => Ok(WampErrorEvent::Subscribe),
+            Messages::Unsubscribe(_) => Ok(WampErrorEvent::Unsubscribe),
+            _ => Err(Error::NoSuchWampErrorType(Box::new(message.clone()))),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Error
 /// Represents an Error message in WAMP protocol.
@@ -142,6 +166,19 @@ pub struct WampError {
 /// // create error with empty default details and custom args and kwargs
 /// let _ = error!(WampErrorEvent::Call, 1, "wamp.error.unknown", args: json!([ 1, 2, 3 ]), kwargs: json!({ "key": "value" }));
 ///
+/// // create error with custom details, args and kwargs
+/// let error = error!(
+///     WampErrorEvent::Call,
+///     1,
+///     "wamp.error.unknown",
+///     json!({ "key": "value" }),
+///     args: json!([1, 2, 3]),
+///     kwargs: json!({ "key": "value" })
+/// );
+/// assert_eq!(error.details, json!({ "key": "value" }));
+/// assert_eq!(error.args, json!([1, 2, 3]));
+/// assert_eq!(error.kwargs, json!({ "key": "value" }));
+///
 /// // note that when you use all values, you do not need keyword arguments for args and kwargs
 /// let _ = error!(WampErrorEvent::Call, 1, "wamp.error.unknown", json!({}), json!([1, 2, 3]), json!({ "key": "value" }));
 /// ```
@@ -174,6 +211,10 @@ macro_rules! error {
         error! {$event, $request_id, $error, $details, serde_json::Value::Null, $kwargs}
     };
 
+    ($event:expr, $request_id:expr, $error:expr, $details:expr, args: $args:expr, kwargs: $kwargs:expr) => {
+        error! {$event, $request_id, $error, $details, $args, $kwargs}
+    };
+
     ($event:expr, $request_id:expr, $error:expr, $details:expr, $args:expr, $kwargs:expr) => {
         WampError {
             event: $event,
@@ -186,36 +227,280 @@ macro_rules! error {
     };
 }
 
+#[macro_export]
+/// # Error For Macro
+/// Constructs the ERROR replying to a request, inferring the [`WampErrorEvent`] and request id
+/// from the request instead of passing them by hand. Details, args and kwargs are given as with
+/// [`error!`](crate::error).
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Call, WampErrorEvent};
+/// use wamp_core::error_for;
+/// use serde_json::json;
+///
+/// let call = Call {
+///     request_id: 7814135,
+///     options: json!({}),
+///     procedure: "com.myapp.ping".into(),
+///     args: json!(null),
+///     kwargs: json!(null)
+/// };
+///
+/// let error = error_for!(&call, "wamp.error.no_such_procedure");
+/// assert_eq!(error.event, WampErrorEvent::Call);
+/// assert_eq!(error.request_id, 7814135);
+/// assert_eq!(error.details, json!({}));
+///
+/// // Details, args and kwargs work like they do with `error!`
+/// let error = error_for!(&call, "wamp.error.invalid_argument", args: json!(["bad input"]));
+/// assert_eq!(error.args, json!(["bad input"]));
+/// ```
+macro_rules! error_for {
+    ($request:expr, $error:expr) => {
+        $crate::error_for! {$request, $error, serde_json::json!({})}
+    };
+
+    ($request:expr, $error:expr, args: $args:expr, kwargs: $kwargs:expr) => {
+        $crate::error_for! {$request, $error, serde_json::json!({}), $args, $kwargs}
+    };
+
+    ($request:expr, $error:expr, args: $args:expr) => {
+        $crate::error_for! {$request, $error, serde_json::json!({}), $args, serde_json::Value::Null}
+    };
+
+    ($request:expr, $error:expr, kwargs: $kwargs:expr) => {
+        $crate::error_for! {$request, $error, serde_json::json!({}), serde_json::Value::Null, $kwargs}
+    };
+
+    ($request:expr, $error:expr, $details:expr) => {
+        $crate::error_for! {$request, $error, $details, serde_json::Value::Null, serde_json::Value::Null}
+    };
+
+    ($request:expr, $error:expr, $details:expr, args: $args:expr) => {
+        $crate::error_for! {$request, $error, $details, $args, serde_json::Value::Null}
+    };
+
+    ($request:expr, $error:expr, $details:expr, kwargs: $kwargs:expr) => {
+        $crate::error_for! {$request, $error, $details, serde_json::Value::Null, $kwargs}
+    };
+
+    ($request:expr, $error:expr, $details:expr, $args:expr, $kwargs:expr) => {{
+        let request = $request;
+        $crate::messages::WampError {
+            event: $crate::messages::ErrorRequest::error_event(request),
+            request_id: $crate::messages::ErrorRequest::request_id(request),
+            details: $details,
+            error: $error.to_string(),
+            args: $args,
+            kwargs: $kwargs,
+        }
+    }};
+}
+
+/// # Error Request
+/// A request message that can be answered with an ERROR.
+pub trait ErrorRequest {
+    /// The event of the ERROR replying to the request.
+    fn error_event(&self) -> WampErrorEvent;
+
+    /// The id of the request.
+    fn request_id(&self) -> u64;
+
+    /// # Application error
+    /// The ERROR replying to the request with the application error `name` below the
+    /// namespace of `errors`, carrying `kwargs`.
+    ///
+    /// Fails when `name` does not make a valid URI, see [`ErrorUriBuilder::child`].
+    fn error_app(
+        &self,
+        errors: &ErrorUriBuilder,
+        name: &str,
+        kwargs: Value,
+    ) -> Result<WampError, Error> {
+        Ok(WampError {
+            event: self.error_event(),
+            request_id: self.request_id(),
+            details: json!({}),
+            error: errors.child(name)?,
+            args: Value::Null,
+            kwargs,
+        })
+    }
+}
+
+/// Generates the `WampError::from_*` constructors and the [`ErrorRequest`] implementations,
+/// one per request type.
+macro_rules! error_from_request {
+    ($($name:ident: $request:ident,)*) => {
+        impl WampError {
+            $(
+                #[doc = concat!(
+                    "Creates the ERROR replying to a [`", stringify!($request), "`] with ",
+                    "`error_uri`, using its request id and [`WampErrorEvent::",
+                    stringify!($request), "`]."
+                )]
+                pub fn $name(request: &$request, error_uri: &str) -> WampError {
+                    WampError {
+                        event: WampErrorEvent::$request,
+                        request_id: request.request_id,
+                        details: json!({}),
+                        error: error_uri.to_string(),
+                        args: Value::Null,
+                        kwargs: Value::Null,
+                    }
+                }
+            )*
+        }
+
+        $(
+            impl ErrorRequest for $request {
+                fn error_event(&self) -> WampErrorEvent {
+                    WampErrorEvent::$request
+                }
+
+                fn request_id(&self) -> u64 {
+                    self.request_id
+                }
+            }
+        )*
+    };
+}
+
+error_from_request! {
+    from_call: Call,
+    from_cancel: Cancel,
+    from_invocation: Invocation,
+    from_publish: Publish,
+    from_register: Register,
+    from_unregister: Unregister,
+    from_subscribe: Subscribe,
+    from_unsubscribe: Unsubscribe,
+}
+
+impl WampError {
+    /// # For request
+    /// Creates the ERROR replying to a request message with the error `uri`, using the request
+    /// id of the request.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Messages, Register, WampError, WampErrorEvent, Welcome};
+    /// use wamp_core::error::WampErrorUri;
+    /// use serde_json::{json, Value};
+    ///
+    /// let request = Messages::from(Register {
+    ///     request_id: 25349185,
+    ///     options: json!({}),
+    ///     procedure: "com.myapp.add".into()
+    /// });
+    ///
+    /// let error = WampError::for_request(&request, WampErrorUri::ProcedureAlreadyExists).unwrap();
+    /// assert_eq!(error.event, WampErrorEvent::Register);
+    /// assert_eq!(error.request_id, 25349185);
+    /// assert_eq!(error.error, "wamp.error.procedure_already_exists");
+    ///
+    /// let welcome = Messages::from(Welcome { session: 1, details: json!({}) });
+    /// assert!(WampError::for_request(&welcome, WampErrorUri::Canceled).is_err());
+    /// ```
+    pub fn for_request(request: &Messages, uri: WampErrorUri) -> Result<WampError, Error> {
+        let event = WampErrorEvent::try_from(request)?;
+        let request_id = match request {
+            Messages::Call(v) => v.request_id,
+            Messages::Cancel(v) => v.request_id,
+            Messages::Invocation(v) => v.request_id,
+            Messages::Publish(v) => v.request_id,
+            Messages::Register(v) => v.request_id,
+            Messages::Unregister(v) => v.request_id,
+            Messages::Subscribe(v) => v.request_id,
+            Messages::Unsubscribe(v) => v.request_id,
+            _ => return Err(Error::NoSuchWampErrorType(Box::new(request.clone()))),
+        };
+        Ok(WampError {
+            event,
+            request_id,
+            details: json!({}),
+            error: uri.to_string(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        })
+    }
+}
+
+object_form!(WampError {
+    event: WampErrorEvent,
+    #[serde(default)]
+    request_id: u64,
+    #[serde(default = "empty_object")]
+    details: Value,
+    error: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
+impl std::fmt::Display for WampError {
+    /// Renders the error for humans, as `ERROR(call, request 7814135): com.myapp.error.x`
+    /// followed by the first positional argument when it is a string.
+    ///
+    /// Use `Serialize` for the wire form, this is only meant for logs.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{WampError, WampErrorEvent};
+    /// use serde_json::json;
+    ///
+    /// let error = WampError {
+    ///     event: WampErrorEvent::Call,
+    ///     request_id: 7814135,
+    ///     details: json!({}),
+    ///     error: "com.myapp.error.overflow".to_string(),
+    ///     args: json!(["value too large"]),
+    ///     kwargs: json!(null)
+    /// };
+    ///
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     r#"ERROR(call, request 7814135): com.myapp.error.overflow ["value too large"]"#
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let event = match self.event {
+            WampErrorEvent::Unsubscribe => "unsubscribe",
+            WampErrorEvent::Subscribe => "subscribe",
+            WampErrorEvent::Publish => "publish",
+            WampErrorEvent::Register => "register",
+            WampErrorEvent::Unregister => "unregister",
+            WampErrorEvent::Invocation => "invocation",
+            WampErrorEvent::Cancel => "cancel",
+            WampErrorEvent::Call => "call",
+        };
+        write!(
+            f,
+            "ERROR({event}, request {}): {}",
+            self.request_id, self.error
+        )?;
+        match self.args.get(0) {
+            Some(message @ Value::String(_)) => write!(f, " [{message}]"),
+            _ => Ok(()),
+        }
+    }
+}
+
 impl WampMessage for WampError {
     const ID: u64 = 8;
+    const NAME: &'static str = "ERROR";
+    const FIELDS: &'static [&'static str] =
+        &["event", "request_id", "details", "error", "args", "kwargs"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        // Callees also receive ERROR, in reply to a failed REGISTER or UNREGISTER.
+        direction_table!(role;
+            Callee: rx tx,
+            Caller: rx,
+            Publisher: rx,
+            Subscriber: rx,
+            Dealer: rx tx,
+            Broker: tx,
+        )
     }
 }
 
@@ -282,86 +567,155 @@ impl Serialize for WampError {
     }
 }
 
-impl<'de> Deserialize<'de> for WampError {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct WampErrorVisitor(
-            PhantomData<u64>,
-            PhantomData<WampErrorEvent>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<String>,
-            PhantomData<Value>,
-            PhantomData<Value>,
+deserialize_message!(
+    WampError,
+    expecting: "A sequence of WampError components.",
+    id_error: "Message id must be present and type u64.",
+    |seq| {
+        let event: WampErrorEvent = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(
+                1,
+                "event",
+                "Message type of error must be present and type u64",
+            ),
+        )?;
+        let request_id = helpers::deser_id_element(
+            &mut seq,
+            "WampError",
+            helpers::FieldContext::new(2, "request_id", "Request ID must be present and type u64"),
+        )?;
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(3, "details", "Details must be present and object like"),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+        let error: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(4, "error", "Error URI must be present and type String"),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+        let args: Value =
+            helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+        let kwargs: Value =
+            helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+        Ok(WampError {
+            event,
+            request_id,
+            details,
+            error,
+            args,
+            kwargs,
+        })
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::messages::{Event, Subscribe, Welcome};
+
+    #[test]
+    fn from_requests() {
+        let call: Call = serde_json::from_str(r#"[48,7814135,{},"com.myapp.add",[1,2]]"#).unwrap();
+        let error = WampError::from_call(&call, "com.myapp.error.overflow");
+        assert_eq!(error.event, WampErrorEvent::Call);
+        assert_eq!(error.request_id, 7814135);
+        assert_eq!(
+            serde_json::to_string(&error).unwrap(),
+            r#"[8,48,7814135,{},"com.myapp.error.overflow"]"#
         );
 
-        impl<'vi> Visitor<'vi> for WampErrorVisitor {
-            type Value = WampError;
+        let subscribe = Subscribe {
+            request_id: 713845233,
+            options: json!({}),
+            topic: "com.myapp.mytopic1".into(),
+        };
+        let error = WampError::from_subscribe(&subscribe, "wamp.error.not_authorized");
+        assert_eq!(error.event, WampErrorEvent::Subscribe);
+        assert_eq!(error.request_id, 713845233);
+    }
 
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of WampError components.")
-            }
+    #[test]
+    fn error_for_infers_event() {
+        let call: Call = serde_json::from_str(r#"[48,1,{},"com.myapp.add"]"#).unwrap();
+        let subscribe: Subscribe = serde_json::from_str(r#"[32,2,{},"com.myapp.topic1"]"#).unwrap();
+        let register: Register = serde_json::from_str(r#"[64,3,{},"com.myapp.add"]"#).unwrap();
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message id must be present and type u64.",
-                )?;
-                helpers::validate_id::<WampError, A, _>(&message_id, "WampError")?;
-                let event: WampErrorEvent = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message type of error must be present and type u64",
-                )?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64",
-                )?;
-                let details: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Details must be present and object like",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                let error: String = helpers::deser_seq_element(
-                    &mut seq,
-                    "Error URI must be present and type String",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(WampError {
-                    event,
-                    request_id,
-                    details,
-                    error,
-                    args,
-                    kwargs,
-                })
-            }
-        }
+        let error = crate::error_for!(&call, "wamp.error.no_such_procedure");
+        assert_eq!((error.event, error.request_id), (WampErrorEvent::Call, 1));
+        let error = crate::error_for!(&subscribe, "wamp.error.not_authorized", json!({ "a": 1 }));
+        assert_eq!(
+            (error.event, error.request_id),
+            (WampErrorEvent::Subscribe, 2)
+        );
+        assert_eq!(error.details, json!({ "a": 1 }));
+        let error = crate::error_for!(
+            &register,
+            WampErrorUri::ProcedureAlreadyExists.as_str(),
+            kwargs: json!({ "uri": "com.myapp.add" })
+        );
+        assert_eq!(
+            (error.event, error.request_id),
+            (WampErrorEvent::Register, 3)
+        );
+        assert_eq!(error.error, "wamp.error.procedure_already_exists");
+        assert_eq!(error.kwargs, json!({ "uri": "com.myapp.add" }));
+    }
 
-        deserializer.deserialize_struct(
-            "WampError",
-            &["event", "request_id", "details", "error", "args", "kwargs"],
-            WampErrorVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
-        )
+    #[test]
+    fn display() {
+        let mut error: WampError =
+            serde_json::from_str(r#"[8,32,713845233,{},"wamp.error.not_authorized"]"#).unwrap();
+        assert_eq!(
+            error.to_string(),
+            "ERROR(subscribe, request 713845233): wamp.error.not_authorized"
+        );
+        error.args = json!([42, "ignored"]);
+        assert_eq!(
+            error.to_string(),
+            "ERROR(subscribe, request 713845233): wamp.error.not_authorized"
+        );
+        error.args = json!(["no \"admin\" role", "second"]);
+        assert_eq!(
+            error.to_string(),
+            r#"ERROR(subscribe, request 713845233): wamp.error.not_authorized ["no \"admin\" role"]"#
+        );
+    }
+
+    #[test]
+    fn error_event_from_messages() {
+        let subscribe = Messages::from(Subscribe {
+            request_id: 713845233,
+            options: json!({}),
+            topic: "com.myapp.mytopic1".into(),
+        });
+        assert_eq!(
+            WampErrorEvent::try_from(&subscribe).unwrap(),
+            WampErrorEvent::Subscribe
+        );
+        let error = WampError::for_request(&subscribe, WampErrorUri::NotAuthorized).unwrap();
+        assert_eq!(error.request_id, 713845233);
+        assert_eq!(error.error, "wamp.error.not_authorized");
+
+        let event = Messages::from(Event {
+            subscription: 1,
+            publication: 2,
+            details: json!({}),
+            args: Value::Null,
+            kwargs: Value::Null,
+        });
+        let welcome = Messages::from(Welcome {
+            session: 1,
+            details: json!({}),
+        });
+        for message in [event, welcome] {
+            assert!(matches!(
+                WampErrorEvent::try_from(&message),
+                Err(Error::NoSuchWampErrorType(m)) if *m == message
+            ));
+        }
     }
 }
@@ -1,11 +1,9 @@
+use crate::error::Error;
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
 
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, MessageDirection, Publish, WampMessage};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Event - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-event-2)
@@ -157,36 +155,154 @@ macro_rules! event {
     }};
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Event Details
+/// Typed view of the `EVENT.Details` keys a broker sets.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Event, EventDetails};
+/// use serde_json::from_str;
+///
+/// let event: Event = from_str(r#"[36,5512315355,4429313566,{"publisher":3335656,"trustlevel":2}]"#).unwrap();
+/// let details = EventDetails::from_event(&event).unwrap();
+///
+/// assert_eq!(details.publisher, Some(3335656));
+/// assert_eq!(details.trust_level, Some(2));
+/// assert_eq!(event.trust_level(), Some(2));
+/// ```
+pub struct EventDetails {
+    /// The session id of the disclosed publisher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<u64>,
+    /// The authid of the disclosed publisher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher_authid: Option<String>,
+    /// The authrole of the disclosed publisher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher_authrole: Option<String>,
+    /// The concrete topic, for prefix and wildcard subscriptions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Whether the event is a retained one, delivered on subscribing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retained: Option<bool>,
+    /// The trust level the broker assigned to the publication.
+    #[serde(
+        default,
+        rename = "trustlevel",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub trust_level: Option<u64>,
+}
+
+impl EventDetails {
+    /// Parse the known details of an EVENT, ignoring every other key.
+    pub fn from_event(event: &Event) -> Result<Self, Error> {
+        match &event.details {
+            Value::Null => Ok(Self::default()),
+            details => Ok(Self::deserialize(details)?),
+        }
+    }
+}
+
+impl Event {
+    /// # From publish
+    /// Turns a PUBLISH into the EVENT a broker delivers for `subscription`.
+    ///
+    /// The payload is copied and the details are left empty. Subscriptions using prefix or
+    /// wildcard matching should also receive the concrete topic, chain [`Event::with_topic`].
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Event, Publish};
+    /// use serde_json::json;
+    ///
+    /// let publish = Publish {
+    ///     request_id: 239714735,
+    ///     options: json!({}),
+    ///     topic: "com.myapp.mytopic1".into(),
+    ///     args: json!(["Hello, world!"]),
+    ///     kwargs: json!(null)
+    /// };
+    ///
+    /// let event = Event::from_publish(&publish, 5512315355, 4429313566);
+    /// assert_eq!(event.args, publish.args);
+    /// assert_eq!(event.details, json!({}));
+    ///
+    /// let event = event.with_topic(&publish.topic);
+    /// assert_eq!(event.details, json!({ "topic": "com.myapp.mytopic1" }));
+    /// ```
+    pub fn from_publish(publish: &Publish, subscription: u64, publication: u64) -> Event {
+        Event {
+            subscription,
+            publication,
+            details: json!({}),
+            args: publish.args.clone(),
+            kwargs: publish.kwargs.clone(),
+        }
+    }
+
+    /// Attaches the concrete topic the event was published to in `details.topic`.
+    pub fn with_topic<T: ToString>(mut self, topic: T) -> Self {
+        if !self.details.is_object() {
+            self.details = json!({});
+        }
+        self.details["topic"] = json!(topic.to_string());
+        self
+    }
+
+    /// The trust level the broker assigned to the publication, `details.trustlevel`.
+    pub fn trust_level(&self) -> Option<u64> {
+        self.details["trustlevel"].as_u64()
+    }
+
+    /// Assigns the trust level of the publication in `details.trustlevel`.
+    pub fn with_trust_level(mut self, trust_level: u64) -> Self {
+        if !self.details.is_object() {
+            self.details = json!({});
+        }
+        self.details["trustlevel"] = json!(trust_level);
+        self
+    }
+}
+
+object_form!(Event {
+    #[serde(default)]
+    subscription: u64,
+    #[serde(default)]
+    publication: u64,
+    #[serde(default = "empty_object")]
+    details: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
 impl WampMessage for Event {
     const ID: u64 = 36;
+    const NAME: &'static str = "EVENT";
+    const FIELDS: &'static [&'static str] =
+        &["subscription", "publication", "details", "args", "kwargs"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &[
+        "publisher",
+        "publisher_authid",
+        "publisher_authrole",
+        "topic",
+        "retained",
+        "trustlevel",
+        "ppt_scheme",
+        "ppt_serializer",
+        "ppt_cipher",
+        "ppt_keyid",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        direction_table!(role;
+            Subscriber: rx,
+            Broker: tx,
+            _: none,
+        )
     }
 }
 
@@ -253,86 +369,49 @@ impl Serialize for Event {
     }
 }
 
-impl<'de> Deserialize<'de> for Event {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct EventVisitor(
-            PhantomData<u8>,
-            PhantomData<u64>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-        );
-
-        impl<'vi> Visitor<'vi> for EventVisitor {
-            type Value = Event;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Event components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Event, A, _>(&message_id, "Event")?;
-                let subscription: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Subscription must be present and type u64.",
-                )?;
-                let publication: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Publication must be present and object like.",
-                )?;
-                let details: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Details must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(Event {
-                    subscription,
-                    publication,
-                    details,
-                    args,
-                    kwargs,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Event",
-            &["subscription", "publication", "details", "args", "kwargs"],
-            EventVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
-        )
-    }
-}
+deserialize_message!(Event, |seq| {
+    let subscription = helpers::deser_id_element(
+        &mut seq,
+        "Event",
+        helpers::FieldContext::new(
+            1,
+            "subscription",
+            "Subscription must be present and type u64.",
+        ),
+    )?;
+    let publication = helpers::deser_id_element(
+        &mut seq,
+        "Event",
+        helpers::FieldContext::new(
+            2,
+            "publication",
+            "Publication must be present and object like.",
+        ),
+    )?;
+    let details: Value = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(3, "details", "Details must be present and object like."),
+    )?;
+    helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+    let args: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+    let kwargs: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+    Ok(Event {
+        subscription,
+        publication,
+        details,
+        args,
+        kwargs,
+    })
+});
 
 #[cfg(test)]
 mod tests {
     use serde_json::{from_str, to_string};
 
     use super::Event;
+    use crate::messages::Publish;
 
     #[test]
     fn test() {
@@ -351,4 +430,22 @@ mod tests {
         assert_eq!(ed, ed2);
         assert_eq!(d, d2);
     }
+
+    #[test]
+    fn from_publish() {
+        let publish = Publish {
+            request_id: 1,
+            options: serde_json::json!({"acknowledge": true}),
+            topic: "com.myapp.topic1".into(),
+            args: serde_json::json!([1, 2]),
+            kwargs: serde_json::json!({"color": "orange"}),
+        };
+        let event = Event::from_publish(&publish, 2, 3);
+        assert_eq!(event.args, publish.args);
+        assert_eq!(event.kwargs, publish.kwargs);
+        assert_eq!(
+            to_string(&event.with_topic("com.myapp.topic1")).unwrap(),
+            r#"[36,2,3,{"topic":"com.myapp.topic1"},[1,2],{"color":"orange"}]"#
+        );
+    }
 }
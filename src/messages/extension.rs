@@ -0,0 +1,427 @@
+//! Messages outside of the standard message set.
+use std::{any::Any, collections::HashMap, fmt};
+
+use serde::{ser::SerializeSeq, Serialize};
+use serde_json::{json, Value};
+
+use super::{Messages, MAX_ID, WAMP_MESSAGE_IDS};
+use crate::error::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Extension
+/// A message with an id no standard message uses, for router or vendor specific extensions.
+///
+/// Extension messages travel as [`Messages::Extension`], holding every wire element including
+/// the id. [`Extension::new`] makes sure the id does not shadow a standard message.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Extension, Messages};
+/// use serde_json::{json, to_string};
+///
+/// let extension = Extension::new(255, vec![json!("x")]).unwrap();
+/// assert_eq!(to_string(&extension).unwrap(), r#"[255,"x"]"#);
+///
+/// let message = Messages::from(extension);
+/// assert_eq!(message.id(), Some(255));
+///
+/// assert!(Extension::new(48, vec![]).is_err());
+/// ```
+pub struct Extension {
+    id: u64,
+    elements: Vec<Value>,
+}
+
+impl Extension {
+    /// Create an extension message from its id and the wire elements after it.
+    ///
+    /// Fails with [`Error::ReservedMessageId`] when `id` is the id of a standard message and
+    /// with [`Error::InvalidId`] when it is outside of the WAMP id range `[1, 2^53]`.
+    pub fn new(id: u64, elements: Vec<Value>) -> Result<Extension, Error> {
+        if WAMP_MESSAGE_IDS.contains(&id) {
+            return Err(Error::ReservedMessageId(id));
+        }
+        if id == 0 || id > MAX_ID {
+            return Err(Error::InvalidId(id));
+        }
+        Ok(Extension::new_unchecked(id, elements))
+    }
+
+    /// Create an extension message with any id, including the id of a standard message.
+    ///
+    /// Peers read such a message as the standard message, only use it to deliberately override
+    /// one.
+    pub fn new_unchecked(id: u64, elements: Vec<Value>) -> Extension {
+        Extension { id, elements }
+    }
+
+    /// The message id.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The wire elements after the message id.
+    pub fn elements(&self) -> &[Value] {
+        &self.elements
+    }
+}
+
+impl TryFrom<&Messages> for Extension {
+    type Error = Error;
+
+    /// The extension message held by [`Messages::Extension`], failing for standard messages
+    /// and extension messages without a numeric id.
+    fn try_from(message: &Messages) -> Result<Self, Self::Error> {
+        let Messages::Extension(values) = message else {
+            return Err(Error::InvalidMessageEnumMember);
+        };
+        match values.split_first() {
+            Some((id, elements)) => Ok(Extension::new_unchecked(
+                id.as_u64().ok_or(Error::InvalidMessageEnumMember)?,
+                elements.to_vec(),
+            )),
+            None => Err(Error::InvalidMessageEnumMember),
+        }
+    }
+}
+
+impl From<Extension> for Messages {
+    fn from(extension: Extension) -> Self {
+        let mut values = Vec::with_capacity(extension.elements.len() + 1);
+        values.push(json!(extension.id));
+        values.extend(extension.elements);
+        Messages::Extension(values)
+    }
+}
+
+impl Serialize for Extension {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.elements.len() + 1))?;
+        seq.serialize_element(&self.id)?;
+        for element in &self.elements {
+            seq.serialize_element(element)?;
+        }
+        seq.end()
+    }
+}
+
+impl Messages {
+    /// # Extension
+    /// An extension message with `id`, followed by `components` on the wire.
+    ///
+    /// Fails like [`Extension::new`] for the id of a standard message or an id outside of the
+    /// WAMP id range. The [`extension!`](crate::extension) macro wraps this.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Messages;
+    /// use serde_json::json;
+    ///
+    /// let message = Messages::extension(255, [json!("x"), json!(1)]).unwrap();
+    ///
+    /// assert_eq!(message, Messages::Extension(vec![json!(255), json!("x"), json!(1)]));
+    /// assert_eq!(message.extension_id(), Some(255));
+    /// assert_eq!(message.extension_components(), Some(&[json!("x"), json!(1)][..]));
+    ///
+    /// assert!(Messages::extension(48, []).is_err());
+    /// ```
+    pub fn extension(
+        id: u64,
+        components: impl IntoIterator<Item = Value>,
+    ) -> Result<Messages, Error> {
+        Ok(Extension::new(id, components.into_iter().collect())?.into())
+    }
+
+    /// The id of an extension message, `None` for standard messages and extension messages
+    /// without a numeric id.
+    pub fn extension_id(&self) -> Option<u64> {
+        match self {
+            Messages::Extension(values) => values.first()?.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// The wire elements of an extension message after its id, `None` for standard messages
+    /// and extension messages without a numeric id.
+    pub fn extension_components(&self) -> Option<&[Value]> {
+        self.extension_id()?;
+        match self {
+            Messages::Extension(values) => Some(&values[1..]),
+            _ => None,
+        }
+    }
+}
+
+#[macro_export]
+/// # Extension Macro
+/// Builds a [`Messages::Extension`] from its id and components with [`Messages::extension`],
+/// converting every component with `serde_json::Value::from`.
+/// ## Examples
+/// ```
+/// use wamp_core::extension;
+/// use wamp_core::error::Error;
+/// use serde_json::json;
+///
+/// let message = extension!(255, "com.myapp.x", 1, json!({ "key": "value" })).unwrap();
+/// assert_eq!(
+///     serde_json::to_string(&message).unwrap(),
+///     r#"[255,"com.myapp.x",1,{"key":"value"}]"#
+/// );
+///
+/// assert!(matches!(extension!(32, 1), Err(Error::ReservedMessageId(32))));
+/// assert!(matches!(extension!(0), Err(Error::InvalidId(0))));
+/// ```
+macro_rules! extension {
+    ($id:expr $(, $component:expr)* $(,)?) => {
+        $crate::messages::Messages::extension(
+            $id,
+            ::std::vec![$(serde_json::Value::from($component)),*],
+        )
+    };
+}
+
+/// A registered decoder, type erased.
+type Decoder = Box<dyn Fn(&Extension) -> Result<Box<dyn Any + Send>, Error> + Send + Sync>;
+
+#[derive(Default)]
+/// # Extension Registry
+/// Decoders turning extension messages into application types, keyed by message id.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Extension, ExtensionRegistry, Messages};
+/// use wamp_core::error::Error;
+/// use serde_json::from_str;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Heartbeat {
+///     sequence: u64,
+/// }
+///
+/// let mut registry = ExtensionRegistry::new();
+/// registry
+///     .register(250, |extension: &Extension| {
+///         let sequence = extension.elements().first().and_then(|v| v.as_u64());
+///         Ok(Heartbeat { sequence: sequence.ok_or(Error::Error("No sequence."))? })
+///     })
+///     .unwrap();
+///
+/// let message: Messages = from_str("[250,3]").unwrap();
+/// let heartbeat = registry.decode_as::<Heartbeat>(&message).unwrap();
+///
+/// assert_eq!(heartbeat, Some(Heartbeat { sequence: 3 }));
+/// ```
+pub struct ExtensionRegistry {
+    decoders: HashMap<u64, Decoder>,
+}
+
+impl ExtensionRegistry {
+    /// Create a registry without decoders.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode extension messages with id `id` with `decoder`, replacing the previous decoder.
+    ///
+    /// Fails with [`Error::ReservedMessageId`] when `id` is the id of a standard message, those
+    /// never deserialize as extension messages.
+    pub fn register<T, F>(&mut self, id: u64, decoder: F) -> Result<(), Error>
+    where
+        T: Any + Send,
+        F: Fn(&Extension) -> Result<T, Error> + Send + Sync + 'static,
+    {
+        if WAMP_MESSAGE_IDS.contains(&id) {
+            return Err(Error::ReservedMessageId(id));
+        }
+        let decoder: Decoder = Box::new(move |extension| {
+            decoder(extension).map(|value| Box::new(value) as Box<dyn Any + Send>)
+        });
+        self.decoders.insert(id, decoder);
+        Ok(())
+    }
+
+    /// Whether a decoder is registered for `id`.
+    pub fn contains(&self, id: u64) -> bool {
+        self.decoders.contains_key(&id)
+    }
+
+    /// # Decode
+    /// Decode an extension message with the decoder registered for its id.
+    ///
+    /// Returns `None` for standard messages and extension messages without a decoder, and
+    /// fails when the decoder does.
+    pub fn decode(&self, message: &Messages) -> Result<Option<Box<dyn Any + Send>>, Error> {
+        let Ok(extension) = Extension::try_from(message) else {
+            return Ok(None);
+        };
+        match self.decoders.get(&extension.id) {
+            Some(decoder) => decoder(&extension).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// # Decode as
+    /// [`decode`](ExtensionRegistry::decode) an extension message into a `T`.
+    ///
+    /// Returns `None` as well when the decoder of the message produces another type.
+    pub fn decode_as<T: Any>(&self, message: &Messages) -> Result<Option<T>, Error> {
+        Ok(self
+            .decode(message)?
+            .and_then(|value| (value as Box<dyn Any>).downcast().ok())
+            .map(|value| *value))
+    }
+}
+
+impl fmt::Debug for ExtensionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ids: Vec<&u64> = self.decoders.keys().collect();
+        ids.sort_unstable();
+        f.debug_struct("ExtensionRegistry")
+            .field("ids", &ids)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, to_string};
+
+    use super::*;
+    use crate::messages::{Call, WampMessage};
+
+    #[test]
+    fn standard_ids_are_reserved() {
+        match Extension::new(Call::ID, vec![json!(1)]) {
+            Err(Error::ReservedMessageId(id)) => assert_eq!(id, 48),
+            other => panic!("unexpected {other:?}"),
+        }
+        for id in WAMP_MESSAGE_IDS {
+            assert!(Extension::new(id, Vec::new()).is_err());
+        }
+
+        let shadow = Extension::new_unchecked(Call::ID, vec![json!(1)]);
+        assert_eq!(shadow.id(), 48);
+        assert_eq!(shadow.elements(), &[json!(1)]);
+        assert_eq!(
+            Messages::from(shadow),
+            Messages::Extension(vec![json!(48), json!(1)])
+        );
+    }
+
+    #[test]
+    fn round_trips_through_messages() {
+        let elements = vec![
+            json!("com.myapp.x"),
+            json!(-1.5),
+            json!(null),
+            json!(true),
+            json!({ "nested": [1, "two", { "three": 3 }] }),
+            json!([]),
+        ];
+        let extension = Extension::new(255, elements).unwrap();
+        let wire = r#"[255,"com.myapp.x",-1.5,null,true,{"nested":[1,"two",{"three":3}]},[]]"#;
+        assert_eq!(to_string(&extension).unwrap(), wire);
+
+        let message = Messages::from(extension);
+        assert_eq!(to_string(&message).unwrap(), wire);
+        assert_eq!(from_str::<Messages>(wire).unwrap(), message);
+        let round_trip: Messages = from_str(&to_string(&message).unwrap()).unwrap();
+        assert_eq!(round_trip, message);
+    }
+
+    #[test]
+    fn messages_extension() {
+        for id in WAMP_MESSAGE_IDS {
+            match Messages::extension(id, [json!(1)]) {
+                Err(Error::ReservedMessageId(reserved)) => assert_eq!(reserved, id),
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+        for id in [0, MAX_ID + 1, u64::MAX] {
+            match crate::extension!(id, "x") {
+                Err(Error::InvalidId(invalid)) => assert_eq!(invalid, id),
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+
+        let message = crate::extension!(MAX_ID, "com.myapp.x", 1.5, json!([null])).unwrap();
+        assert_eq!(message.extension_id(), Some(MAX_ID));
+        assert_eq!(
+            message.extension_components(),
+            Some(&[json!("com.myapp.x"), json!(1.5), json!([null])][..])
+        );
+        let round_trip: Messages = from_str(&to_string(&message).unwrap()).unwrap();
+        assert_eq!(round_trip, message);
+        assert_eq!(
+            round_trip.extension_components(),
+            message.extension_components()
+        );
+
+        let empty = crate::extension!(255).unwrap();
+        assert_eq!(empty.extension_components(), Some(&[][..]));
+        assert_eq!(Messages::Extension(vec![json!("x")]).extension_id(), None);
+        assert_eq!(Messages::Extension(Vec::new()).extension_components(), None);
+        let call = Messages::from(crate::call!("procedure"));
+        assert_eq!(call.extension_id(), None);
+        assert_eq!(call.extension_components(), None);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Presence {
+        user: String,
+        online: bool,
+    }
+
+    #[test]
+    fn registry_decodes_custom_frames() {
+        let mut registry = ExtensionRegistry::new();
+        registry
+            .register(200, |extension: &Extension| match extension.elements() {
+                [Value::String(user), Value::Bool(online)] => Ok(Presence {
+                    user: user.clone(),
+                    online: *online,
+                }),
+                _ => Err(Error::Error("Malformed presence.")),
+            })
+            .unwrap();
+        registry
+            .register(201, |extension: &Extension| Ok(extension.elements().len()))
+            .unwrap();
+        assert!(matches!(
+            registry.register(Call::ID, |_: &Extension| Ok(())),
+            Err(Error::ReservedMessageId(48))
+        ));
+        assert!(registry.contains(200));
+        assert!(!registry.contains(48));
+
+        let frame: Messages = from_str(r#"[200,"joe",true]"#).unwrap();
+        assert_eq!(
+            registry.decode_as::<Presence>(&frame).unwrap(),
+            Some(Presence {
+                user: "joe".to_string(),
+                online: true,
+            })
+        );
+        assert_eq!(registry.decode_as::<usize>(&frame).unwrap(), None);
+        let count = registry
+            .decode(&from_str(r#"[201,1,2,3]"#).unwrap())
+            .unwrap();
+        assert_eq!(
+            *(count.unwrap() as Box<dyn Any>)
+                .downcast::<usize>()
+                .unwrap(),
+            3
+        );
+
+        let malformed: Messages = from_str(r#"[200,"joe"]"#).unwrap();
+        assert!(registry.decode(&malformed).is_err());
+        let unknown: Messages = from_str(r#"[202]"#).unwrap();
+        assert!(registry.decode(&unknown).unwrap().is_none());
+        let call: Messages = from_str(r#"[48,1,{},"com.myapp.add"]"#).unwrap();
+        assert!(registry.decode(&call).unwrap().is_none());
+        assert_eq!(
+            format!("{registry:?}"),
+            "ExtensionRegistry { ids: [200, 201] }"
+        );
+    }
+}
@@ -1,11 +1,7 @@
 use super::{helpers, MessageDirection, WampMessage};
 use crate::roles::Roles;
-use serde::{
-    de::{self, Visitor},
-    Deserialize, Serialize,
-};
+use serde::Serialize;
 use serde_json::Value;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Goodbye - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-goodbye-2)
@@ -114,34 +110,12 @@ macro_rules! goodbye {
 
 impl WampMessage for Goodbye {
     const ID: u64 = 6;
+    const NAME: &'static str = "GOODBYE";
+    const FIELDS: &'static [&'static str] = &["details", "reason"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &["message"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &true,
-                sends: &true,
-            },
-        }
+        direction_table!(role; _: rx tx)
     }
 }
 
@@ -156,43 +130,23 @@ impl Serialize for Goodbye {
     }
 }
 
-impl<'de> Deserialize<'de> for Goodbye {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct GoodbyeVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for GoodbyeVisitor {
-            type Value = Goodbye;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("WAMP Goodbye frame, expressed as a sequence.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'vi>,
-            {
-                let message_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Message ID must be type u8.")?;
-                helpers::validate_id::<Goodbye, A, _>(&message_id, "Goodbye")?;
-                let details: Value =
-                    helpers::deser_seq_element(&mut seq, "Details must be a JSON value.")?;
-                let reason: String =
-                    helpers::deser_seq_element(&mut seq, "Reason must be a String.")?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                Ok(Goodbye { reason, details })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Goodbye",
-            &["reason", "details"],
-            GoodbyeVisitor(PhantomData, PhantomData, PhantomData),
-        )
+deserialize_message!(
+    Goodbye,
+    expecting: "WAMP Goodbye frame, expressed as a sequence.",
+    id_error: "Message ID must be type u8.",
+    |seq| {
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(1, "details", "Details must be a JSON value."),
+        )?;
+        let reason: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "reason", "Reason must be a String."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+        Ok(Goodbye { reason, details })
     }
-}
+);
 
 #[cfg(test)]
 mod tests {
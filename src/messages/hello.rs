@@ -1,11 +1,12 @@
 use super::{helpers, MessageDirection, WampMessage};
+use crate::auth::AuthMethod;
+use crate::error::Error;
+use crate::parse::{typed_details, ParseWarning};
 use crate::roles::Roles;
-use serde::{
-    de::{self, Visitor},
-    Deserialize, Serialize,
-};
-use serde_json::Value;
-use std::marker::PhantomData;
+use crate::uri::{Realm, UriMode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Hello - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-hello-2)
@@ -18,7 +19,7 @@ use std::marker::PhantomData;
 /// # let mut hello_message2 = hello!("realm");
 ///
 /// let hello_message = Hello {
-///     realm: "realm".to_string(),
+///     realm: "realm".into(),
 ///     details: json!({})
 /// };
 ///
@@ -31,7 +32,7 @@ use std::marker::PhantomData;
 ///
 /// // Create an Hello message
 /// let hello = Hello {
-///     realm: "realm".to_string(),
+///     realm: "realm".into(),
 ///     details: json!({ "key": "value" })
 /// };
 ///
@@ -62,18 +63,19 @@ use std::marker::PhantomData;
 /// ```
 /// Implements serde Serialize trait for hello
 pub struct Hello {
-    pub realm: String,
+    pub realm: Cow<'static, str>,
     pub details: Value,
 }
 
 #[macro_export]
 /// # Hello Macro - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-hello-2)
 /// Macro that allows for default empty implementation of details object on hello.
+///
+/// `hello!(realm, roles: [..])` builds the `details.roles` dict from a list of
+/// [`Roles`](crate::roles::Roles).
 /// ## Examples
 /// ```
-/// use wamp_core::messages::{self, Hello};
-/// use wamp_core::hello;
-/// use serde_json::json;
+/// use wamp_core::prelude::*;
 ///
 /// // Construct with default empty details object
 /// let mut hello_message = hello!("realm");
@@ -90,7 +92,7 @@ pub struct Hello {
 ///
 /// // These macro invocations are the same as the following:
 /// let hello_message3 = Hello {
-///     realm: "realm".to_string(),
+///     realm: "realm".into(),
 ///     details: json!({
 ///         "key": "value"
 ///     })
@@ -98,50 +100,338 @@ pub struct Hello {
 ///
 /// assert_eq!(hello_message, hello_message3);
 /// assert_eq!(hello_message2, hello_message3);
+///
+/// // Announce roles, each as an empty dict
+/// let hello_message4 = hello!("realm", roles: [Roles::Caller, Roles::Subscriber]);
+///
+/// assert_eq!(hello_message4, hello!("realm", json!({
+///     "roles": { "caller": {}, "subscriber": {} }
+/// })));
 /// ```
 macro_rules! hello {
     ($realm:expr) => {
         hello! {$realm, {serde_json::json!({})}}
     };
 
+    ($realm:expr, roles: [$($role:expr),* $(,)?]) => {
+        hello! {$realm, {
+            let roles: serde_json::Map<String, serde_json::Value> = [$($role),*]
+                .iter()
+                .map(|role: &$crate::roles::Roles| (role.name().to_string(), serde_json::json!({})))
+                .collect();
+            serde_json::json!({ "roles": roles })
+        }}
+    };
+
     ($realm:expr, $details:expr) => {
         Hello {
-            realm: $realm.to_string(),
+            realm: $realm.into(),
             details: $details,
         }
     };
 }
 
+#[macro_export]
+/// # Details Macro
+/// Macro building the details object of a HELLO or WELCOME, most of all its `roles`.
+///
+/// - `roles: [caller, subscriber]` announces each role as `{}`.
+/// - `roles: [caller { progressive_call_results, call_timeout: false }]` announces the
+///   features of a role, a feature without value is enabled.
+/// - Any other `key: value` pair is inserted as `serde_json::json!(value)`, the value being a
+///   JSON literal or an expression.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::Hello;
+/// use wamp_core::{details, hello};
+/// use serde_json::json;
+///
+/// // Announce roles without features
+/// let details = details! { roles: [caller, subscriber], authid: "bob" };
+/// assert_eq!(details, json!({
+///     "roles": { "caller": {}, "subscriber": {} },
+///     "authid": "bob"
+/// }));
+///
+/// // Announce the features of a role
+/// let hello_message = hello!("realm", details! {
+///     roles: [callee { progressive_call_results, shared_registration: true }, publisher],
+///     authmethods: ["ticket"]
+/// });
+///
+/// // Which is the same as creating this:
+/// let hello_message2 = Hello {
+///     realm: "realm".into(),
+///     details: json!({
+///         "roles": {
+///             "callee": {
+///                 "features": { "progressive_call_results": true, "shared_registration": true }
+///             },
+///             "publisher": {}
+///         },
+///         "authmethods": ["ticket"]
+///     })
+/// };
+///
+/// assert_eq!(hello_message, hello_message2);
+/// ```
+macro_rules! details {
+    (@entries $map:ident;) => {};
+    (@entries $map:ident; roles: [$($roles:tt)*] $(, $($rest:tt)*)?) => {
+        let mut roles = serde_json::Map::new();
+        $crate::details!(@roles roles; $($roles)*);
+        $map.insert("roles".to_string(), serde_json::Value::Object(roles));
+        $crate::details!(@entries $map; $($($rest)*)?);
+    };
+    (@entries $map:ident; $key:ident: $value:tt $(, $($rest:tt)*)?) => {
+        $map.insert(stringify!($key).to_string(), serde_json::json!($value));
+        $crate::details!(@entries $map; $($($rest)*)?);
+    };
+    (@entries $map:ident; $key:ident: $value:expr $(, $($rest:tt)*)?) => {
+        $map.insert(stringify!($key).to_string(), serde_json::json!($value));
+        $crate::details!(@entries $map; $($($rest)*)?);
+    };
+
+    (@roles $roles:ident;) => {};
+    (@roles $roles:ident; $role:ident { $($feature:ident $(: $enabled:expr)?),* $(,)? } $(, $($rest:tt)*)?) => {
+        let features: serde_json::Map<String, serde_json::Value> = [$((
+            stringify!($feature).to_string(),
+            serde_json::json!($crate::details!(@enabled $($enabled)?)),
+        ),)*]
+        .into_iter()
+        .collect();
+        $roles.insert(stringify!($role).to_string(), serde_json::json!({ "features": features }));
+        $crate::details!(@roles $roles; $($($rest)*)?);
+    };
+    (@roles $roles:ident; $role:ident $(, $($rest:tt)*)?) => {
+        $roles.insert(stringify!($role).to_string(), serde_json::json!({}));
+        $crate::details!(@roles $roles; $($($rest)*)?);
+    };
+
+    (@enabled) => {
+        true
+    };
+    (@enabled $enabled:expr) => {
+        $enabled
+    };
+
+    () => {
+        serde_json::Value::Object(serde_json::Map::new())
+    };
+    ($($entries:tt)*) => {{
+        let mut details = serde_json::Map::new();
+        $crate::details!(@entries details; $($entries)*);
+        serde_json::Value::Object(details)
+    }};
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Hello Details
+/// Typed view of the `HELLO.Details` keys.
+pub struct HelloDetails {
+    /// The client roles and the features they announce, keyed by role name.
+    #[serde(default)]
+    pub roles: Map<String, Value>,
+    /// The client implementation, for example `"AutobahnPython-0.10.2"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Authentication methods in order of preference.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authmethods: Vec<AuthMethod>,
+    /// The identity to authenticate as.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authid: Option<String>,
+    /// Additional authentication information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authextra: Option<Value>,
+}
+
+/// # Default agent
+/// The agent string identifying this crate, `"wamp-core/<version>"`.
+///
+/// Nothing sends it implicitly, opt in with [`HelloDetails::with_default_agent`].
+pub fn default_agent() -> &'static str {
+    concat!("wamp-core/", env!("CARGO_PKG_VERSION"))
+}
+
+impl HelloDetails {
+    /// # With agent
+    /// Sets the agent string announced to the router.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::HelloDetails;
+    ///
+    /// let details = HelloDetails::default().with_agent(concat!("my-app/", env!("CARGO_PKG_VERSION")));
+    /// assert!(details.agent.unwrap().starts_with("my-app/"));
+    /// ```
+    pub fn with_agent<T: Into<String>>(mut self, agent: T) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+
+    /// # With default agent
+    /// Announces [`default_agent`] unless an agent is already set.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{default_agent, HelloDetails};
+    ///
+    /// let details = HelloDetails::default().with_default_agent();
+    /// assert_eq!(details.agent.as_deref(), Some(default_agent()));
+    ///
+    /// let details = HelloDetails::default().with_agent("my-app").with_default_agent();
+    /// assert_eq!(details.agent.as_deref(), Some("my-app"));
+    /// ```
+    pub fn with_default_agent(mut self) -> Self {
+        if self.agent.is_none() {
+            self.agent = Some(default_agent().to_string());
+        }
+        self
+    }
+}
+
+impl Hello {
+    /// # Details typed
+    /// Parse the details into [`HelloDetails`], failing with
+    /// [`Error::MissingRoles`](crate::error::Error::MissingRoles) when they have no `roles`.
+    pub fn details_typed(&self) -> Result<HelloDetails, Error> {
+        typed_details(Self::NAME, &self.details, false).map(|(details, _)| details)
+    }
+
+    /// # Details typed lenient
+    /// Parse the details into [`HelloDetails`] like [`details_typed`](Self::details_typed), but
+    /// treat missing `roles` as an empty object, reported as [`WarningKind::MissingRoles`](crate::WarningKind::MissingRoles).
+    ///
+    /// Minimal clients, such as ones written for embedded devices, may leave out `roles`
+    /// although the spec requires it. Other malformed keys still fail.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Hello;
+    /// use wamp_core::WarningKind;
+    /// use serde_json::from_str;
+    ///
+    /// let hello: Hello = from_str(r#"[1,"realm1",{"agent":"tiny-client"}]"#).unwrap();
+    /// assert!(hello.details_typed().is_err());
+    ///
+    /// let (details, warnings) = hello.details_typed_lenient().unwrap();
+    /// assert!(details.roles.is_empty());
+    /// assert_eq!(details.agent.as_deref(), Some("tiny-client"));
+    /// assert_eq!(warnings[0].kind, WarningKind::MissingRoles);
+    /// assert_eq!(warnings[0].location.to_string(), "HELLO.details.roles");
+    /// ```
+    pub fn details_typed_lenient(&self) -> Result<(HelloDetails, Vec<ParseWarning>), Error> {
+        typed_details(Self::NAME, &self.details, true)
+    }
+
+    /// # Realm checked
+    /// The realm, validated as a WAMP URI in `mode`.
+    ///
+    /// Deserializing accepts any string as realm, routers call this (or
+    /// [`validate`](WampMessage::validate), which checks the realm in the configured
+    /// [`uri_mode`](super::ValidationConfig::uri_mode)) before looking the realm up.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Hello;
+    /// use wamp_core::uri::UriMode;
+    /// use serde_json::from_str;
+    ///
+    /// let hello: Hello = from_str(r#"[1,"MyRealm",{}]"#).unwrap();
+    ///
+    /// assert_eq!(hello.realm_checked(UriMode::Loose).unwrap().as_str(), "MyRealm");
+    /// assert!(hello.realm_checked(UriMode::Strict).is_err());
+    /// ```
+    pub fn realm_checked(&self, mode: UriMode) -> Result<Realm, Error> {
+        Realm::parse(&self.realm, mode)
+    }
+
+    /// # For realm
+    /// Builds a HELLO joining an already validated realm.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Hello, HelloDetails};
+    /// use wamp_core::uri::Realm;
+    /// use serde_json::{json, to_string};
+    ///
+    /// let details = HelloDetails {
+    ///     agent: Some("wamp-core".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let hello = Hello::for_realm(Realm::new("realm1").unwrap(), details);
+    ///
+    /// assert_eq!(to_string(&hello).unwrap(), r#"[1,"realm1",{"agent":"wamp-core","roles":{}}]"#);
+    /// ```
+    pub fn for_realm(realm: Realm, details: HelloDetails) -> Hello {
+        Hello {
+            realm: realm.into(),
+            details: serde_json::to_value(details).expect("HelloDetails serializes to an object"),
+        }
+    }
+
+    /// # With features
+    /// Builds a HELLO announcing `roles`, each with its advanced features as
+    /// `details.roles.<role>.features`.
+    ///
+    /// The features are usually one of the [`features`](crate::features) structs serialized to a
+    /// value. Roles without any feature are announced as `{}`, and a role listed twice keeps its
+    /// last features.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Hello;
+    /// use wamp_core::features::CallerFeatures;
+    /// use wamp_core::uri::Realm;
+    /// use wamp_core::Roles;
+    /// use serde_json::{json, to_value};
+    ///
+    /// let caller = CallerFeatures { progressive_call_results: true, ..Default::default() };
+    /// let hello = Hello::with_features(
+    ///     Realm::new("realm1").unwrap(),
+    ///     &[(Roles::Caller, to_value(caller).unwrap()), (Roles::Subscriber, json!({}))],
+    /// );
+    ///
+    /// assert_eq!(hello.details, json!({
+    ///     "roles": {
+    ///         "caller": { "features": { "progressive_call_results": true } },
+    ///         "subscriber": {}
+    ///     }
+    /// }));
+    /// ```
+    pub fn with_features(realm: Realm, roles: &[(Roles, Value)]) -> Hello {
+        let roles = roles
+            .iter()
+            .map(|(role, features)| {
+                let mut announced = Map::new();
+                if features.as_object().is_none_or(|f| !f.is_empty()) {
+                    announced.insert("features".to_string(), features.clone());
+                }
+                (role.name().to_string(), Value::Object(announced))
+            })
+            .collect();
+        Hello::for_realm(
+            realm,
+            HelloDetails {
+                roles,
+                ..Default::default()
+            },
+        )
+    }
+}
+
 impl WampMessage for Hello {
     const ID: u64 = 1;
+    const NAME: &'static str = "HELLO";
+    const FIELDS: &'static [&'static str] = &["realm", "details"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] =
+        &["roles", "agent", "authmethods", "authid", "authextra"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: tx,
+            Caller: tx,
+            Publisher: tx,
+            Subscriber: tx,
+            Dealer: rx,
+            Broker: rx,
+        )
     }
 }
 
@@ -156,40 +446,158 @@ impl Serialize for Hello {
     }
 }
 
-impl<'de> Deserialize<'de> for Hello {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct HelloVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for HelloVisitor {
-            type Value = Hello;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("WAMP Hello frame, expressed as a sequence.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'vi>,
-            {
-                let message_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Message ID must be type u8.")?;
-                helpers::validate_id::<Hello, A, _>(&message_id, "Hello")?;
-                let realm: String =
-                    helpers::deser_seq_element(&mut seq, "realm must be a String.")?;
-                let details: Value =
-                    helpers::deser_seq_element(&mut seq, "Details must be a JSON value.")?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                Ok(Hello { realm, details })
-            }
-        }
+deserialize_message!(
+    Hello,
+    expecting: "WAMP Hello frame, expressed as a sequence.",
+    id_error: "Message ID must be type u8.",
+    |seq| {
+        let realm: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(1, "realm", "realm must be a String."),
+        )?;
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "details", "Details must be a JSON value."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+        Ok(Hello {
+            realm: realm.into(),
+            details,
+        })
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, from_value, json, to_value};
+
+    use super::{default_agent, Hello, HelloDetails};
+    use crate::features::{CalleeFeatures, CallerFeatures, PublisherFeatures, SubscriberFeatures};
+    use crate::messages::{ValidationConfig, WampMessage};
+    use crate::roles::Roles;
+    use crate::uri::{Realm, UriMode};
+
+    #[test]
+    fn agent_round_trip() {
+        let realm = || Realm::new("realm1").unwrap();
+        let plain = Hello::for_realm(realm(), HelloDetails::default());
+        assert!(plain.details.get("agent").is_none());
 
-        deserializer.deserialize_struct(
-            "Hello",
-            &["realm", "details"],
-            HelloVisitor(PhantomData, PhantomData, PhantomData),
+        let details = HelloDetails::default().with_agent("my-app/1.0");
+        let hello = Hello::for_realm(realm(), details.clone());
+        let hello: Hello = from_str(&serde_json::to_string(&hello).unwrap()).unwrap();
+        assert_eq!(from_value::<HelloDetails>(hello.details).unwrap(), details);
+
+        let hello = Hello::for_realm(realm(), HelloDetails::default().with_default_agent());
+        assert_eq!(hello.details["agent"], default_agent());
+    }
+
+    #[test]
+    fn realm_validation() {
+        let upper: Hello = from_str(r#"[1,"MyRealm",{"roles":{}}]"#).unwrap();
+        let spaces: Hello = from_str(r#"[1,"not a realm!!",{"roles":{}}]"#).unwrap();
+
+        assert!(upper.realm_checked(UriMode::Strict).is_err());
+        assert!(spaces.realm_checked(UriMode::Strict).is_err());
+        assert!(upper.realm_checked(UriMode::Loose).is_ok());
+        assert!(spaces.realm_checked(UriMode::Loose).is_err());
+
+        let strict = ValidationConfig {
+            uri_mode: UriMode::Strict,
+            ..Default::default()
+        };
+        assert!(upper.validate(&ValidationConfig::default()).is_ok());
+        assert!(upper.validate(&strict).is_err());
+        assert!(spaces.validate(&ValidationConfig::default()).is_err());
+    }
+
+    #[test]
+    fn autobahn_hello_features() {
+        let caller = CallerFeatures {
+            progressive_call_results: true,
+            call_timeout: true,
+            call_canceling: true,
+            caller_identification: true,
+            ..Default::default()
+        };
+        let callee = CalleeFeatures {
+            progressive_call_results: true,
+            call_timeout: true,
+            call_canceling: true,
+            caller_identification: true,
+            pattern_based_registration: true,
+            shared_registration: true,
+            ..Default::default()
+        };
+        let publisher = PublisherFeatures {
+            publisher_identification: true,
+            subscriber_blackwhite_listing: true,
+            publisher_exclusion: true,
+            ..Default::default()
+        };
+        let subscriber = SubscriberFeatures {
+            publisher_identification: true,
+            pattern_based_subscription: true,
+            ..Default::default()
+        };
+        let hello = Hello::with_features(
+            Realm::new("realm1").unwrap(),
+            &[
+                (Roles::Caller, to_value(caller).unwrap()),
+                (Roles::Callee, to_value(callee).unwrap()),
+                (Roles::Publisher, to_value(publisher).unwrap()),
+                (Roles::Subscriber, to_value(subscriber).unwrap()),
+            ],
+        );
+
+        let expected: Hello = from_str(
+            r#"[1,"realm1",{"roles":{"caller":{"features":{"caller_identification":true,"progressive_call_results":true,"call_canceling":true,"call_timeout":true}},"callee":{"features":{"caller_identification":true,"pattern_based_registration":true,"shared_registration":true,"progressive_call_results":true,"call_canceling":true,"call_timeout":true}},"publisher":{"features":{"publisher_identification":true,"subscriber_blackwhite_listing":true,"publisher_exclusion":true}},"subscriber":{"features":{"publisher_identification":true,"pattern_based_subscription":true}}}}]"#,
         )
+        .unwrap();
+        assert_eq!(hello, expected);
+        assert_eq!(
+            from_value::<CalleeFeatures>(hello.details["roles"]["callee"]["features"].clone())
+                .unwrap(),
+            callee
+        );
+    }
+
+    #[test]
+    fn details_macro() {
+        let caller = CallerFeatures {
+            progressive_call_results: true,
+            ..Default::default()
+        };
+        let hello = Hello::with_features(
+            Realm::new("realm1").unwrap(),
+            &[
+                (Roles::Caller, to_value(caller).unwrap()),
+                (Roles::Subscriber, json!({})),
+            ],
+        );
+        let details = crate::details! {
+            roles: [caller { progressive_call_results }, subscriber,],
+        };
+        assert_eq!(details, hello.details);
+
+        let authid = "bob".to_string();
+        let details = crate::details! {
+            authid: authid.as_str(),
+            roles: [callee { call_timeout: false }],
+            authextra: { "ticket": null }
+        };
+        assert_eq!(
+            details,
+            json!({
+                "authid": "bob",
+                "roles": { "callee": { "features": { "call_timeout": false } } },
+                "authextra": { "ticket": null }
+            })
+        );
+        assert_eq!(crate::details! {}, json!({}));
+        assert_eq!(
+            crate::details! { roles: [caller {}] },
+            json!({ "roles": { "caller": { "features": {} } } })
+        );
     }
 }
@@ -1,11 +1,7 @@
 use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::{
-    de::{self, Visitor},
-    Deserialize, Serialize,
-};
+use crate::{features::Feature, roles::Roles};
+use serde::Serialize;
 use serde_json::Value;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Interrupt - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-interrupt)
@@ -114,34 +110,17 @@ macro_rules! interrupt {
 
 impl WampMessage for Interrupt {
     const ID: u64 = 69;
+    const NAME: &'static str = "INTERRUPT";
+    const FIELDS: &'static [&'static str] = &["request_id", "options"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &["mode", "reason"];
+    const REQUIRED_FEATURE: Option<Feature> = Some(Feature::Role(Roles::Callee, "call_canceling"));
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: rx,
+            Dealer: tx,
+            _: none,
+        )
     }
 }
 
@@ -156,46 +135,27 @@ impl Serialize for Interrupt {
     }
 }
 
-impl<'de> Deserialize<'de> for Interrupt {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct InterruptVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for InterruptVisitor {
-            type Value = Interrupt;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("WAMP Interrupt frame, expressed as a sequence.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'vi>,
-            {
-                let message_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Message ID must be type u64.")?;
-                helpers::validate_id::<Interrupt, A, _>(&message_id, "Interrupt")?;
-                let request_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Request ID must be a u64.")?;
-                let options: Value =
-                    helpers::deser_seq_element(&mut seq, "Options must be a JSON value.")?;
-                helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
-                Ok(Interrupt {
-                    request_id,
-                    options,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
+deserialize_message!(
+    Interrupt,
+    expecting: "WAMP Interrupt frame, expressed as a sequence.",
+    id_error: "Message ID must be type u64.",
+    |seq| {
+        let request_id = helpers::deser_id_element(
+            &mut seq,
             "Interrupt",
-            &["request_id", "options"],
-            InterruptVisitor(PhantomData, PhantomData, PhantomData),
-        )
+            helpers::FieldContext::new(1, "request_id", "Request ID must be a u64."),
+        )?;
+        let options: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "options", "Options must be a JSON value."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
+        Ok(Interrupt {
+            request_id,
+            options,
+        })
     }
-}
+);
 /*
 #[cfg(test)]
 mod tests {
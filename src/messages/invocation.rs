@@ -1,10 +1,8 @@
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, Call, MessageDirection, WampMessage};
+use crate::error::Error;
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Invocation - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-invocation-2)
@@ -156,36 +154,165 @@ macro_rules! invocation {
     }};
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Invocation Details
+/// Typed view of the `INVOCATION.Details` keys a dealer sets.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Invocation, InvocationDetails};
+/// use serde_json::from_str;
+///
+/// let invocation: Invocation = from_str(r#"[68,6131533,9823529,{"caller":3335656,"trustlevel":1}]"#).unwrap();
+/// let details = InvocationDetails::from_invocation(&invocation).unwrap();
+///
+/// assert_eq!(details.caller, Some(3335656));
+/// assert_eq!(details.trust_level, Some(1));
+/// assert_eq!(invocation.trust_level(), Some(1));
+/// ```
+pub struct InvocationDetails {
+    /// The session id of the disclosed caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller: Option<u64>,
+    /// The authid of the disclosed caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller_authid: Option<String>,
+    /// The authrole of the disclosed caller.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caller_authrole: Option<String>,
+    /// The called procedure, for prefix and wildcard registrations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub procedure: Option<String>,
+    /// Whether the caller accepts progressive results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receive_progress: Option<bool>,
+    /// The call timeout in milliseconds, forwarded when the callee enforces it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// The trust level the dealer assigned to the call.
+    #[serde(
+        default,
+        rename = "trustlevel",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub trust_level: Option<u64>,
+}
+
+impl InvocationDetails {
+    /// Parse the known details of an INVOCATION, ignoring every other key.
+    pub fn from_invocation(invocation: &Invocation) -> Result<Self, Error> {
+        match &invocation.details {
+            Value::Null => Ok(Self::default()),
+            details => Ok(Self::deserialize(details)?),
+        }
+    }
+}
+
+impl Invocation {
+    /// # From call
+    /// Turns a CALL into the INVOCATION a dealer sends to the callee of `registration`.
+    ///
+    /// The payload is copied, and `receive_progress` in the call options is forwarded in the
+    /// invocation details. A caller asking for `disclose_me` can only be disclosed with its
+    /// session id, which the CALL does not carry, chain [`Invocation::with_caller`] for that.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Call, Invocation};
+    /// use serde_json::json;
+    ///
+    /// let call = Call {
+    ///     request_id: 7814135,
+    ///     options: json!({ "receive_progress": true, "disclose_me": true }),
+    ///     procedure: "com.myapp.user.new".into(),
+    ///     args: json!(["johnny"]),
+    ///     kwargs: json!(null)
+    /// };
+    ///
+    /// let invocation = Invocation::from_call(&call, 9823529, 6131533);
+    /// assert_eq!(invocation.request_id, 6131533);
+    /// assert_eq!(invocation.registration, 9823529);
+    /// assert_eq!(invocation.details, json!({ "receive_progress": true }));
+    ///
+    /// let invocation = invocation.with_caller(3335656);
+    /// assert_eq!(invocation.details["caller"], json!(3335656));
+    /// ```
+    pub fn from_call(call: &Call, registration: u64, invocation_request_id: u64) -> Invocation {
+        let mut details = json!({});
+        if call.options["receive_progress"].as_bool() == Some(true) {
+            details["receive_progress"] = json!(true);
+        }
+        Invocation {
+            request_id: invocation_request_id,
+            registration,
+            details,
+            args: call.args.clone(),
+            kwargs: call.kwargs.clone(),
+        }
+    }
+
+    /// Discloses the caller session id in `details.caller`.
+    pub fn with_caller(mut self, caller: u64) -> Self {
+        if !self.details.is_object() {
+            self.details = json!({});
+        }
+        self.details["caller"] = json!(caller);
+        self
+    }
+
+    /// The trust level the dealer assigned to the call, `details.trustlevel`.
+    pub fn trust_level(&self) -> Option<u64> {
+        self.details["trustlevel"].as_u64()
+    }
+
+    /// Assigns the trust level of the call in `details.trustlevel`.
+    pub fn with_trust_level(mut self, trust_level: u64) -> Self {
+        if !self.details.is_object() {
+            self.details = json!({});
+        }
+        self.details["trustlevel"] = json!(trust_level);
+        self
+    }
+}
+
+object_form!(Invocation {
+    #[serde(default)]
+    request_id: u64,
+    #[serde(default)]
+    registration: u64,
+    #[serde(default = "empty_object")]
+    details: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
 impl WampMessage for Invocation {
     const ID: u64 = 68;
+    const NAME: &'static str = "INVOCATION";
+    const FIELDS: &'static [&'static str] =
+        &["request_id", "registration", "details", "args", "kwargs"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &[
+        "caller",
+        "caller_authid",
+        "caller_authrole",
+        "procedure",
+        "receive_progress",
+        "progress",
+        "timeout",
+        "ppt_scheme",
+        "ppt_serializer",
+        "ppt_cipher",
+        "ppt_keyid",
+        "trustlevel",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: rx,
+            Dealer: tx,
+            _: none,
+        )
     }
 }
 
@@ -246,86 +373,45 @@ impl Serialize for Invocation {
     }
 }
 
-impl<'de> Deserialize<'de> for Invocation {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct InvocationVisitor(
-            PhantomData<u8>,
-            PhantomData<u64>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-        );
-
-        impl<'vi> Visitor<'vi> for InvocationVisitor {
-            type Value = Invocation;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Invocation components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Invocation, A, _>(&message_id, "Invocation")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                let registration: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "registration must be present and object like.",
-                )?;
-                let details: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Details must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(Invocation {
-                    request_id,
-                    registration,
-                    details,
-                    args,
-                    kwargs,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Invocation",
-            &["request_id", "registration", "details", "args", "kwargs"],
-            InvocationVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
-        )
-    }
-}
+deserialize_message!(Invocation, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Invocation",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let registration = helpers::deser_id_element(
+        &mut seq,
+        "Invocation",
+        helpers::FieldContext::new(
+            2,
+            "registration",
+            "registration must be present and object like.",
+        ),
+    )?;
+    let details: Value = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(3, "details", "Details must be present and object like."),
+    )?;
+    helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
+    let args: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+    let kwargs: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+    Ok(Invocation {
+        request_id,
+        registration,
+        details,
+        args,
+        kwargs,
+    })
+});
 
 #[cfg(test)]
 mod tests {
     use serde_json::{from_str, to_string};
 
     use super::Invocation;
+    use crate::messages::Call;
 
     #[test]
     fn test() {
@@ -344,4 +430,32 @@ mod tests {
         let d2 = to_string(&ed).unwrap();
         assert_eq!(d, d2);
     }
+
+    #[test]
+    fn from_call() {
+        let call = Call {
+            request_id: 1,
+            options: serde_json::json!({"receive_progress": true}),
+            procedure: "com.myapp.add".into(),
+            args: serde_json::json!([1, 2]),
+            kwargs: serde_json::json!({"round": true}),
+        };
+        let invocation = Invocation::from_call(&call, 2, 3);
+        assert_eq!(invocation.args, call.args);
+        assert_eq!(invocation.kwargs, call.kwargs);
+        assert_eq!(invocation.details["receive_progress"], true);
+        assert_eq!(
+            to_string(&invocation).unwrap(),
+            r#"[68,3,2,{"receive_progress":true},[1,2],{"round":true}]"#
+        );
+
+        let call = Call {
+            options: serde_json::json!({"receive_progress": false}),
+            ..call
+        };
+        assert_eq!(
+            Invocation::from_call(&call, 2, 3).details,
+            serde_json::json!({})
+        );
+    }
 }
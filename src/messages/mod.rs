@@ -1,58 +1,373 @@
+/// Builds the body of [`WampMessage::direction`] from a compact role matrix.
+///
+/// Each entry maps a role to `rx`, `tx`, `rx tx` or `none`, a trailing `_` entry covers every
+/// role that is not listed.
+/// ```ignore
+/// direction_table!(role;
+///     Caller: tx,
+///     Dealer: rx,
+///     _: none,
+/// )
+/// ```
+macro_rules! direction_table {
+    (@dir none) => {
+        $crate::messages::MessageDirection {
+            receives: false,
+            sends: false,
+        }
+    };
+    (@dir rx) => {
+        $crate::messages::MessageDirection {
+            receives: true,
+            sends: false,
+        }
+    };
+    (@dir tx) => {
+        $crate::messages::MessageDirection {
+            receives: false,
+            sends: true,
+        }
+    };
+    (@dir rx tx) => {
+        $crate::messages::MessageDirection {
+            receives: true,
+            sends: true,
+        }
+    };
+    (@munch $role:expr; [$($arms:tt)*]; _: $($dir:ident)+ $(,)?) => {
+        match $role {
+            $($arms)*
+            _ => &direction_table!(@dir $($dir)+),
+        }
+    };
+    (@munch $role:expr; [$($arms:tt)*]; $r:ident: $($dir:ident)+ $(, $($rest:tt)*)?) => {
+        direction_table!(@munch $role;
+            [$($arms)* $crate::roles::Roles::$r => &direction_table!(@dir $($dir)+),];
+            $($($rest)*)?)
+    };
+    (@munch $role:expr; [$($arms:tt)*];) => {
+        match $role {
+            $($arms)*
+        }
+    };
+    ($role:expr; $($spec:tt)*) => {
+        direction_table!(@munch $role; []; $($spec)*)
+    };
+}
+
+/// Generates a `pub mod object_form` serde adapter for a message struct, serializing it as an
+/// object keyed by field name instead of the positional wire form.
+///
+/// Fields are listed with the serde attributes of the named form, `empty_object` is in scope
+/// for `default = "empty_object"`.
+macro_rules! object_form {
+    ($message:ident { $($(#[$attr:meta])* $field:ident: $ty:ty,)* }) => {
+        #[doc = concat!("# Object form\nSerde adapter reading and writing [`", stringify!($message), "`]")]
+        /// as an object keyed by field name, for embedding messages in configuration files.
+        ///
+        /// Use it with `#[serde(with = "...")]` on a field. Ids default to `0` and the
+        /// options or details to `{}` when left out, absent payloads are `Null`.
+        pub mod object_form {
+            use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+            #[allow(unused_imports)]
+            use serde_json::{json, Value};
+
+            #[allow(unused_imports)]
+            use super::*;
+
+            #[allow(dead_code)]
+            fn empty_object() -> Value {
+                json!({})
+            }
+
+            #[derive(Serialize, Deserialize)]
+            #[serde(deny_unknown_fields)]
+            struct Object {
+                $($(#[$attr])* $field: $ty,)*
+            }
+
+            /// Serialize the message as an object.
+            pub fn serialize<S: Serializer>(
+                message: &$message,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                Object {
+                    $($field: message.$field.clone(),)*
+                }
+                .serialize(serializer)
+            }
+
+            /// Deserialize the message from an object, rejecting messages their `Serialize`
+            /// implementation rejects, such as options that are not an object.
+            ///
+            /// The check serializes the message to a `Value` once. Checks of the wire form
+            /// `Deserialize` applies beyond that, such as the id range, do not run.
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<$message, D::Error> {
+                let object = Object::deserialize(deserializer)?;
+                let message = $message {
+                    $($field: object.$field,)*
+                };
+                serde_json::to_value(&message).map_err(de::Error::custom)?;
+                Ok(message)
+            }
+        }
+    };
+}
+
+/// Implements `Deserialize` for a message struct from its positional wire form.
+///
+/// The generated visitor reads the message id and checks it against [`WampMessage::ID`], then
+/// runs `$body`, which reads the remaining elements from `$seq` and evaluates to the message.
+/// The `SeqAccess` of the body is named `A`, for helpers taking it as a type parameter.
+///
+/// The `expecting` text and the error for a missing or mistyped message id default to
+/// `"A sequence of <Message> components."` and `"Message ID must be present and type u8."`,
+/// messages with texts of their own pass them before the body.
+macro_rules! deserialize_message {
+    ($message:ident, |$seq:ident| $body:block) => {
+        deserialize_message!(
+            $message,
+            expecting: concat!("A sequence of ", stringify!($message), " components."),
+            id_error: "Message ID must be present and type u8.",
+            |$seq| $body
+        );
+    };
+    (
+        $message:ident,
+        expecting: $expecting:expr,
+        id_error: $id_error:expr,
+        |$seq:ident| $body:block
+    ) => {
+        impl<'de> serde::Deserialize<'de> for $message {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct MessageVisitor;
+
+                impl<'vi> serde::de::Visitor<'vi> for MessageVisitor {
+                    type Value = $message;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str($expecting)
+                    }
+
+                    fn visit_seq<A>(self, mut $seq: A) -> Result<Self::Value, A::Error>
+                    where
+                        A: serde::de::SeqAccess<'vi>,
+                    {
+                        let message_id: u64 = $crate::messages::helpers::deser_seq_element(
+                            &mut $seq,
+                            $crate::messages::helpers::FieldContext::new(
+                                0,
+                                "message_id",
+                                $id_error,
+                            ),
+                        )?;
+                        $crate::messages::helpers::validate_id::<$message, A, _>(
+                            &message_id,
+                            stringify!($message),
+                        )?;
+                        $body
+                    }
+                }
+
+                deserializer.deserialize_struct(
+                    stringify!($message),
+                    <$message as $crate::messages::WampMessage>::FIELDS,
+                    MessageVisitor,
+                )
+            }
+        }
+    };
+}
+
+/// Implements `unknown_keys` for message structs, listing the keys of their `options` or
+/// `details` object missing from [`WampMessage::KNOWN_OPTION_KEYS`] or
+/// [`WampMessage::KNOWN_DETAIL_KEYS`].
+macro_rules! unknown_keys {
+    ($field:ident, $known:ident: $($message:ty),* $(,)?) => {
+        $(
+            impl $message {
+                #[doc = concat!(
+                    "# Unknown keys\nThe keys of `", stringify!($field), "` this crate does not ",
+                    "know, in order, see [`WampMessage::", stringify!($known), "`]."
+                )]
+                ///
+                /// Peers may send keys of their own, so this is meant for debugging interop
+                /// issues, not for rejecting messages.
+                pub fn unknown_keys(&self) -> Vec<&str> {
+                    validate::unknown_keys(&self.$field, <$message>::$known)
+                }
+
+                /// # Unknown key warnings
+                /// The unknown keys as [`WarningKind::UnknownKey`](crate::WarningKind::UnknownKey)
+                /// warnings, leaving out custom keys starting with `_`.
+                pub fn unknown_key_warnings(&self) -> Vec<crate::ParseWarning> {
+                    self.unknown_keys()
+                        .into_iter()
+                        .filter(|key| !key.starts_with('_'))
+                        .map(|key| {
+                            let location = crate::FieldPath::new(<$message>::NAME)
+                                .join(stringify!($field))
+                                .join(key);
+                            crate::ParseWarning::new(
+                                location,
+                                crate::WarningKind::UnknownKey,
+                                "is not known, kept it",
+                            )
+                        })
+                        .collect()
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `Display` for message structs, rendering the same wire JSON as `Serialize`.
+///
+/// Messages that do not serialize, for example with options that are not an object, render as
+/// `<invalid Type: reason>` instead, `Display` is used for logging and must not fail.
+///
+/// [`WampError`] is left out, its `Display` is a human readable summary.
+macro_rules! wire_display {
+    ($($message:ty),* $(,)?) => {
+        $(
+            impl std::fmt::Display for $message {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match serde_json::to_string(self) {
+                        Ok(json) => f.write_str(&json),
+                        Err(error) => {
+                            write!(f, "<invalid {}: {error}>", stringify!($message))
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
 pub mod abort;
 pub mod authenticate;
 pub mod call;
+mod canonical;
 pub mod cancel;
 pub mod challenge;
 pub mod error;
 pub mod event;
+pub mod extension;
 pub mod goodbye;
 pub mod hello;
 pub mod interrupt;
 pub mod invocation;
+/// Reference counted payloads shared between cloned messages.
+pub mod payload;
+/// Payload passthrough mode (PPT) metadata.
+pub mod ppt;
 pub mod publish;
 pub mod published;
+/// Redaction of sensitive values for logging.
+pub mod redact;
 pub mod register;
 pub mod registered;
 pub mod result;
 pub mod subscribe;
 pub mod subscribed;
+/// Compile time typed payload messages.
+pub mod typed;
 pub mod unregister;
 pub mod unregistered;
 pub mod unsubscribe;
 pub mod unsubscribed;
+/// Semantic validation of outgoing messages.
+pub mod validate;
 pub mod welcome;
 pub mod r#yield;
 
 pub use abort::Abort;
 pub use authenticate::Authenticate;
-pub use call::Call;
+pub use call::{Call, CallOptions};
 pub use cancel::Cancel;
 pub use challenge::Challenge;
-pub use error::{WampError, WampErrorEvent};
-pub use event::Event;
+pub use error::{ErrorRequest, WampError, WampErrorEvent};
+pub use event::{Event, EventDetails};
+pub use extension::{Extension, ExtensionRegistry};
 pub use goodbye::Goodbye;
-pub use hello::Hello;
+pub use hello::{default_agent, Hello, HelloDetails};
 pub use interrupt::Interrupt;
-pub use invocation::Invocation;
+pub use invocation::{Invocation, InvocationDetails};
+pub use payload::{Payload, SharedEvent};
+pub use ppt::PptOptions;
 pub use publish::Publish;
 pub use published::Published;
 pub use r#yield::Yield;
+pub use redact::RedactionPolicy;
 pub use register::Register;
 pub use registered::Registered;
-pub use result::WampResult;
+pub use result::{CallFailure, CallOutcome, WampResult};
 pub use subscribe::Subscribe;
 pub use subscribed::Subscribed;
 use tungstenite::Message;
+pub use typed::{TypedCall, TypedEvent, TypedInvocation, TypedPublish, TypedResult, TypedYield};
 pub use unregister::Unregister;
 pub use unregistered::Unregistered;
 pub use unsubscribe::Unsubscribe;
 pub use unsubscribed::Unsubscribed;
+pub use validate::{Severity, ValidationConfig, ValidationIssue};
 pub use welcome::Welcome;
 
-use serde::{de, Deserialize, Deserializer};
-use serde_json::{from_str, from_value, json, Value};
+use serde::{de, Deserialize, Serialize};
+use serde_json::{from_value, Value};
 
-use crate::roles::Roles;
+unknown_keys!(
+    options, KNOWN_OPTION_KEYS:
+    Call, Cancel, Interrupt, Publish, Register, Subscribe, Yield,
+);
+unknown_keys!(
+    details, KNOWN_DETAIL_KEYS:
+    Abort, Event, Goodbye, Hello, Invocation, WampResult, Welcome,
+);
+
+wire_display!(
+    Abort,
+    Authenticate,
+    Call,
+    Cancel,
+    Challenge,
+    Event,
+    Goodbye,
+    Hello,
+    Interrupt,
+    Invocation,
+    Publish,
+    Published,
+    Register,
+    Registered,
+    WampResult,
+    Subscribe,
+    Subscribed,
+    Unregister,
+    Unregistered,
+    Unsubscribe,
+    Unsubscribed,
+    Welcome,
+    Yield,
+    SharedEvent,
+);
+
+use crate::{
+    features::Feature,
+    roles::Roles,
+    transport::{Encoding, FrameKind},
+};
+
+/// The largest valid WAMP id, `2^53`.
+///
+/// Session, publication, subscription, registration and request ids must all be in the range
+/// `[1, MAX_ID]`, so they are exactly representable in IEEE 754 doubles.
+pub const MAX_ID: u64 = 1 << 53;
 
 /// # Message parsing helpers
 ///
@@ -81,22 +396,66 @@ pub(crate) mod helpers {
     use serde_json::Value;
     use std::fmt::Display;
 
-    use super::WampMessage;
+    use super::{WampMessage, MAX_ID};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    /// Where an element sits in a message, reported when the element is missing.
+    pub(crate) struct FieldContext {
+        /// What the element must be, the error message.
+        pub(crate) message: &'static str,
+        /// Position of the element in the frame, the message id being at 0.
+        pub(crate) index: usize,
+        /// Wire name of the element, see [`WampMessage::FIELDS`].
+        pub(crate) field: &'static str,
+    }
+
+    impl FieldContext {
+        pub(crate) const fn new(index: usize, field: &'static str, message: &'static str) -> Self {
+            FieldContext {
+                message,
+                index,
+                field,
+            }
+        }
+    }
+
+    impl Display for FieldContext {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.message)
+        }
+    }
 
-    pub(crate) fn deser_seq_element<
-        'de,
-        T: PartialEq + Deserialize<'de>,
-        E: Display,
-        A: SeqAccess<'de>,
-    >(
+    pub(crate) fn deser_seq_element<'de, T: Deserialize<'de>, A: SeqAccess<'de>>(
         seq: &mut A,
-        error: E,
+        context: FieldContext,
     ) -> Result<T, <A as SeqAccess<'de>>::Error> {
-        let element: Option<T> = seq.next_element()?;
-        if element != None {
-            Ok(element.unwrap())
+        if let Some(element) = seq.next_element()? {
+            Ok(element)
         } else {
-            Err(serde::de::Error::custom(error))
+            Err(serde::de::Error::custom(context))
+        }
+    }
+
+    /// Read the id at `context` of `message`, accepting only integers up to `MAX_ID`.
+    ///
+    /// `0` is accepted, routers use it as the request id of the messages they send unprompted.
+    /// Floats are rejected even without a fractional part,
+    /// [`parse_lossy`](crate::parse::parse_lossy) repairs those.
+    pub(crate) fn deser_id_element<'de, A: SeqAccess<'de>>(
+        seq: &mut A,
+        message: &'static str,
+        context: FieldContext,
+    ) -> Result<u64, <A as SeqAccess<'de>>::Error> {
+        let value: Value = deser_seq_element(seq, context)?;
+        let FieldContext { index, field, .. } = context;
+        match value.as_u64() {
+            Some(id) if id <= MAX_ID => Ok(id),
+            Some(id) => Err(de::Error::custom(format_args!(
+                "{message}[{index}] ({field}) must be at most 2^53, got {id}"
+            ))),
+            None => Err(de::Error::custom(format_args!(
+                "{message}[{index}] ({field}) must be an integer, got {value}"
+            ))),
         }
     }
 
@@ -173,35 +532,242 @@ pub(crate) mod helpers {
             Err(S::Error::custom(e))
         }
     }
+
+    /// Serialize `value` as JSON into `buf`, replacing its content but keeping its capacity.
+    pub(crate) fn serialize_into<T: serde::Serialize>(
+        value: &T,
+        buf: &mut String,
+    ) -> Result<(), crate::error::Error> {
+        let mut bytes = std::mem::take(buf).into_bytes();
+        bytes.clear();
+        let result = serde_json::to_writer(&mut bytes, value);
+        if result.is_err() {
+            bytes.clear();
+        }
+        *buf = String::from_utf8(bytes).expect("serde_json only writes valid UTF-8");
+        Ok(result?)
+    }
+
+    /// A writer counting the bytes written to it, and dropping them.
+    struct ByteCounter(usize);
+
+    impl std::io::Write for ByteCounter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Length of the JSON serialization of `value` in bytes, without allocating it.
+    pub(crate) fn serialized_size<T: serde::Serialize>(
+        value: &T,
+    ) -> Result<usize, crate::error::Error> {
+        let mut counter = ByteCounter(0);
+        serde_json::to_writer(&mut counter, value)?;
+        Ok(counter.0)
+    }
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 /// # Message Direction
 /// Indicates the Message Direction for a specified Role.
 ///
 /// Receives means that the specified Role is allowed to receive the message.
 /// Sends means that the specified Role allowed to send the message.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Call, WampMessage};
+/// use wamp_core::roles::Roles;
+///
+/// let direction = Call::direction(Roles::Caller);
+/// assert!(direction.sends);
+/// assert!(!direction.receives);
+/// ```
 pub struct MessageDirection {
-    pub receives: &'static bool,
-    pub sends: &'static bool,
+    /// Whether the role is allowed to receive the message.
+    pub receives: bool,
+    /// Whether the role is allowed to send the message.
+    pub sends: bool,
+}
+
+/// # Direction Matrix
+/// The direction of every message for every role, one `(message id, role, direction)` row per
+/// cell, messages in the order of [`MessageKind::ALL`] and roles in the order of [`Roles::ALL`].
+///
+/// Built from the [`WampMessage::direction`] implementations, for tooling rendering the full
+/// capability matrix without going through every message type.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{direction_matrix, Subscribe, WampMessage};
+/// use wamp_core::roles::Roles;
+///
+/// let senders: Vec<Roles> = direction_matrix()
+///     .into_iter()
+///     .filter(|(id, _, direction)| *id == Subscribe::ID && direction.sends)
+///     .map(|(_, role, _)| role)
+///     .collect();
+///
+/// assert_eq!(senders, vec![Roles::Subscriber]);
+/// ```
+pub fn direction_matrix() -> [(u64, Roles, MessageDirection); 144] {
+    let none = MessageDirection {
+        receives: false,
+        sends: false,
+    };
+    let mut matrix = [(0, Roles::Callee, none); 144];
+    let cells = MessageKind::ALL
+        .iter()
+        .flat_map(|kind| Roles::ALL.map(|role| (kind.id(), role, *kind.direction(role))));
+    for (cell, row) in matrix.iter_mut().zip(cells) {
+        *cell = row;
+    }
+    matrix
 }
 
 pub trait WampMessage {
     const ID: u64;
 
+    /// The name of the message in the WAMP protocol, for example `"CALL"`.
+    const NAME: &'static str;
+
+    /// Names of the elements following the message id, in wire order.
+    ///
+    /// This is the field list the deserializer of the message is driven by, and the labels used
+    /// by [`Messages::to_annotated_string`].
+    const FIELDS: &'static [&'static str];
+
+    /// Known keys of the `options` object, checked by [`WampMessage::validate`].
+    ///
+    /// Empty for messages without options, and for those whose options are not checked.
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &[];
+
+    /// Known keys of the `details` object, checked by [`WampMessage::validate`].
+    ///
+    /// Empty for messages without details, and for those whose details are not checked.
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &[];
+
+    /// The feature a session must have negotiated to exchange the message, see
+    /// [`NegotiatedFeatures::permits`](crate::features::NegotiatedFeatures::permits).
+    ///
+    /// `None` for the messages of the basic profile.
+    const REQUIRED_FEATURE: Option<Feature> = None;
+
     /// # Direction method
     /// Indicates the Message Direction for a specified Role.
     ///
     /// Receives means that the specified Role is allowed to receive the message.
     /// Sends means that the specified Role allowed to send the message.
+    ///
+    /// The returned reference points at a promoted constant, no allocation takes place.
     fn direction(role: Roles) -> &'static MessageDirection;
+
+    /// # All directions
+    /// The direction of the message for every role, in the order of [`Roles::ALL`].
+    ///
+    /// Useful for tooling rendering the full capability matrix of a message.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Subscribe, WampMessage};
+    /// use wamp_core::roles::Roles;
+    ///
+    /// let senders: Vec<Roles> = Subscribe::all_directions()
+    ///     .into_iter()
+    ///     .filter(|(_, direction)| direction.sends)
+    ///     .map(|(role, _)| role)
+    ///     .collect();
+    ///
+    /// assert_eq!(senders, vec![Roles::Subscriber]);
+    /// ```
+    fn all_directions() -> [(Roles, MessageDirection); 6] {
+        Roles::ALL.map(|role| (role, *Self::direction(role)))
+    }
+
+    /// # Validate
+    /// Checks the content of every field, beyond the shape checked when deserializing, see
+    /// the [`validate`] module.
+    ///
+    /// Returns every issue found, warnings included.
+    fn validate(&self, config: &ValidationConfig) -> Result<(), Vec<ValidationIssue>>
+    where
+        Self: Serialize + Sized,
+    {
+        validate::validate_fields(
+            self,
+            Self::FIELDS,
+            Self::KNOWN_OPTION_KEYS,
+            Self::KNOWN_DETAIL_KEYS,
+            config,
+        )
+    }
+
+    /// # Serialize into
+    /// Serialize the message as JSON into `buf`, replacing its content.
+    ///
+    /// The allocation of `buf` is reused, so a connection serializing many messages only grows
+    /// one buffer instead of allocating a `String` per message. On failure `buf` is left empty.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Subscribed, WampMessage};
+    ///
+    /// let mut buf = String::with_capacity(64);
+    /// Subscribed { request_id: 3, subscription: 5512315355 }.serialize_into(&mut buf).unwrap();
+    /// assert_eq!(buf, r#"[33,3,5512315355]"#);
+    ///
+    /// Subscribed { request_id: 4, subscription: 1 }.serialize_into(&mut buf).unwrap();
+    /// assert_eq!(buf, r#"[33,4,1]"#);
+    /// assert!(buf.capacity() >= 64);
+    /// ```
+    fn serialize_into(&self, buf: &mut String) -> Result<(), crate::error::Error>
+    where
+        Self: Serialize + Sized,
+    {
+        helpers::serialize_into(self, buf)
+    }
+}
+
+/// # WAMP Message Extension
+/// Conveniences implemented for every message struct, also exported from the
+/// [`prelude`](crate::prelude).
+/// ## Examples
+/// ```
+/// use wamp_core::prelude::*;
+/// use wamp_core::messages::Subscribed;
+///
+/// let subscribed = Subscribed { request_id: 3, subscription: 5512315355 };
+///
+/// assert_eq!(subscribed.to_json().unwrap(), r#"[33,3,5512315355]"#);
+/// assert_eq!(subscribed.serialized_size().unwrap(), 17);
+/// assert!(matches!(subscribed.to_messages(), Messages::Subscribed(_)));
+/// ```
+pub trait WampMessageExt: WampMessage + Serialize + Into<Messages> + Sized {
+    /// Serialize the message as a JSON string.
+    fn to_json(&self) -> Result<String, crate::error::Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Wrap the message in [`Messages`].
+    fn to_messages(self) -> Messages {
+        self.into()
+    }
+
+    /// Length of the JSON serialization of the message in bytes, computed without allocating
+    /// it.
+    fn serialized_size(&self) -> Result<usize, crate::error::Error> {
+        helpers::serialized_size(self)
+    }
 }
 
+impl<T: WampMessage + Serialize + Into<Messages>> WampMessageExt for T {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Messages Enum
 /// This represents each of the messages described in the WAMP protocol.
 ///
-/// This includes its own deserializer (you should serialize using the inner struct always).
+/// This includes its own serializer and deserializer, serializing delegates to the inner struct.
 ///
 /// It also implements `From<*n> for Messages` where n = each WAMP message.
 /// # Examples
@@ -216,7 +782,7 @@ pub trait WampMessage {
 /// let mut message2 = Messages::Call(Call {
 ///     request_id: 1,
 ///     options: json!({}),
-///     procedure: "topic".to_string(),
+///     procedure: "topic".into(),
 ///     args: Value::Null,
 ///     kwargs: Value::Null
 /// });
@@ -255,13 +821,249 @@ pub enum Messages {
     Unsubscribed(Unsubscribed),
     Welcome(Welcome),
     Yield(Yield),
+    /// A message with an id no standard message uses, see [`Extension`]. Holds every wire
+    /// element, id included, and serializes back to the same bare array.
     Extension(Vec<Value>),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// # Message Descriptor
+/// The constants of a message type, as listed in [`ALL`].
+pub struct MessageDescriptor {
+    /// The message type id, see [`WampMessage::ID`].
+    pub id: u64,
+    /// The upper case message name, see [`WampMessage::NAME`].
+    pub name: &'static str,
+    /// The kind of the message.
+    pub kind: MessageKind,
+    /// The wire field names after the message id, see [`WampMessage::FIELDS`].
+    pub fields: &'static [&'static str],
+    /// The feature the message requires, see [`WampMessage::REQUIRED_FEATURE`].
+    pub required_feature: Option<Feature>,
+}
+
+/// # Descriptor for
+/// The descriptor of the message type with `id`, `None` for unknown ids such as extension
+/// messages.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{descriptor_for, MessageKind};
+///
+/// let call = descriptor_for(48).unwrap();
+/// assert_eq!(call.name, "CALL");
+/// assert_eq!(call.kind, MessageKind::Call);
+/// assert_eq!(call.fields, &["request_id", "options", "procedure", "args", "kwargs"]);
+/// assert_eq!(call.required_feature, None);
+///
+/// assert!(descriptor_for(300).is_none());
+/// ```
+pub fn descriptor_for(id: u64) -> Option<&'static MessageDescriptor> {
+    let index = WAMP_MESSAGE_IDS.iter().position(|known| *known == id)?;
+    Some(&ALL[index])
+}
+
+/// # Id to name
+/// The upper case name of the message type with `id`, `None` for unknown ids, see
+/// [`WAMP_MESSAGE_NAMES`].
+/// ## Examples
+/// ```
+/// use wamp_core::messages::id_to_name;
+///
+/// assert_eq!(id_to_name(36), Some("EVENT"));
+/// assert_eq!(id_to_name(300), None);
+/// ```
+pub fn id_to_name(id: u64) -> Option<&'static str> {
+    WAMP_MESSAGE_NAMES
+        .iter()
+        .find(|(known, _)| *known == id)
+        .map(|(_, name)| *name)
+}
+
+/// # Name to id
+/// The id of the message type called `name`, ignoring case, `None` for unknown names, see
+/// [`WAMP_MESSAGE_NAMES`].
+/// ## Examples
+/// ```
+/// use wamp_core::messages::name_to_id;
+///
+/// assert_eq!(name_to_id("EVENT"), Some(36));
+/// assert_eq!(name_to_id("event"), Some(36));
+/// assert_eq!(name_to_id("EXTENSION"), None);
+/// ```
+pub fn name_to_id(name: &str) -> Option<u64> {
+    WAMP_MESSAGE_NAMES
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(name))
+        .map(|(id, _)| *id)
+}
+
+/// Generates [`MessageKind`], [`ALL`], [`WAMP_MESSAGE_IDS`], [`WAMP_MESSAGE_NAMES`] and
+/// [`Messages::kind`] from `Variant: Type` pairs.
+macro_rules! message_kinds {
+    ($($variant:ident: $message:ty,)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        /// # Message Kind
+        /// The kind of a WAMP message, without its content.
+        ///
+        /// Gives access to the [`WampMessage`] constants of every message type at runtime.
+        /// ## Examples
+        /// ```
+        /// use wamp_core::messages::{MessageKind, Messages, Subscribed};
+        ///
+        /// let message = Messages::from(Subscribed { request_id: 1, subscription: 2 });
+        /// let kind = message.kind().unwrap();
+        ///
+        /// assert_eq!(kind, MessageKind::Subscribed);
+        /// assert_eq!(kind.id(), 33);
+        /// assert_eq!(kind.name(), "SUBSCRIBED");
+        /// assert_eq!(kind.fields(), &["request_id", "subscription"]);
+        /// ```
+        pub enum MessageKind {
+            $(
+                #[doc = concat!("See [`", stringify!($message), "`].")]
+                $variant,
+            )*
+        }
+
+        impl MessageKind {
+            /// Every message kind, in the order of the [`Messages`] variants.
+            pub const ALL: &'static [MessageKind] = &[$(MessageKind::$variant,)*];
+
+            /// The message type id, see [`WampMessage::ID`].
+            pub fn id(&self) -> u64 {
+                match self {
+                    $(MessageKind::$variant => <$message>::ID,)*
+                }
+            }
+
+            /// The upper case message name, see [`WampMessage::NAME`].
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(MessageKind::$variant => <$message>::NAME,)*
+                }
+            }
+
+            /// The direction of the message for `role`, see [`WampMessage::direction`].
+            pub fn direction(&self, role: Roles) -> &'static MessageDirection {
+                match self {
+                    $(MessageKind::$variant => <$message>::direction(role),)*
+                }
+            }
+
+            /// The wire field names after the message id, see [`WampMessage::FIELDS`].
+            pub fn fields(&self) -> &'static [&'static str] {
+                match self {
+                    $(MessageKind::$variant => <$message>::FIELDS,)*
+                }
+            }
+
+            /// The feature the message requires, see [`WampMessage::REQUIRED_FEATURE`].
+            pub fn required_feature(&self) -> Option<Feature> {
+                match self {
+                    $(MessageKind::$variant => <$message>::REQUIRED_FEATURE,)*
+                }
+            }
+        }
+
+        /// # All messages
+        /// A descriptor of every message type, in the order of the [`Messages`] variants.
+        ///
+        /// Lets tooling map message ids to names and fields without constructing messages, see
+        /// [`descriptor_for`].
+        pub const ALL: &[MessageDescriptor] = &[
+            $(MessageDescriptor {
+                id: <$message>::ID,
+                name: <$message>::NAME,
+                kind: MessageKind::$variant,
+                fields: <$message>::FIELDS,
+                required_feature: <$message>::REQUIRED_FEATURE,
+            },)*
+        ];
+
+        /// # WAMP message ids
+        /// The id of every message type, in the order of [`ALL`].
+        ///
+        /// Messages with any other id are deserialized as [`Messages::Extension`].
+        /// ## Examples
+        /// ```
+        /// use wamp_core::messages::WAMP_MESSAGE_IDS;
+        ///
+        /// assert!(WAMP_MESSAGE_IDS.contains(&48));
+        /// assert!(!WAMP_MESSAGE_IDS.contains(&300));
+        /// ```
+        pub const WAMP_MESSAGE_IDS: [u64; 24] = [$(<$message>::ID,)*];
+
+        /// # WAMP message names
+        /// The id and upper case name of every message type, in the order of [`ALL`].
+        pub const WAMP_MESSAGE_NAMES: [(u64, &str); 24] =
+            [$((<$message>::ID, <$message>::NAME),)*];
+
+        impl Messages {
+            /// Deserialize the wire elements of a message of a known kind.
+            fn from_components(
+                kind: MessageKind,
+                components: Vec<Value>,
+            ) -> Result<Messages, serde_json::Error> {
+                let components = Value::Array(components);
+                match kind {
+                    $(MessageKind::$variant => from_value(components).map(Messages::$variant),)*
+                }
+            }
+
+            /// # Kind
+            /// The kind of the message, `None` for Extension messages.
+            pub fn kind(&self) -> Option<MessageKind> {
+                match self {
+                    $(Messages::$variant(_) => Some(MessageKind::$variant),)*
+                    Messages::Extension(_) => None,
+                }
+            }
+
+            /// # Validate
+            /// Validates the wrapped message, see [`WampMessage::validate`]. Extension messages
+            /// are not checked.
+            pub fn validate(&self, config: &ValidationConfig) -> Result<(), Vec<ValidationIssue>> {
+                match self {
+                    $(Messages::$variant(message) => message.validate(config),)*
+                    Messages::Extension(_) => Ok(()),
+                }
+            }
+        }
+    };
+}
+
+message_kinds! {
+    Abort: Abort,
+    Authenticate: Authenticate,
+    Call: Call,
+    Cancel: Cancel,
+    Challenge: Challenge,
+    Error: WampError,
+    Event: Event,
+    Goodbye: Goodbye,
+    Hello: Hello,
+    Interrupt: Interrupt,
+    Invocation: Invocation,
+    Publish: Publish,
+    Published: Published,
+    Register: Register,
+    Registered: Registered,
+    Result: WampResult,
+    Subscribe: Subscribe,
+    Subscribed: Subscribed,
+    Unregister: Unregister,
+    Unregistered: Unregistered,
+    Unsubscribe: Unsubscribe,
+    Unsubscribed: Unsubscribed,
+    Welcome: Welcome,
+    Yield: Yield,
+}
+
 impl Messages {
     /// # Get Message ID
     ///
-    /// Get the message ID of a WAMP message. This uses the static u64 for any known WAMP messages.
+    /// Get the message ID of a WAMP message, the id of its [`kind`](Messages::kind) for any
+    /// known WAMP message.
     ///
     /// For Extension messages, it attempts to get the ID and returns None otherwise.
     ///
@@ -277,39 +1079,454 @@ impl Messages {
     /// assert_eq!(message_id, Some(48));
     /// ```
     pub fn id(&self) -> Option<u64> {
+        match self.kind() {
+            Some(kind) => Some(kind.id()),
+            None => self.extension_id(),
+        }
+    }
+
+    /// # Get Message Args
+    ///
+    /// Positional arguments of the payload carrying messages (Call, Error, Event, Invocation,
+    /// Publish, Result and Yield), `None` for every other message.
+    pub fn args(&self) -> Option<&Value> {
         match self {
-            Messages::Authenticate(_) => Some(Authenticate::ID),
-            Messages::Abort(_) => Some(Abort::ID),
-            Messages::Call(_) => Some(Call::ID),
-            Messages::Cancel(_) => Some(Cancel::ID),
-            Messages::Challenge(_) => Some(Authenticate::ID),
-            Messages::Error(_) => Some(WampError::ID),
-            Messages::Event(_) => Some(Event::ID),
-            Messages::Goodbye(_) => Some(Goodbye::ID),
-            Messages::Hello(_) => Some(Hello::ID),
-            Messages::Interrupt(_) => Some(Interrupt::ID),
-            Messages::Invocation(_) => Some(Invocation::ID),
-            Messages::Publish(_) => Some(Publish::ID),
-            Messages::Published(_) => Some(Published::ID),
-            Messages::Register(_) => Some(Register::ID),
-            Messages::Registered(_) => Some(Registered::ID),
-            Messages::Result(_) => Some(WampResult::ID),
-            Messages::Subscribe(_) => Some(Subscribe::ID),
-            Messages::Subscribed(_) => Some(Subscribed::ID),
-            Messages::Unregister(_) => Some(Unregister::ID),
-            Messages::Unregistered(_) => Some(Unregistered::ID),
-            Messages::Unsubscribe(_) => Some(Unsubscribe::ID),
-            Messages::Unsubscribed(_) => Some(Unsubscribed::ID),
-            Messages::Welcome(_) => Some(Welcome::ID),
-            Messages::Yield(_) => Some(Yield::ID),
-            Messages::Extension(values) => {
-                if let Some(value) = values.first() {
-                    value.as_u64()
-                } else {
-                    None
-                }
+            Messages::Call(v) => Some(&v.args),
+            Messages::Error(v) => Some(&v.args),
+            Messages::Event(v) => Some(&v.args),
+            Messages::Invocation(v) => Some(&v.args),
+            Messages::Publish(v) => Some(&v.args),
+            Messages::Result(v) => Some(&v.args),
+            Messages::Yield(v) => Some(&v.args),
+            _ => None,
+        }
+    }
+
+    /// # Get Message Kwargs
+    ///
+    /// Keyword arguments of the payload carrying messages, `None` for every other message, see
+    /// [`Messages::args`].
+    pub fn kwargs(&self) -> Option<&Value> {
+        match self {
+            Messages::Call(v) => Some(&v.kwargs),
+            Messages::Error(v) => Some(&v.kwargs),
+            Messages::Event(v) => Some(&v.kwargs),
+            Messages::Invocation(v) => Some(&v.kwargs),
+            Messages::Publish(v) => Some(&v.kwargs),
+            Messages::Result(v) => Some(&v.kwargs),
+            Messages::Yield(v) => Some(&v.kwargs),
+            _ => None,
+        }
+    }
+
+    /// # Get Binary Kwarg
+    ///
+    /// The keyword argument `key` decoded as binary data, see [`payload::binary`]. `None` when
+    /// the message has no such keyword argument.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Messages;
+    /// use serde_json::from_str;
+    ///
+    /// let call: Messages = from_str(r#"[48,1,{},"com.myapp.sign",[],{"data":"\u0000EOP/n7g="}]"#).unwrap();
+    ///
+    /// assert_eq!(call.kwarg_bytes("data").unwrap(), Some(vec![0x10, 0xe3, 0xff, 0x9f, 0xb8]));
+    /// assert_eq!(call.kwarg_bytes("other").unwrap(), None);
+    /// ```
+    pub fn kwarg_bytes(&self, key: &str) -> Result<Option<Vec<u8>>, crate::error::Error> {
+        self.kwargs()
+            .and_then(|kwargs| kwargs.get(key))
+            .map(payload::binary::decode)
+            .transpose()
+    }
+
+    /// # Close info
+    ///
+    /// The reason and details of a GOODBYE or ABORT, the two messages ending a session, `None`
+    /// for every other message.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Abort, Messages};
+    /// use serde_json::json;
+    ///
+    /// let abort = Messages::from(Abort {
+    ///     details: json!({ "message": "The realm does not exist." }),
+    ///     reason: "wamp.error.no_such_realm".to_string()
+    /// });
+    ///
+    /// let info = abort.close_info().unwrap();
+    /// assert!(info.aborted);
+    /// assert_eq!(info.reason, "wamp.error.no_such_realm");
+    /// assert_eq!(info.details["message"], "The realm does not exist.");
+    /// ```
+    pub fn close_info(&self) -> Option<CloseInfo> {
+        let (aborted, details, reason) = match self {
+            Messages::Goodbye(v) => (false, &v.details, &v.reason),
+            Messages::Abort(v) => (true, &v.details, &v.reason),
+            _ => return None,
+        };
+        Some(CloseInfo {
+            aborted,
+            reason: reason.clone(),
+            details: details.clone(),
+        })
+    }
+
+    fn layout(&self) -> Option<(&'static str, &'static [&'static str])> {
+        self.kind().map(|kind| (kind.name(), kind.fields()))
+    }
+
+    /// # Annotated string
+    ///
+    /// Render the message as its wire form with every element after the message id labeled by
+    /// its field name, for protocol traces. Extension messages are rendered unlabeled.
+    ///
+    /// The labels come from [`WampMessage::FIELDS`], so they always match the deserializer.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Messages, Subscribed};
+    ///
+    /// let message = Messages::from(Subscribed { request_id: 713845233, subscription: 5512315355 });
+    ///
+    /// assert_eq!(
+    ///     message.to_annotated_string(),
+    ///     "SUBSCRIBED [33, request_id=713845233, subscription=5512315355]"
+    /// );
+    /// ```
+    pub fn to_annotated_string(&self) -> String {
+        let (name, fields) = self.layout().unwrap_or(("EXTENSION", &[]));
+        let elements = match serde_json::to_value(self) {
+            Ok(Value::Array(elements)) => elements,
+            Ok(other) => vec![other],
+            Err(error) => return format!("{name} <invalid: {error}>"),
+        };
+        let mut annotated = Vec::with_capacity(elements.len());
+        for (index, element) in elements.iter().enumerate() {
+            match index.checked_sub(1).and_then(|field| fields.get(field)) {
+                Some(field) => annotated.push(format!("{field}={element}")),
+                None => annotated.push(element.to_string()),
             }
         }
+        format!("{name} [{}]", annotated.join(", "))
+    }
+
+    /// # Canonical bytes
+    ///
+    /// The message as [RFC 8785](https://www.rfc-editor.org/rfc/rfc8785) style canonical JSON,
+    /// a stable byte representation for signing.
+    ///
+    /// Object keys are sorted, there is no whitespace and numbers are written in their shortest
+    /// form, `1.0` as `1`. Empty and absent `args` and `kwargs` are written alike, so two
+    /// messages that only differ in those, in key order or in formatting yield identical bytes.
+    /// Integers are written as they are, WAMP ids never exceed 2^53.
+    ///
+    /// Fails when the message does not serialize.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Messages;
+    /// use serde_json::from_str;
+    ///
+    /// let a: Messages = from_str(r#"[48, 1, {"b": 2.0, "a": 1}, "com.myapp.add", []]"#).unwrap();
+    /// let b: Messages = from_str(r#"[48,1,{"a":1,"b":2},"com.myapp.add"]"#).unwrap();
+    ///
+    /// assert_eq!(a.canonical_bytes().unwrap(), b.canonical_bytes().unwrap());
+    /// assert_eq!(
+    ///     a.canonical_bytes().unwrap(),
+    ///     br#"[48,1,{"a":1,"b":2},"com.myapp.add"]"#
+    /// );
+    /// ```
+    pub fn canonical_bytes(&self) -> Result<Vec<u8>, crate::error::Error> {
+        let mut elements = match serde_json::to_value(self)? {
+            Value::Array(elements) => elements,
+            other => vec![other],
+        };
+        if let Some((_, fields)) = self.layout() {
+            canonical::elide_empty_payload(&mut elements, fields);
+        }
+        let mut bytes = Vec::new();
+        canonical::write_canonical(&Value::Array(elements), &mut bytes);
+        Ok(bytes)
+    }
+
+    /// # Annotated object
+    ///
+    /// The message as an object keyed by field name instead of position, `None` for Extension
+    /// messages. Absent trailing fields are left out.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Messages, Subscribed};
+    /// use serde_json::json;
+    ///
+    /// let message = Messages::from(Subscribed { request_id: 1, subscription: 2 });
+    ///
+    /// assert_eq!(
+    ///     message.to_annotated_object(),
+    ///     Some(json!({ "request_id": 1, "subscription": 2 }))
+    /// );
+    /// ```
+    pub fn to_annotated_object(&self) -> Option<Value> {
+        let (_, fields) = self.layout()?;
+        let Ok(Value::Array(elements)) = serde_json::to_value(self) else {
+            return None;
+        };
+        let object = fields
+            .iter()
+            .zip(elements.into_iter().skip(1))
+            .map(|(field, element)| (field.to_string(), element))
+            .collect();
+        Some(Value::Object(object))
+    }
+
+    /// # Decode
+    ///
+    /// Decode a serialized message with `encoding`. Only JSON is supported, other encodings fail
+    /// with [`Error::UnsupportedEncoding`](crate::error::Error::UnsupportedEncoding).
+    pub fn decode(data: &[u8], encoding: Encoding) -> Result<Messages, crate::error::Error> {
+        match encoding {
+            Encoding::Json => {
+                let message = serde_json::from_slice(data);
+                crate::trace::decoded(&message, data.len());
+                Ok(message?)
+            }
+            encoding => Err(crate::error::Error::UnsupportedEncoding(encoding)),
+        }
+    }
+
+    /// # Encode
+    ///
+    /// Serialize the message with `encoding`, the counterpart of [`Messages::decode`].
+    pub fn encode(&self, encoding: Encoding) -> Result<Vec<u8>, crate::error::Error> {
+        match encoding {
+            Encoding::Json => {
+                let data = serde_json::to_vec(self)?;
+                crate::trace::encoded(self, data.len());
+                Ok(data)
+            }
+            encoding => Err(crate::error::Error::UnsupportedEncoding(encoding)),
+        }
+    }
+
+    /// # Serialize into
+    /// Serialize the message as JSON into `buf`, reusing its allocation, see
+    /// [`WampMessage::serialize_into`].
+    pub fn serialize_into(&self, buf: &mut String) -> Result<(), crate::error::Error> {
+        helpers::serialize_into(self, buf)
+    }
+
+    /// # Serialized size
+    /// Length of the JSON serialization of the message in bytes, see
+    /// [`WampMessageExt::serialized_size`].
+    pub fn serialized_size(&self) -> Result<usize, crate::error::Error> {
+        helpers::serialized_size(self)
+    }
+
+    /// # From WebSocket message
+    ///
+    /// Decode a WebSocket message of a connection that negotiated `encoding`.
+    ///
+    /// Text and binary messages are decoded with `encoding`. A close frame fails with
+    /// [`Error::ConnectionClosed`](crate::error::Error::ConnectionClosed) holding its code and
+    /// reason, so session code can treat it like a GOODBYE. Ping, pong or raw frames, which carry
+    /// no WAMP message, fail with [`Error::ControlFrame`](crate::error::Error::ControlFrame).
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Messages;
+    /// use wamp_core::transport::Encoding;
+    /// use wamp_core::error::Error;
+    /// use wamp_core::transport::FrameKind;
+    /// use wamp_core::tungstenite::Message;
+    ///
+    /// let frame = Message::Binary(br#"[33,3,5512315355]"#.to_vec());
+    /// assert!(matches!(Messages::from_ws_message(frame, Encoding::Json), Ok(Messages::Subscribed(_))));
+    ///
+    /// let frame = Message::Ping(vec![]);
+    /// assert!(matches!(
+    ///     Messages::from_ws_message(frame, Encoding::Json),
+    ///     Err(Error::ControlFrame(FrameKind::Ping))
+    /// ));
+    /// ```
+    pub fn from_ws_message(
+        message: Message,
+        encoding: Encoding,
+    ) -> Result<Messages, crate::error::Error> {
+        match message {
+            Message::Text(text) => Messages::decode(text.as_bytes(), encoding),
+            Message::Binary(data) => Messages::decode(&data, encoding),
+            Message::Close(frame) => Err(crate::error::Error::ConnectionClosed {
+                code: frame.as_ref().map(|frame| frame.code.into()),
+                reason: frame
+                    .map(|frame| frame.reason.into_owned())
+                    .unwrap_or_default(),
+            }),
+            Message::Ping(_) => Err(crate::error::Error::ControlFrame(FrameKind::Ping)),
+            Message::Pong(_) => Err(crate::error::Error::ControlFrame(FrameKind::Pong)),
+            Message::Frame(_) => Err(crate::error::Error::ControlFrame(FrameKind::Frame)),
+        }
+    }
+
+    /// # Serialize with limit
+    ///
+    /// Serialize the message to its JSON wire form, failing with [`Error::PayloadSizeExceeded`](crate::error::Error::PayloadSizeExceeded)
+    /// when the result is longer than `max_bytes`.
+    ///
+    /// Routers reject frames above their maximum message size, this lets you catch that before
+    /// handing the frame to the transport (for example to cap the size of a Result or Yield).
+    ///
+    /// This is the same as calling [`Messages::serialize_with`] with only `max_bytes` set.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Messages, Yield};
+    /// use wamp_core::error::Error;
+    /// use serde_json::{json, Value};
+    ///
+    /// let message = Messages::from(Yield {
+    ///     request_id: 1,
+    ///     options: json!({}),
+    ///     args: json!(vec![1; 1000]),
+    ///     kwargs: Value::Null
+    /// });
+    ///
+    /// assert!(message.serialize_with_limit(4096).is_ok());
+    ///
+    /// match message.serialize_with_limit(128) {
+    ///     Err(Error::PayloadSizeExceeded { size, max }) => {
+    ///         assert_eq!(size, 2011);
+    ///         assert_eq!(max, 128);
+    ///     }
+    ///     _ => panic!("expected the payload size to be exceeded"),
+    /// }
+    /// ```
+    pub fn serialize_with_limit(&self, max_bytes: usize) -> Result<String, crate::error::Error> {
+        self.serialize_with(&SerializeLimits::new().max_bytes(max_bytes))
+    }
+
+    /// # Serialize with limits
+    ///
+    /// Serialize the message to its JSON wire form, enforcing every limit set on `limits`.
+    ///
+    /// The args length is checked before serializing, so it is cheaper than a byte limit when
+    /// the number of positional arguments is all you care about.
+    ///
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Messages, SerializeLimits};
+    /// use wamp_core::error::Error;
+    /// use wamp_core::publish;
+    /// use wamp_core::messages::Publish;
+    /// use serde_json::json;
+    ///
+    /// let limits = SerializeLimits::new().max_args(3);
+    ///
+    /// let message = Messages::from(publish!("topic", args: json!([1, 2, 3])));
+    /// assert!(message.serialize_with(&limits).is_ok());
+    ///
+    /// let message = Messages::from(publish!("topic", args: json!([1, 2, 3, 4])));
+    /// assert!(matches!(
+    ///     message.serialize_with(&limits),
+    ///     Err(Error::InvalidArgument { len: 4, max: 3 })
+    /// ));
+    /// ```
+    pub fn serialize_with(&self, limits: &SerializeLimits) -> Result<String, crate::error::Error> {
+        if let (Some(max), Some(Value::Array(args))) = (limits.max_args, self.args()) {
+            if args.len() > max {
+                return Err(crate::error::Error::InvalidArgument {
+                    len: args.len(),
+                    max,
+                });
+            }
+        }
+        let data = serde_json::to_string(self)?;
+        crate::trace::encoded(self, data.len());
+        match limits.max_bytes {
+            Some(max) if data.len() > max => Err(crate::error::Error::PayloadSizeExceeded {
+                size: data.len(),
+                max,
+            }),
+            _ => Ok(data),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Close Info
+/// Why a session is ending, as returned by [`Messages::close_info`].
+pub struct CloseInfo {
+    /// Whether the session was aborted, `false` for a GOODBYE.
+    pub aborted: bool,
+    /// The reason URI, for example `wamp.close.normal`.
+    pub reason: String,
+    /// The details of the GOODBYE or ABORT.
+    pub details: Value,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// # Serialize Limits
+/// Limits enforced by [`Messages::serialize_with`]. Every limit is off until set.
+pub struct SerializeLimits {
+    /// Maximum length of the serialized message in bytes.
+    pub max_bytes: Option<usize>,
+    /// Maximum number of positional arguments.
+    pub max_args: Option<usize>,
+}
+
+impl SerializeLimits {
+    /// Create limits with every check disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject messages whose serialized form is longer than `max_bytes`.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Reject messages carrying more than `max_args` positional arguments.
+    pub fn max_args(mut self, max_args: usize) -> Self {
+        self.max_args = Some(max_args);
+        self
+    }
+}
+
+impl Serialize for Messages {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Messages::Abort(v) => v.serialize(serializer),
+            Messages::Authenticate(v) => v.serialize(serializer),
+            Messages::Call(v) => v.serialize(serializer),
+            Messages::Cancel(v) => v.serialize(serializer),
+            Messages::Challenge(v) => v.serialize(serializer),
+            Messages::Error(v) => v.serialize(serializer),
+            Messages::Event(v) => v.serialize(serializer),
+            Messages::Goodbye(v) => v.serialize(serializer),
+            Messages::Hello(v) => v.serialize(serializer),
+            Messages::Interrupt(v) => v.serialize(serializer),
+            Messages::Invocation(v) => v.serialize(serializer),
+            Messages::Publish(v) => v.serialize(serializer),
+            Messages::Published(v) => v.serialize(serializer),
+            Messages::Register(v) => v.serialize(serializer),
+            Messages::Registered(v) => v.serialize(serializer),
+            Messages::Result(v) => v.serialize(serializer),
+            Messages::Subscribe(v) => v.serialize(serializer),
+            Messages::Subscribed(v) => v.serialize(serializer),
+            Messages::Unregister(v) => v.serialize(serializer),
+            Messages::Unregistered(v) => v.serialize(serializer),
+            Messages::Unsubscribe(v) => v.serialize(serializer),
+            Messages::Unsubscribed(v) => v.serialize(serializer),
+            Messages::Welcome(v) => v.serialize(serializer),
+            Messages::Yield(v) => v.serialize(serializer),
+            Messages::Extension(v) => v.serialize(serializer),
+        }
     }
 }
 
@@ -356,8 +1573,11 @@ impl TryFrom<Messages> for WampError {
 impl TryFrom<tungstenite::Message> for Messages {
     type Error = crate::error::Error;
 
+    /// Decodes a WebSocket message, treating binary messages as JSON.
+    ///
+    /// See [`Messages::from_ws_message`] for the errors returned for other frames.
     fn try_from(value: Message) -> Result<Self, crate::error::Error> {
-        Ok(from_str(value.to_text()?)?)
+        Messages::from_ws_message(value, Encoding::Json)
     }
 }
 
@@ -410,49 +1630,547 @@ impl<'de> Deserialize<'de> for Messages {
             None => Err(de::Error::custom("value")),
         }?;
 
-        fn helper<'d, T, D>(wamp_components: Vec<Value>) -> Result<T, D::Error>
-        where
-            T: for<'de> Deserialize<'de>,
-            D: Deserializer<'d>,
-        {
-            let value: T = from_value(json!(wamp_components)).map_err(de::Error::custom)?;
-            Ok(value)
-        }
-
-        match wamp_message_id {
-            Abort::ID => Ok(Self::Abort(helper::<Abort, D>(wamp_components)?)),
-            Authenticate::ID => Ok(Self::Authenticate(helper::<Authenticate, D>(
-                wamp_components,
-            )?)),
-            Call::ID => Ok(Self::Call(helper::<Call, D>(wamp_components)?)),
-            Cancel::ID => Ok(Self::Cancel(helper::<Cancel, D>(wamp_components)?)),
-            Challenge::ID => Ok(Self::Challenge(helper::<Challenge, D>(wamp_components)?)),
-            WampError::ID => Ok(Self::Error(helper::<WampError, D>(wamp_components)?)),
-            Event::ID => Ok(Self::Event(helper::<Event, D>(wamp_components)?)),
-            Goodbye::ID => Ok(Self::Goodbye(helper::<Goodbye, D>(wamp_components)?)),
-            Hello::ID => Ok(Self::Hello(helper::<Hello, D>(wamp_components)?)),
-            Interrupt::ID => Ok(Self::Interrupt(helper::<Interrupt, D>(wamp_components)?)),
-            Invocation::ID => Ok(Self::Invocation(helper::<Invocation, D>(wamp_components)?)),
-            Publish::ID => Ok(Self::Publish(helper::<Publish, D>(wamp_components)?)),
-            Published::ID => Ok(Self::Published(helper::<Published, D>(wamp_components)?)),
-            Register::ID => Ok(Self::Register(helper::<Register, D>(wamp_components)?)),
-            Registered::ID => Ok(Self::Registered(helper::<Registered, D>(wamp_components)?)),
-            WampResult::ID => Ok(Self::Result(helper::<WampResult, D>(wamp_components)?)),
-            Subscribe::ID => Ok(Self::Subscribe(helper::<Subscribe, D>(wamp_components)?)),
-            Subscribed::ID => Ok(Self::Subscribed(helper::<Subscribed, D>(wamp_components)?)),
-            Unregister::ID => Ok(Self::Unregister(helper::<Unregister, D>(wamp_components)?)),
-            Unregistered::ID => Ok(Self::Unregistered(helper::<Unregistered, D>(
-                wamp_components,
-            )?)),
-            Unsubscribe::ID => Ok(Self::Unsubscribe(helper::<Unsubscribe, D>(
-                wamp_components,
-            )?)),
-            Unsubscribed::ID => Ok(Self::Unsubscribed(helper::<Unsubscribed, D>(
-                wamp_components,
-            )?)),
-            Welcome::ID => Ok(Self::Welcome(helper::<Welcome, D>(wamp_components)?)),
-            Yield::ID => Ok(Self::Yield(helper::<Yield, D>(wamp_components)?)),
-            _ => Ok(Self::Extension(wamp_components)),
+        match descriptor_for(wamp_message_id) {
+            Some(descriptor) => Messages::from_components(descriptor.kind, wamp_components)
+                .map_err(de::Error::custom),
+            None => Ok(Self::Extension(wamp_components)),
+        }
+    }
+}
+
+/// The frames of `tests/fixtures/frames.jsonl`, holding every message kind, with the messages
+/// they parse to. Panics on a frame that does not parse as a standard message.
+#[cfg(test)]
+pub(crate) fn fixture_frames() -> Vec<(&'static str, Messages)> {
+    const FRAMES: &str = include_str!("../../tests/fixtures/frames.jsonl");
+    FRAMES
+        .lines()
+        .map(|frame| match serde_json::from_str::<Messages>(frame) {
+            Ok(message) if message.kind().is_some() => (frame, message),
+            other => panic!("fixture frame {frame} parsed to {other:?}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// The "Message Codes and Direction" table of the spec, columns in the order
+    /// Publisher, Broker, Subscriber, Caller, Dealer, Callee.
+    ///
+    /// ABORT follows the session establishment section instead of the table, which lists it as
+    /// receive only for clients although "both the Router and the Client may abort".
+    #[rustfmt::skip]
+    const SPEC: [(u64, [&str; 6]); 24] = [
+        (Hello::ID, ["tx", "rx", "tx", "tx", "rx", "tx"]),
+        (Welcome::ID, ["rx", "tx", "rx", "rx", "tx", "rx"]),
+        (Abort::ID, ["rxtx", "rxtx", "rxtx", "rxtx", "rxtx", "rxtx"]),
+        (Challenge::ID, ["rx", "tx", "rx", "rx", "tx", "rx"]),
+        (Authenticate::ID, ["tx", "rx", "tx", "tx", "rx", "tx"]),
+        (Goodbye::ID, ["rxtx", "rxtx", "rxtx", "rxtx", "rxtx", "rxtx"]),
+        (WampError::ID, ["rx", "tx", "rx", "rx", "rxtx", "rxtx"]),
+        (Publish::ID, ["tx", "rx", "", "", "", ""]),
+        (Published::ID, ["rx", "tx", "", "", "", ""]),
+        (Subscribe::ID, ["", "rx", "tx", "", "", ""]),
+        (Subscribed::ID, ["", "tx", "rx", "", "", ""]),
+        (Unsubscribe::ID, ["", "rx", "tx", "", "", ""]),
+        (Unsubscribed::ID, ["", "tx", "rx", "", "", ""]),
+        (Event::ID, ["", "tx", "rx", "", "", ""]),
+        (Call::ID, ["", "", "", "tx", "rx", ""]),
+        (Cancel::ID, ["", "", "", "tx", "rx", ""]),
+        (WampResult::ID, ["", "", "", "rx", "tx", ""]),
+        (Register::ID, ["", "", "", "", "rx", "tx"]),
+        (Registered::ID, ["", "", "", "", "tx", "rx"]),
+        (Unregister::ID, ["", "", "", "", "rx", "tx"]),
+        (Unregistered::ID, ["", "", "", "", "tx", "rx"]),
+        (Invocation::ID, ["", "", "", "", "tx", "rx"]),
+        (Interrupt::ID, ["", "", "", "", "tx", "rx"]),
+        (Yield::ID, ["", "", "", "", "rx", "tx"]),
+    ];
+
+    const COLUMNS: [Roles; 6] = [
+        Roles::Publisher,
+        Roles::Broker,
+        Roles::Subscriber,
+        Roles::Caller,
+        Roles::Dealer,
+        Roles::Callee,
+    ];
+
+    #[test]
+    fn descriptors_are_complete_and_unique() {
+        assert_eq!(ALL.len(), MessageKind::ALL.len());
+        for (descriptor, kind) in ALL.iter().zip(MessageKind::ALL) {
+            assert_eq!(descriptor.kind, *kind);
+            assert_eq!(descriptor.id, kind.id());
+            assert_eq!(descriptor.name, kind.name());
+            assert_eq!(descriptor.fields, kind.fields());
+            assert_eq!(descriptor_for(descriptor.id), Some(descriptor));
+        }
+        let mut ids: Vec<u64> = ALL.iter().map(|descriptor| descriptor.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), ALL.len());
+    }
+
+    #[test]
+    fn direction_matrix_covers_every_cell() {
+        let matrix = direction_matrix();
+        assert_eq!(matrix.len(), MessageKind::ALL.len() * Roles::ALL.len());
+        for (cell, (id, role, direction)) in matrix.iter().enumerate() {
+            let kind = MessageKind::ALL[cell / Roles::ALL.len()];
+            assert_eq!(*id, kind.id());
+            assert_eq!(*role, Roles::ALL[cell % Roles::ALL.len()]);
+            assert_eq!(direction, kind.direction(*role));
+        }
+        let call_by_caller = MessageDirection {
+            receives: false,
+            sends: true,
+        };
+        assert!(matrix.contains(&(Call::ID, Roles::Caller, call_by_caller)));
+    }
+
+    /// Client roles and the router role on the other end of their connection.
+    const PEERS: [(Roles, Roles); 4] = [
+        (Roles::Publisher, Roles::Broker),
+        (Roles::Subscriber, Roles::Broker),
+        (Roles::Caller, Roles::Dealer),
+        (Roles::Callee, Roles::Dealer),
+    ];
+
+    #[test]
+    fn direction_tables_pair_peers() {
+        for kind in MessageKind::ALL {
+            let name = kind.name();
+            let all = Roles::ALL.map(|role| *kind.direction(role));
+            assert!(all.iter().any(|d| d.sends), "{name} is never sent");
+            assert!(all.iter().any(|d| d.receives), "{name} is never received");
+            for router in [Roles::Broker, Roles::Dealer] {
+                let clients = PEERS.iter().filter(|(_, r)| *r == router);
+                if kind.direction(router).sends {
+                    assert!(
+                        clients.clone().any(|(c, _)| kind.direction(*c).receives),
+                        "{name} sent by {router:?} but no client receives it"
+                    );
+                }
+                if kind.direction(router).receives {
+                    assert!(
+                        clients.clone().any(|(c, _)| kind.direction(*c).sends),
+                        "{name} received by {router:?} but no client sends it"
+                    );
+                }
+            }
+            for (client, router) in PEERS {
+                if kind.direction(client).sends {
+                    assert!(
+                        kind.direction(router).receives,
+                        "{name} sent by {client:?} but not received by {router:?}"
+                    );
+                }
+                if kind.direction(client).receives {
+                    assert!(
+                        kind.direction(router).sends,
+                        "{name} received by {client:?} but not sent by {router:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn direction_tables_match_spec() {
+        for (id, row) in SPEC {
+            let kind = descriptor_for(id).unwrap().kind;
+            for (role, cell) in COLUMNS.iter().zip(row) {
+                let expected = MessageDirection {
+                    receives: cell.contains("rx"),
+                    sends: cell.contains("tx"),
+                };
+                assert_eq!(
+                    *kind.direction(*role),
+                    expected,
+                    "message {id}, role {role:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn publish_is_not_sent_by_callers() {
+        let none = MessageDirection {
+            receives: false,
+            sends: false,
+        };
+        assert_eq!(*Publish::direction(Roles::Caller), none);
+        assert_eq!(*Published::direction(Roles::Callee), none);
+        assert!(Publish::direction(Roles::Publisher).sends);
+        assert!(Publish::direction(Roles::Broker).receives);
+    }
+
+    #[test]
+    fn callee_receives_registration_errors() {
+        let callee = WampError::direction(Roles::Callee);
+        assert!(callee.sends);
+        assert!(callee.receives);
+    }
+
+    #[test]
+    fn annotated_strings() {
+        let call = Messages::from(Call {
+            request_id: 7814135,
+            options: json!({}),
+            procedure: "com.myapp.user.new".into(),
+            args: json!(["johnny"]),
+            kwargs: json!({"firstname": "John", "surname": "Doe"}),
+        });
+        assert_eq!(
+            call.to_annotated_string(),
+            r#"CALL [48, request_id=7814135, options={}, procedure="com.myapp.user.new", args=["johnny"], kwargs={"firstname":"John","surname":"Doe"}]"#
+        );
+
+        let error = Messages::from(WampError {
+            event: error::WampErrorEvent::Call,
+            request_id: 7814135,
+            details: json!({}),
+            error: "wamp.error.no_such_procedure".to_string(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        });
+        assert_eq!(
+            error.to_annotated_string(),
+            r#"ERROR [8, event=48, request_id=7814135, details={}, error="wamp.error.no_such_procedure"]"#
+        );
+
+        let goodbye = Messages::from(Goodbye {
+            details: json!({"message": "The host is shutting down now."}),
+            reason: "wamp.close.system_shutdown".to_string(),
+        });
+        assert_eq!(
+            goodbye.to_annotated_string(),
+            r#"GOODBYE [6, details={"message":"The host is shutting down now."}, reason="wamp.close.system_shutdown"]"#
+        );
+
+        let extension = Messages::Extension(vec![json!(255), json!("x")]);
+        assert_eq!(extension.to_annotated_string(), r#"EXTENSION [255, "x"]"#);
+    }
+
+    #[test]
+    fn display_is_wire_json() {
+        let call = crate::call!("topic");
+        assert_eq!(format!("{}", call), serde_json::to_string(&call).unwrap());
+
+        let goodbye = Goodbye {
+            details: json!({}),
+            reason: "wamp.close.close_realm".to_string(),
+        };
+        assert_eq!(goodbye.to_string(), r#"[6,{},"wamp.close.close_realm"]"#);
+
+        let invalid = Call {
+            options: json!("fast"),
+            ..crate::call!("topic")
+        };
+        assert!(serde_json::to_string(&invalid).is_err());
+        let display = invalid.to_string();
+        assert!(display.starts_with("<invalid Call: "), "{display}");
+    }
+
+    #[test]
+    fn websocket_frames() {
+        use crate::error::Error;
+        use tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+
+        let text = Message::Text(r#"[6,{},"wamp.close.normal"]"#.to_string());
+        assert!(matches!(Messages::try_from(text), Ok(Messages::Goodbye(_))));
+        let binary = Message::Binary(br#"[6,{},"wamp.close.normal"]"#.to_vec());
+        assert!(matches!(
+            Messages::try_from(binary.clone()),
+            Ok(Messages::Goodbye(_))
+        ));
+        assert!(matches!(
+            Messages::from_ws_message(binary, Encoding::MessagePack),
+            Err(Error::UnsupportedEncoding(Encoding::MessagePack))
+        ));
+        let invalid = Message::Binary(vec![0x93, 0x06, 0x80]);
+        assert!(matches!(
+            Messages::try_from(invalid),
+            Err(Error::SerdeJsonError(_))
+        ));
+        let close = Message::Close(Some(CloseFrame {
+            code: CloseCode::Away,
+            reason: "server shutdown".into(),
+        }));
+        assert!(Messages::try_from(close.clone()).unwrap_err().is_close());
+        match Messages::try_from(close) {
+            Err(Error::ConnectionClosed { code, reason }) => {
+                assert_eq!(code, Some(1001));
+                assert_eq!(reason, "server shutdown");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(matches!(
+            Messages::try_from(Message::Close(None)),
+            Err(Error::ConnectionClosed { code: None, reason }) if reason.is_empty()
+        ));
+        assert!(Messages::try_from(Message::Close(None))
+            .unwrap_err()
+            .is_close());
+        let failure = Message::Close(Some(CloseFrame {
+            code: CloseCode::Protocol,
+            reason: "bad frame".into(),
+        }));
+        assert!(!Messages::try_from(failure).unwrap_err().is_close());
+        assert!(matches!(
+            Messages::try_from(Message::Ping(vec![1])),
+            Err(Error::ControlFrame(FrameKind::Ping))
+        ));
+        assert!(matches!(
+            Messages::try_from(Message::Pong(vec![1])),
+            Err(Error::ControlFrame(FrameKind::Pong))
+        ));
+    }
+
+    #[test]
+    fn serialize_into_reuses_buffer() {
+        let mut buf = String::with_capacity(1024);
+        let allocation = buf.as_ptr();
+        for request_id in 1..=1000 {
+            let message = Messages::from(Subscribed {
+                request_id,
+                subscription: 5512315355,
+            });
+            message.serialize_into(&mut buf).unwrap();
+            assert_eq!(buf, serde_json::to_string(&message).unwrap());
+            assert_eq!(buf.as_ptr(), allocation);
+        }
+        assert_eq!(buf.capacity(), 1024);
+
+        let invalid = Messages::from(Call {
+            request_id: 1,
+            options: json!([]),
+            procedure: "com.myapp.add".into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        });
+        assert!(invalid.serialize_into(&mut buf).is_err());
+        assert!(buf.is_empty());
+        assert_eq!(buf.as_ptr(), allocation);
+    }
+
+    #[test]
+    fn id_name_lookups() {
+        for (id, name) in [(1, "HELLO"), (8, "ERROR"), (48, "CALL"), (70, "YIELD")] {
+            assert_eq!(id_to_name(id), Some(name));
+            assert_eq!(name_to_id(name), Some(id));
+        }
+        for descriptor in ALL {
+            assert_eq!(name_to_id(id_to_name(descriptor.id).unwrap()), Some(descriptor.id));
+        }
+        assert_eq!(ALL.len(), 24);
+        assert_eq!(id_to_name(0), None);
+        assert_eq!(id_to_name(255), None);
+        assert_eq!(name_to_id("NOT_A_MESSAGE"), None);
+    }
+
+    #[test]
+    fn message_ids_match_types() {
+        let ids = [
+            Abort::ID,
+            Authenticate::ID,
+            Call::ID,
+            Cancel::ID,
+            Challenge::ID,
+            WampError::ID,
+            Event::ID,
+            Goodbye::ID,
+            Hello::ID,
+            Interrupt::ID,
+            Invocation::ID,
+            Publish::ID,
+            Published::ID,
+            Register::ID,
+            Registered::ID,
+            WampResult::ID,
+            Subscribe::ID,
+            Subscribed::ID,
+            Unregister::ID,
+            Unregistered::ID,
+            Unsubscribe::ID,
+            Unsubscribed::ID,
+            Welcome::ID,
+            Yield::ID,
+        ];
+        assert_eq!(WAMP_MESSAGE_IDS, ids);
+        for ((id, name), descriptor) in WAMP_MESSAGE_NAMES.iter().zip(ALL) {
+            assert_eq!((*id, *name), (descriptor.id, descriptor.name));
+        }
+        for id in 0..=255 {
+            let extension = serde_json::from_value::<Messages>(json!([id]))
+                .is_ok_and(|message| matches!(message, Messages::Extension(_)));
+            assert_eq!(extension, !WAMP_MESSAGE_IDS.contains(&id));
+        }
+    }
+
+    #[test]
+    fn id_matches_kind() {
+        for (frame, message) in fixture_frames() {
+            let kind = message.kind().unwrap();
+            assert_eq!(message.id(), Some(kind.id()), "{frame}");
+            assert_eq!(
+                serde_json::from_str::<Vec<Value>>(frame).unwrap()[0],
+                kind.id()
+            );
+        }
+        let challenge: Messages = serde_json::from_str(r#"[4,"ticket",{}]"#).unwrap();
+        assert_eq!(challenge.id(), Some(Challenge::ID));
+        assert_eq!(Messages::Extension(vec![json!(255)]).id(), Some(255));
+        assert_eq!(Messages::Extension(vec![json!("x")]).id(), None);
+    }
+
+    #[test]
+    fn deserialize_message_error_texts() {
+        let error = serde_json::from_str::<Abort>("{}").unwrap_err().to_string();
+        assert!(error.contains("expected WAMP Abort frame, expressed as a sequence."));
+        let error = serde_json::from_str::<Call>("{}").unwrap_err().to_string();
+        assert!(error.contains("expected A sequence of Call components."));
+        let error = serde_json::from_str::<Cancel>("[]").unwrap_err().to_string();
+        assert!(error.contains("Message ID must be type u64."));
+        let error = serde_json::from_str::<Subscribe>("[]").unwrap_err().to_string();
+        assert!(error.contains("Message id must be present and type u64."));
+        let error = serde_json::from_str::<Call>("[]").unwrap_err().to_string();
+        assert!(error.contains("Message ID must be present and type u8."));
+    }
+
+    #[test]
+    fn fixture_frames_round_trip() {
+        let frames = fixture_frames();
+        for (frame, message) in &frames {
+            let kind = message.kind().unwrap();
+            let wire = serde_json::to_string(message).unwrap();
+            assert_eq!(
+                &serde_json::from_str::<Messages>(&wire).unwrap(),
+                message,
+                "{frame} does not round trip"
+            );
+
+            // Dropping any element the message can not do without fails.
+            let elements: Vec<Value> = serde_json::from_str(frame).unwrap();
+            let required = kind
+                .fields()
+                .iter()
+                .filter(|field| !matches!(**field, "args" | "kwargs"))
+                .count();
+            let required = match kind {
+                MessageKind::Unsubscribed => 1,
+                _ => required,
+            };
+            for len in 1..=required {
+                let truncated = Value::Array(elements[..len].to_vec());
+                assert!(
+                    serde_json::from_value::<Messages>(truncated).is_err(),
+                    "{frame} truncated to {len} elements parsed"
+                );
+            }
         }
+        for kind in MessageKind::ALL {
+            assert!(
+                frames
+                    .iter()
+                    .any(|(_, message)| message.kind() == Some(*kind)),
+                "no {} frame in the fixture",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn missing_elements_report_their_context() {
+        let error = serde_json::from_str::<Subscribed>("[33,1]").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("subscription must be present and object like."));
+        let error = serde_json::from_str::<Hello>("[1]").unwrap_err();
+        assert!(error.to_string().starts_with("realm must be a String."));
+        let error = serde_json::from_str::<Hello>("[]").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Message ID must be type u8."));
+
+        let context = helpers::FieldContext::new(2, "details", "Details must be a JSON value.");
+        assert_eq!((context.index, context.field), (2, "details"));
+        assert_eq!(context.to_string(), context.message);
+    }
+
+    #[test]
+    fn close_info_of_goodbye_and_abort() {
+        let goodbye = Messages::from(Goodbye {
+            details: json!({}),
+            reason: "wamp.close.system_shutdown".to_string(),
+        });
+        assert_eq!(
+            goodbye.close_info(),
+            Some(CloseInfo {
+                aborted: false,
+                reason: "wamp.close.system_shutdown".to_string(),
+                details: json!({}),
+            })
+        );
+
+        let abort: Messages = serde_json::from_str(
+            r#"[3,{"message":"Unknown ticket."},"wamp.error.authentication_denied"]"#,
+        )
+        .unwrap();
+        let info = abort.close_info().unwrap();
+        assert!(info.aborted);
+        assert_eq!(info.reason, "wamp.error.authentication_denied");
+        assert_eq!(info.details, json!({ "message": "Unknown ticket." }));
+
+        let published = Messages::from(Published {
+            request_id: 1,
+            publication: 2,
+        });
+        assert_eq!(published.close_info(), None);
+    }
+
+    #[test]
+    fn canonical_bytes_are_stable() {
+        let literal = Messages::from(Call {
+            request_id: 7,
+            options: json!({ "timeout": 1000, "disclose_me": true }),
+            procedure: "com.myapp.add".into(),
+            args: json!([1, 2.0]),
+            kwargs: json!({}),
+        });
+        let mut built = crate::call!(
+            "com.myapp.add",
+            json!({ "disclose_me": true, "timeout": 1000 }),
+            args: json!([1, 2])
+        );
+        built.request_id = 7;
+        let parsed: Messages = serde_json::from_str(
+            r#"[ 48, 7, { "timeout": 1000.0, "disclose_me": true }, "com.myapp.add", [1.0, 2], {} ]"#,
+        )
+        .unwrap();
+
+        let expected = br#"[48,7,{"disclose_me":true,"timeout":1000},"com.myapp.add",[1,2]]"#;
+        assert_eq!(literal.canonical_bytes().unwrap(), expected);
+        assert_eq!(Messages::from(built).canonical_bytes().unwrap(), expected);
+        assert_eq!(parsed.canonical_bytes().unwrap(), expected);
+
+        let empty = Messages::from(Call {
+            request_id: 7,
+            options: json!({}),
+            procedure: "com.myapp.add".into(),
+            args: json!([]),
+            kwargs: Value::Null,
+        });
+        let absent: Messages = serde_json::from_str(r#"[48,7,{},"com.myapp.add"]"#).unwrap();
+        assert_eq!(empty.canonical_bytes().unwrap(), absent.canonical_bytes().unwrap());
+        assert_ne!(empty.canonical_bytes().unwrap(), literal.canonical_bytes().unwrap());
+
+        let extension = Messages::Extension(vec![json!(255), json!({ "b": 1, "a": 2 })]);
+        assert_eq!(extension.canonical_bytes().unwrap(), br#"[255,{"a":2,"b":1}]"#);
     }
 }
@@ -0,0 +1,384 @@
+//! # Shared payloads
+//! Reference counted `args` and `kwargs` for messages that get cloned many times.
+//!
+//! A router fanning a publication out to thousands of subscribers clones the same EVENT for
+//! every one of them, and cloning a [`Value`] copies the whole payload. A [`Payload`] keeps
+//! `args` and `kwargs` behind an [`Arc`], so cloning it only bumps a reference count, while the
+//! mutators copy the payload on write when it is still shared.
+//!
+//! [`SharedEvent`] is the [`Event`] built on top of it, and serializes to exactly the same wire
+//! format.
+//! ## Examples
+//! ```
+//! use wamp_core::messages::{Event, SharedEvent};
+//! use serde_json::{json, to_string};
+//!
+//! let event = SharedEvent::from(Event {
+//!     subscription: 1,
+//!     publication: 2,
+//!     details: json!({}),
+//!     args: json!(["hello"]),
+//!     kwargs: json!(null)
+//! });
+//!
+//! let copy = event.clone();
+//! assert!(copy.payload.ptr_eq(&event.payload));
+//!
+//! let changed = copy.with_kwarg("key", json!("value"));
+//! assert!(!changed.payload.ptr_eq(&event.payload));
+//! assert_eq!(to_string(&event).unwrap(), r#"[36,1,2,{},["hello"]]"#);
+//! assert_eq!(to_string(&changed).unwrap(), r#"[36,1,2,{},["hello"],{"key":"value"}]"#);
+//! ```
+use std::sync::Arc;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{json, Value};
+
+use super::{helpers, Event, WampMessage};
+use crate::error::Error;
+
+pub mod binary {
+    //! # Binary values
+    //! The binary conversion of the JSON encoding.
+    //!
+    //! JSON has no binary type, so WAMP sends binary data as a string holding a `\0` followed by
+    //! the standard, padded base64 encoding of the bytes. Routers use it for cryptosign
+    //! challenges and payload passthru payloads among others.
+    //! ## Examples
+    //! ```
+    //! use wamp_core::messages::payload::binary;
+    //! use serde_json::json;
+    //!
+    //! let value = binary::encode(&[0x10, 0xe3, 0xff, 0x9f, 0xb8]);
+    //!
+    //! assert_eq!(value, json!("\0EOP/n7g="));
+    //! assert_eq!(binary::decode(&value).unwrap(), vec![0x10, 0xe3, 0xff, 0x9f, 0xb8]);
+    //! ```
+    use serde_json::Value;
+
+    use crate::error::Error;
+
+    /// The first character of a string holding binary data.
+    pub const PREFIX: char = '\0';
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    /// Encode `bytes` as a binary string value.
+    pub fn encode(bytes: &[u8]) -> Value {
+        let mut encoded = String::with_capacity(1 + bytes.len().div_ceil(3) * 4);
+        encoded.push(PREFIX);
+        for chunk in bytes.chunks(3) {
+            let byte = |index: usize| u32::from(chunk.get(index).copied().unwrap_or(0));
+            let group = byte(0) << 16 | byte(1) << 8 | byte(2);
+            for index in 0..4 {
+                if index <= chunk.len() {
+                    let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                    encoded.push(char::from(ALPHABET[sextet as usize]));
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        Value::String(encoded)
+    }
+
+    /// Whether `value` is a binary string, without checking its base64.
+    pub fn is_binary(value: &Value) -> bool {
+        value.as_str().is_some_and(|s| s.starts_with(PREFIX))
+    }
+
+    fn sextet(c: u8) -> Option<u32> {
+        let sextet = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(u32::from(sextet))
+    }
+
+    /// Decode a binary string value.
+    ///
+    /// Fails with [`Error::InvalidBinary`] when `value` is not a string starting with `\0`, or
+    /// when the rest is not padded base64.
+    pub fn decode(value: &Value) -> Result<Vec<u8>, Error> {
+        let encoded = value
+            .as_str()
+            .and_then(|s| s.strip_prefix(PREFIX))
+            .ok_or(Error::InvalidBinary("not a string starting with \\0"))?
+            .as_bytes();
+        if encoded.len() % 4 != 0 {
+            return Err(Error::InvalidBinary("base64 length is not a multiple of 4"));
+        }
+        let groups = encoded.len() / 4;
+        let mut bytes = Vec::with_capacity(groups * 3);
+        for (index, chunk) in encoded.chunks(4).enumerate() {
+            let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if padding > 2 || (padding > 0 && index + 1 != groups) {
+                return Err(Error::InvalidBinary("misplaced base64 padding"));
+            }
+            let mut group = 0;
+            for c in &chunk[..4 - padding] {
+                let sextet = sextet(*c).ok_or(Error::InvalidBinary("invalid base64 character"))?;
+                group = group << 6 | sextet;
+            }
+            group <<= 6 * padding;
+            bytes.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+        }
+        Ok(bytes)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// # Payload
+/// Positional and keyword arguments shared between clones.
+///
+/// `args` is an array or `Null` and `kwargs` an object or `Null`, as on the untyped messages.
+pub struct Payload {
+    args: Arc<Value>,
+    kwargs: Arc<Value>,
+}
+
+impl Payload {
+    /// Create a payload from positional and keyword arguments.
+    pub fn new(args: Value, kwargs: Value) -> Self {
+        Payload {
+            args: Arc::new(args),
+            kwargs: Arc::new(kwargs),
+        }
+    }
+
+    /// Positional arguments, `Null` when absent.
+    pub fn args(&self) -> &Value {
+        &self.args
+    }
+
+    /// Keyword arguments, `Null` when absent.
+    pub fn kwargs(&self) -> &Value {
+        &self.kwargs
+    }
+
+    /// The keyword argument `key` decoded as binary data, see [`binary`], `None` when absent.
+    pub fn kwarg_bytes(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        self.kwargs.get(key).map(binary::decode).transpose()
+    }
+
+    /// Whether both payloads share the same `args` and `kwargs` allocations.
+    pub fn ptr_eq(&self, other: &Payload) -> bool {
+        Arc::ptr_eq(&self.args, &other.args) && Arc::ptr_eq(&self.kwargs, &other.kwargs)
+    }
+
+    /// Append a positional argument, copying `args` first if it is shared.
+    ///
+    /// `Null` args become an array, any other non array value is replaced.
+    pub fn with_arg(mut self, value: Value) -> Self {
+        let args = Arc::make_mut(&mut self.args);
+        match args {
+            Value::Array(args) => args.push(value),
+            _ => *args = json!([value]),
+        }
+        self
+    }
+
+    /// Set a keyword argument, copying `kwargs` first if it is shared.
+    ///
+    /// `Null` kwargs become an object, any other non object value is replaced.
+    pub fn with_kwarg<K: ToString>(mut self, key: K, value: Value) -> Self {
+        let kwargs = Arc::make_mut(&mut self.kwargs);
+        match kwargs {
+            Value::Object(kwargs) => {
+                kwargs.insert(key.to_string(), value);
+            }
+            _ => *kwargs = json!({ key.to_string(): value }),
+        }
+        self
+    }
+
+    /// Take `args` and `kwargs` out, copying only the parts that are still shared.
+    pub fn into_parts(self) -> (Value, Value) {
+        (
+            Arc::unwrap_or_clone(self.args),
+            Arc::unwrap_or_clone(self.kwargs),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Shared Event
+/// An [`Event`] whose payload is shared between clones, for brokers delivering one publication
+/// to many subscribers.
+pub struct SharedEvent {
+    /// The subscription the event is delivered for.
+    pub subscription: u64,
+    /// The publication id assigned by the broker.
+    pub publication: u64,
+    /// Event details.
+    pub details: Value,
+    /// The shared `args` and `kwargs`.
+    pub payload: Payload,
+}
+
+impl SharedEvent {
+    /// Set a keyword argument on this event only, see [`Payload::with_kwarg`].
+    pub fn with_kwarg<K: ToString>(mut self, key: K, value: Value) -> Self {
+        self.payload = self.payload.with_kwarg(key, value);
+        self
+    }
+
+    /// The trust level the broker assigned to the publication, `details.trustlevel`.
+    pub fn trust_level(&self) -> Option<u64> {
+        self.details["trustlevel"].as_u64()
+    }
+
+    /// Assigns the trust level of the publication in `details.trustlevel`, leaving the payload
+    /// shared.
+    pub fn with_trust_level(mut self, trust_level: u64) -> Self {
+        if !self.details.is_object() {
+            self.details = json!({});
+        }
+        self.details["trustlevel"] = json!(trust_level);
+        self
+    }
+}
+
+impl From<Event> for SharedEvent {
+    fn from(event: Event) -> Self {
+        SharedEvent {
+            subscription: event.subscription,
+            publication: event.publication,
+            details: event.details,
+            payload: Payload::new(event.args, event.kwargs),
+        }
+    }
+}
+
+impl From<SharedEvent> for Event {
+    fn from(event: SharedEvent) -> Self {
+        let (args, kwargs) = event.payload.into_parts();
+        Event {
+            subscription: event.subscription,
+            publication: event.publication,
+            details: event.details,
+            args,
+            kwargs,
+        }
+    }
+}
+
+impl Serialize for SharedEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let args = helpers::ser_value_is_args::<S, _>(
+            self.payload.args(),
+            "Args must be Array like or Null.",
+        )?;
+        let kwargs = helpers::ser_value_is_kwargs::<S, _>(
+            self.payload.kwargs(),
+            "Kwargs must be Object like or Null.",
+        )?;
+        let head = (
+            Event::ID,
+            &self.subscription,
+            &self.publication,
+            &self.details,
+        );
+        match (args.is_null(), kwargs.is_null()) {
+            (true, true) => head.serialize(serializer),
+            (true, false) => {
+                (head.0, head.1, head.2, head.3, json!([]), kwargs).serialize(serializer)
+            }
+            (false, true) => (head.0, head.1, head.2, head.3, args).serialize(serializer),
+            (false, false) => (head.0, head.1, head.2, head.3, args, kwargs).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Event::deserialize(deserializer).map(SharedEvent::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json, to_string};
+
+    use super::*;
+
+    #[test]
+    fn same_wire_format() {
+        for d in [
+            r#"[36,1,2,{}]"#,
+            r#"[36,1,2,{},[1]]"#,
+            r#"[36,1,2,{},[],{"color":"orange"}]"#,
+            r#"[36,1,2,{"topic":"a.b"},[1],{"color":"orange"}]"#,
+        ] {
+            let event: Event = from_str(d).unwrap();
+            let shared: SharedEvent = from_str(d).unwrap();
+            assert_eq!(to_string(&shared).unwrap(), to_string(&event).unwrap());
+            assert_eq!(Event::from(shared), event);
+        }
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let payload = Payload::new(json!([1]), json!(null));
+        let copy = payload.clone().with_arg(json!(2));
+        assert_eq!(payload.args(), &json!([1]));
+        assert_eq!(copy.args(), &json!([1, 2]));
+        assert!(Arc::ptr_eq(&payload.kwargs, &copy.kwargs));
+        assert_eq!(
+            copy.with_kwarg("a", json!(true)).kwargs(),
+            &json!({"a": true})
+        );
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        // The example of the binary conversion rule of the spec.
+        let spec = [0x10, 0xe3, 0xff, 0x9f, 0xb8];
+        let value = binary::encode(&spec);
+        assert_eq!(to_string(&value).unwrap(), r#""\u0000EOP/n7g=""#);
+        assert!(binary::is_binary(&value));
+        assert_eq!(binary::decode(&value).unwrap(), spec);
+
+        for (bytes, encoded) in [
+            (&b""[..], "\0"),
+            (b"f", "\0Zg=="),
+            (b"fo", "\0Zm8="),
+            (b"foo", "\0Zm9v"),
+            (b"foobar", "\0Zm9vYmFy"),
+        ] {
+            assert_eq!(binary::encode(bytes), json!(encoded));
+            assert_eq!(binary::decode(&json!(encoded)).unwrap(), bytes);
+        }
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(binary::decode(&binary::encode(&all)).unwrap(), all);
+
+        for invalid in [
+            json!("Zm9v"),
+            json!(1),
+            json!("\0Zm9"),
+            json!("\0Zg==Zm9v"),
+            json!("\0Z==="),
+        ] {
+            assert!(matches!(
+                binary::decode(&invalid),
+                Err(Error::InvalidBinary(_))
+            ));
+        }
+        assert!(!binary::is_binary(&json!("Zm9v")));
+
+        let payload = Payload::new(json!([]), json!({ "data": value, "name": "joe" }));
+        assert_eq!(payload.kwarg_bytes("data").unwrap(), Some(spec.to_vec()));
+        assert_eq!(payload.kwarg_bytes("missing").unwrap(), None);
+        assert!(payload.kwarg_bytes("name").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{Call, Event, Invocation, Publish, WampResult, Yield};
+use crate::error::Error;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Payload Passthrough Mode - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-payload-passthru-mode)
+/// Typed view of the `ppt_*` keys used by the payload passthrough / end-to-end encryption extension.
+///
+/// The keys live in the options or details object of the payload carrying messages:
+///
+/// | Message      | Object    |
+/// |--------------|-----------|
+/// | `Call`       | `options` |
+/// | `Publish`    | `options` |
+/// | `Yield`      | `options` |
+/// | `Invocation` | `details` |
+/// | `Event`      | `details` |
+/// | `WampResult` | `details` |
+///
+/// The router passes the args payload through untouched, so it stays an opaque value
+/// (usually a single binary-in-JSON element) as far as this crate is concerned.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Call, PptOptions};
+/// use serde_json::{json, Value};
+///
+/// let mut call = Call {
+///     request_id: 1,
+///     options: json!({ "timeout": 1000 }),
+///     procedure: "com.myapp.secret".into(),
+///     args: json!(["\0c2VjcmV0"]),
+///     kwargs: Value::Null
+/// };
+///
+/// call.set_ppt(PptOptions {
+///     ppt_scheme: Some("wamp".to_string()),
+///     ppt_serializer: Some("cbor".to_string()),
+///     ppt_cipher: Some("xsalsa20poly1305".to_string()),
+///     ppt_keyid: Some("key1".to_string()),
+/// }).unwrap();
+///
+/// assert_eq!(call.options, json!({
+///     "timeout": 1000,
+///     "ppt_scheme": "wamp",
+///     "ppt_serializer": "cbor",
+///     "ppt_cipher": "xsalsa20poly1305",
+///     "ppt_keyid": "key1"
+/// }));
+/// assert_eq!(call.ppt().ppt_scheme.as_deref(), Some("wamp"));
+/// ```
+pub struct PptOptions {
+    /// Identifies the payload schema, `"wamp"` for the WAMP defined scheme or a custom one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_scheme: Option<String>,
+    /// Serializer used for the inner payload (`native`, `json`, `msgpack`, `cbor`, `flatbuffers`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_serializer: Option<String>,
+    /// Cipher used to encrypt the payload, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_cipher: Option<String>,
+    /// Identifier of the key used to encrypt the payload, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppt_keyid: Option<String>,
+}
+
+impl PptOptions {
+    /// Key names used for the payload passthrough metadata, in wire spelling.
+    pub const KEYS: [&'static str; 4] = ["ppt_scheme", "ppt_serializer", "ppt_cipher", "ppt_keyid"];
+
+    /// Read the `ppt_*` keys out of an options or details object.
+    ///
+    /// Keys that are missing, or that are not strings, are left as `None`.
+    pub fn from_value(value: &Value) -> Self {
+        let get = |key: &str| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        };
+        PptOptions {
+            ppt_scheme: get("ppt_scheme"),
+            ppt_serializer: get("ppt_serializer"),
+            ppt_cipher: get("ppt_cipher"),
+            ppt_keyid: get("ppt_keyid"),
+        }
+    }
+
+    /// Write the `ppt_*` keys into an options or details object.
+    ///
+    /// Keys set to `None` are removed, every other key of the object is left untouched.
+    /// A `Null` value is turned into an object first, any other value that is not an object
+    /// fails with [`Error::Error`] and is left untouched.
+    pub fn apply_to(&self, value: &mut Value) -> Result<(), Error> {
+        if value.is_null() {
+            *value = Value::Object(Map::new());
+        }
+        let Some(map) = value.as_object_mut() else {
+            return Err(Error::Error(
+                "Payload passthrough keys can only be set on an object.",
+            ));
+        };
+        let fields = [
+            &self.ppt_scheme,
+            &self.ppt_serializer,
+            &self.ppt_cipher,
+            &self.ppt_keyid,
+        ];
+        for (key, field) in Self::KEYS.iter().zip(fields) {
+            match field {
+                Some(v) => {
+                    map.insert(key.to_string(), Value::String(v.clone()));
+                }
+                None => {
+                    map.remove(*key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether none of the `ppt_*` keys are set.
+    pub fn is_empty(&self) -> bool {
+        self.ppt_scheme.is_none()
+            && self.ppt_serializer.is_none()
+            && self.ppt_cipher.is_none()
+            && self.ppt_keyid.is_none()
+    }
+}
+
+macro_rules! ppt_accessors {
+    ($typ:ident, $field:ident) => {
+        impl $typ {
+            #[doc = concat!("Payload passthrough metadata read from the `", stringify!($field), "` object.")]
+            pub fn ppt(&self) -> PptOptions {
+                PptOptions::from_value(&self.$field)
+            }
+
+            #[doc = concat!("Set the payload passthrough metadata on the `", stringify!($field), "` object.")]
+            ///
+            /// Fails like [`PptOptions::apply_to`].
+            pub fn set_ppt(&mut self, ppt: PptOptions) -> Result<(), Error> {
+                ppt.apply_to(&mut self.$field)
+            }
+        }
+    };
+}
+
+ppt_accessors!(Call, options);
+ppt_accessors!(Publish, options);
+ppt_accessors!(Yield, options);
+ppt_accessors!(Invocation, details);
+ppt_accessors!(Event, details);
+ppt_accessors!(WampResult, details);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json, to_string};
+
+    use super::PptOptions;
+    use crate::messages::{Event, Publish};
+
+    #[test]
+    fn round_trip() {
+        let d1 = r#"[16,1,{"ppt_cipher":"xsalsa20poly1305","ppt_keyid":"key1","ppt_scheme":"wamp","ppt_serializer":"cbor"},"com.myapp.topic",["\u0000AQID"]]"#;
+        let p1: Publish = from_str(d1).unwrap();
+        let ppt = p1.ppt();
+        assert_eq!(
+            ppt,
+            PptOptions {
+                ppt_scheme: Some("wamp".to_string()),
+                ppt_serializer: Some("cbor".to_string()),
+                ppt_cipher: Some("xsalsa20poly1305".to_string()),
+                ppt_keyid: Some("key1".to_string()),
+            }
+        );
+
+        let mut e1 = Event {
+            subscription: 2,
+            publication: 3,
+            details: json!({}),
+            args: p1.args.clone(),
+            kwargs: serde_json::Value::Null,
+        };
+        e1.set_ppt(ppt.clone()).unwrap();
+        let e2: Event = from_str(&to_string(&e1).unwrap()).unwrap();
+        assert_eq!(e2.ppt(), ppt);
+        assert_eq!(e2.args, p1.args);
+
+        e1.set_ppt(PptOptions::default()).unwrap();
+        assert_eq!(e1.details, json!({}));
+        assert!(e1.ppt().is_empty());
+
+        e1.details = serde_json::Value::Null;
+        e1.set_ppt(ppt.clone()).unwrap();
+        assert_eq!(e1.ppt(), ppt);
+        e1.details = json!(["not", "an", "object"]);
+        assert!(e1.set_ppt(ppt).is_err());
+        assert_eq!(e1.details, json!(["not", "an", "object"]));
+    }
+}
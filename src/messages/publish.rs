@@ -1,9 +1,7 @@
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use std::borrow::Cow;
 
 use super::{helpers, MessageDirection, WampMessage};
 
@@ -19,7 +17,7 @@ use super::{helpers, MessageDirection, WampMessage};
 /// let publish = Publish {
 ///     request_id: 1,
 ///     options: json!({ }),
-///     topic: "topic".to_string(),
+///     topic: "topic".into(),
 ///     args: Value::Null,
 ///     kwargs: Value::Null
 /// };
@@ -37,7 +35,7 @@ use super::{helpers, MessageDirection, WampMessage};
 /// let publish = Publish {
 ///     request_id: 7814135,
 ///     options: json!({}),
-///     topic: "com.myapp.user.new".to_string(),
+///     topic: "com.myapp.user.new".into(),
 ///     args: json!(["johnny"]),
 ///     kwargs: json!({"firstname":"John","surname":"Doe"})
 /// };
@@ -56,7 +54,7 @@ use super::{helpers, MessageDirection, WampMessage};
 /// let publish = Publish {
 ///     request_id: 7814135,
 ///     options: json!({}),
-///     topic: "com.myapp.user.new".to_string(),
+///     topic: "com.myapp.user.new".into(),
 ///     args: json!(["johnny"]),
 ///     kwargs: json!({"firstname":"John","surname":"Doe"})
 /// };
@@ -69,7 +67,7 @@ use super::{helpers, MessageDirection, WampMessage};
 pub struct Publish {
     pub request_id: u64,
     pub options: Value,
-    pub topic: String,
+    pub topic: Cow<'static, str>,
     pub args: Value,
     pub kwargs: Value,
 }
@@ -88,7 +86,7 @@ pub struct Publish {
 ///
 /// // Which is the same as creating this:
 /// let publish2 = Publish {
-///     topic: "topic".to_string(),
+///     topic: "topic".into(),
 ///     request_id: 1,
 ///     options: json!({}),
 ///     args: Value::Null,
@@ -116,12 +114,37 @@ pub struct Publish {
 /// // Create a publish with custom options, and both custom args and kwargs
 /// // Note that when you use all "required" arguments for the struuct, keyword arguments should not be used for args and kwargs
 /// let _ = publish!("topic", json!({}), json!([]), json!({}));
+///
+/// // Create an acknowledged publish, optionally with custom args and kwargs
+/// let publish = publish!("topic", acknowledge);
+/// assert_eq!(publish.options, json!({ "acknowledge": true }));
+/// assert!(publish.expects_ack());
+///
+/// let publish = publish!("topic", acknowledge, args: json!([1]), kwargs: json!({ "key": "value" }));
+/// assert_eq!(publish.options, json!({ "acknowledge": true }));
+/// assert_eq!(publish.args, json!([1]));
 /// ```
 macro_rules! publish {
     ($topic:expr) => {
         publish! {$topic, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
     };
 
+    ($topic:expr, acknowledge) => {
+        publish! {$topic, serde_json::json!({ "acknowledge": true }), serde_json::Value::Null, serde_json::Value::Null}
+    };
+
+    ($topic:expr, acknowledge, args: $args:expr) => {
+        publish! {$topic, serde_json::json!({ "acknowledge": true }), $args, serde_json::Value::Null}
+    };
+
+    ($topic:expr, acknowledge, kwargs: $kwargs:expr) => {
+        publish! {$topic, serde_json::json!({ "acknowledge": true }), serde_json::Value::Null, $kwargs}
+    };
+
+    ($topic:expr, acknowledge, args: $args:expr, kwargs: $kwargs:expr) => {
+        publish! {$topic, serde_json::json!({ "acknowledge": true }), $args, $kwargs}
+    };
+
     ($topic:expr, $options:expr) => {
         publish! {$topic, $options, serde_json::Value::Null, serde_json::Value::Null}
     };
@@ -150,43 +173,83 @@ macro_rules! publish {
         Publish {
             request_id: $crate::factories::increment(),
             options: $options,
-            topic: $topic.to_string(),
+            topic: $topic.into(),
             args: $args,
             kwargs: $kwargs,
         }
     }};
 }
 
+impl Publish {
+    /// Whether the broker answers this publish with PUBLISHED, only when `options.acknowledge`
+    /// is `true`.
+    pub fn expects_ack(&self) -> bool {
+        self.options["acknowledge"].as_bool() == Some(true)
+    }
+
+    /// # Acknowledged
+    /// Sets `options.acknowledge`, asking the broker to answer with PUBLISHED or ERROR.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::publish;
+    /// use wamp_core::messages::Publish;
+    /// use serde_json::json;
+    ///
+    /// let publish = publish!("com.myapp.topic1");
+    /// assert!(!publish.expects_ack());
+    ///
+    /// let publish = publish.acknowledged();
+    /// assert!(publish.expects_ack());
+    /// assert_eq!(publish.options, json!({ "acknowledge": true }));
+    /// ```
+    pub fn acknowledged(mut self) -> Self {
+        if !self.options.is_object() {
+            self.options = json!({});
+        }
+        self.options["acknowledge"] = json!(true);
+        self
+    }
+}
+
+object_form!(Publish {
+    #[serde(default)]
+    request_id: u64,
+    #[serde(default = "empty_object")]
+    options: Value,
+    topic: Cow<'static, str>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
 impl WampMessage for Publish {
     const ID: u64 = 16;
+    const NAME: &'static str = "PUBLISH";
+    const FIELDS: &'static [&'static str] = &["request_id", "options", "topic", "args", "kwargs"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &[
+        "acknowledge",
+        "exclude_me",
+        "exclude",
+        "exclude_authid",
+        "exclude_authrole",
+        "eligible",
+        "eligible_authid",
+        "eligible_authrole",
+        "disclose_me",
+        "retain",
+        "ppt_scheme",
+        "ppt_serializer",
+        "ppt_cipher",
+        "ppt_keyid",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Publisher: tx,
+            Broker: rx,
+            _: none,
+        )
     }
 }
 
@@ -235,75 +298,30 @@ impl Serialize for Publish {
     }
 }
 
-impl<'de> Deserialize<'de> for Publish {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct PublishVisitor(
-            PhantomData<u8>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<String>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-        );
-
-        impl<'vi> Visitor<'vi> for PublishVisitor {
-            type Value = Publish;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Publish components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Publish, A, _>(&message_id, "Publish")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64.",
-                )?;
-                let options: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "Options must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
-                let topic: String =
-                    helpers::deser_seq_element(&mut seq, "topic must be present and object like.")?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(Publish {
-                    request_id,
-                    options,
-                    topic,
-                    args,
-                    kwargs,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Publish",
-            &["request_id", "options", "topic", "args", "kwargs"],
-            PublishVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
-        )
-    }
-}
+deserialize_message!(Publish, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Publish",
+        helpers::FieldContext::new(1, "request_id", "Request ID must be present and type u64."),
+    )?;
+    let options: Value = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(2, "options", "Options must be present and object like."),
+    )?;
+    helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
+    let topic: String = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(3, "topic", "topic must be present and object like."),
+    )?;
+    let args: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+    let kwargs: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+    Ok(Publish {
+        request_id,
+        options,
+        topic: topic.into(),
+        args,
+        kwargs,
+    })
+});
@@ -1,10 +1,7 @@
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
 
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, MessageDirection, Publish, WampMessage};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Published - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-published-2)
@@ -98,36 +95,45 @@ macro_rules! published {
     };
 }
 
+impl Published {
+    /// # For
+    /// The PUBLISHED a broker answers `publish` with, echoing its request id.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Publish, Published};
+    /// use serde_json::json;
+    ///
+    /// let publish = Publish {
+    ///     request_id: 239714735,
+    ///     options: json!({ "acknowledge": true }),
+    ///     topic: "com.myapp.mytopic1".into(),
+    ///     args: json!(null),
+    ///     kwargs: json!(null)
+    /// };
+    ///
+    /// let published = Published::r#for(&publish, 5512315355);
+    /// assert_eq!(published.request_id, publish.request_id);
+    /// assert_eq!(published.publication, 5512315355);
+    /// ```
+    pub fn r#for(publish: &Publish, publication_id: u64) -> Published {
+        Published {
+            request_id: publish.request_id,
+            publication: publication_id,
+        }
+    }
+}
+
 impl WampMessage for Published {
     const ID: u64 = 17;
+    const NAME: &'static str = "PUBLISHED";
+    const FIELDS: &'static [&'static str] = &["request_id", "publication"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        direction_table!(role;
+            Publisher: rx,
+            Broker: tx,
+            _: none,
+        )
     }
 }
 
@@ -140,56 +146,33 @@ impl Serialize for Published {
     }
 }
 
-impl<'de> Deserialize<'de> for Published {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct PublishedVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for PublishedVisitor {
-            type Value = Published;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Published components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Published, A, _>(&message_id, "Published")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                let publication: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "publication must be present and object like.",
-                )?;
-                Ok(Published {
-                    request_id,
-                    publication,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Published",
-            &["request_id", "publication"],
-            PublishedVisitor(PhantomData, PhantomData, PhantomData),
-        )
-    }
-}
+deserialize_message!(Published, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Published",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let publication = helpers::deser_id_element(
+        &mut seq,
+        "Published",
+        helpers::FieldContext::new(
+            2,
+            "publication",
+            "publication must be present and object like.",
+        ),
+    )?;
+    Ok(Published {
+        request_id,
+        publication,
+    })
+});
 
 #[cfg(test)]
 mod tests {
     use serde_json::{from_str, to_string};
 
     use super::Published;
+    use crate::messages::Publish;
 
     #[test]
     fn test() {
@@ -201,4 +184,14 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Published>(d1).unwrap(), p1);
     }
+
+    #[test]
+    fn echoes_request_id() {
+        let publish: Publish =
+            from_str(r#"[16,239714735,{"acknowledge":true},"com.myapp.mytopic1"]"#).unwrap();
+        assert_eq!(
+            to_string(&Published::r#for(&publish, 4429313566)).unwrap(),
+            r#"[17,239714735,4429313566]"#
+        );
+    }
 }
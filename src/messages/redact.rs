@@ -0,0 +1,239 @@
+use regex::Regex;
+use serde_json::Value;
+
+use super::Messages;
+
+/// Keys redacted by [`RedactionPolicy::default`].
+pub const DEFAULT_REDACTED_KEYS: [&str; 6] = [
+    "*password*",
+    "*secret*",
+    "ticket",
+    "*token*",
+    "signature",
+    "*_key",
+];
+
+#[derive(Debug, Clone)]
+/// # Redaction Policy
+/// Describes which object keys of a message are sensitive, for [`Messages::redacted`].
+///
+/// Keys are matched case-insensitively against glob patterns, where `*` matches any run of
+/// characters and `?` a single character. The value of a matching key is replaced by the
+/// placeholder, at any depth of the options, details, args and kwargs of a message.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{Messages, Publish, RedactionPolicy};
+/// use serde_json::json;
+///
+/// let policy = RedactionPolicy::new().key("password").key("x_*");
+/// let message = Messages::from(Publish {
+///     request_id: 1,
+///     options: json!({ "X_Trace": "abc" }),
+///     topic: "com.myapp.login".into(),
+///     args: json!([]),
+///     kwargs: json!({ "user": "bob", "Password": "hunter2" })
+/// });
+///
+/// let Messages::Publish(redacted) = message.redacted(&policy) else { unreachable!() };
+/// assert_eq!(redacted.options, json!({ "X_Trace": "***" }));
+/// assert_eq!(redacted.kwargs, json!({ "user": "bob", "Password": "***" }));
+/// ```
+pub struct RedactionPolicy {
+    keys: Vec<Regex>,
+    /// The value sensitive values are replaced with, `"***"` by default.
+    pub placeholder: String,
+}
+
+impl RedactionPolicy {
+    /// A policy without any redacted keys. Authenticate signatures and challenge secrets are
+    /// always redacted.
+    pub fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            placeholder: "***".to_string(),
+        }
+    }
+
+    /// Add a key glob pattern to redact.
+    pub fn key(mut self, pattern: &str) -> Self {
+        let pattern = regex::escape(pattern)
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".");
+        self.keys
+            .push(Regex::new(&format!("(?i)^{pattern}$")).expect("escaped glob is a valid regex"));
+        self
+    }
+
+    /// Replace the placeholder sensitive values are replaced with.
+    pub fn placeholder<T: ToString>(mut self, placeholder: T) -> Self {
+        self.placeholder = placeholder.to_string();
+        self
+    }
+
+    /// Whether `key` matches one of the redacted key patterns.
+    pub fn matches(&self, key: &str) -> bool {
+        self.keys.iter().any(|pattern| pattern.is_match(key))
+    }
+
+    /// Redact every matching key of `value` in place, recursing into nested objects and arrays.
+    pub fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    if self.matches(key) {
+                        *value = Value::String(self.placeholder.clone());
+                    } else {
+                        self.redact(value);
+                    }
+                }
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| self.redact(value)),
+            _ => {}
+        }
+    }
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        DEFAULT_REDACTED_KEYS
+            .iter()
+            .fold(Self::new(), |policy, key| policy.key(key))
+    }
+}
+
+impl Messages {
+    /// # Redacted
+    /// Clone the message with its sensitive values replaced by the policy placeholder, so it
+    /// can be logged.
+    ///
+    /// The Authenticate signature and the `challenge` and `salt` of a Challenge are always
+    /// replaced, every other object key is redacted when it matches `policy`.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Authenticate, Messages, RedactionPolicy};
+    /// use serde_json::json;
+    ///
+    /// let message = Messages::from(Authenticate {
+    ///     signature: "secret-ticket".to_string(),
+    ///     details: json!({})
+    /// });
+    ///
+    /// let Messages::Authenticate(redacted) = message.redacted(&RedactionPolicy::default()) else {
+    ///     unreachable!()
+    /// };
+    /// assert_eq!(redacted.signature, "***");
+    /// ```
+    pub fn redacted(&self, policy: &RedactionPolicy) -> Messages {
+        let mut message = self.clone();
+        let values: Vec<&mut Value> = match &mut message {
+            Messages::Abort(v) => vec![&mut v.details],
+            Messages::Authenticate(v) => {
+                v.signature = policy.placeholder.clone();
+                vec![&mut v.details]
+            }
+            Messages::Call(v) => vec![&mut v.options, &mut v.args, &mut v.kwargs],
+            Messages::Cancel(v) => vec![&mut v.options],
+            Messages::Challenge(v) => {
+                if let Value::Object(details) = &mut v.details {
+                    for key in ["challenge", "salt"] {
+                        if let Some(value) = details.get_mut(key) {
+                            *value = Value::String(policy.placeholder.clone());
+                        }
+                    }
+                }
+                vec![&mut v.details]
+            }
+            Messages::Error(v) => vec![&mut v.details, &mut v.args, &mut v.kwargs],
+            Messages::Event(v) => vec![&mut v.details, &mut v.args, &mut v.kwargs],
+            Messages::Goodbye(v) => vec![&mut v.details],
+            Messages::Hello(v) => vec![&mut v.details],
+            Messages::Interrupt(v) => vec![&mut v.options],
+            Messages::Invocation(v) => vec![&mut v.details, &mut v.args, &mut v.kwargs],
+            Messages::Publish(v) => vec![&mut v.options, &mut v.args, &mut v.kwargs],
+            Messages::Register(v) => vec![&mut v.options],
+            Messages::Result(v) => vec![&mut v.details, &mut v.args, &mut v.kwargs],
+            Messages::Subscribe(v) => vec![&mut v.options],
+            Messages::Welcome(v) => vec![&mut v.details],
+            Messages::Yield(v) => vec![&mut v.options, &mut v.args, &mut v.kwargs],
+            Messages::Extension(values) => values.iter_mut().collect(),
+            Messages::Published(_)
+            | Messages::Registered(_)
+            | Messages::Subscribed(_)
+            | Messages::Unregister(_)
+            | Messages::Unregistered(_)
+            | Messages::Unsubscribe(_)
+            | Messages::Unsubscribed(_) => vec![],
+        };
+        for value in values {
+            policy.redact(value);
+        }
+        message
+    }
+
+    /// # Redacted summary
+    /// The JSON wire form of the [redacted](Messages::redacted) message, for logging.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Hello, Messages, RedactionPolicy};
+    /// use serde_json::json;
+    ///
+    /// let message = Messages::from(Hello {
+    ///     realm: "realm1".into(),
+    ///     details: json!({ "authextra": { "ticket": "abc" } })
+    /// });
+    ///
+    /// assert_eq!(
+    ///     message.redacted_summary(&RedactionPolicy::default()),
+    ///     r#"[1,"realm1",{"authextra":{"ticket":"***"}}]"#
+    /// );
+    /// ```
+    pub fn redacted_summary(&self, policy: &RedactionPolicy) -> String {
+        serde_json::to_string(&self.redacted(policy)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::messages::{Challenge, Invocation};
+
+    #[test]
+    fn nested_keys() {
+        let message = Messages::from(Invocation {
+            request_id: 1,
+            registration: 2,
+            details: json!({}),
+            args: json!([{ "auth": { "Access_Token": "abc" } }]),
+            kwargs: json!({ "user": { "name": "bob", "credentials": [{ "PASSWORD": "x" }] } }),
+        });
+        let Messages::Invocation(redacted) = message.redacted(&RedactionPolicy::default()) else {
+            unreachable!()
+        };
+        assert_eq!(
+            redacted.args,
+            json!([{ "auth": { "Access_Token": "***" } }])
+        );
+        assert_eq!(
+            redacted.kwargs,
+            json!({ "user": { "name": "bob", "credentials": [{ "PASSWORD": "***" }] } })
+        );
+    }
+
+    #[test]
+    fn challenge() {
+        let message = Messages::from(Challenge {
+            authmethod: "wampcra".to_string(),
+            details: json!({ "challenge": "{\"nonce\":\"x\"}", "salt": "s", "iterations": 1000 }),
+        });
+        let policy = RedactionPolicy::new().placeholder("<redacted>");
+        let Messages::Challenge(redacted) = message.redacted(&policy) else {
+            unreachable!()
+        };
+        assert_eq!(
+            redacted.details,
+            json!({ "challenge": "<redacted>", "salt": "<redacted>", "iterations": 1000 })
+        );
+    }
+}
@@ -1,6 +1,6 @@
-use std::marker::PhantomData;
+use std::borrow::Cow;
 
-use serde::{de::Visitor, Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::{messages::helpers, roles::Roles};
@@ -20,7 +20,7 @@ use super::{MessageDirection, WampMessage};
 ///
 /// let registerd = Register {
 ///     request_id: 1,
-///     procedure: "procedure".to_string(),
+///     procedure: "procedure".into(),
 ///     options: json!({})
 /// };
 /// # registerd1.options = json!({});
@@ -39,7 +39,7 @@ use super::{MessageDirection, WampMessage};
 ///
 /// let register = Register {
 ///     request_id: 1,
-///     procedure: "com.myapp.myprocedure1".to_string(),
+///     procedure: "com.myapp.myprocedure1".into(),
 ///     options: json!({})
 /// };
 ///
@@ -67,7 +67,7 @@ use super::{MessageDirection, WampMessage};
 pub struct Register {
     pub request_id: u64,
     pub options: Value,
-    pub procedure: String,
+    pub procedure: Cow<'static, str>,
 }
 
 #[macro_export]
@@ -87,7 +87,7 @@ pub struct Register {
 /// let register2 = Register {
 ///     request_id: 1,
 ///     options: json!({}),
-///     procedure: procedure.to_string()
+///     procedure: procedure.into()
 /// };
 ///
 /// assert_eq!(register, register2);
@@ -98,7 +98,7 @@ macro_rules! register {
     };
     ($procedure:expr, $options:expr) => {
         Register {
-            procedure: $procedure.to_string(),
+            procedure: $procedure.into(),
             options: $options,
             request_id: $crate::factories::increment(),
         }
@@ -107,34 +107,22 @@ macro_rules! register {
 
 impl WampMessage for Register {
     const ID: u64 = 64;
+    const NAME: &'static str = "REGISTER";
+    const FIELDS: &'static [&'static str] = &["request_id", "options", "procedure"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &[
+        "match",
+        "invoke",
+        "disclose_caller",
+        "concurrency",
+        "force_reregister",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: tx,
+            Dealer: rx,
+            _: none,
+        )
     }
 }
 
@@ -147,60 +135,34 @@ impl Serialize for Register {
     }
 }
 
-impl<'de> Deserialize<'de> for Register {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct RegisterVisitor(
-            PhantomData<u64>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<String>,
-        );
-
-        impl<'vi> Visitor<'vi> for RegisterVisitor {
-            type Value = Register;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Register components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message id must be present and type u64.",
-                )?;
-                helpers::validate_id::<Register, A, _>(&message_id, "Register")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64",
-                )?;
-                let options: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "options must be present and object like",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                let procedure: String = helpers::deser_seq_element(
-                    &mut seq,
-                    "procedure URI must be present and type String",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                Ok(Register {
-                    request_id,
-                    options,
-                    procedure,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
+deserialize_message!(
+    Register,
+    expecting: "A sequence of Register components.",
+    id_error: "Message id must be present and type u64.",
+    |seq| {
+        let request_id = helpers::deser_id_element(
+            &mut seq,
             "Register",
-            &["request_id", "options", "procedure"],
-            RegisterVisitor(PhantomData, PhantomData, PhantomData, PhantomData),
-        )
+            helpers::FieldContext::new(1, "request_id", "Request ID must be present and type u64"),
+        )?;
+        let options: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "options", "options must be present and object like"),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
+        let procedure: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(
+                3,
+                "procedure",
+                "procedure URI must be present and type String",
+            ),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
+        Ok(Register {
+            request_id,
+            options,
+            procedure: procedure.into(),
+        })
     }
-}
+);
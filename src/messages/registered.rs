@@ -1,9 +1,6 @@
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, MessageDirection, Register, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Registered - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-registered-2)
@@ -97,36 +94,43 @@ macro_rules! registered {
     };
 }
 
+impl Registered {
+    /// # For
+    /// The REGISTERED a dealer answers `register` with, echoing its request id.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Register, Registered};
+    /// use serde_json::json;
+    ///
+    /// let register = Register {
+    ///     request_id: 25349185,
+    ///     options: json!({}),
+    ///     procedure: "com.myapp.myprocedure1".into()
+    /// };
+    ///
+    /// let registered = Registered::r#for(&register, 5512315355);
+    /// assert_eq!(registered.request_id, register.request_id);
+    /// assert_eq!(registered.registration, 5512315355);
+    /// ```
+    pub fn r#for(register: &Register, registration_id: u64) -> Registered {
+        Registered {
+            request_id: register.request_id,
+            registration: registration_id,
+        }
+    }
+}
+
 impl WampMessage for Registered {
     const ID: u64 = 65;
+    const NAME: &'static str = "REGISTERED";
+    const FIELDS: &'static [&'static str] = &["request_id", "registration"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: rx,
+            Dealer: tx,
+            _: none,
+        )
     }
 }
 
@@ -139,56 +143,33 @@ impl Serialize for Registered {
     }
 }
 
-impl<'de> Deserialize<'de> for Registered {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct RegisteredVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for RegisteredVisitor {
-            type Value = Registered;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Registered components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Registered, A, _>(&message_id, "Registered")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                let registration: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "registration must be present and object like.",
-                )?;
-                Ok(Registered {
-                    request_id,
-                    registration,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Registered",
-            &["request_id", "registration"],
-            RegisteredVisitor(PhantomData, PhantomData, PhantomData),
-        )
-    }
-}
+deserialize_message!(Registered, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Registered",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let registration = helpers::deser_id_element(
+        &mut seq,
+        "Registered",
+        helpers::FieldContext::new(
+            2,
+            "registration",
+            "registration must be present and object like.",
+        ),
+    )?;
+    Ok(Registered {
+        request_id,
+        registration,
+    })
+});
 
 #[cfg(test)]
 mod tests {
     use serde_json::{from_str, to_string};
 
     use super::Registered;
+    use crate::messages::Register;
 
     #[test]
     fn test() {
@@ -200,4 +181,13 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Registered>(d1).unwrap(), p1);
     }
+
+    #[test]
+    fn echoes_request_id() {
+        let register: Register = from_str(r#"[64,25349185,{},"com.myapp.myprocedure1"]"#).unwrap();
+        assert_eq!(
+            to_string(&Registered::r#for(&register, 2103333224)).unwrap(),
+            r#"[65,25349185,2103333224]"#
+        );
+    }
 }
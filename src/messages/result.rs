@@ -1,10 +1,9 @@
-use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use serde_json::{json, Value};
+use super::{helpers, MessageDirection, Messages, WampError, WampErrorEvent, WampMessage};
+use crate::{error::Error, roles::Roles};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{from_value, json, Value};
 use std::fmt::Formatter;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Result - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-result-2)
@@ -151,36 +150,35 @@ macro_rules! result {
     };
 }
 
+object_form!(WampResult {
+    #[serde(default)]
+    request_id: u64,
+    #[serde(default = "empty_object")]
+    details: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
 impl WampMessage for WampResult {
     const ID: u64 = 50;
+    const NAME: &'static str = "RESULT";
+    const FIELDS: &'static [&'static str] = &["request_id", "details", "args", "kwargs"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &[
+        "progress",
+        "ppt_scheme",
+        "ppt_serializer",
+        "ppt_cipher",
+        "ppt_keyid",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        direction_table!(role;
+            Caller: rx,
+            Dealer: tx,
+            _: none,
+        )
     }
 }
 
@@ -213,71 +211,112 @@ impl Serialize for WampResult {
     }
 }
 
-impl<'de> Deserialize<'de> for WampResult {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct WampResultVisitor(
-            PhantomData<u64>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-        );
+deserialize_message!(WampResult, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "WampResult",
+        helpers::FieldContext::new(1, "request_id", "Request ID must be present and type u64."),
+    )?;
+    let details: Value = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(2, "details", "details must be present and object like."),
+    )?;
+    helpers::deser_value_is_object::<A, _>(&details, "details must be object like.")?;
+    let args: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+    let kwargs: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+    Ok(WampResult {
+        request_id,
+        details,
+        args,
+        kwargs,
+    })
+});
 
-        impl<'vi> Visitor<'vi> for WampResultVisitor {
-            type Value = WampResult;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of WampResult components.")
-            }
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Call Outcome
+/// The final answer to a CALL, either its RESULT or its ERROR.
+/// ## Examples
+/// ```
+/// use wamp_core::messages::{CallFailure, CallOutcome, Messages};
+/// use serde_json::from_str;
+///
+/// let message: Messages = from_str(r#"[50,7814135,{},[30]]"#).unwrap();
+/// let outcome = CallOutcome::try_from(message).unwrap();
+/// assert_eq!(outcome.parse_args::<(u64,)>().unwrap(), (30,));
+///
+/// let message: Messages = from_str(r#"[8,48,7814135,{},"com.myapp.error.overflow"]"#).unwrap();
+/// let outcome = CallOutcome::try_from(message).unwrap();
+/// assert!(matches!(outcome.parse_args::<(u64,)>(), Err(CallFailure::Wamp(_))));
+/// ```
+pub enum CallOutcome {
+    /// The call succeeded.
+    Result(WampResult),
+    /// The call failed.
+    Error(WampError),
+}
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<WampResult, A, _>(&message_id, "WampResult")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64.",
-                )?;
-                let details: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "details must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&details, "details must be object like.")?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(WampResult {
-                    request_id,
-                    details,
-                    args,
-                    kwargs,
-                })
-            }
+#[derive(Debug)]
+/// # Call Failure
+/// Why [`CallOutcome::parse_args`] did not produce a value.
+pub enum CallFailure {
+    /// The call failed with an ERROR.
+    Wamp(WampError),
+    /// The call succeeded, but its args do not decode to the requested type.
+    Decode(serde_json::Error),
+}
+
+impl std::fmt::Display for CallFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallFailure::Wamp(error) => error.fmt(f),
+            CallFailure::Decode(error) => write!(f, "invalid result args: {error}"),
         }
+    }
+}
 
-        deserializer.deserialize_struct(
-            "WampResult",
-            &["request_id", "details", "args", "kwargs"],
-            WampResultVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
-        )
+impl CallOutcome {
+    /// The request id of the call answered.
+    pub fn request_id(&self) -> u64 {
+        match self {
+            CallOutcome::Result(result) => result.request_id,
+            CallOutcome::Error(error) => error.request_id,
+        }
+    }
+
+    /// Convert into a std `Result`.
+    #[allow(clippy::result_large_err)]
+    pub fn into_result(self) -> Result<WampResult, WampError> {
+        match self {
+            CallOutcome::Result(result) => Ok(result),
+            CallOutcome::Error(error) => Err(error),
+        }
+    }
+
+    /// Decode the positional arguments of the result as `T`, a tuple, tuple struct or `Vec`.
+    ///
+    /// A result without args has `Null` args, which only decode to `()` or an `Option`.
+    #[allow(clippy::result_large_err)]
+    pub fn parse_args<T: DeserializeOwned>(self) -> Result<T, CallFailure> {
+        let result = self.into_result().map_err(CallFailure::Wamp)?;
+        from_value(result.args).map_err(CallFailure::Decode)
+    }
+}
+
+impl TryFrom<Messages> for CallOutcome {
+    type Error = Error;
+
+    /// Accepts RESULT messages, and ERROR messages answering a CALL. Anything else fails with
+    /// [`Error::InvalidFrameReceived`].
+    fn try_from(message: Messages) -> Result<Self, Error> {
+        match message {
+            Messages::Result(result) => Ok(CallOutcome::Result(result)),
+            Messages::Error(error) if error.event == WampErrorEvent::Call => {
+                Ok(CallOutcome::Error(error))
+            }
+            message => Err(Error::InvalidFrameReceived(Box::new(message))),
+        }
     }
 }
 
@@ -301,4 +340,44 @@ mod tests {
         assert_eq!(from_str::<WampResult>(d1).unwrap(), w1);
         assert_eq!(to_string(&w1).unwrap(), d1);
     }
+
+    #[test]
+    fn call_outcomes() {
+        use super::{CallFailure, CallOutcome};
+        use crate::{error::Error, messages::Messages};
+
+        let outcome = |frame: &str| CallOutcome::try_from(from_str::<Messages>(frame).unwrap());
+
+        let success = outcome(r#"[50,1,{},["alice",30]]"#).unwrap();
+        assert_eq!(success.request_id(), 1);
+        assert!(success.clone().into_result().is_ok());
+        assert_eq!(
+            success.parse_args::<(String, u8)>().unwrap(),
+            ("alice".to_string(), 30)
+        );
+
+        let error = outcome(r#"[8,48,2,{},"wamp.error.no_such_procedure"]"#).unwrap();
+        assert_eq!(error.request_id(), 2);
+        match error.parse_args::<(String, u8)>() {
+            Err(CallFailure::Wamp(error)) => {
+                assert_eq!(error.error, "wamp.error.no_such_procedure")
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let wrong_type = outcome(r#"[50,3,{},["alice","thirty"]]"#).unwrap();
+        assert!(matches!(
+            wrong_type.parse_args::<(String, u8)>(),
+            Err(CallFailure::Decode(_))
+        ));
+
+        assert!(matches!(
+            outcome(r#"[8,32,4,{},"wamp.error.not_authorized"]"#),
+            Err(Error::InvalidFrameReceived(_))
+        ));
+        assert!(matches!(
+            outcome(r#"[33,5,6]"#),
+            Err(Error::InvalidFrameReceived(_))
+        ));
+    }
 }
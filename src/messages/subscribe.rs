@@ -1,8 +1,8 @@
 use super::{MessageDirection, WampMessage};
 use crate::{messages::helpers, roles::Roles};
-use serde::{de::Visitor, Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::Value;
-use std::marker::PhantomData;
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Subscribe - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-subscribe-2)
@@ -17,7 +17,7 @@ use std::marker::PhantomData;
 ///
 /// let subscribed = Subscribe {
 ///     request_id: 1,
-///     topic: "topic".to_string(),
+///     topic: "topic".into(),
 ///     options: json!({})
 /// };
 /// # subscribed1.options = json!({});
@@ -36,7 +36,7 @@ use std::marker::PhantomData;
 ///
 /// let subscribe = Subscribe {
 ///     request_id: 1,
-///     topic: "com.myapp.mytopic1".to_string(),
+///     topic: "com.myapp.mytopic1".into(),
 ///     options: json!({})
 /// };
 ///
@@ -64,7 +64,7 @@ use std::marker::PhantomData;
 pub struct Subscribe {
     pub request_id: u64,
     pub options: Value,
-    pub topic: String,
+    pub topic: Cow<'static, str>,
 }
 
 #[macro_export]
@@ -84,7 +84,7 @@ pub struct Subscribe {
 /// let subscribe2 = Subscribe {
 ///     request_id: 1,
 ///     options: json!({}),
-///     topic: topic.to_string()
+///     topic: topic.into()
 /// };
 ///
 /// assert_eq!(subscribe, subscribe2);
@@ -95,7 +95,7 @@ macro_rules! subscribe {
     };
     ($topic:expr, $options:expr) => {
         Subscribe {
-            topic: $topic.to_string(),
+            topic: $topic.into(),
             options: $options,
             request_id: $crate::factories::increment(),
         }
@@ -104,34 +104,16 @@ macro_rules! subscribe {
 
 impl WampMessage for Subscribe {
     const ID: u64 = 32;
+    const NAME: &'static str = "SUBSCRIBE";
+    const FIELDS: &'static [&'static str] = &["request_id", "options", "topic"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &["match", "get_retained"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Subscriber: tx,
+            Broker: rx,
+            _: none,
+        )
     }
 }
 
@@ -144,63 +126,33 @@ impl Serialize for Subscribe {
     }
 }
 
-impl<'de> Deserialize<'de> for Subscribe {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct SubscribeVisitor(
-            PhantomData<u64>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<String>,
-        );
-
-        impl<'vi> Visitor<'vi> for SubscribeVisitor {
-            type Value = Subscribe;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Subscribe components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: serde::de::SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message id must be present and type u64.",
-                )?;
-                helpers::validate_id::<Subscribe, A, _>(&message_id, "Subscribe")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64",
-                )?;
-                let options: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "options must be present and object like",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                let topic: String = helpers::deser_seq_element(
-                    &mut seq,
-                    "topic URI must be present and type String",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                Ok(Subscribe {
-                    request_id,
-                    options,
-                    topic,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
+deserialize_message!(
+    Subscribe,
+    expecting: "A sequence of Subscribe components.",
+    id_error: "Message id must be present and type u64.",
+    |seq| {
+        let request_id = helpers::deser_id_element(
+            &mut seq,
             "Subscribe",
-            &["request_id", "options", "topic"],
-            SubscribeVisitor(PhantomData, PhantomData, PhantomData, PhantomData),
-        )
+            helpers::FieldContext::new(1, "request_id", "Request ID must be present and type u64"),
+        )?;
+        let options: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "options", "options must be present and object like"),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
+        let topic: String = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(3, "topic", "topic URI must be present and type String"),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
+        Ok(Subscribe {
+            request_id,
+            options,
+            topic: topic.into(),
+        })
     }
-}
+);
 
 #[cfg(test)]
 mod tests {
@@ -214,7 +166,7 @@ mod tests {
         let r1 = Subscribe {
             request_id: 713845233,
             options: json!({}),
-            topic: "com.myapp.mytopic1".to_string(),
+            topic: "com.myapp.mytopic1".into(),
         };
         assert_eq!(d1, to_string(&r1).unwrap());
         assert_eq!(r1, from_str::<Subscribe>(d1).unwrap())
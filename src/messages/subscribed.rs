@@ -1,9 +1,6 @@
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, MessageDirection, Subscribe, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Subscribed - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-subscribed-2)
@@ -97,36 +94,43 @@ macro_rules! subscribed {
     };
 }
 
+impl Subscribed {
+    /// # For
+    /// The SUBSCRIBED a broker answers `subscribe` with, echoing its request id.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Subscribe, Subscribed};
+    /// use serde_json::json;
+    ///
+    /// let subscribe = Subscribe {
+    ///     request_id: 713845233,
+    ///     options: json!({}),
+    ///     topic: "com.myapp.mytopic1".into()
+    /// };
+    ///
+    /// let subscribed = Subscribed::r#for(&subscribe, 5512315355);
+    /// assert_eq!(subscribed.request_id, subscribe.request_id);
+    /// assert_eq!(subscribed.subscription, 5512315355);
+    /// ```
+    pub fn r#for(subscribe: &Subscribe, subscription_id: u64) -> Subscribed {
+        Subscribed {
+            request_id: subscribe.request_id,
+            subscription: subscription_id,
+        }
+    }
+}
+
 impl WampMessage for Subscribed {
     const ID: u64 = 33;
+    const NAME: &'static str = "SUBSCRIBED";
+    const FIELDS: &'static [&'static str] = &["request_id", "subscription"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        direction_table!(role;
+            Subscriber: rx,
+            Broker: tx,
+            _: none,
+        )
     }
 }
 
@@ -139,47 +143,23 @@ impl Serialize for Subscribed {
     }
 }
 
-impl<'de> Deserialize<'de> for Subscribed {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct SubscribedVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for SubscribedVisitor {
-            type Value = Subscribed;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Subscribed components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Subscribed, A, _>(&message_id, "Subscribed")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                let subscription: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "subscription must be present and object like.",
-                )?;
-                Ok(Subscribed {
-                    request_id,
-                    subscription,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Subscribed",
-            &["request_id", "subscription"],
-            SubscribedVisitor(PhantomData, PhantomData, PhantomData),
-        )
-    }
-}
+deserialize_message!(Subscribed, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Subscribed",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let subscription = helpers::deser_id_element(
+        &mut seq,
+        "Subscribed",
+        helpers::FieldContext::new(
+            2,
+            "subscription",
+            "subscription must be present and object like.",
+        ),
+    )?;
+    Ok(Subscribed {
+        request_id,
+        subscription,
+    })
+});
@@ -0,0 +1,241 @@
+//! # Typed payloads
+//! Compile time typed versions of the payload carrying messages.
+//!
+//! Each `Typed*` message holds its `args` and `kwargs` as your own types instead of
+//! [`Value`](serde_json::Value), and serializes to exactly the same wire format as the untyped
+//! message it wraps. The untyped messages stay the interchange layer, convert with
+//! `into_untyped` and `TryFrom`.
+//!
+//! Args must serialize to a JSON array (a tuple, tuple struct or `Vec`) and kwargs to a JSON
+//! object, use `()` for a payload part you do not send.
+//! ## Examples
+//! ```
+//! use wamp_core::messages::{Call, TypedCall};
+//! use serde::{Deserialize, Serialize};
+//! use serde_json::{json, to_string};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Add(i64, i64);
+//!
+//! let call = TypedCall {
+//!     request_id: 1,
+//!     options: json!({}),
+//!     procedure: "com.myapp.add".into(),
+//!     args: Add(1, 2),
+//!     kwargs: (),
+//! };
+//!
+//! assert_eq!(to_string(&call).unwrap(), r#"[48,1,{},"com.myapp.add",[1,2]]"#);
+//!
+//! let untyped: Call = call.into_untyped().unwrap();
+//! let typed = TypedCall::<Add, ()>::try_from(untyped).unwrap();
+//! assert_eq!(typed.args, Add(1, 2));
+//! ```
+use std::borrow::Cow;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use serde_json::{from_value, to_value, Value};
+
+use super::{Call, Event, Invocation, Publish, WampResult, Yield};
+use crate::error::Error;
+
+macro_rules! typed_message {
+    ($(#[$doc:meta])* $typed:ident, $untyped:ident { $($(#[$field_doc:meta])* $field:ident: $typ:ty),* $(,)? }) => {
+        #[derive(Debug, Clone, PartialEq)]
+        $(#[$doc])*
+        pub struct $typed<A, K> {
+            $($(#[$field_doc])* pub $field: $typ,)*
+            /// Positional arguments, serialized as a JSON array.
+            pub args: A,
+            /// Keyword arguments, serialized as a JSON object.
+            pub kwargs: K,
+        }
+
+        impl<A: Serialize, K: Serialize> $typed<A, K> {
+            #[doc = concat!("Convert into an untyped [`", stringify!($untyped), "`], failing when the payload does not serialize to JSON.")]
+            pub fn into_untyped(self) -> Result<$untyped, Error> {
+                Ok($untyped {
+                    $($field: self.$field,)*
+                    args: to_value(self.args)?,
+                    kwargs: to_value(self.kwargs)?,
+                })
+            }
+        }
+
+        impl<A: DeserializeOwned, K: DeserializeOwned> TryFrom<$untyped> for $typed<A, K> {
+            type Error = Error;
+
+            fn try_from(value: $untyped) -> Result<Self, Error> {
+                Ok($typed {
+                    $($field: value.$field,)*
+                    args: from_value(value.args)?,
+                    kwargs: from_value(value.kwargs)?,
+                })
+            }
+        }
+
+        impl<A: Serialize, K: Serialize> Serialize for $typed<A, K> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                use serde::ser::Error;
+                let untyped = $untyped {
+                    $($field: self.$field.clone(),)*
+                    args: to_value(&self.args).map_err(S::Error::custom)?,
+                    kwargs: to_value(&self.kwargs).map_err(S::Error::custom)?,
+                };
+                untyped.serialize(serializer)
+            }
+        }
+
+        impl<'de, A: DeserializeOwned, K: DeserializeOwned> Deserialize<'de> for $typed<A, K> {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                use serde::de::Error;
+                let untyped = $untyped::deserialize(deserializer)?;
+                $typed::try_from(untyped).map_err(|error| D::Error::custom(format!("{error:?}")))
+            }
+        }
+    };
+}
+
+typed_message! {
+    /// # Typed Call
+    /// A [`Call`] with typed args and kwargs.
+    TypedCall, Call {
+        /// The request id of the call.
+        request_id: u64,
+        /// Call options.
+        options: Value,
+        /// The procedure to call.
+        procedure: Cow<'static, str>,
+    }
+}
+
+typed_message! {
+    /// # Typed Publish
+    /// A [`Publish`] with typed args and kwargs.
+    TypedPublish, Publish {
+        /// The request id of the publication.
+        request_id: u64,
+        /// Publish options.
+        options: Value,
+        /// The topic to publish to.
+        topic: Cow<'static, str>,
+    }
+}
+
+typed_message! {
+    /// # Typed Event
+    /// An [`Event`] with typed args and kwargs.
+    TypedEvent, Event {
+        /// The subscription the event is delivered for.
+        subscription: u64,
+        /// The publication id of the event.
+        publication: u64,
+        /// Event details.
+        details: Value,
+    }
+}
+
+typed_message! {
+    /// # Typed Invocation
+    /// An [`Invocation`] with typed args and kwargs.
+    TypedInvocation, Invocation {
+        /// The request id of the invocation.
+        request_id: u64,
+        /// The registration being invoked.
+        registration: u64,
+        /// Invocation details.
+        details: Value,
+    }
+}
+
+typed_message! {
+    /// # Typed Result
+    /// A [`WampResult`] with typed args and kwargs.
+    TypedResult, WampResult {
+        /// The request id of the call this is the result of.
+        request_id: u64,
+        /// Result details.
+        details: Value,
+    }
+}
+
+typed_message! {
+    /// # Typed Yield
+    /// A [`Yield`] with typed args and kwargs.
+    TypedYield, Yield {
+        /// The request id of the invocation this yields to.
+        request_id: u64,
+        /// Yield options.
+        options: Value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_str, json, to_string};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Point(i64, i64);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Style {
+        color: String,
+        width: u8,
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = r#"[36,5512315355,4429313566,{},[3,4],{"color":"red","width":2}]"#;
+        let event: TypedEvent<Point, Style> = from_str(data).unwrap();
+        assert_eq!(event.args, Point(3, 4));
+        assert_eq!(event.kwargs.color, "red");
+        assert_eq!(to_string(&event).unwrap(), data);
+        let untyped = event.clone().into_untyped().unwrap();
+        assert_eq!(untyped.kwargs, json!({"color": "red", "width": 2}));
+        assert_eq!(TypedEvent::try_from(untyped).unwrap(), event);
+
+        let result = TypedResult {
+            request_id: 7,
+            details: json!({}),
+            args: vec![Point(1, 2)],
+            kwargs: (),
+        };
+        assert_eq!(to_string(&result).unwrap(), r#"[50,7,{},[[1,2]]]"#);
+        let yielded: TypedYield<(String,), ()> = from_str(r#"[70,6131533,{},["ok"]]"#).unwrap();
+        assert_eq!(yielded.args.0, "ok");
+    }
+
+    #[test]
+    fn mismatched_payload() {
+        let invocation = Invocation {
+            request_id: 1,
+            registration: 2,
+            details: json!({}),
+            args: json!(["three", 4]),
+            kwargs: json!({"color": "red"}),
+        };
+        assert!(TypedInvocation::<Point, ()>::try_from(invocation.clone()).is_err());
+        assert!(TypedInvocation::<(String, u8), Style>::try_from(invocation).is_err());
+        assert!(from_str::<TypedCall<Point, ()>>(r#"[48,1,{},"com.myapp.add",[1]]"#).is_err());
+        let publish = TypedPublish {
+            request_id: 1,
+            options: json!({}),
+            topic: "com.myapp.topic".into(),
+            args: Style {
+                color: "red".to_string(),
+                width: 1,
+            },
+            kwargs: (),
+        };
+        assert!(to_string(&publish).is_err());
+    }
+}
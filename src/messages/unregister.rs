@@ -1,9 +1,6 @@
 use super::{helpers, MessageDirection, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Unregister - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-unregister-2)
@@ -100,34 +97,15 @@ macro_rules! unregister {
 
 impl WampMessage for Unregister {
     const ID: u64 = 66;
+    const NAME: &'static str = "UNREGISTER";
+    const FIELDS: &'static [&'static str] = &["request_id", "registration"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: tx,
+            Dealer: rx,
+            _: none,
+        )
     }
 }
 
@@ -140,50 +118,26 @@ impl Serialize for Unregister {
     }
 }
 
-impl<'de> Deserialize<'de> for Unregister {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct UnregisterVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for UnregisterVisitor {
-            type Value = Unregister;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Unregister components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Unregister, A, _>(&message_id, "Unregister")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                let registration: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "registration must be present and object like.",
-                )?;
-                Ok(Unregister {
-                    request_id,
-                    registration,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Unregister",
-            &["request_id", "registration"],
-            UnregisterVisitor(PhantomData, PhantomData, PhantomData),
-        )
-    }
-}
+deserialize_message!(Unregister, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Unregister",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let registration = helpers::deser_id_element(
+        &mut seq,
+        "Unregister",
+        helpers::FieldContext::new(
+            2,
+            "registration",
+            "registration must be present and object like.",
+        ),
+    )?;
+    Ok(Unregister {
+        request_id,
+        registration,
+    })
+});
 
 #[cfg(test)]
 mod tests {
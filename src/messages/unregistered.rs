@@ -1,9 +1,6 @@
 use super::{helpers, MessageDirection, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Unregistered - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-subscribed-2)
@@ -86,34 +83,15 @@ macro_rules! unregistered {
 
 impl WampMessage for Unregistered {
     const ID: u64 = 67;
+    const NAME: &'static str = "UNREGISTERED";
+    const FIELDS: &'static [&'static str] = &["request_id"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: rx,
+            Dealer: tx,
+            _: none,
+        )
     }
 }
 
@@ -126,43 +104,14 @@ impl Serialize for Unregistered {
     }
 }
 
-impl<'de> Deserialize<'de> for Unregistered {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct UnregisteredVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for UnregisteredVisitor {
-            type Value = Unregistered;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Unregistered components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Unregistered, A, _>(&message_id, "Unregistered")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                Ok(Unregistered { request_id })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Unregistered",
-            &["request_id", "registration"],
-            UnregisteredVisitor(PhantomData, PhantomData, PhantomData),
-        )
-    }
-}
+deserialize_message!(Unregistered, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Unregistered",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    Ok(Unregistered { request_id })
+});
 
 #[cfg(test)]
 mod tests {
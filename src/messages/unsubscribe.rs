@@ -1,9 +1,6 @@
 use super::{helpers, MessageDirection, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Unsubscribe - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-unsubscribe-2)
@@ -101,34 +98,15 @@ macro_rules! unsubscribe {
 
 impl WampMessage for Unsubscribe {
     const ID: u64 = 34;
+    const NAME: &'static str = "UNSUBSCRIBE";
+    const FIELDS: &'static [&'static str] = &["request_id", "subscription"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Subscriber: tx,
+            Broker: rx,
+            _: none,
+        )
     }
 }
 
@@ -141,50 +119,26 @@ impl Serialize for Unsubscribe {
     }
 }
 
-impl<'de> Deserialize<'de> for Unsubscribe {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct UnsubscribeVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for UnsubscribeVisitor {
-            type Value = Unsubscribe;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Unsubscribe components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Unsubscribe, A, _>(&message_id, "Unsubscribe")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                let subscription: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "subscription must be present and object like.",
-                )?;
-                Ok(Unsubscribe {
-                    request_id,
-                    subscription,
-                })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Unsubscribe",
-            &["request_id", "subscription"],
-            UnsubscribeVisitor(PhantomData, PhantomData, PhantomData),
-        )
-    }
-}
+deserialize_message!(Unsubscribe, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Unsubscribe",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let subscription = helpers::deser_id_element(
+        &mut seq,
+        "Unsubscribe",
+        helpers::FieldContext::new(
+            2,
+            "subscription",
+            "subscription must be present and object like.",
+        ),
+    )?;
+    Ok(Unsubscribe {
+        request_id,
+        subscription,
+    })
+});
 
 #[cfg(test)]
 mod tests {
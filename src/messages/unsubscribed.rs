@@ -1,9 +1,7 @@
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, validate, MessageDirection, ValidationConfig, ValidationIssue, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
+use serde::Serialize;
+use serde_json::Value;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Unsubscribed - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-unsubscribed-2)
@@ -12,10 +10,12 @@ use std::marker::PhantomData;
 /// ```
 /// use wamp_core::messages::Unsubscribed;
 /// use wamp_core::unsubscribed;
+/// use serde_json::Value;
 /// # let unsubscribed_message1 = unsubscribed!(1);
 ///
 /// let unsubscribed_message = Unsubscribed {
-///     request_id: 1
+///     request_id: 1,
+///     details: Value::Null
 /// };
 ///
 /// # assert_eq!(unsubscribed_message, unsubscribed_message1);
@@ -25,11 +25,12 @@ use std::marker::PhantomData;
 /// Implements serde Serialize trait for Unsubscribed
 /// ```
 /// use wamp_core::messages::Unsubscribed;
-/// use serde_json::{json, to_string};
+/// use serde_json::{json, to_string, Value};
 ///
 /// // Create an Unsubscribe message
 /// let unsubscribed = Unsubscribed {
-///     request_id: 1
+///     request_id: 1,
+///     details: Value::Null
 /// };
 ///
 /// // Establish raw json data string
@@ -58,6 +59,33 @@ use std::marker::PhantomData;
 /// ```
 pub struct Unsubscribed {
     pub request_id: u64,
+    /// Revocation details, `Null` when replying to an UNSUBSCRIBE.
+    ///
+    /// A broker revoking a subscription sends request id `0` with the `subscription` and
+    /// optionally a `reason`, see [`Unsubscribed::revoked_subscription`].
+    pub details: Value,
+}
+
+impl Unsubscribed {
+    /// # Revoked subscription
+    /// The subscription the broker revoked, `None` when this replies to an UNSUBSCRIBE.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Unsubscribed;
+    /// use serde_json::from_str;
+    ///
+    /// let revoked: Unsubscribed = from_str(r#"[35,0,{"subscription":5512315355,"reason":"wamp.authentication.lost"}]"#).unwrap();
+    /// assert_eq!(revoked.revoked_subscription(), Some(5512315355));
+    ///
+    /// let reply: Unsubscribed = from_str(r#"[35,85346237]"#).unwrap();
+    /// assert_eq!(reply.revoked_subscription(), None);
+    /// ```
+    pub fn revoked_subscription(&self) -> Option<u64> {
+        if self.request_id != 0 {
+            return None;
+        }
+        self.details.get("subscription")?.as_u64()
+    }
 }
 
 #[macro_export]
@@ -67,52 +95,62 @@ pub struct Unsubscribed {
 /// ```
 /// use wamp_core::messages::Unsubscribed;
 /// use wamp_core::unsubscribed;
+/// use serde_json::Value;
 ///
 /// # let unsubscribed_message1 = unsubscribed!(1);
 ///
 /// let unsubscribed_message = Unsubscribed {
-///     request_id: 1
+///     request_id: 1,
+///     details: Value::Null
 /// };
 ///
 /// # assert_eq!(unsubscribed_message, unsubscribed_message1);
 /// ```
 macro_rules! unsubscribed {
     ($request_id:expr) => {
+        unsubscribed!($request_id, serde_json::Value::Null)
+    };
+    ($request_id:expr, $details:expr) => {
         Unsubscribed {
             request_id: $request_id,
+            details: $details,
         }
     };
 }
 
 impl WampMessage for Unsubscribed {
     const ID: u64 = 35;
+    const NAME: &'static str = "UNSUBSCRIBED";
+    const FIELDS: &'static [&'static str] = &["request_id", "details"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &["subscription", "reason"];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
+        direction_table!(role;
+            Subscriber: rx,
+            Broker: tx,
+            _: none,
+        )
+    }
+
+    /// Validates like the other messages, but accepts the request id `0` of a revocation,
+    /// see [`Unsubscribed::revoked_subscription`].
+    fn validate(&self, config: &ValidationConfig) -> Result<(), Vec<ValidationIssue>> {
+        let result = validate::validate_fields(
+            self,
+            Self::FIELDS,
+            Self::KNOWN_OPTION_KEYS,
+            Self::KNOWN_DETAIL_KEYS,
+            config,
+        );
+        let Err(mut issues) = result else {
+            return result;
+        };
+        if self.revoked_subscription().is_some() {
+            issues.retain(|issue| issue.field != "request_id");
+        }
+        match issues.is_empty() {
+            true => Ok(()),
+            false => Err(issues),
         }
     }
 }
@@ -122,44 +160,27 @@ impl Serialize for Unsubscribed {
     where
         S: serde::Serializer,
     {
-        (Self::ID, &self.request_id).serialize(serializer)
+        if self.details.is_null() {
+            return (Self::ID, &self.request_id).serialize(serializer);
+        }
+        let details =
+            helpers::ser_value_is_object::<S, _>(&self.details, "Details must be object like.")?;
+        (Self::ID, &self.request_id, details).serialize(serializer)
     }
 }
 
-impl<'de> Deserialize<'de> for Unsubscribed {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct UnsubscribedVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<u64>);
-
-        impl<'vi> Visitor<'vi> for UnsubscribedVisitor {
-            type Value = Unsubscribed;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Unsubscribed components.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Unsubscribed, A, _>(&message_id, "Unsubscribed")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "request_id must be present and type u64.",
-                )?;
-                Ok(Unsubscribed { request_id })
-            }
-        }
-
-        deserializer.deserialize_struct(
-            "Unsubscribed",
-            &["request_id", "registration"],
-            UnsubscribedVisitor(PhantomData, PhantomData, PhantomData),
-        )
+deserialize_message!(Unsubscribed, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Unsubscribed",
+        helpers::FieldContext::new(1, "request_id", "request_id must be present and type u64."),
+    )?;
+    let details: Value = seq.next_element()?.unwrap_or(Value::Null);
+    if !details.is_null() {
+        helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
     }
-}
+    Ok(Unsubscribed {
+        request_id,
+        details,
+    })
+});
@@ -0,0 +1,383 @@
+//! # Validation
+//! Semantic checks of a message, for frames about to be sent.
+//!
+//! Deserializing only checks the shape of a frame. [`WampMessage::validate`] additionally checks
+//! the content of every field, driven by [`WampMessage::FIELDS`],
+//! [`WampMessage::KNOWN_OPTION_KEYS`] and [`WampMessage::KNOWN_DETAIL_KEYS`]:
+//!
+//! - ids are in `[1, 2^53]`,
+//! - URIs are valid for their usage, pattern based subscriptions and registrations are checked
+//!   against their `match` policy,
+//! - options and details are objects, args an array and kwargs an object,
+//! - options and details only hold keys known for the message, custom keys starting with `_`
+//!   excepted. Unknown keys are reported as [`Severity::Warning`], unless
+//!   [`ValidationConfig::deny_unknown_keys`] is set.
+//! ## Examples
+//! ```
+//! use wamp_core::messages::{Call, Severity, ValidationConfig, WampMessage};
+//! use serde_json::{json, Value};
+//!
+//! let call = Call {
+//!     request_id: 1,
+//!     options: json!({ "timeout": 1000, "colour": "blue" }),
+//!     procedure: "com.myapp.add".into(),
+//!     args: json!([1, 2]),
+//!     kwargs: Value::Null
+//! };
+//!
+//! let issues = call.validate(&ValidationConfig::default()).unwrap_err();
+//! assert_eq!(issues.len(), 1);
+//! assert_eq!(issues[0].severity, Severity::Warning);
+//! assert_eq!(issues[0].field, "options");
+//! ```
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::MAX_ID;
+use crate::uri::{uri_rules::WampRules, UriMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// # Severity
+/// How serious a [`ValidationIssue`] is.
+pub enum Severity {
+    /// The message is valid, but probably not what was meant.
+    Warning,
+    /// The message violates the protocol.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Validation Issue
+/// A problem found by [`WampMessage::validate`](super::WampMessage::validate).
+pub struct ValidationIssue {
+    /// How serious the issue is.
+    pub severity: Severity,
+    /// The wire name of the offending field.
+    pub field: &'static str,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error<T: ToString>(field: &'static str, message: T) -> Self {
+        ValidationIssue {
+            severity: Severity::Error,
+            field,
+            message: message.to_string(),
+        }
+    }
+
+    fn warning<T: ToString>(field: &'static str, message: T) -> Self {
+        ValidationIssue {
+            severity: Severity::Warning,
+            field,
+            message: message.to_string(),
+        }
+    }
+
+    /// Whether the issue is a protocol violation.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} in {}: {}", self.severity, self.field, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// # Validation Config
+/// Settings of [`WampMessage::validate`](super::WampMessage::validate).
+pub struct ValidationConfig {
+    /// How strictly URIs are checked.
+    pub uri_mode: UriMode,
+    /// Whether unknown option and detail keys are reported.
+    pub unknown_keys: bool,
+    /// Report unknown keys as errors instead of warnings.
+    ///
+    /// Only meant for outgoing messages, the spec requires peers to ignore keys they do not
+    /// know in incoming messages.
+    pub deny_unknown_keys: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            uri_mode: UriMode::Loose,
+            unknown_keys: true,
+            deny_unknown_keys: false,
+        }
+    }
+}
+
+/// The URI rule of `field`, `None` when the field is not a URI.
+fn uri_rule(field: &str, options: Option<&Value>) -> Option<WampRules> {
+    match field {
+        "procedure" | "topic" => Some(match options.and_then(|o| o["match"].as_str()) {
+            Some("prefix") => WampRules::Prefix,
+            Some("wildcard") => WampRules::PrefixOrWildcard,
+            _ => WampRules::URI,
+        }),
+        "error" | "reason" | "realm" => Some(WampRules::URI),
+        _ => None,
+    }
+}
+
+/// The keys of `object` missing from `known`, in order. Non objects have no keys.
+pub(crate) fn unknown_keys<'a>(object: &'a Value, known: &[&str]) -> Vec<&'a str> {
+    object
+        .as_object()
+        .into_iter()
+        .flat_map(|object| object.keys())
+        .map(String::as_str)
+        .filter(|key| !known.contains(key))
+        .collect()
+}
+
+fn check_field(
+    field: &'static str,
+    value: &Value,
+    options: Option<&Value>,
+    known_keys: &[&str],
+    config: &ValidationConfig,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    match field {
+        "request_id" | "session" | "subscription" | "publication" | "registration" => {
+            match value.as_u64() {
+                Some(id) if (1..=MAX_ID).contains(&id) => {}
+                _ => issues.push(ValidationIssue::error(
+                    field,
+                    format!("{value} is not an id in [1, 2^53]"),
+                )),
+            }
+        }
+        "options" | "details" | "extra" if !value.is_object() => {
+            issues.push(ValidationIssue::error(field, "must be an object"));
+        }
+        "options" | "details" if config.unknown_keys && !known_keys.is_empty() => {
+            for key in unknown_keys(value, known_keys) {
+                if key.starts_with('_') {
+                    continue;
+                }
+                let message = format!("unknown {field} key {key:?}");
+                issues.push(if config.deny_unknown_keys {
+                    ValidationIssue::error(field, message)
+                } else {
+                    ValidationIssue::warning(field, message)
+                });
+            }
+        }
+        "args" if !value.is_array() && !value.is_null() => {
+            issues.push(ValidationIssue::error(field, "must be an array"));
+        }
+        "kwargs" if !value.is_object() && !value.is_null() => {
+            issues.push(ValidationIssue::error(field, "must be an object"));
+        }
+        _ => {
+            let Some(rule) = uri_rule(field, options) else {
+                return;
+            };
+            match value.as_str() {
+                Some(uri) if config.uri_mode.check(rule, uri) => {}
+                _ => issues.push(ValidationIssue::error(
+                    field,
+                    format!("{value} is not a valid {:?} URI", config.uri_mode),
+                )),
+            }
+        }
+    }
+}
+
+/// The default implementation of [`WampMessage::validate`](super::WampMessage::validate).
+pub(crate) fn validate_fields<M: Serialize>(
+    message: &M,
+    fields: &'static [&'static str],
+    known_options: &[&str],
+    known_details: &[&str],
+    config: &ValidationConfig,
+) -> Result<(), Vec<ValidationIssue>> {
+    let elements = match serde_json::to_value(message) {
+        Ok(Value::Array(elements)) => elements,
+        Ok(_) => return Ok(()),
+        Err(e) => return Err(vec![ValidationIssue::error("message", e)]),
+    };
+    let values: Vec<(&'static str, &Value)> = fields
+        .iter()
+        .copied()
+        .zip(elements.iter().skip(1))
+        .collect();
+    let options = values
+        .iter()
+        .find(|(field, _)| *field == "options")
+        .map(|(_, value)| *value);
+
+    let mut issues = Vec::new();
+    for (field, value) in values.iter().copied() {
+        let known_keys = match field {
+            "options" => known_options,
+            "details" => known_details,
+            _ => &[],
+        };
+        check_field(field, value, options, known_keys, config, &mut issues);
+    }
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::auth::{AuthMethod, WelcomeDetails};
+    use crate::messages::{
+        Call, HelloDetails, Messages, Subscribe, Unsubscribed, WampMessage, Welcome,
+    };
+
+    fn call(procedure: &str) -> Call {
+        Call {
+            request_id: 1,
+            options: json!({}),
+            procedure: procedure.to_string().into(),
+            args: json!([1]),
+            kwargs: Value::Null,
+        }
+    }
+
+    #[test]
+    fn bad_procedure_uri() {
+        let config = ValidationConfig::default();
+        assert_eq!(call("com.myapp.add").validate(&config), Ok(()));
+
+        let issues = call("com.myapp..add").validate(&config).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].field, "procedure");
+
+        let strict = ValidationConfig {
+            uri_mode: UriMode::Strict,
+            ..config
+        };
+        assert!(Messages::from(call("com.MyApp.add"))
+            .validate(&config)
+            .is_ok());
+        assert!(Messages::from(call("com.MyApp.add"))
+            .validate(&strict)
+            .is_err());
+
+        let zero = Call {
+            request_id: 0,
+            ..call("com.myapp.add")
+        };
+        assert_eq!(zero.validate(&config).unwrap_err()[0].field, "request_id");
+    }
+
+    #[test]
+    fn unknown_subscribe_option() {
+        let subscribe = |options: Value, topic: &str| Subscribe {
+            request_id: 1,
+            options,
+            topic: topic.to_string().into(),
+        };
+        let config = ValidationConfig::default();
+
+        let issues = subscribe(json!({ "match": "prefix", "colour": "blue" }), "com.myapp")
+            .validate(&config)
+            .unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].message, r#"unknown options key "colour""#);
+
+        let quiet = ValidationConfig {
+            unknown_keys: false,
+            ..config
+        };
+        let custom = subscribe(json!({ "colour": "blue", "_tag": 1 }), "com.myapp.topic1");
+        assert!(custom.validate(&quiet).is_ok());
+        assert_eq!(custom.validate(&config).unwrap_err().len(), 1);
+
+        assert!(subscribe(json!({ "match": "wildcard" }), "com..topic1")
+            .validate(&config)
+            .is_ok());
+        assert!(subscribe(json!({}), "com..topic1")
+            .validate(&config)
+            .is_err());
+    }
+
+    #[test]
+    fn vendor_welcome_details() {
+        let welcome: Welcome = serde_json::from_str(
+            r#"[2,9129137332,{"roles":{"broker":{}},"authrole":"user","x_cb_node":"node1","_vendor_trace":"abc"}]"#,
+        )
+        .unwrap();
+        assert_eq!(welcome.unknown_keys(), vec!["_vendor_trace", "x_cb_node"]);
+
+        let config = ValidationConfig::default();
+        let issues = welcome.validate(&config).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].message, r#"unknown details key "x_cb_node""#);
+
+        let strict = ValidationConfig {
+            deny_unknown_keys: true,
+            ..config
+        };
+        assert!(welcome.validate(&strict).unwrap_err()[0].is_error());
+    }
+
+    #[test]
+    fn typed_details_keys_are_known() {
+        let hello = HelloDetails {
+            authmethods: vec![AuthMethod::Ticket],
+            authid: Some("joe".to_string()),
+            authextra: Some(json!({})),
+            ..Default::default()
+        }
+        .with_default_agent();
+        let hello = serde_json::to_value(hello).unwrap();
+        assert_eq!(
+            crate::messages::Hello::KNOWN_DETAIL_KEYS.len(),
+            hello.as_object().unwrap().len()
+        );
+        assert!(unknown_keys(&hello, crate::messages::Hello::KNOWN_DETAIL_KEYS).is_empty());
+
+        let welcome = WelcomeDetails {
+            roles: [("broker".to_string(), json!({}))].into_iter().collect(),
+            authid: Some("joe".to_string()),
+            authrole: Some("user".to_string()),
+            authmethod: Some(AuthMethod::Ticket),
+            authprovider: Some("static".to_string()),
+            authextra: Some(json!({})),
+            agent: Some("router".to_string()),
+        };
+        let welcome = serde_json::to_value(welcome).unwrap();
+        assert!(unknown_keys(&welcome, Welcome::KNOWN_DETAIL_KEYS).is_empty());
+    }
+
+    #[test]
+    fn revoking_unsubscribed() {
+        let config = ValidationConfig::default();
+        let revoked = Unsubscribed {
+            request_id: 0,
+            details: json!({ "subscription": 5, "reason": "wamp.authentication.lost" }),
+        };
+        assert_eq!(revoked.validate(&config), Ok(()));
+        assert_eq!(Messages::from(revoked).validate(&config), Ok(()));
+
+        let reply = Unsubscribed {
+            request_id: 0,
+            details: json!({}),
+        };
+        let issues = reply.validate(&config).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "request_id");
+    }
+}
@@ -1,11 +1,14 @@
-use super::{helpers, MessageDirection, WampMessage};
-use crate::roles::Roles;
-use serde::{
-    de::{self, Visitor},
-    Deserialize, Serialize,
+use super::{helpers, MessageDirection, WampMessage, MAX_ID};
+use crate::{
+    auth::WelcomeDetails,
+    error::Error,
+    features::RouterFeatures,
+    parse::{typed_details, ParseWarning},
+    roles::Roles,
+    session::SessionIdentity,
 };
-use serde_json::Value;
-use std::marker::PhantomData;
+use serde::Serialize;
+use serde_json::{json, Value};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Welcome - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-welcome-2)
@@ -69,6 +72,9 @@ pub struct Welcome {
 #[macro_export]
 /// # welcome Macro - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-welcome)
 /// Macro that allows for default empty implementation of details object on Cabcel.
+///
+/// `welcome!(session, roles: [..])` builds the `details.roles` dict announcing the router
+/// [`Roles`](crate::roles::Roles).
 /// ## Examples
 /// ```
 /// use wamp_core::messages::{self, Welcome};
@@ -99,11 +105,29 @@ pub struct Welcome {
 ///
 /// assert_eq!(welcome_message, welcome_message3);
 /// assert_eq!(welcome_message2, welcome_message3);
+///
+/// // Announce the router roles, each as an empty dict
+/// use wamp_core::roles::Roles;
+///
+/// let welcome_message4 = welcome!(1, roles: [Roles::Broker, Roles::Dealer]);
+///
+/// assert_eq!(welcome_message4, welcome!(1, json!({
+///     "roles": { "broker": {}, "dealer": {} }
+/// })));
 /// ```
 macro_rules! welcome {
     ($session:expr) => {
         welcome!($session, serde_json::Value::Null)
     };
+    ($session:expr, roles: [$($role:expr),* $(,)?]) => {
+        welcome!($session, {
+            let roles: serde_json::Map<String, serde_json::Value> = [$($role),*]
+                .iter()
+                .map(|role: &$crate::roles::Roles| (role.name().to_string(), serde_json::json!({})))
+                .collect();
+            serde_json::json!({ "roles": roles })
+        })
+    };
     ($session:expr, $details:expr) => {
         Welcome {
             session: $session,
@@ -112,36 +136,152 @@ macro_rules! welcome {
     };
 }
 
+impl Welcome {
+    /// # New checked
+    /// Creates a Welcome message, rejecting session ids outside of `[1, 2^53]`.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Welcome, MAX_ID};
+    /// use wamp_core::error::Error;
+    /// use serde_json::json;
+    ///
+    /// assert!(Welcome::new_checked(MAX_ID, json!({})).is_ok());
+    /// assert!(matches!(Welcome::new_checked(0, json!({})), Err(Error::InvalidId(0))));
+    /// ```
+    pub fn new_checked(session: u64, details: Value) -> Result<Welcome, Error> {
+        if session == 0 || session > MAX_ID {
+            return Err(Error::InvalidId(session));
+        }
+        Ok(Welcome { session, details })
+    }
+
+    /// # Router agent
+    /// The agent string the router announced, `None` when it sent none.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Welcome;
+    /// use serde_json::from_str;
+    ///
+    /// let welcome: Welcome = from_str(r#"[2,1,{"agent":"crossbar-22.1.1","roles":{"broker":{}}}]"#).unwrap();
+    /// assert_eq!(welcome.router_agent(), Some("crossbar-22.1.1"));
+    /// ```
+    pub fn router_agent(&self) -> Option<&str> {
+        self.details.get("agent")?.as_str()
+    }
+
+    /// Parse the advanced features announced by the router's broker and dealer roles.
+    pub fn features(&self) -> RouterFeatures {
+        RouterFeatures::from_details(&self.details)
+    }
+
+    /// # Details typed
+    /// Parse the details into [`WelcomeDetails`], failing with [`Error::MissingRoles`] when
+    /// they have no `roles`.
+    pub fn details_typed(&self) -> Result<WelcomeDetails, Error> {
+        typed_details(Self::NAME, &self.details, false).map(|(details, _)| details)
+    }
+
+    /// # Details typed lenient
+    /// Parse the details into [`WelcomeDetails`] like [`details_typed`](Self::details_typed),
+    /// but treat missing `roles` as an empty object, reported as
+    /// [`WarningKind::MissingRoles`](crate::WarningKind::MissingRoles).
+    ///
+    /// Minimal routers, such as ones embedded in devices, may answer with little more than the
+    /// session id, although the spec requires `roles`. Other malformed keys still fail.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Welcome;
+    /// use wamp_core::WarningKind;
+    /// use serde_json::from_str;
+    ///
+    /// let welcome: Welcome = from_str(r#"[2,9129137332,{"authid":"joe"}]"#).unwrap();
+    /// assert!(welcome.details_typed().is_err());
+    ///
+    /// let (details, warnings) = welcome.details_typed_lenient().unwrap();
+    /// assert!(details.roles.is_empty());
+    /// assert_eq!(details.authid.as_deref(), Some("joe"));
+    /// assert_eq!(warnings[0].kind, WarningKind::MissingRoles);
+    /// assert_eq!(warnings[0].location.to_string(), "WELCOME.details.roles");
+    /// ```
+    pub fn details_typed_lenient(&self) -> Result<(WelcomeDetails, Vec<ParseWarning>), Error> {
+        typed_details(Self::NAME, &self.details, true)
+    }
+
+    /// # For identity
+    /// The WELCOME a router sends once it authenticated a session as `identity`, announcing
+    /// the router `roles`, for example `{"broker": {}, "dealer": {}}`.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::Welcome;
+    /// use wamp_core::session::SessionIdentity;
+    /// use serde_json::json;
+    ///
+    /// let identity = SessionIdentity::anonymous(9129137332);
+    /// let welcome = Welcome::for_identity(&identity, &json!({ "broker": {}, "dealer": {} }));
+    ///
+    /// assert_eq!(welcome.session, 9129137332);
+    /// assert_eq!(welcome.details, json!({
+    ///     "roles": { "broker": {}, "dealer": {} },
+    ///     "authid": "9129137332",
+    ///     "authrole": "anonymous",
+    ///     "authmethod": "anonymous"
+    /// }));
+    /// assert_eq!(welcome.identity(), Some(identity));
+    /// ```
+    pub fn for_identity(identity: &SessionIdentity, roles: &Value) -> Welcome {
+        let mut details = json!({
+            "roles": roles,
+            "authid": identity.authid,
+            "authrole": identity.authrole,
+            "authmethod": identity.authmethod,
+        });
+        if let Some(authprovider) = &identity.authprovider {
+            details["authprovider"] = json!(authprovider);
+        }
+        Welcome {
+            session: identity.session,
+            details,
+        }
+    }
+
+    /// # Identity
+    /// Who the router authenticated the session as, `None` when the details lack the
+    /// `authid`, `authrole` or `authmethod`, or when they are malformed.
+    pub fn identity(&self) -> Option<SessionIdentity> {
+        let details = WelcomeDetails::from_welcome(self).ok()?;
+        Some(SessionIdentity {
+            session: self.session,
+            authid: details.authid?,
+            authrole: details.authrole?,
+            authmethod: details.authmethod?,
+            authprovider: details.authprovider,
+        })
+    }
+}
+
 impl WampMessage for Welcome {
     const ID: u64 = 2;
+    const NAME: &'static str = "WELCOME";
+    const FIELDS: &'static [&'static str] = &["session", "details"];
+    const KNOWN_DETAIL_KEYS: &'static [&'static str] = &[
+        "roles",
+        "agent",
+        "authid",
+        "authrole",
+        "authmethod",
+        "authprovider",
+        "authextra",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-        }
+        direction_table!(role;
+            Callee: rx,
+            Caller: rx,
+            Publisher: rx,
+            Subscriber: rx,
+            Dealer: tx,
+            Broker: tx,
+        )
     }
 }
 
@@ -156,49 +296,31 @@ impl Serialize for Welcome {
     }
 }
 
-impl<'de> Deserialize<'de> for Welcome {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        struct WelcomeVisitor(PhantomData<u8>, PhantomData<String>, PhantomData<Value>);
-
-        impl<'vi> Visitor<'vi> for WelcomeVisitor {
-            type Value = Welcome;
-
-            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                formatter.write_str("WAMP Welcome frame, expressed as a sequence.")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'vi>,
-            {
-                let message_id: u64 =
-                    helpers::deser_seq_element(&mut seq, "Message ID must be type u64.")?;
-                helpers::validate_id::<Welcome, A, _>(&message_id, "Welcome")?;
-                let session: u64 =
-                    helpers::deser_seq_element(&mut seq, "Request ID must be a u64.")?;
-                let details: Value =
-                    helpers::deser_seq_element(&mut seq, "details must be a JSON value.")?;
-                helpers::deser_value_is_object::<A, _>(&details, "details must be object like.")?;
-                Ok(Welcome { session, details })
-            }
-        }
-
-        deserializer.deserialize_struct(
+deserialize_message!(
+    Welcome,
+    expecting: "WAMP Welcome frame, expressed as a sequence.",
+    id_error: "Message ID must be type u64.",
+    |seq| {
+        let session = helpers::deser_id_element(
+            &mut seq,
             "Welcome",
-            &["session", "details"],
-            WelcomeVisitor(PhantomData, PhantomData, PhantomData),
-        )
+            helpers::FieldContext::new(1, "session", "Request ID must be a u64."),
+        )?;
+        let details: Value = helpers::deser_seq_element(
+            &mut seq,
+            helpers::FieldContext::new(2, "details", "details must be a JSON value."),
+        )?;
+        helpers::deser_value_is_object::<A, _>(&details, "details must be object like.")?;
+        Ok(Welcome { session, details })
     }
-}
+);
 
 #[cfg(test)]
 mod tests {
     use serde_json::{from_str, json, to_string};
 
     use super::*;
+    use crate::auth::AuthMethod;
 
     #[test]
     fn test() {
@@ -212,4 +334,56 @@ mod tests {
         assert_eq!(w1, from_str(d1).unwrap());
         assert_eq!(d1, to_string(&w1).unwrap());
     }
+
+    #[test]
+    fn new_checked() {
+        assert!(matches!(
+            Welcome::new_checked(0, json!({})),
+            Err(Error::InvalidId(0))
+        ));
+        assert_eq!(Welcome::new_checked(1, json!({})).unwrap().session, 1);
+        assert!(matches!(
+            Welcome::new_checked(MAX_ID + 1, json!({})),
+            Err(Error::InvalidId(9007199254740993))
+        ));
+    }
+
+    #[test]
+    fn identity_round_trip() {
+        let frames = [
+            r#"[2,9129137332,{"roles":{"broker":{"features":{"publisher_exclusion":true}},"dealer":{"features":{"progressive_call_results":true}}},"authid":"FWK7-DCEA-7MWE-UQWA","authrole":"anonymous","authmethod":"anonymous","authprovider":"static"}]"#,
+            r#"[2,3251278072152162,{"roles":{"broker":{},"dealer":{}},"authid":"joe","authrole":"user","authmethod":"ticket"}]"#,
+        ];
+        for frame in frames {
+            let welcome: Welcome = from_str(frame).unwrap();
+            let identity = welcome.identity().unwrap();
+            assert_eq!(
+                Welcome::for_identity(&identity, &welcome.details["roles"]),
+                welcome
+            );
+        }
+
+        let welcome: Welcome = from_str(frames[0]).unwrap();
+        assert_eq!(
+            welcome.identity().unwrap(),
+            SessionIdentity {
+                session: 9129137332,
+                authid: "FWK7-DCEA-7MWE-UQWA".to_string(),
+                authrole: "anonymous".to_string(),
+                authmethod: AuthMethod::Anonymous,
+                authprovider: Some("static".to_string()),
+            }
+        );
+
+        let welcome: Welcome = from_str(r#"[2,1,{"roles":{"broker":{}},"authid":"joe"}]"#).unwrap();
+        assert_eq!(welcome.identity(), None);
+        assert_eq!(
+            Welcome {
+                session: 1,
+                details: Value::Null
+            }
+            .identity(),
+            None
+        );
+    }
 }
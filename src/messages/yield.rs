@@ -1,10 +1,7 @@
-use super::{helpers, MessageDirection, WampMessage};
+use super::{helpers, Invocation, MessageDirection, WampMessage};
 use crate::roles::Roles;
-use serde::de::{SeqAccess, Visitor};
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::fmt::Formatter;
-use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// # Yield - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-yield-2)
@@ -151,36 +148,85 @@ macro_rules! r#yield {
     };
 }
 
+impl Yield {
+    /// # For invocation
+    /// The final YIELD answering `invocation` with the given payload.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::messages::{Invocation, Yield};
+    /// use serde_json::{json, Value};
+    ///
+    /// let invocation = Invocation {
+    ///     request_id: 6131533,
+    ///     registration: 9823529,
+    ///     details: json!({ "receive_progress": true }),
+    ///     args: json!([1, 2]),
+    ///     kwargs: Value::Null
+    /// };
+    ///
+    /// let result = Yield::for_invocation(&invocation, json!([3]), Value::Null);
+    /// assert_eq!(result.request_id, 6131533);
+    /// assert_eq!(result.options, json!({}));
+    ///
+    /// let progress = Yield::progress_for_invocation(&invocation, json!([1]), Value::Null).unwrap();
+    /// assert_eq!(progress.options, json!({ "progress": true }));
+    /// ```
+    pub fn for_invocation(invocation: &Invocation, args: Value, kwargs: Value) -> Yield {
+        Yield {
+            request_id: invocation.request_id,
+            options: json!({}),
+            args,
+            kwargs,
+        }
+    }
+
+    /// # Progress for invocation
+    /// A progressive YIELD answering `invocation`, `None` when the caller did not ask for
+    /// progressive results with `receive_progress`.
+    pub fn progress_for_invocation(
+        invocation: &Invocation,
+        args: Value,
+        kwargs: Value,
+    ) -> Option<Yield> {
+        if invocation.details["receive_progress"].as_bool() != Some(true) {
+            return None;
+        }
+        Some(Yield {
+            options: json!({ "progress": true }),
+            ..Yield::for_invocation(invocation, args, kwargs)
+        })
+    }
+}
+
+object_form!(Yield {
+    #[serde(default)]
+    request_id: u64,
+    #[serde(default = "empty_object")]
+    options: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    args: Value,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    kwargs: Value,
+});
+
 impl WampMessage for Yield {
     const ID: u64 = 70;
+    const NAME: &'static str = "YIELD";
+    const FIELDS: &'static [&'static str] = &["request_id", "options", "args", "kwargs"];
+    const KNOWN_OPTION_KEYS: &'static [&'static str] = &[
+        "progress",
+        "ppt_scheme",
+        "ppt_serializer",
+        "ppt_cipher",
+        "ppt_keyid",
+    ];
 
     fn direction(role: Roles) -> &'static MessageDirection {
-        match role {
-            Roles::Callee => &MessageDirection {
-                receives: &false,
-                sends: &true,
-            },
-            Roles::Caller => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Publisher => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Subscriber => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-            Roles::Dealer => &MessageDirection {
-                receives: &true,
-                sends: &false,
-            },
-            Roles::Broker => &MessageDirection {
-                receives: &false,
-                sends: &false,
-            },
-        }
+        direction_table!(role;
+            Callee: tx,
+            Dealer: rx,
+            _: none,
+        )
     }
 }
 
@@ -213,70 +259,53 @@ impl Serialize for Yield {
     }
 }
 
-impl<'de> Deserialize<'de> for Yield {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct YieldVisitor(
-            PhantomData<u64>,
-            PhantomData<u64>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-            PhantomData<Value>,
-        );
+deserialize_message!(Yield, |seq| {
+    let request_id = helpers::deser_id_element(
+        &mut seq,
+        "Yield",
+        helpers::FieldContext::new(1, "request_id", "Request ID must be present and type u64."),
+    )?;
+    let options: Value = helpers::deser_seq_element(
+        &mut seq,
+        helpers::FieldContext::new(2, "options", "options must be present and object like."),
+    )?;
+    helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
+    let args: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
+    let kwargs: Value =
+        helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
+    Ok(Yield {
+        request_id,
+        options,
+        args,
+        kwargs,
+    })
+});
 
-        impl<'vi> Visitor<'vi> for YieldVisitor {
-            type Value = Yield;
-            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("A sequence of Yield components.")
-            }
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json, to_string, Value};
 
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'vi>,
-            {
-                let message_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Message ID must be present and type u8.",
-                )?;
-                helpers::validate_id::<Yield, A, _>(&message_id, "Yield")?;
-                let request_id: u64 = helpers::deser_seq_element(
-                    &mut seq,
-                    "Request ID must be present and type u64.",
-                )?;
-                let options: Value = helpers::deser_seq_element(
-                    &mut seq,
-                    "options must be present and object like.",
-                )?;
-                helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                let args: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Args must be array like or null.",
-                )?;
-                let kwargs: Value = helpers::deser_args_kwargs_element(
-                    &mut seq,
-                    "Kwargs must be object like or null.",
-                )?;
-                Ok(Yield {
-                    request_id,
-                    options,
-                    args,
-                    kwargs,
-                })
-            }
-        }
+    use super::Yield;
+    use crate::messages::Invocation;
 
-        deserializer.deserialize_struct(
-            "Yield",
-            &["request_id", "options", "args", "kwargs"],
-            YieldVisitor(
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-                PhantomData,
-            ),
-        )
+    #[test]
+    fn responds_to_invocation() {
+        let invocation: Invocation = from_str(r#"[68,6131533,9823529,{},["johnny"]]"#).unwrap();
+        let result = Yield::for_invocation(&invocation, json!(["ok"]), Value::Null);
+        assert_eq!(to_string(&result).unwrap(), r#"[70,6131533,{},["ok"]]"#);
+        assert_eq!(
+            Yield::progress_for_invocation(&invocation, json!([1]), Value::Null),
+            None
+        );
+
+        let invocation: Invocation =
+            from_str(r#"[68,6131534,9823529,{"receive_progress":true}]"#).unwrap();
+        let progress =
+            Yield::progress_for_invocation(&invocation, json!([1]), Value::Null).unwrap();
+        assert_eq!(
+            to_string(&progress).unwrap(),
+            r#"[70,6131534,{"progress":true},[1]]"#
+        );
     }
 }
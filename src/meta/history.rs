@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{meta_call, result_arg};
+use crate::{
+    error::Error,
+    messages::{Call, Event, WampResult},
+};
+
+/// `wamp.topic.history.last` - the last `limit` events published to the subscription's topic.
+pub fn last(subscription: u64, limit: u64) -> Call {
+    meta_call("wamp.topic.history.last", json!([subscription, limit]))
+}
+
+/// `wamp.topic.history.since` - events published since an ISO 8601 `timestamp`.
+pub fn since<T: ToString>(subscription: u64, timestamp: T) -> Call {
+    meta_call(
+        "wamp.topic.history.since",
+        json!([subscription, timestamp.to_string()]),
+    )
+}
+
+/// `wamp.topic.history.after` - events published after the given publication id.
+pub fn after(subscription: u64, publication: u64) -> Call {
+    meta_call(
+        "wamp.topic.history.after",
+        json!([subscription, publication]),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Historical Event
+/// One event record returned by the `wamp.topic.history.*` procedures.
+/// ## Examples
+/// ```
+/// use wamp_core::meta::history::{self, HistoricalEvent};
+/// use wamp_core::messages::WampResult;
+/// use serde_json::{from_str, json};
+///
+/// let call = history::last(1, 10);
+/// assert_eq!(call.args, json!([1, 10]));
+///
+/// let result: WampResult = from_str(r#"[50,2,{},[[{"timestamp":"2023-10-01T12:00:00.000Z","subscription":1,"publication":5,"topic":"com.myapp.topic1","args":["hello"]}]]]"#).unwrap();
+///
+/// let events = HistoricalEvent::from_result(&result).unwrap();
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].publication, 5);
+/// assert_eq!(events[0].args, json!(["hello"]));
+/// ```
+pub struct HistoricalEvent {
+    /// The publication id assigned by the broker.
+    pub publication: u64,
+    /// ISO 8601 timestamp of the publication.
+    pub timestamp: String,
+    /// The subscription the event was stored for, if reported by the router.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription: Option<u64>,
+    /// The concrete topic the event was published to, if reported by the router.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Positional payload, `Null` when absent.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    /// Keyword payload, `Null` when absent.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub kwargs: Value,
+}
+
+impl HistoricalEvent {
+    /// Parse the list of event records out of a history procedure result.
+    pub fn from_result(result: &WampResult) -> Result<Vec<Self>, Error> {
+        result_arg(result, 0)
+    }
+
+    /// Turn the record back into the `Event` the subscriber would have received.
+    ///
+    /// The topic, when known, is attached as `details.topic`.
+    pub fn into_event(self, subscription: u64) -> Event {
+        let details = match self.topic {
+            Some(topic) => json!({ "topic": topic }),
+            None => json!({}),
+        };
+        Event {
+            subscription,
+            publication: self.publication,
+            details,
+            args: self.args,
+            kwargs: self.kwargs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json};
+
+    use super::*;
+
+    #[test]
+    fn crossbar_history() {
+        let d1 = r#"[50,7814135,{},[[{"timestamp":"2017-06-18T11:56:40.164Z","subscription":4710232738133218,"publication":1766216513651014,"topic":"com.example.history","args":[1]},{"timestamp":"2017-06-18T11:56:41.171Z","subscription":4710232738133218,"publication":6298473612014431,"topic":"com.example.history","args":[2],"kwargs":{"counter":2}}]]]"#;
+        let result: WampResult = from_str(d1).unwrap();
+        let events = HistoricalEvent::from_result(&result).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].publication, 1766216513651014);
+        assert_eq!(events[0].kwargs, serde_json::Value::Null);
+        assert_eq!(events[1].timestamp, "2017-06-18T11:56:41.171Z");
+        assert_eq!(events[1].kwargs, json!({"counter": 2}));
+
+        let event = events[1].clone().into_event(4710232738133218);
+        assert_eq!(event.details, json!({"topic": "com.example.history"}));
+        assert_eq!(event.args, json!([2]));
+
+        assert_eq!(
+            since(4710232738133218, "2017-06-18T11:56:40Z").procedure,
+            "wamp.topic.history.since"
+        );
+        assert_eq!(after(1, 2).args, json!([1, 2]));
+    }
+}
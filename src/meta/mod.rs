@@ -0,0 +1,96 @@
+//! # Meta API - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-meta-api)
+//! Typed constructors for the `wamp.*` meta procedures, and parsers for the results they return.
+//!
+//! Every constructor returns a plain [`Call`] with an auto-incremented request id, so the crate
+//! stays transport free, you still send the message yourself.
+//! ## Examples
+//! ```
+//! use wamp_core::meta::registration;
+//! use wamp_core::uri::MatchPolicy;
+//! use serde_json::json;
+//!
+//! let call = registration::lookup("com.myapp", Some(MatchPolicy::Prefix));
+//!
+//! assert_eq!(call.procedure, "wamp.registration.lookup");
+//! assert_eq!(call.args, json!(["com.myapp", { "match": "prefix" }]));
+//! ```
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{error::Error, messages::Call, messages::WampResult, uri::MatchPolicy};
+
+/// Event history procedures (`wamp.topic.history.*`).
+pub mod history;
+/// Registration meta procedures (`wamp.registration.*`).
+pub mod registration;
+/// Subscription meta procedures (`wamp.subscription.*`).
+pub mod subscription;
+
+pub(crate) fn meta_call(procedure: &'static str, args: Value) -> Call {
+    Call {
+        request_id: crate::factories::increment(),
+        options: json!({}),
+        procedure: procedure.into(),
+        args,
+        kwargs: Value::Null,
+    }
+}
+
+pub(crate) fn lookup_options(policy: Option<MatchPolicy>) -> Value {
+    match policy {
+        Some(policy) => json!({ "match": policy.as_str() }),
+        None => json!({}),
+    }
+}
+
+/// Deserialize the positional argument at `index` of a meta procedure result.
+pub fn result_arg<T: DeserializeOwned>(result: &WampResult, index: usize) -> Result<T, Error> {
+    match result.args.get(index) {
+        Some(value) => Ok(T::deserialize(value)?),
+        None => Err(Error::Error(
+            "Meta procedure result is missing a positional argument.",
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Ids by match policy
+/// Result of `wamp.registration.list` and `wamp.subscription.list`, the ids of every
+/// registration or subscription on the realm grouped by their match policy.
+/// ## Examples
+/// ```
+/// use wamp_core::meta::IdsByPolicy;
+/// use wamp_core::messages::WampResult;
+/// use serde_json::json;
+///
+/// let result = WampResult {
+///     request_id: 1,
+///     details: json!({}),
+///     args: json!([{ "exact": [1, 2], "prefix": [3], "wildcard": [] }]),
+///     kwargs: json!(null)
+/// };
+///
+/// let ids = IdsByPolicy::from_result(&result).unwrap();
+/// assert_eq!(ids.exact, vec![1, 2]);
+/// assert_eq!(ids.prefix, vec![3]);
+/// assert!(ids.wildcard.is_empty());
+/// ```
+pub struct IdsByPolicy {
+    /// Ids using the exact match policy.
+    #[serde(default)]
+    pub exact: Vec<u64>,
+    /// Ids using the prefix match policy.
+    #[serde(default)]
+    pub prefix: Vec<u64>,
+    /// Ids using the wildcard match policy.
+    #[serde(default)]
+    pub wildcard: Vec<u64>,
+}
+
+impl IdsByPolicy {
+    /// Parse the first positional argument of a `*.list` result.
+    pub fn from_result(result: &WampResult) -> Result<Self, Error> {
+        result_arg(result, 0)
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{lookup_options, meta_call, result_arg};
+use crate::{
+    error::Error,
+    messages::{Call, WampResult},
+    uri::MatchPolicy,
+};
+
+/// `wamp.registration.list` - ids of all registrations, see [`IdsByPolicy`](super::IdsByPolicy).
+pub fn list() -> Call {
+    meta_call("wamp.registration.list", json!([]))
+}
+
+/// `wamp.registration.lookup` - id of the registration for `procedure` with the given match policy.
+///
+/// Parse the result with [`lookup_result`].
+pub fn lookup<T: ToString>(procedure: T, policy: Option<MatchPolicy>) -> Call {
+    meta_call(
+        "wamp.registration.lookup",
+        json!([procedure.to_string(), lookup_options(policy)]),
+    )
+}
+
+/// `wamp.registration.match` - id of the registration an invocation of `procedure` would be routed to.
+///
+/// Parse the result with [`lookup_result`].
+pub fn r#match<T: ToString>(procedure: T) -> Call {
+    meta_call("wamp.registration.match", json!([procedure.to_string()]))
+}
+
+/// `wamp.registration.get` - details of a registration, see [`RegistrationDetails`].
+pub fn get(registration: u64) -> Call {
+    meta_call("wamp.registration.get", json!([registration]))
+}
+
+/// `wamp.registration.list_callees` - session ids attached to a registration.
+///
+/// Parse the result with [`list_callees_result`].
+pub fn list_callees(registration: u64) -> Call {
+    meta_call("wamp.registration.list_callees", json!([registration]))
+}
+
+/// `wamp.registration.count_callees` - number of sessions attached to a registration.
+///
+/// Parse the result with [`count_callees_result`].
+pub fn count_callees(registration: u64) -> Call {
+    meta_call("wamp.registration.count_callees", json!([registration]))
+}
+
+/// Parse the result of `lookup` or `match`, `None` when no registration exists.
+pub fn lookup_result(result: &WampResult) -> Result<Option<u64>, Error> {
+    result_arg(result, 0)
+}
+
+/// Parse the result of `list_callees`.
+pub fn list_callees_result(result: &WampResult) -> Result<Vec<u64>, Error> {
+    result_arg(result, 0)
+}
+
+/// Parse the result of `count_callees`.
+pub fn count_callees_result(result: &WampResult) -> Result<u64, Error> {
+    result_arg(result, 0)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Registration Details
+/// Result of `wamp.registration.get`.
+/// ## Examples
+/// ```
+/// use wamp_core::meta::registration::RegistrationDetails;
+/// use wamp_core::messages::WampResult;
+/// use wamp_core::uri::MatchPolicy;
+/// use serde_json::from_str;
+///
+/// let result: WampResult = from_str(r#"[50,1,{},[{"id":2,"created":"2023-10-01T12:00:00.000Z","uri":"com.myapp.add","match":"exact","invoke":"single"}]]"#).unwrap();
+///
+/// let details = RegistrationDetails::from_result(&result).unwrap();
+/// assert_eq!(details.id, 2);
+/// assert_eq!(details.uri, "com.myapp.add");
+/// assert_eq!(details.match_policy, MatchPolicy::Exact);
+/// assert_eq!(details.invoke, "single");
+/// ```
+pub struct RegistrationDetails {
+    /// The registration id.
+    pub id: u64,
+    /// ISO 8601 timestamp of when the registration was created.
+    pub created: String,
+    /// The registered procedure URI or pattern.
+    pub uri: String,
+    /// The match policy of the registration.
+    #[serde(rename = "match")]
+    pub match_policy: MatchPolicy,
+    /// The invocation policy of the registration (`single`, `roundrobin`, `random`, `first`, `last`).
+    pub invoke: String,
+}
+
+impl RegistrationDetails {
+    /// Parse the result of `wamp.registration.get`.
+    pub fn from_result(result: &WampResult) -> Result<Self, Error> {
+        result_arg(result, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json, to_string};
+
+    use super::*;
+    use crate::messages::WampResult;
+
+    #[test]
+    fn constructors() {
+        let call = count_callees(4);
+        assert_eq!(call.procedure, "wamp.registration.count_callees");
+        assert_eq!(call.args, json!([4]));
+        let call = lookup("com.myapp.add", None);
+        assert_eq!(call.args, json!(["com.myapp.add", {}]));
+        assert!(to_string(&list())
+            .unwrap()
+            .ends_with(r#",{},"wamp.registration.list",[]]"#));
+    }
+
+    #[test]
+    fn results() {
+        let r1: WampResult = from_str(r#"[50,1,{},[null]]"#).unwrap();
+        assert_eq!(lookup_result(&r1).unwrap(), None);
+        let r2: WampResult = from_str(r#"[50,1,{},[2520718617407516]]"#).unwrap();
+        assert_eq!(lookup_result(&r2).unwrap(), Some(2520718617407516));
+        let r3: WampResult =
+            from_str(r#"[50,1,{},[[1531384361009282,7788402936812540]]]"#).unwrap();
+        assert_eq!(
+            list_callees_result(&r3).unwrap(),
+            vec![1531384361009282, 7788402936812540]
+        );
+        let r4: WampResult = from_str(r#"[50,1,{},[2]]"#).unwrap();
+        assert_eq!(count_callees_result(&r4).unwrap(), 2);
+        let r5: WampResult = from_str(r#"[50,1,{}]"#).unwrap();
+        assert!(count_callees_result(&r5).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{lookup_options, meta_call, result_arg};
+use crate::{
+    error::Error,
+    messages::{Call, WampResult},
+    uri::MatchPolicy,
+};
+
+/// `wamp.subscription.list` - ids of all subscriptions, see [`IdsByPolicy`](super::IdsByPolicy).
+pub fn list() -> Call {
+    meta_call("wamp.subscription.list", json!([]))
+}
+
+/// `wamp.subscription.lookup` - id of the subscription for `topic` with the given match policy.
+///
+/// Parse the result with [`lookup_result`].
+pub fn lookup<T: ToString>(topic: T, policy: Option<MatchPolicy>) -> Call {
+    meta_call(
+        "wamp.subscription.lookup",
+        json!([topic.to_string(), lookup_options(policy)]),
+    )
+}
+
+/// `wamp.subscription.match` - ids of every subscription an event on `topic` would be dispatched to.
+///
+/// Parse the result with [`match_result`].
+pub fn r#match<T: ToString>(topic: T) -> Call {
+    meta_call("wamp.subscription.match", json!([topic.to_string()]))
+}
+
+/// `wamp.subscription.get` - details of a subscription, see [`SubscriptionDetails`].
+pub fn get(subscription: u64) -> Call {
+    meta_call("wamp.subscription.get", json!([subscription]))
+}
+
+/// `wamp.subscription.list_subscribers` - session ids attached to a subscription.
+///
+/// Parse the result with [`list_subscribers_result`].
+pub fn list_subscribers(subscription: u64) -> Call {
+    meta_call("wamp.subscription.list_subscribers", json!([subscription]))
+}
+
+/// `wamp.subscription.count_subscribers` - number of sessions attached to a subscription.
+///
+/// Parse the result with [`count_subscribers_result`].
+pub fn count_subscribers(subscription: u64) -> Call {
+    meta_call("wamp.subscription.count_subscribers", json!([subscription]))
+}
+
+/// Parse the result of `lookup`, `None` when no subscription exists.
+pub fn lookup_result(result: &WampResult) -> Result<Option<u64>, Error> {
+    result_arg(result, 0)
+}
+
+/// Parse the result of `match`, an empty list when no subscription matches.
+pub fn match_result(result: &WampResult) -> Result<Vec<u64>, Error> {
+    Ok(result_arg::<Option<Vec<u64>>>(result, 0)?.unwrap_or_default())
+}
+
+/// Parse the result of `list_subscribers`.
+pub fn list_subscribers_result(result: &WampResult) -> Result<Vec<u64>, Error> {
+    result_arg(result, 0)
+}
+
+/// Parse the result of `count_subscribers`.
+pub fn count_subscribers_result(result: &WampResult) -> Result<u64, Error> {
+    result_arg(result, 0)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+/// # Subscription Details
+/// Result of `wamp.subscription.get`.
+/// ## Examples
+/// ```
+/// use wamp_core::meta::subscription::SubscriptionDetails;
+/// use wamp_core::messages::WampResult;
+/// use wamp_core::uri::MatchPolicy;
+/// use serde_json::from_str;
+///
+/// let result: WampResult = from_str(r#"[50,1,{},[{"id":3,"created":"2023-10-01T12:00:00.000Z","uri":"com.myapp.","match":"prefix"}]]"#).unwrap();
+///
+/// let details = SubscriptionDetails::from_result(&result).unwrap();
+/// assert_eq!(details.id, 3);
+/// assert_eq!(details.uri, "com.myapp.");
+/// assert_eq!(details.match_policy, MatchPolicy::Prefix);
+/// ```
+pub struct SubscriptionDetails {
+    /// The subscription id.
+    pub id: u64,
+    /// ISO 8601 timestamp of when the subscription was created.
+    pub created: String,
+    /// The subscribed topic URI or pattern.
+    pub uri: String,
+    /// The match policy of the subscription.
+    #[serde(rename = "match")]
+    pub match_policy: MatchPolicy,
+}
+
+impl SubscriptionDetails {
+    /// Parse the result of `wamp.subscription.get`.
+    pub fn from_result(result: &WampResult) -> Result<Self, Error> {
+        result_arg(result, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json};
+
+    use super::*;
+    use crate::{messages::WampResult, meta::IdsByPolicy};
+
+    #[test]
+    fn constructors() {
+        let call = lookup("com.myapp.", Some(MatchPolicy::Wildcard));
+        assert_eq!(call.procedure, "wamp.subscription.lookup");
+        assert_eq!(call.args, json!(["com.myapp.", { "match": "wildcard" }]));
+        assert_eq!(r#match("com.myapp.x").args, json!(["com.myapp.x"]));
+        assert_eq!(
+            list_subscribers(9).procedure,
+            "wamp.subscription.list_subscribers"
+        );
+    }
+
+    #[test]
+    fn results() {
+        let r1: WampResult = from_str(
+            r#"[50,1,{},[{"exact":[6329599219318366],"prefix":[],"wildcard":[4131426025349232]}]]"#,
+        )
+        .unwrap();
+        let ids = IdsByPolicy::from_result(&r1).unwrap();
+        assert_eq!(ids.exact, vec![6329599219318366]);
+        assert_eq!(ids.wildcard, vec![4131426025349232]);
+        let r2: WampResult = from_str(r#"[50,1,{},[null]]"#).unwrap();
+        assert!(match_result(&r2).unwrap().is_empty());
+        let r3: WampResult = from_str(r#"[50,1,{},[[1,2]]]"#).unwrap();
+        assert_eq!(match_result(&r3).unwrap(), vec![1, 2]);
+        let r4: WampResult = from_str(r#"[50,1,{},[3]]"#).unwrap();
+        assert_eq!(count_subscribers_result(&r4).unwrap(), 3);
+    }
+}
@@ -0,0 +1,462 @@
+//! # Parsing
+//! Entry points turning a JSON frame into a [`Messages`].
+//!
+//! [`parse`] is strict, it accepts exactly the frames the deserializers accept. [`parse_lossy`]
+//! first repairs a few structural mistakes legacy peers are known to make, and reports every
+//! repair as a [`ParseWarning`]:
+//!
+//! - `null` options, details or extra become `{}`,
+//! - a trailing `[]` kwargs is dropped, as if the peer left kwargs out,
+//! - ids sent as floats without a fractional part, such as `1.0`, become integers.
+//!
+//! Payload data is never changed, args and kwargs with content are passed through as is, and
+//! frames that stay malformed after the repairs fail like they do with [`parse`].
+//! ## Examples
+//! ```
+//! use wamp_core::{parse, parse_lossy, WarningKind};
+//!
+//! let frame = r#"[33,1.0,5]"#;
+//! assert!(parse(frame).is_err());
+//!
+//! let (message, warnings) = parse_lossy(frame).unwrap();
+//! assert_eq!(message, parse(r#"[33,1,5]"#).unwrap());
+//! assert_eq!(warnings[0].kind, WarningKind::FloatIdTruncated);
+//! assert_eq!(warnings[0].to_string(), "float_id_truncated at SUBSCRIBED.request_id: was the float 1.0, used 1");
+//! ```
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{
+    error::Error,
+    messages::{descriptor_for, Messages, MAX_ID},
+    transport::Encoding,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # Field Path
+/// Where in a message a [`ParseWarning`] applies: the message name followed by the field and
+/// any nested keys, displayed dotted as in `HELLO.details.roles`.
+pub struct FieldPath {
+    segments: Vec<String>,
+}
+
+impl FieldPath {
+    /// The path of the message named `message`, see [`WampMessage::NAME`](crate::messages::WampMessage::NAME).
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        FieldPath {
+            segments: vec![message.into()],
+        }
+    }
+
+    /// The path of `segment` within this path.
+    pub fn join<T: Into<String>>(mut self, segment: T) -> Self {
+        self.segments.push(segment.into());
+        self
+    }
+
+    /// The message name followed by the nested fields and keys.
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.segments.join("."))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Warning Kind
+/// What a [`ParseWarning`] is about. Displayed as a stable snake case code, see
+/// [`WarningKind::as_str`].
+pub enum WarningKind {
+    /// An options, details or extra field was `null`, and was replaced by `{}`.
+    CoercedNullToObject,
+    /// The trailing kwargs field was an empty array, and was dropped.
+    DroppedEmptyKwargs,
+    /// An id was a float without fractional part, and was replaced by the integer.
+    FloatIdTruncated,
+    /// The details of a HELLO or WELCOME had no `roles`, an empty object was used.
+    MissingRoles,
+    /// An options or details key this crate does not know, kept as is.
+    UnknownKey,
+}
+
+impl WarningKind {
+    /// The snake case code of the kind, such as `float_id_truncated`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningKind::CoercedNullToObject => "coerced_null_to_object",
+            WarningKind::DroppedEmptyKwargs => "dropped_empty_kwargs",
+            WarningKind::FloatIdTruncated => "float_id_truncated",
+            WarningKind::MissingRoles => "missing_roles",
+            WarningKind::UnknownKey => "unknown_key",
+        }
+    }
+}
+
+impl fmt::Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # Parse Warning
+/// Something a lenient operation repaired or let pass instead of failing, such as a repair
+/// [`parse_lossy`] applied to a frame.
+///
+/// Displayed as `{kind} at {location}: {detail}`, a format kept stable for searching logs.
+/// ## Examples
+/// ```
+/// use wamp_core::{parse_lossy, WarningKind};
+///
+/// let (_, warnings) = parse_lossy(r#"[33,1.0,5]"#).unwrap();
+///
+/// assert_eq!(warnings[0].kind, WarningKind::FloatIdTruncated);
+/// assert_eq!(warnings[0].location.to_string(), "SUBSCRIBED.request_id");
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "float_id_truncated at SUBSCRIBED.request_id: was the float 1.0, used 1"
+/// );
+/// ```
+pub struct ParseWarning {
+    /// Where the warning applies.
+    pub location: FieldPath,
+    /// What the warning is about.
+    pub kind: WarningKind,
+    /// What was found, and what was done about it.
+    pub detail: String,
+}
+
+impl ParseWarning {
+    /// Create a warning of `kind` at `location`.
+    pub fn new<T: ToString>(location: FieldPath, kind: WarningKind, detail: T) -> Self {
+        ParseWarning {
+            location,
+            kind,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}: {}", self.kind, self.location, self.detail)
+    }
+}
+
+/// # Log warnings
+/// Report every warning as a `tracing` warn event, with `location` and `kind` fields and the
+/// detail as message. Does nothing unless the `tracing` feature is enabled.
+/// ## Examples
+/// ```
+/// use wamp_core::{parse_lossy, parse::log_warnings};
+///
+/// let (_, warnings) = parse_lossy(r#"[36,5,6,null]"#).unwrap();
+/// log_warnings(&warnings);
+/// ```
+pub fn log_warnings(warnings: &[ParseWarning]) {
+    #[cfg(feature = "tracing")]
+    for warning in warnings {
+        tracing::warn!(
+            location = %warning.location,
+            kind = %warning.kind,
+            "{}",
+            warning.detail
+        );
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = warnings;
+}
+
+/// # Parse
+/// Parse a JSON frame, rejecting anything the deserializers do not accept.
+/// ## Examples
+/// ```
+/// use wamp_core::{parse, Messages};
+///
+/// let message = parse(r#"[33,1,5]"#).unwrap();
+///
+/// assert!(matches!(message, Messages::Subscribed(_)));
+/// assert!(parse(r#"[33,1,{}]"#).is_err());
+/// ```
+pub fn parse(frame: &str) -> Result<Messages, Error> {
+    Messages::decode(frame.as_bytes(), Encoding::Json)
+}
+
+/// The integer `value` stands for, when it is a float id without fractional part.
+fn float_id(value: &Value) -> Option<u64> {
+    if value.is_u64() {
+        return None;
+    }
+    let float = value.as_f64()?;
+    let in_range = float.fract() == 0.0 && (1.0..=MAX_ID as f64).contains(&float);
+    in_range.then_some(float as u64)
+}
+
+/// # Parse lossy
+/// Parse a JSON frame after repairing the structural mistakes listed in the
+/// [module documentation](self), returning the message with every repair applied.
+/// ## Examples
+/// ```
+/// use wamp_core::{parse_lossy, WarningKind};
+///
+/// let (_, warnings) = parse_lossy(r#"[36,5,6,null,["hello"],[]]"#).unwrap();
+/// let kinds: Vec<WarningKind> = warnings.iter().map(|warning| warning.kind).collect();
+///
+/// assert_eq!(kinds, vec![WarningKind::CoercedNullToObject, WarningKind::DroppedEmptyKwargs]);
+/// assert_eq!(warnings[0].to_string(), "coerced_null_to_object at EVENT.details: was null, used {}");
+/// ```
+pub fn parse_lossy(frame: &str) -> Result<(Messages, Vec<ParseWarning>), Error> {
+    let mut value: Value = serde_json::from_str(frame)?;
+    let mut warnings = Vec::new();
+    let descriptor = value
+        .get(0)
+        .and_then(Value::as_u64)
+        .and_then(descriptor_for);
+    if let (Some(descriptor), Some(elements)) = (descriptor, value.as_array_mut()) {
+        let location = |field: &str| FieldPath::new(descriptor.name).join(field);
+        for (field, element) in descriptor.fields.iter().zip(elements.iter_mut().skip(1)) {
+            match *field {
+                "options" | "details" | "extra" if element.is_null() => {
+                    *element = Value::Object(Map::new());
+                    warnings.push(ParseWarning::new(
+                        location(field),
+                        WarningKind::CoercedNullToObject,
+                        "was null, used {}",
+                    ));
+                }
+                "request_id" | "session" | "subscription" | "publication" | "registration" => {
+                    if let Some(id) = float_id(element) {
+                        warnings.push(ParseWarning::new(
+                            location(field),
+                            WarningKind::FloatIdTruncated,
+                            format!("was the float {element}, used {id}"),
+                        ));
+                        *element = Value::from(id);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if descriptor.fields.last() == Some(&"kwargs")
+            && elements.len() == descriptor.fields.len() + 1
+            && elements.last() == Some(&Value::Array(vec![]))
+        {
+            elements.pop();
+            warnings.push(ParseWarning::new(
+                location("kwargs"),
+                WarningKind::DroppedEmptyKwargs,
+                "was [], dropped it",
+            ));
+        }
+    }
+    let message = serde_json::from_value(value)?;
+    Ok((message, warnings))
+}
+
+/// Parse the details of a HELLO or WELCOME, named `message`, into `T`.
+///
+/// Strict parsing fails with [`Error::MissingRoles`] when the details have no `roles`, lenient
+/// parsing uses an empty object instead and reports [`WarningKind::MissingRoles`].
+pub(crate) fn typed_details<T: DeserializeOwned>(
+    message: &'static str,
+    details: &Value,
+    lenient: bool,
+) -> Result<(T, Vec<ParseWarning>), Error> {
+    if details.get("roles").is_some() {
+        return Ok((T::deserialize(details)?, Vec::new()));
+    }
+    if !lenient {
+        return Err(Error::MissingRoles);
+    }
+    let mut details = match details {
+        Value::Null => Map::new(),
+        Value::Object(details) => details.clone(),
+        other => return Ok((T::deserialize(other)?, Vec::new())),
+    };
+    details.insert("roles".to_string(), Value::Object(Map::new()));
+    let typed = T::deserialize(Value::Object(details))?;
+    let warning = ParseWarning::new(
+        FieldPath::new(message).join("details").join("roles"),
+        WarningKind::MissingRoles,
+        "was missing, used {}",
+    );
+    Ok((typed, vec![warning]))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn lossy_coercions() {
+        let (message, warnings) = parse_lossy(r#"[48,7,null,"com.myapp.add",[1,2]]"#).unwrap();
+        assert_eq!(
+            message,
+            parse(r#"[48,7,{},"com.myapp.add",[1,2]]"#).unwrap()
+        );
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::new(
+                FieldPath::new("CALL").join("options"),
+                WarningKind::CoercedNullToObject,
+                "was null, used {}"
+            )]
+        );
+
+        let (message, warnings) = parse_lossy(r#"[50,7,{},[3],[]]"#).unwrap();
+        assert_eq!(message, parse(r#"[50,7,{},[3]]"#).unwrap());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::DroppedEmptyKwargs);
+        assert_eq!(warnings[0].location.segments(), &["RESULT", "kwargs"]);
+
+        let (message, warnings) = parse_lossy(r#"[36,5.0,6.0,{},[1.0]]"#).unwrap();
+        assert_eq!(message, parse(r#"[36,5,6,{},[1.0]]"#).unwrap());
+        assert_eq!(message.args(), Some(&json!([1.0])));
+        let kinds: Vec<WarningKind> = warnings.iter().map(|warning| warning.kind).collect();
+        assert_eq!(kinds, vec![WarningKind::FloatIdTruncated; 2]);
+        assert_eq!(warnings[1].location.to_string(), "EVENT.publication");
+
+        let (message, warnings) = parse_lossy(r#"[33,1,5]"#).unwrap();
+        assert_eq!(message, parse(r#"[33,1,5]"#).unwrap());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn lossy_leaves_payload_alone() {
+        let (message, warnings) = parse_lossy(r#"[16,1,{},"com.myapp.topic1",[null],{}]"#).unwrap();
+        assert_eq!(message.args(), Some(&json!([null])));
+        assert!(warnings.is_empty());
+
+        let frame = r#"[16,1,{},"com.myapp.topic1",[],[1]]"#;
+        let (message, warnings) = parse_lossy(frame).unwrap();
+        assert_eq!(message, parse(frame).unwrap());
+        assert!(warnings.is_empty());
+
+        assert!(parse_lossy(r#"[33,1.5,5]"#).is_err());
+        assert!(parse_lossy(r#"[33,-1.0,5]"#).is_err());
+        assert!(parse_lossy(r#"[48,7,"fast","com.myapp.add"]"#).is_err());
+    }
+
+    #[test]
+    fn missing_roles() {
+        const FIXTURE: &str = include_str!("../tests/fixtures/missing_roles.jsonl");
+        let mut messages = FIXTURE.lines().map(|frame| parse(frame).unwrap());
+
+        let Some(Messages::Hello(hello)) = messages.next() else {
+            panic!("expected a HELLO");
+        };
+        assert!(matches!(hello.details_typed(), Err(Error::MissingRoles)));
+        let (details, warnings) = hello.details_typed_lenient().unwrap();
+        assert!(details.roles.is_empty());
+        assert_eq!(details.authid.as_deref(), Some("device-17"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, WarningKind::MissingRoles);
+
+        for message in messages {
+            let Messages::Welcome(welcome) = message else {
+                panic!("expected a WELCOME");
+            };
+            assert!(matches!(welcome.details_typed(), Err(Error::MissingRoles)));
+            let (details, warnings) = welcome.details_typed_lenient().unwrap();
+            assert!(details.roles.is_empty());
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].location.to_string(), "WELCOME.details.roles");
+        }
+
+        let welcome = match parse(r#"[2,1,{"roles":{"broker":{}},"authid":"joe"}]"#).unwrap() {
+            Messages::Welcome(welcome) => welcome,
+            other => panic!("unexpected {other:?}"),
+        };
+        let (details, warnings) = welcome.details_typed_lenient().unwrap();
+        assert_eq!(details, welcome.details_typed().unwrap());
+        assert_eq!(details.roles["broker"], json!({}));
+        assert!(warnings.is_empty());
+
+        let malformed = match parse(r#"[2,1,{"authid":5}]"#).unwrap() {
+            Messages::Welcome(welcome) => welcome,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert!(malformed.details_typed_lenient().is_err());
+    }
+
+    #[test]
+    fn float_ids() {
+        let text = |error: Error| match error {
+            Error::SerdeJsonError(error) => error.to_string(),
+            other => panic!("unexpected {other:?}"),
+        };
+        let error = |frame: &str| text(parse(frame).unwrap_err());
+
+        assert!(error(r#"[33,1.5,2]"#)
+            .starts_with("Subscribed[1] (request_id) must be an integer, got 1.5"));
+        assert!(error(r#"[33,1.0,2]"#)
+            .starts_with("Subscribed[1] (request_id) must be an integer, got 1.0"));
+        assert!(error(r#"[36,5,-6,{}]"#)
+            .starts_with("Event[2] (publication) must be an integer, got -6"));
+        assert!(error(r#"[2,9007199254740993,{}]"#)
+            .starts_with("Welcome[1] (session) must be at most 2^53, got 9007199254740993"));
+        assert!(parse(r#"[2,9007199254740992,{}]"#).is_ok());
+
+        // Only the lossy parser accepts floats without a fractional part.
+        let (message, _) = parse_lossy(r#"[33,1.0,2.0]"#).unwrap();
+        assert_eq!(message, parse(r#"[33,1,2]"#).unwrap());
+        let error = text(parse_lossy(r#"[33,1,2.5]"#).unwrap_err());
+        assert!(error.starts_with("Subscribed[2] (subscription) must be an integer, got 2.5"));
+    }
+
+    #[test]
+    fn warning_display_is_stable() {
+        let display = |frame: &str| -> Vec<String> {
+            let (_, warnings) = parse_lossy(frame).unwrap();
+            warnings.iter().map(ToString::to_string).collect()
+        };
+        assert_eq!(
+            display(r#"[36,5.0,6,null,["hello"],[]]"#),
+            vec![
+                "float_id_truncated at EVENT.subscription: was the float 5.0, used 5",
+                "coerced_null_to_object at EVENT.details: was null, used {}",
+                "dropped_empty_kwargs at EVENT.kwargs: was [], dropped it",
+            ]
+        );
+
+        let Messages::Hello(hello) = parse(r#"[1,"realm1",{"agent":"tiny"}]"#).unwrap() else {
+            panic!("expected a HELLO");
+        };
+        let (_, warnings) = hello.details_typed_lenient().unwrap();
+        assert_eq!(
+            warnings[0].to_string(),
+            "missing_roles at HELLO.details.roles: was missing, used {}"
+        );
+
+        let Messages::Call(call) =
+            parse(r#"[48,1,{"timeout":10,"colour":"blue","_trace":1},"com.myapp.add"]"#).unwrap()
+        else {
+            panic!("expected a CALL");
+        };
+        let warnings = call.unknown_key_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].to_string(),
+            "unknown_key at CALL.options.colour: is not known, kept it"
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    #[tracing_test::traced_test]
+    fn logged_warnings() {
+        let (_, warnings) = parse_lossy(r#"[33,1.0,5]"#).unwrap();
+        log_warnings(&warnings);
+        assert!(logs_contain("was the float 1.0, used 1"));
+        assert!(logs_contain("location=SUBSCRIBED.request_id"));
+        assert!(logs_contain("kind=float_id_truncated"));
+    }
+}
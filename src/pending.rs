@@ -0,0 +1,232 @@
+//! # Pending requests
+//! Client side bookkeeping of the requests still waiting for their response.
+//!
+//! Track every request you send, and resolve every message you receive, the tracker hands the
+//! original request back once its final response (or ERROR) arrived. Publishes are only
+//! tracked when they ask for an acknowledgement, the broker never answers the others.
+//! ## Examples
+//! ```
+//! use wamp_core::pending::PendingRequests;
+//! use wamp_core::messages::{Messages, Publish, Published};
+//! use serde_json::json;
+//!
+//! let mut pending = PendingRequests::new();
+//!
+//! let publish = Publish {
+//!     request_id: 1,
+//!     options: json!({}),
+//!     topic: "com.myapp.topic1".into(),
+//!     args: json!(null),
+//!     kwargs: json!(null)
+//! };
+//!
+//! assert!(!pending.track(&Messages::from(publish.clone())));
+//! assert!(pending.track(&Messages::from(Publish { request_id: 2, ..publish }.acknowledged())));
+//! assert_eq!(pending.outstanding(), vec![2]);
+//!
+//! let published = Messages::from(Published { request_id: 2, publication: 7 });
+//! assert!(pending.resolve(&published).is_some());
+//! assert!(pending.is_empty());
+//! ```
+use std::collections::HashMap;
+
+use crate::messages::{Messages, WampErrorEvent};
+
+#[derive(Debug, Clone, Default)]
+/// # Pending Requests
+/// Requests sent by a client session, keyed by request id, until their response arrives.
+pub struct PendingRequests {
+    requests: HashMap<u64, Messages>,
+}
+
+/// The request id of a request expecting a response, `None` for every other message.
+pub(crate) fn request_id(request: &Messages) -> Option<u64> {
+    match request {
+        Messages::Call(v) => Some(v.request_id),
+        Messages::Publish(v) if v.expects_ack() => Some(v.request_id),
+        Messages::Register(v) => Some(v.request_id),
+        Messages::Subscribe(v) => Some(v.request_id),
+        Messages::Unregister(v) => Some(v.request_id),
+        Messages::Unsubscribe(v) => Some(v.request_id),
+        _ => None,
+    }
+}
+
+/// The request id `response` answers, `None` for progressive results and non responses.
+fn response_id(response: &Messages) -> Option<u64> {
+    match response {
+        Messages::Result(v) if v.details["progress"].as_bool() == Some(true) => None,
+        Messages::Result(v) => Some(v.request_id),
+        Messages::Published(v) => Some(v.request_id),
+        Messages::Registered(v) => Some(v.request_id),
+        Messages::Subscribed(v) => Some(v.request_id),
+        Messages::Unregistered(v) => Some(v.request_id),
+        Messages::Unsubscribed(v) => Some(v.request_id),
+        Messages::Error(v) => Some(v.request_id),
+        _ => None,
+    }
+}
+
+/// Whether `response` is the kind of message answering `request`.
+fn answers(request: &Messages, response: &Messages) -> bool {
+    match (request, response) {
+        (Messages::Call(_), Messages::Result(_))
+        | (Messages::Publish(_), Messages::Published(_))
+        | (Messages::Register(_), Messages::Registered(_))
+        | (Messages::Subscribe(_), Messages::Subscribed(_))
+        | (Messages::Unregister(_), Messages::Unregistered(_))
+        | (Messages::Unsubscribe(_), Messages::Unsubscribed(_)) => true,
+        (_, Messages::Error(error)) => {
+            WampErrorEvent::try_from(request).is_ok_and(|event| event == error.event)
+        }
+        _ => false,
+    }
+}
+
+impl PendingRequests {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking a request, returning whether it expects a response.
+    ///
+    /// Messages that are not answered, including publishes without `acknowledge`, are ignored.
+    pub fn track(&mut self, request: &Messages) -> bool {
+        match request_id(request) {
+            Some(id) => {
+                self.requests.insert(id, request.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop tracking the request `response` answers, and return it.
+    ///
+    /// Returns `None` when the message is not a response to a tracked request, including
+    /// progressive results, which leave their call pending.
+    pub fn resolve(&mut self, response: &Messages) -> Option<Messages> {
+        let id = response_id(response)?;
+        if !answers(self.requests.get(&id)?, response) {
+            return None;
+        }
+        self.requests.remove(&id)
+    }
+
+    /// Stop tracking request `id` without a response, such as after a timeout, and return it.
+    pub fn remove(&mut self, id: u64) -> Option<Messages> {
+        self.requests.remove(&id)
+    }
+
+    /// Ids of the requests still waiting for a response, in ascending order.
+    pub fn outstanding(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.requests.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// The requests waiting for a response with their id, in ascending id order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (u64, &Messages)> {
+        let mut requests: Vec<(u64, &Messages)> = self
+            .requests
+            .iter()
+            .map(|(id, request)| (*id, request))
+            .collect();
+        requests.sort_unstable_by_key(|(id, _)| *id);
+        requests.into_iter()
+    }
+
+    /// Whether request `id` is waiting for a response.
+    pub fn contains(&self, id: u64) -> bool {
+        self.requests.contains_key(&id)
+    }
+
+    /// Number of requests waiting for a response.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Whether no request is waiting for a response.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, json};
+
+    use super::*;
+    use crate::messages::{Publish, Published};
+
+    fn publish(request_id: u64) -> Publish {
+        Publish {
+            request_id,
+            options: json!({}),
+            topic: "com.myapp.topic1".into(),
+            args: json!(["hello"]),
+            kwargs: json!(null),
+        }
+    }
+
+    #[test]
+    fn unacknowledged_publish_is_not_tracked() {
+        let mut pending = PendingRequests::new();
+        assert!(!pending.track(&Messages::from(publish(1))));
+        assert!(pending.track(&Messages::from(publish(2).acknowledged())));
+        assert_eq!(pending.outstanding(), vec![2]);
+
+        let unrelated = Messages::from(Published {
+            request_id: 1,
+            publication: 5,
+        });
+        assert_eq!(pending.resolve(&unrelated), None);
+
+        let published = Messages::from(Published {
+            request_id: 2,
+            publication: 6,
+        });
+        assert_eq!(
+            pending.resolve(&published),
+            Some(Messages::from(publish(2).acknowledged()))
+        );
+        assert!(pending.outstanding().is_empty());
+    }
+
+    #[test]
+    fn iter_sorted_is_stable() {
+        let mut pending = PendingRequests::new();
+        for id in [40, 7, 1000, 3, 512] {
+            pending.track(&Messages::from(publish(id).acknowledged()));
+        }
+        let ids: Vec<u64> = pending.iter_sorted().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec![3, 7, 40, 512, 1000]);
+        assert_eq!(ids, pending.outstanding());
+        for (id, request) in pending.iter_sorted() {
+            assert_eq!(request, &Messages::from(publish(id).acknowledged()));
+        }
+    }
+
+    #[test]
+    fn calls_resolve_on_final_result_or_error() {
+        let mut pending = PendingRequests::new();
+        let call: Messages = from_str(r#"[48,7,{},"com.myapp.add",[1,2]]"#).unwrap();
+        let subscribe: Messages = from_str(r#"[32,8,{},"com.myapp.topic1"]"#).unwrap();
+        assert!(pending.track(&call));
+        assert!(pending.track(&subscribe));
+
+        let progress: Messages = from_str(r#"[50,7,{"progress":true},[1]]"#).unwrap();
+        assert_eq!(pending.resolve(&progress), None);
+        let wrong_kind: Messages = from_str(r#"[33,7,5]"#).unwrap();
+        assert_eq!(pending.resolve(&wrong_kind), None);
+        assert_eq!(pending.len(), 2);
+
+        let result: Messages = from_str(r#"[50,7,{},[3]]"#).unwrap();
+        assert_eq!(pending.resolve(&result), Some(call));
+
+        let error: Messages = from_str(r#"[8,32,8,{},"wamp.error.not_authorized"]"#).unwrap();
+        assert_eq!(pending.resolve(&error), Some(subscribe));
+        assert!(pending.is_empty());
+    }
+}
@@ -0,0 +1,36 @@
+//! # Prelude
+//! The types, traits and macros most code using the crate needs, to import with a glob.
+//!
+//! Besides the message structs, the [`Messages`] enum and their traits, it re-exports
+//! `serde_json::{json, Value}`, which the message fields and macros are built on.
+//! ## Examples
+//! ```
+//! use wamp_core::prelude::*;
+//!
+//! let message = Published { request_id: 1, publication: 2 }.to_messages();
+//!
+//! assert!(matches!(message, Messages::Published(_)));
+//! assert_eq!(message.serialized_size().unwrap(), 8);
+//!
+//! let hello_message = hello!("realm", roles: [Roles::Caller, Roles::Subscriber]);
+//! assert_eq!(hello_message.details["roles"], json!({ "caller": {}, "subscriber": {} }));
+//! ```
+pub use serde_json::{json, Value};
+
+#[cfg(feature = "tokio")]
+pub use crate::codec::WampCodec;
+pub use crate::codec::{JsonLinesDecoder, JsonLinesEncoder};
+pub use crate::error::Error;
+pub use crate::messages::{
+    Abort, Authenticate, Call, CallOptions, Cancel, Challenge, ErrorRequest, Event, Goodbye, Hello,
+    Interrupt, Invocation, Messages, Publish, Published, Register, Registered, Subscribe,
+    Subscribed, Unregister, Unregistered, Unsubscribe, Unsubscribed, WampError, WampMessage,
+    WampMessageExt, WampResult, Welcome, Yield,
+};
+pub use crate::roles::Roles;
+pub use crate::transport::{Encoding, FrameHandler};
+pub use crate::{
+    abort, authenticate, call, cancel, challenge, details, error, error_for, event, extension,
+    goodbye, hello, interrupt, invocation, publish, published, r#yield, register, registered,
+    result, subscribe, subscribed, unregister, unregistered, unsubscribe, unsubscribed, welcome,
+};
@@ -0,0 +1,676 @@
+//! # Publish & Subscribe - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-publish-and-subscribe)
+//! Broker side helpers turning a PUBLISH into the EVENTs delivered to subscribers.
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    error::Error,
+    messages::{Event, Payload, Publish, SharedEvent, Unsubscribed},
+    uri::MatchPolicy,
+};
+
+/// A WAMP session id.
+pub type SessionId = u64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Matched Subscription
+/// A subscription whose topic or pattern matched the topic of a publication.
+pub struct MatchedSubscription {
+    /// The subscription id.
+    pub subscription: u64,
+    /// The match policy of the subscription.
+    pub policy: MatchPolicy,
+    /// The sessions attached to the subscription.
+    pub subscribers: Vec<SessionId>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// # Receiver Identity
+/// The identity of a subscriber, used to apply subscriber black and white listing.
+pub struct ReceiverIdentity {
+    /// The session id of the subscriber.
+    pub session: SessionId,
+    /// The authenticated identity of the subscriber.
+    pub authid: Option<String>,
+    /// The role of the subscriber.
+    pub authrole: Option<String>,
+}
+
+/// Whether `value` is in the list under `key`, `None` when the option is absent.
+fn listed(options: &Value, key: &str, value: Option<Value>) -> Option<bool> {
+    let list = options.get(key)?.as_array()?;
+    Some(value.is_some_and(|value| list.contains(&value)))
+}
+
+/// # Is eligible
+/// Applies the subscriber black and white listing options of a PUBLISH to a receiver.
+///
+/// A receiver must be in every white list given (`eligible`, `eligible_authid`,
+/// `eligible_authrole`) and in none of the black lists (`exclude`, `exclude_authid`,
+/// `exclude_authrole`).
+/// ## Examples
+/// ```
+/// use wamp_core::pubsub::{is_eligible, ReceiverIdentity};
+/// use serde_json::json;
+///
+/// let receiver = ReceiverIdentity { session: 2, authid: None, authrole: Some("user".to_string()) };
+///
+/// assert!(is_eligible(&json!({}), &receiver));
+/// assert!(is_eligible(&json!({ "eligible_authrole": ["user", "admin"] }), &receiver));
+/// assert!(!is_eligible(&json!({ "exclude": [2] }), &receiver));
+/// ```
+pub fn is_eligible(options: &Value, receiver: &ReceiverIdentity) -> bool {
+    let session = Some(json!(receiver.session));
+    let authid = receiver.authid.as_ref().map(|authid| json!(authid));
+    let authrole = receiver.authrole.as_ref().map(|authrole| json!(authrole));
+    let eligible = [
+        listed(options, "eligible", session.clone()),
+        listed(options, "eligible_authid", authid.clone()),
+        listed(options, "eligible_authrole", authrole.clone()),
+    ];
+    let excluded = [
+        listed(options, "exclude", session),
+        listed(options, "exclude_authid", authid),
+        listed(options, "exclude_authrole", authrole),
+    ];
+    eligible.iter().all(|listed| listed.unwrap_or(true))
+        && !excluded.iter().any(|listed| listed.unwrap_or(false))
+}
+
+/// # Plan fan-out
+/// Same as [`plan_shared_fanout`], but every EVENT owns a copy of the payload.
+#[deprecated(note = "copies the payload for every subscriber, use `plan_shared_fanout`")]
+pub fn plan_fanout(
+    publish: &Publish,
+    publication: u64,
+    matches: &[MatchedSubscription],
+    identities: &dyn Fn(u64) -> Option<ReceiverIdentity>,
+) -> Vec<(SessionId, Event)> {
+    plan_shared_fanout(publish, publication, matches, identities)
+        .into_iter()
+        .map(|(session, event)| (session, Event::from(event)))
+        .collect()
+}
+
+/// # Plan shared fan-out
+/// Builds the EVENT delivered to every eligible subscriber of the matched subscriptions, all
+/// sharing the `publication` id.
+///
+/// Every subscriber of every match receives one EVENT for the subscription it matched through.
+/// Subscribers `identities` returns `None` for, sessions that are gone, are skipped. When a
+/// subscription matched by prefix or wildcard, the concrete topic is attached as
+/// `details.topic`.
+///
+/// Publisher exclusion is not applied, add the publisher session to `exclude` when it must not
+/// receive its own event.
+///
+/// Every EVENT shares the payload of the PUBLISH, which is copied once no matter how many
+/// subscribers receive it.
+/// ## Examples
+/// ```
+/// use wamp_core::pubsub::{plan_shared_fanout, MatchedSubscription, ReceiverIdentity};
+/// use wamp_core::messages::Publish;
+/// use wamp_core::uri::MatchPolicy;
+/// use serde_json::json;
+///
+/// let publish = Publish {
+///     request_id: 1,
+///     options: json!({}),
+///     topic: "com.myapp.topic1".into(),
+///     args: json!(["hello"]),
+///     kwargs: json!(null)
+/// };
+/// let matches = [MatchedSubscription {
+///     subscription: 5512315355,
+///     policy: MatchPolicy::Prefix,
+///     subscribers: vec![7, 8],
+/// }];
+/// let identities = |session| Some(ReceiverIdentity { session, ..Default::default() });
+///
+/// let events = plan_shared_fanout(&publish, 4429313566, &matches, &identities);
+///
+/// assert_eq!(events.len(), 2);
+/// assert_eq!(events[0].0, 7);
+/// assert_eq!(events[0].1.publication, 4429313566);
+/// assert_eq!(events[0].1.details, json!({ "topic": "com.myapp.topic1" }));
+/// assert!(events[0].1.payload.ptr_eq(&events[1].1.payload));
+/// ```
+pub fn plan_shared_fanout(
+    publish: &Publish,
+    publication: u64,
+    matches: &[MatchedSubscription],
+    identities: &dyn Fn(u64) -> Option<ReceiverIdentity>,
+) -> Vec<(SessionId, SharedEvent)> {
+    let payload = Payload::new(publish.args.clone(), publish.kwargs.clone());
+    let mut events = Vec::new();
+    for matched in matches {
+        let details = match matched.policy {
+            MatchPolicy::Exact => json!({}),
+            MatchPolicy::Prefix | MatchPolicy::Wildcard => json!({ "topic": publish.topic }),
+        };
+        for &session in &matched.subscribers {
+            let Some(receiver) = identities(session) else {
+                continue;
+            };
+            if !is_eligible(&publish.options, &receiver) {
+                continue;
+            }
+            let event = SharedEvent {
+                subscription: matched.subscription,
+                publication,
+                details: details.clone(),
+                payload: payload.clone(),
+            };
+            events.push((session, event));
+        }
+    }
+    events
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    children: HashMap<String, TrieNode>,
+    exact: Vec<u64>,
+    /// Prefix patterns equal to the path of the node.
+    prefix: Vec<u64>,
+    /// Prefix patterns equal to the path of the node followed by a `.`.
+    prefix_below: Vec<u64>,
+    wildcard: Vec<u64>,
+}
+
+impl TrieNode {
+    fn ids(&mut self, policy: MatchPolicy, below: bool) -> &mut Vec<u64> {
+        match policy {
+            MatchPolicy::Exact => &mut self.exact,
+            MatchPolicy::Prefix if below => &mut self.prefix_below,
+            MatchPolicy::Prefix => &mut self.prefix,
+            MatchPolicy::Wildcard => &mut self.wildcard,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.children.is_empty()
+            && self.exact.is_empty()
+            && self.prefix.is_empty()
+            && self.prefix_below.is_empty()
+            && self.wildcard.is_empty()
+    }
+}
+
+/// The components a pattern is stored under, and whether it is a prefix ending with `.`.
+fn pattern_path(pattern: &str, policy: MatchPolicy) -> (Vec<&str>, bool) {
+    if policy == MatchPolicy::Prefix {
+        if pattern.is_empty() {
+            return (Vec::new(), false);
+        }
+        if let Some(pattern) = pattern.strip_suffix('.') {
+            return (pattern.split('.').collect(), true);
+        }
+    }
+    (pattern.split('.').collect(), false)
+}
+
+#[derive(Debug, Clone, Default)]
+/// # Subscription Trie
+/// An index of subscriptions keyed on URI components, finding every subscription matching a
+/// topic without scanning all of them.
+///
+/// Patterns follow their [`MatchPolicy`]: exact patterns must equal the topic, prefix patterns
+/// match like [`is_prefix_of`](crate::uri::is_prefix_of) and wildcard patterns like
+/// [`is_wildcard_match`](crate::uri::is_wildcard_match). Matching costs one lookup per topic
+/// component, plus one branch per wildcard component on the way.
+/// ## Examples
+/// ```
+/// use wamp_core::pubsub::SubscriptionTrie;
+/// use wamp_core::uri::MatchPolicy;
+///
+/// let mut trie = SubscriptionTrie::new();
+/// trie.insert("com.myapp.user.create", MatchPolicy::Exact, 1);
+/// trie.insert("com.myapp", MatchPolicy::Prefix, 2);
+/// trie.insert("com.myapp..create", MatchPolicy::Wildcard, 3);
+/// trie.insert("com.other", MatchPolicy::Prefix, 4);
+///
+/// assert_eq!(trie.matches("com.myapp.user.create"), vec![1, 2, 3]);
+/// assert_eq!(trie.matches("com.myapp.user.delete"), vec![2]);
+/// assert!(trie.matches("com.myapplication").is_empty());
+/// ```
+pub struct SubscriptionTrie {
+    root: TrieNode,
+    len: usize,
+}
+
+impl SubscriptionTrie {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add subscription `id` for `pattern` with `policy`.
+    pub fn insert(&mut self, pattern: &str, policy: MatchPolicy, id: u64) {
+        let (path, below) = pattern_path(pattern, policy);
+        let mut node = &mut self.root;
+        for component in path {
+            node = node.children.entry(component.to_string()).or_default();
+        }
+        node.ids(policy, below).push(id);
+        self.len += 1;
+    }
+
+    /// Remove subscription `id` for `pattern` with `policy`, returning whether it was indexed.
+    pub fn remove(&mut self, pattern: &str, policy: MatchPolicy, id: u64) -> bool {
+        fn remove_from(
+            node: &mut TrieNode,
+            path: &[&str],
+            policy: MatchPolicy,
+            below: bool,
+            id: u64,
+        ) -> bool {
+            let Some((component, rest)) = path.split_first() else {
+                let ids = node.ids(policy, below);
+                let len = ids.len();
+                ids.retain(|entry| *entry != id);
+                return ids.len() != len;
+            };
+            let Some(child) = node.children.get_mut(*component) else {
+                return false;
+            };
+            let removed = remove_from(child, rest, policy, below, id);
+            if child.is_empty() {
+                node.children.remove(*component);
+            }
+            removed
+        }
+
+        let (path, below) = pattern_path(pattern, policy);
+        let removed = remove_from(&mut self.root, &path, policy, below, id);
+        if removed {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Ids of every subscription matching `topic`, in ascending order.
+    pub fn matches(&self, topic: &str) -> Vec<u64> {
+        let components: Vec<&str> = topic.split('.').collect();
+        let mut ids = Vec::new();
+        // Nodes reached through wildcard components only hold matching wildcard patterns.
+        let mut stack = vec![(&self.root, 0, true)];
+        while let Some((node, depth, literal)) = stack.pop() {
+            if literal {
+                ids.extend(&node.prefix);
+                if depth < components.len() {
+                    ids.extend(&node.prefix_below);
+                }
+            }
+            let Some(component) = components.get(depth) else {
+                if literal {
+                    ids.extend(&node.exact);
+                }
+                ids.extend(&node.wildcard);
+                continue;
+            };
+            if let Some(child) = node.children.get(*component) {
+                stack.push((child, depth + 1, literal));
+            }
+            if !component.is_empty() {
+                if let Some(any) = node.children.get("") {
+                    stack.push((any, depth + 1, false));
+                }
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Number of indexed subscriptions.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no subscription is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[derive(Debug)]
+/// # Event Router
+/// Subscriber side dispatch of EVENTs to the handler registered for their subscription.
+///
+/// The router can be shared between threads, registration takes a write lock while routing
+/// only takes a read lock. Handlers are kept behind an [`Arc`], so [`EventRouter::route`] hands
+/// out a handle that stays valid while other threads (un)register subscriptions.
+/// ## Examples
+/// ```
+/// use wamp_core::pubsub::EventRouter;
+/// use wamp_core::messages::{Event, Unsubscribed};
+/// use serde_json::from_str;
+///
+/// let router = EventRouter::new();
+/// router.register(5512315355, "com.myapp.topic1", "on_topic1");
+///
+/// let event: Event = from_str(r#"[36,5512315355,4429313566,{},["hello"]]"#).unwrap();
+/// assert_eq!(*router.route(&event).unwrap(), "on_topic1");
+///
+/// let revoked: Unsubscribed = from_str(r#"[35,0,{"subscription":5512315355}]"#).unwrap();
+/// assert_eq!(router.handle_unsubscribed(&revoked), Some(5512315355));
+/// assert!(router.route(&event).is_err());
+/// ```
+pub struct EventRouter<H> {
+    handlers: RwLock<HashMap<u64, (String, Arc<H>)>>,
+}
+
+impl<H> Default for EventRouter<H> {
+    fn default() -> Self {
+        EventRouter {
+            handlers: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<H> EventRouter<H> {
+    /// Create a router without subscriptions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<u64, (String, Arc<H>)>> {
+        self.handlers.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<u64, (String, Arc<H>)>> {
+        self.handlers.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register `handler` for the events of `subscription` on `topic`, returning the handler it
+    /// replaces.
+    pub fn register<T: ToString>(&self, subscription: u64, topic: T, handler: H) -> Option<Arc<H>> {
+        self.write()
+            .insert(subscription, (topic.to_string(), Arc::new(handler)))
+            .map(|(_, handler)| handler)
+    }
+
+    /// Remove the handler of `subscription`, returning it.
+    pub fn unregister(&self, subscription: u64) -> Option<Arc<H>> {
+        self.write()
+            .remove(&subscription)
+            .map(|(_, handler)| handler)
+    }
+
+    /// The handler for `event`.
+    ///
+    /// Fails with [`Error::UnknownSubscription`] when no handler is registered for the
+    /// subscription of the event.
+    pub fn route(&self, event: &Event) -> Result<Arc<H>, Error> {
+        self.read()
+            .get(&event.subscription)
+            .map(|(_, handler)| handler.clone())
+            .ok_or(Error::UnknownSubscription(event.subscription))
+    }
+
+    /// The topic `subscription` was registered for.
+    pub fn topic(&self, subscription: u64) -> Option<String> {
+        self.read()
+            .get(&subscription)
+            .map(|(topic, _)| topic.clone())
+    }
+
+    /// Drop the handler of a subscription revoked by the broker.
+    ///
+    /// Returns the revoked subscription when its handler was registered, `None` for replies to
+    /// an UNSUBSCRIBE, which are expected to [`unregister`](EventRouter::unregister) explicitly.
+    pub fn handle_unsubscribed(&self, unsubscribed: &Unsubscribed) -> Option<u64> {
+        let subscription = unsubscribed.revoked_subscription()?;
+        self.unregister(subscription).map(|_| subscription)
+    }
+
+    /// Number of registered subscriptions.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Whether no subscription is registered.
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fanout_to_many_subscribers() {
+        let publish = Publish {
+            request_id: 1,
+            options: json!({ "exclude": [3], "exclude_authrole": ["banned"] }),
+            topic: "com.myapp.topic1".into(),
+            args: json!([vec![0u8; 1024]]),
+            kwargs: json!({ "key": "value" }),
+        };
+        let matches = [
+            MatchedSubscription {
+                subscription: 1,
+                policy: MatchPolicy::Exact,
+                subscribers: (1..=1000).collect(),
+            },
+            MatchedSubscription {
+                subscription: 2,
+                policy: MatchPolicy::Wildcard,
+                subscribers: vec![1, 2000],
+            },
+        ];
+        let identities = |session: u64| match session {
+            2000 => None,
+            _ => Some(ReceiverIdentity {
+                session,
+                authid: None,
+                authrole: Some(if session == 10 { "banned" } else { "user" }.to_string()),
+            }),
+        };
+        let events = plan_shared_fanout(&publish, 99, &matches, &identities);
+        assert_eq!(events.len(), 999);
+        assert!(events.iter().all(|(_, event)| event.publication == 99));
+        assert!(events
+            .iter()
+            .all(|(session, _)| *session != 3 && *session != 10));
+        let (session, event) = events.last().unwrap();
+        assert_eq!(*session, 1);
+        assert_eq!(event.subscription, 2);
+        assert_eq!(event.details, json!({ "topic": "com.myapp.topic1" }));
+        assert_eq!(events[0].1.details, json!({}));
+        assert_eq!(events[0].1.payload.args(), &publish.args);
+        assert_eq!(events[0].1.payload.kwargs(), &publish.kwargs);
+    }
+
+    #[test]
+    fn shared_fanout_copies_payload_once() {
+        let publish = Publish {
+            request_id: 1,
+            options: json!({}),
+            topic: "com.myapp.topic1".into(),
+            args: json!(["x".repeat(1 << 16)]),
+            kwargs: json!(null),
+        };
+        let matches = [MatchedSubscription {
+            subscription: 1,
+            policy: MatchPolicy::Exact,
+            subscribers: (1..=1000).collect(),
+        }];
+        let identities = |session| {
+            Some(ReceiverIdentity {
+                session,
+                ..Default::default()
+            })
+        };
+        let events = plan_shared_fanout(&publish, 99, &matches, &identities);
+        assert_eq!(events.len(), 1000);
+        let first = &events[0].1.payload;
+        assert!(events.iter().all(|(_, event)| event.payload.ptr_eq(first)));
+        assert_eq!(first.args(), &publish.args);
+    }
+
+    #[test]
+    fn eligibility() {
+        let receiver = ReceiverIdentity {
+            session: 5,
+            authid: Some("joe".to_string()),
+            authrole: None,
+        };
+        assert!(is_eligible(&json!({ "eligible": [5, 6] }), &receiver));
+        assert!(!is_eligible(&json!({ "eligible": [6] }), &receiver));
+        assert!(!is_eligible(
+            &json!({ "eligible_authrole": ["user"] }),
+            &receiver
+        ));
+        assert!(!is_eligible(
+            &json!({ "exclude_authid": ["joe"] }),
+            &receiver
+        ));
+        assert!(is_eligible(
+            &json!({ "exclude_authrole": ["user"] }),
+            &receiver
+        ));
+    }
+
+    #[test]
+    fn trie_overlapping_policies() {
+        let mut trie = SubscriptionTrie::new();
+        trie.insert("com.myapp.user.create", MatchPolicy::Exact, 1);
+        trie.insert("com.myapp.user", MatchPolicy::Exact, 2);
+        trie.insert("com.myapp.user", MatchPolicy::Prefix, 3);
+        trie.insert("com.myapp.user.", MatchPolicy::Prefix, 4);
+        trie.insert("", MatchPolicy::Prefix, 5);
+        trie.insert("com.myapp..create", MatchPolicy::Wildcard, 6);
+        trie.insert("..", MatchPolicy::Wildcard, 7);
+        trie.insert("com.myapp.user.", MatchPolicy::Wildcard, 8);
+        trie.insert("com.myapp.user.create", MatchPolicy::Wildcard, 9);
+        assert_eq!(trie.len(), 9);
+
+        assert_eq!(
+            trie.matches("com.myapp.user.create"),
+            vec![1, 3, 4, 5, 6, 8, 9]
+        );
+        assert_eq!(trie.matches("com.myapp.user"), vec![2, 3, 5, 7]);
+        assert_eq!(trie.matches("com.myapp.users.create"), vec![5, 6]);
+        assert_eq!(trie.matches("com.myapp.group.create"), vec![5, 6]);
+        assert_eq!(trie.matches("org"), vec![5]);
+
+        assert!(trie.remove("com.myapp.user", MatchPolicy::Prefix, 3));
+        assert!(!trie.remove("com.myapp.user", MatchPolicy::Prefix, 3));
+        assert!(!trie.remove("com.myapp.user", MatchPolicy::Wildcard, 2));
+        assert!(trie.remove("com.myapp..create", MatchPolicy::Wildcard, 6));
+        assert_eq!(trie.matches("com.myapp.user.create"), vec![1, 4, 5, 8, 9]);
+        assert_eq!(trie.len(), 7);
+    }
+
+    #[test]
+    fn trie_agrees_with_linear_scan() {
+        use crate::uri::{is_prefix_of, is_wildcard_match};
+
+        let words = ["com", "myapp", "user", "create", "delete", "group", "x"];
+        let uri = |n: usize, len: usize| -> String {
+            (0..len)
+                .map(|i| words[(n / 7usize.pow(i as u32)) % words.len()])
+                .collect::<Vec<_>>()
+                .join(".")
+        };
+        let mut patterns = Vec::new();
+        for n in 0..5000 {
+            let policy = [
+                MatchPolicy::Exact,
+                MatchPolicy::Prefix,
+                MatchPolicy::Wildcard,
+            ][n % 3];
+            let mut pattern = uri(n * 31, 1 + n % 4);
+            if policy == MatchPolicy::Wildcard {
+                pattern = pattern
+                    .split('.')
+                    .enumerate()
+                    .map(|(i, c)| if (n + i) % 3 == 0 { "" } else { c })
+                    .collect::<Vec<_>>()
+                    .join(".");
+            }
+            patterns.push((pattern, policy, n as u64));
+        }
+        let mut trie = SubscriptionTrie::new();
+        for (pattern, policy, id) in &patterns {
+            trie.insert(pattern, *policy, *id);
+        }
+
+        let mut matched = 0;
+        for n in 0..2000 {
+            let topic = uri(n * 17, 1 + n % 5);
+            let expected: Vec<u64> = patterns
+                .iter()
+                .filter(|(pattern, policy, _)| match policy {
+                    MatchPolicy::Exact => pattern == &topic,
+                    MatchPolicy::Prefix => is_prefix_of(pattern, &topic),
+                    MatchPolicy::Wildcard => is_wildcard_match(pattern, &topic),
+                })
+                .map(|(_, _, id)| *id)
+                .collect();
+            let ids = trie.matches(&topic);
+            assert_eq!(ids, expected, "{topic}");
+            matched += ids.len();
+        }
+        assert!(matched > 0);
+    }
+
+    #[test]
+    fn route_interleaved_events() {
+        let router = Arc::new(EventRouter::new());
+        let topics = ["com.myapp.a", "com.myapp.b", "com.myapp.c"];
+        for (subscription, topic) in (1..).zip(topics) {
+            assert!(router.register(subscription, topic, topic).is_none());
+        }
+        let event = |subscription: u64, publication: u64| Event {
+            subscription,
+            publication,
+            details: json!({}),
+            args: json!([publication]),
+            kwargs: Value::Null,
+        };
+
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let router = router.clone();
+                std::thread::spawn(move || {
+                    for publication in 0..300u64 {
+                        let subscription = (publication + thread) % 3 + 1;
+                        let handler = router.route(&event(subscription, publication)).unwrap();
+                        assert_eq!(Some(handler.to_string()), router.topic(subscription));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let reply = Unsubscribed {
+            request_id: 7,
+            details: Value::Null,
+        };
+        assert_eq!(router.handle_unsubscribed(&reply), None);
+        let revoked: Unsubscribed = serde_json::from_str(
+            r#"[35,0,{"subscription":2,"reason":"wamp.authentication.lost"}]"#,
+        )
+        .unwrap();
+        assert_eq!(router.handle_unsubscribed(&revoked), Some(2));
+        assert_eq!(router.handle_unsubscribed(&revoked), None);
+
+        assert_eq!(*router.route(&event(1, 1)).unwrap(), "com.myapp.a");
+        assert!(matches!(
+            router.route(&event(2, 2)),
+            Err(Error::UnknownSubscription(2))
+        ));
+        assert!(router.unregister(3).is_some());
+        assert_eq!(router.len(), 1);
+    }
+}
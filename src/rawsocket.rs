@@ -0,0 +1,347 @@
+//! # Raw socket transport - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-rawsocket-transport)
+//! Codec for the WAMP raw socket transport, which runs WAMP directly over a reliable byte stream
+//! such as TCP or a Unix domain socket instead of WebSocket.
+//!
+//! A connection starts with a 4 octet handshake sent by the client and answered by the router:
+//!
+//! | octet | content |
+//! |-------|---------|
+//! | 1 | magic `0x7F` |
+//! | 2 | high nibble: maximum message length `2^(9 + LENGTH)`, low nibble: serializer |
+//! | 3, 4 | reserved, must be `0x00` |
+//!
+//! A router rejecting the connection answers with the error code in the high nibble of octet 2
+//! and a zero low nibble.
+//!
+//! After the handshake every message is sent as a frame, a 4 octet header followed by the
+//! payload. The low 3 bits of the first octet hold the [`FrameType`], the next 3 octets the
+//! payload length as a big endian integer.
+use crate::{error::Error, transport::Encoding};
+
+/// The first octet of every handshake.
+pub const MAGIC: u8 = 0x7F;
+
+/// Smallest maximum message length a peer can announce, `2^9` bytes.
+pub const MIN_MAX_LEN: u32 = 1 << 9;
+
+/// Largest maximum message length a peer can announce, `2^24` bytes.
+pub const MAX_MAX_LEN: u32 = 1 << 24;
+
+/// Serializer id of `encoding` in the handshake.
+fn serializer_id(encoding: Encoding) -> u8 {
+    match encoding {
+        Encoding::Json => 1,
+        Encoding::MessagePack => 2,
+        Encoding::Cbor => 3,
+    }
+}
+
+/// Encoding of a handshake serializer id.
+fn serializer(id: u8) -> Option<Encoding> {
+    Encoding::ALL
+        .into_iter()
+        .find(|encoding| serializer_id(*encoding) == id)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Handshake Error
+/// Error codes a router answers a rejected handshake with.
+pub enum HandshakeError {
+    /// The requested serializer is not supported.
+    SerializerUnsupported = 1,
+    /// The announced maximum message length is not acceptable.
+    MaxLenUnacceptable = 2,
+    /// The reserved bits were used.
+    ReservedBits = 3,
+    /// The router reached its maximum connection count.
+    MaxConnections = 4,
+}
+
+impl HandshakeError {
+    /// The handshake error for an error code.
+    pub fn from_code(code: u8) -> Option<HandshakeError> {
+        match code {
+            1 => Some(HandshakeError::SerializerUnsupported),
+            2 => Some(HandshakeError::MaxLenUnacceptable),
+            3 => Some(HandshakeError::ReservedBits),
+            4 => Some(HandshakeError::MaxConnections),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Handshake
+/// A parsed raw socket handshake.
+pub enum Handshake {
+    /// The peer speaks `serializer` and accepts messages of up to `max_len` bytes.
+    Accept {
+        /// Serializer used for the connection.
+        serializer: Encoding,
+        /// Maximum message length in bytes the peer is willing to receive.
+        max_len: u32,
+    },
+    /// The router rejected the connection.
+    Reject(HandshakeError),
+}
+
+/// # Encode handshake
+/// Encodes the handshake announcing `serializer` and a maximum message length.
+///
+/// The length is announced as a power of two, so `max_len` is rounded up to the next one and
+/// clamped to `[2^9, 2^24]`.
+/// ## Examples
+/// ```
+/// use wamp_core::rawsocket::encode_handshake;
+/// use wamp_core::transport::Encoding;
+///
+/// assert_eq!(encode_handshake(Encoding::Json, 16 * 1024 * 1024), [0x7F, 0xF1, 0x00, 0x00]);
+/// assert_eq!(encode_handshake(Encoding::MessagePack, 1000), [0x7F, 0x12, 0x00, 0x00]);
+/// ```
+pub fn encode_handshake(serializer: Encoding, max_len: u32) -> [u8; 4] {
+    let max_len = max_len.clamp(MIN_MAX_LEN, MAX_MAX_LEN).next_power_of_two();
+    let length = (max_len.trailing_zeros() - 9) as u8;
+    [MAGIC, length << 4 | serializer_id(serializer), 0, 0]
+}
+
+/// # Encode handshake error
+/// Encodes the reply of a router rejecting a handshake.
+/// ## Examples
+/// ```
+/// use wamp_core::rawsocket::{encode_handshake_error, HandshakeError};
+///
+/// assert_eq!(
+///     encode_handshake_error(HandshakeError::SerializerUnsupported),
+///     [0x7F, 0x10, 0x00, 0x00]
+/// );
+/// ```
+pub fn encode_handshake_error(error: HandshakeError) -> [u8; 4] {
+    [MAGIC, (error as u8) << 4, 0, 0]
+}
+
+/// # Parse handshake
+/// Parses a handshake sent by a client, or the reply of a router.
+/// ## Examples
+/// ```
+/// use wamp_core::rawsocket::{parse_handshake, Handshake, HandshakeError};
+/// use wamp_core::transport::Encoding;
+///
+/// assert_eq!(
+///     parse_handshake(&[0x7F, 0xF1, 0x00, 0x00]).unwrap(),
+///     Handshake::Accept { serializer: Encoding::Json, max_len: 16 * 1024 * 1024 }
+/// );
+/// assert_eq!(
+///     parse_handshake(&[0x7F, 0x40, 0x00, 0x00]).unwrap(),
+///     Handshake::Reject(HandshakeError::MaxConnections)
+/// );
+/// assert!(parse_handshake(&[0x00, 0xF1, 0x00, 0x00]).is_err());
+/// ```
+pub fn parse_handshake(handshake: &[u8; 4]) -> Result<Handshake, Error> {
+    let [magic, octet, reserved @ ..] = *handshake;
+    if magic != MAGIC {
+        return Err(Error::Error(
+            "Raw socket handshake does not start with 0x7F.",
+        ));
+    }
+    if reserved != [0, 0] {
+        return Err(Error::Error("Raw socket handshake uses reserved octets."));
+    }
+    let (high, low) = (octet >> 4, octet & 0x0F);
+    if low == 0 {
+        return match HandshakeError::from_code(high) {
+            Some(error) => Ok(Handshake::Reject(error)),
+            None => Err(Error::Error("Unknown raw socket handshake error code.")),
+        };
+    }
+    match serializer(low) {
+        Some(serializer) => Ok(Handshake::Accept {
+            serializer,
+            max_len: 1 << (9 + high as u32),
+        }),
+        None => Err(Error::Error("Unsupported raw socket serializer.")),
+    }
+}
+
+/// Largest payload a single frame can carry, `2^24 - 1` bytes.
+pub const MAX_FRAME_LEN: usize = (1 << 24) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Frame Type
+/// Type of a raw socket frame.
+pub enum FrameType {
+    /// A serialized WAMP message.
+    Regular = 0,
+    /// A ping, which the peer answers with a pong carrying the same payload.
+    Ping = 1,
+    /// The answer to a ping.
+    Pong = 2,
+}
+
+/// # Encode frame
+/// Frames a serialized WAMP message as a regular frame.
+///
+/// Payloads longer than [`MAX_FRAME_LEN`] fail with [`Error::PayloadSizeExceeded`].
+/// ## Examples
+/// ```
+/// use wamp_core::rawsocket::encode_frame;
+///
+/// let frame = encode_frame(b"[6,{},\"x\"]").unwrap();
+///
+/// assert_eq!(frame, b"\x00\x00\x00\x0a[6,{},\"x\"]");
+/// ```
+pub fn encode_frame(payload: &[u8]) -> Result<Vec<u8>, Error> {
+    encode_frame_with(FrameType::Regular, payload)
+}
+
+/// # Encode frame with
+/// Frames a payload with the given frame type, see [`encode_frame`].
+pub fn encode_frame_with(frame_type: FrameType, payload: &[u8]) -> Result<Vec<u8>, Error> {
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(Error::PayloadSizeExceeded {
+            size: payload.len(),
+            max: MAX_FRAME_LEN,
+        });
+    }
+    let len = (payload.len() as u32).to_be_bytes();
+    let mut frame = Vec::with_capacity(payload.len() + 4);
+    frame.extend_from_slice(&[frame_type as u8, len[1], len[2], len[3]]);
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// What [`decode_frame`] reads: the frame type, the payload and the number of bytes the frame
+/// occupies.
+pub type Frame<'a> = (FrameType, &'a [u8], usize);
+
+/// # Decode frame
+/// Reads the frame at the start of `buffer`, returning its type, its payload and the number of
+/// bytes it occupies, `4 + payload.len()`. Anything after those bytes belongs to the next frame.
+///
+/// Returns `Ok(None)` while `buffer` holds only part of the frame, so streaming callers can
+/// wait for more bytes. An unknown frame type fails right away, and a frame longer than
+/// `max_len`, the maximum message length announced in the handshake, fails with
+/// [`Error::PayloadSizeExceeded`] as soon as its header is complete.
+/// ## Examples
+/// ```
+/// use wamp_core::rawsocket::{decode_frame, encode_frame, FrameType};
+///
+/// let frame = encode_frame(b"[6,{},\"x\"]").unwrap();
+/// assert_eq!(decode_frame(&frame[..6], 512).unwrap(), None);
+///
+/// let (frame_type, payload, consumed) = decode_frame(&frame, 512).unwrap().unwrap();
+/// assert_eq!(frame_type, FrameType::Regular);
+/// assert_eq!(payload, b"[6,{},\"x\"]");
+/// assert_eq!(consumed, frame.len());
+/// ```
+pub fn decode_frame(buffer: &[u8], max_len: u32) -> Result<Option<Frame<'_>>, Error> {
+    let frame_type = match buffer.first() {
+        None => return Ok(None),
+        Some(0) => FrameType::Regular,
+        Some(1) => FrameType::Ping,
+        Some(2) => FrameType::Pong,
+        Some(_) => return Err(Error::Error("Unknown raw socket frame type.")),
+    };
+    let Some((&[_, a, b, c], rest)) = buffer.split_first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes([0, a, b, c]);
+    if len > max_len {
+        return Err(Error::PayloadSizeExceeded {
+            size: len as usize,
+            max: max_len as usize,
+        });
+    }
+    Ok(rest
+        .get(..len as usize)
+        .map(|payload| (frame_type, payload, 4 + payload.len())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshakes() {
+        for (serializer, max_len) in [
+            (Encoding::Json, MIN_MAX_LEN),
+            (Encoding::MessagePack, 1 << 16),
+            (Encoding::Cbor, MAX_MAX_LEN),
+        ] {
+            let bytes = encode_handshake(serializer, max_len);
+            assert_eq!(
+                parse_handshake(&bytes).unwrap(),
+                Handshake::Accept {
+                    serializer,
+                    max_len
+                }
+            );
+        }
+        assert_eq!(encode_handshake(Encoding::Json, 0), [0x7F, 0x01, 0, 0]);
+        assert_eq!(
+            encode_handshake(Encoding::Json, u32::MAX),
+            [0x7F, 0xF1, 0, 0]
+        );
+        assert!(parse_handshake(&[0x7F, 0xF1, 0x00, 0x01]).is_err());
+        assert!(parse_handshake(&[0x7F, 0xF4, 0x00, 0x00]).is_err());
+        assert!(parse_handshake(&[0x7F, 0x50, 0x00, 0x00]).is_err());
+        assert_eq!(
+            parse_handshake(&encode_handshake_error(HandshakeError::ReservedBits)).unwrap(),
+            Handshake::Reject(HandshakeError::ReservedBits)
+        );
+    }
+
+    #[test]
+    fn frames() {
+        let payload = br#"[1,"realm1",{}]"#;
+        let mut buffer = encode_frame(payload).unwrap();
+        assert_eq!(buffer[..4], [0, 0, 0, payload.len() as u8]);
+        buffer.extend(encode_frame_with(FrameType::Ping, b"abc").unwrap());
+        let (frame_type, first, consumed) = decode_frame(&buffer, MIN_MAX_LEN).unwrap().unwrap();
+        assert_eq!((frame_type, first), (FrameType::Regular, &payload[..]));
+        assert_eq!(consumed, 4 + payload.len());
+        let (frame_type, second, _) = decode_frame(&buffer[consumed..], MIN_MAX_LEN)
+            .unwrap()
+            .unwrap();
+        assert_eq!((frame_type, second), (FrameType::Ping, &b"abc"[..]));
+        assert!(decode_frame(&[8, 0, 0, 0], MIN_MAX_LEN).is_err());
+        assert!(decode_frame(&[8], MIN_MAX_LEN).is_err());
+    }
+
+    #[test]
+    fn partial_frame() {
+        let frame = encode_frame(br#"[1,"realm1",{}]"#).unwrap();
+        for end in 0..frame.len() {
+            assert_eq!(
+                decode_frame(&frame[..end], MIN_MAX_LEN).unwrap(),
+                None,
+                "{end}"
+            );
+        }
+        let (_, payload, consumed) = decode_frame(&frame, MIN_MAX_LEN).unwrap().unwrap();
+        assert_eq!((payload, consumed), (&frame[4..], frame.len()));
+    }
+
+    #[test]
+    fn over_length_frame() {
+        let frame = encode_frame(&[b' '; 513]).unwrap();
+        assert!(matches!(
+            decode_frame(&frame, MIN_MAX_LEN),
+            Err(Error::PayloadSizeExceeded {
+                size: 513,
+                max: 512
+            })
+        ));
+        assert!(decode_frame(&frame[..4], MIN_MAX_LEN).is_err());
+        assert_eq!(
+            decode_frame(&[0, 0xFF, 0xFF, 0xFF], MAX_MAX_LEN).unwrap(),
+            None
+        );
+        assert!(matches!(
+            encode_frame(&vec![b' '; MAX_FRAME_LEN + 1]),
+            Err(Error::PayloadSizeExceeded {
+                size,
+                max: MAX_FRAME_LEN
+            }) if size == MAX_FRAME_LEN + 1
+        ));
+    }
+}
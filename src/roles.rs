@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Roles {
     Callee,
     Caller,
@@ -5,4 +6,28 @@ pub enum Roles {
     Subscriber,
     Dealer,
     Broker
-}
\ No newline at end of file
+}
+
+impl Roles {
+    /// Every WAMP role, in declaration order.
+    pub const ALL: [Roles; 6] = [
+        Roles::Callee,
+        Roles::Caller,
+        Roles::Publisher,
+        Roles::Subscriber,
+        Roles::Dealer,
+        Roles::Broker,
+    ];
+
+    /// The lower case name of the role, as used for the keys of `details.roles`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Roles::Callee => "callee",
+            Roles::Caller => "caller",
+            Roles::Publisher => "publisher",
+            Roles::Subscriber => "subscriber",
+            Roles::Dealer => "dealer",
+            Roles::Broker => "broker",
+        }
+    }
+}
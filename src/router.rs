@@ -0,0 +1,2177 @@
+//! # Router
+//! The state of an embedded router, without any I/O.
+//!
+//! A [`Realm`] ties together the subscriptions and registrations of its sessions and the ids it
+//! hands out. Every operation takes the message a session sent and returns the messages to send
+//! back, keyed by the session they are for, so the realm can be driven by any transport, or by
+//! tests calling it directly. The realm is fully deterministic: ids are handed out sequentially
+//! and the `random` invocation policy draws from a fixed seed. A [`Handshake`] opens the session
+//! before it joins the realm.
+//! ## Examples
+//! ```
+//! use wamp_core::router::Realm;
+//! use wamp_core::session::SessionIdentity;
+//! use wamp_core::messages::{Publish, Subscribe};
+//! use serde_json::json;
+//!
+//! let mut realm = Realm::new("realm1");
+//! realm.join(SessionIdentity::anonymous(1));
+//! realm.join(SessionIdentity::anonymous(2));
+//!
+//! let subscribe = Subscribe { request_id: 1, options: json!({}), topic: "com.myapp.topic1".into() };
+//! let subscribed = realm.subscribe(1, subscribe).unwrap();
+//!
+//! let publish = Publish {
+//!     request_id: 1,
+//!     options: json!({}),
+//!     topic: "com.myapp.topic1".into(),
+//!     args: json!(["hello"]),
+//!     kwargs: json!(null)
+//! };
+//! let (published, events) = realm.publish(2, publish).unwrap();
+//!
+//! assert_eq!(published, None);
+//! assert_eq!(events.len(), 1);
+//! assert_eq!(events[0].0, 1);
+//! assert_eq!(events[0].1.subscription, subscribed.subscription);
+//! ```
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    auth::AuthMethod,
+    error::{Error, WampErrorUri},
+    factories::{IdGenerator, IdPolicy},
+    features::NegotiatedFeatures,
+    messages::{
+        Abort, Call, Challenge, Goodbye, Hello, Invocation, Messages, Publish, Published, Register,
+        Registered, SharedEvent, Subscribe, Subscribed, Unregister, Unregistered, Unsubscribe,
+        Unsubscribed, WampError, WampErrorEvent, WampResult, Welcome, Yield,
+    },
+    pending::PendingRequests,
+    pubsub::{
+        plan_shared_fanout, MatchedSubscription, ReceiverIdentity, SessionId, SubscriptionTrie,
+    },
+    rpc::{RetryDecision, RetryPlanner},
+    session::{OutgoingQueue, SessionIdentity},
+    uri::{constants::CLOSE_GOODBYE_AND_OUT, MatchPolicy},
+};
+
+/// A WAMP subscription id.
+pub type SubscriptionId = u64;
+
+/// A WAMP registration id.
+pub type RegistrationId = u64;
+
+/// What [`Realm::publish`] sends: the PUBLISHED when asked for, and the EVENT of every receiver,
+/// all sharing the payload of the PUBLISH.
+pub type Publication = (Option<Published>, Vec<(SessionId, SharedEvent)>);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// # Invocation Policy - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-shared-registration)
+/// The `invoke` option of a REGISTER, choosing the callee of a shared registration.
+pub enum InvocationPolicy {
+    /// The procedure can only have one callee.
+    #[default]
+    Single,
+    /// Callees take turns, in the order they registered.
+    RoundRobin,
+    /// A random callee.
+    Random,
+    /// The callee that registered first.
+    First,
+    /// The callee that registered last.
+    Last,
+}
+
+impl InvocationPolicy {
+    /// The policy as spelled in the `invoke` option.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvocationPolicy::Single => "single",
+            InvocationPolicy::RoundRobin => "roundrobin",
+            InvocationPolicy::Random => "random",
+            InvocationPolicy::First => "first",
+            InvocationPolicy::Last => "last",
+        }
+    }
+
+    /// The callee to invoke for the `turn`th call, `random` being a random number.
+    fn pick(&self, callees: &[SessionId], turn: u64, random: u64) -> Option<SessionId> {
+        let index = match self {
+            InvocationPolicy::Single | InvocationPolicy::First => 0,
+            InvocationPolicy::Last => callees.len().checked_sub(1)?,
+            InvocationPolicy::RoundRobin => (turn % callees.len().max(1) as u64) as usize,
+            InvocationPolicy::Random => (random % callees.len().max(1) as u64) as usize,
+        };
+        callees.get(index).copied()
+    }
+}
+
+/// The value of `key` in `options`, the default when absent, `None` when it is not a known
+/// value.
+fn option<T: Default + for<'de> Deserialize<'de>>(options: &Value, key: &str) -> Option<T> {
+    match options.get(key) {
+        None | Some(Value::Null) => Some(T::default()),
+        Some(value) => T::deserialize(value).ok(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Subscription
+/// A subscription of a [`SubscriptionRegistry`].
+pub struct Subscription {
+    topic: String,
+    policy: MatchPolicy,
+    subscribers: Vec<SessionId>,
+}
+
+impl Subscription {
+    /// The subscribed topic or pattern.
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// The match policy of the subscription.
+    pub fn policy(&self) -> MatchPolicy {
+        self.policy
+    }
+
+    /// The subscribed sessions, in the order they subscribed.
+    pub fn subscribers(&self) -> &[SessionId] {
+        &self.subscribers
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// # Subscription Registry
+/// The subscriptions of a realm, sessions subscribing to the same topic with the same match
+/// policy share one subscription.
+pub struct SubscriptionRegistry {
+    ids: HashMap<(String, MatchPolicy), SubscriptionId>,
+    subscriptions: HashMap<SubscriptionId, Subscription>,
+    trie: SubscriptionTrie,
+}
+
+impl SubscriptionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `session` to the subscription for `topic` with `policy`, creating it with the id
+    /// returned by `new_id` when there is none yet.
+    pub fn subscribe(
+        &mut self,
+        session: SessionId,
+        topic: &str,
+        policy: MatchPolicy,
+        new_id: impl FnOnce() -> SubscriptionId,
+    ) -> SubscriptionId {
+        let key = (topic.to_string(), policy);
+        let id = match self.ids.get(&key) {
+            Some(id) => *id,
+            None => {
+                let id = new_id();
+                self.ids.insert(key, id);
+                self.trie.insert(topic, policy, id);
+                self.subscriptions.insert(
+                    id,
+                    Subscription {
+                        topic: topic.to_string(),
+                        policy,
+                        subscribers: Vec::new(),
+                    },
+                );
+                id
+            }
+        };
+        let subscribers = &mut self.subscriptions.get_mut(&id).unwrap().subscribers;
+        if !subscribers.contains(&session) {
+            subscribers.push(session);
+        }
+        id
+    }
+
+    /// Detach `session` from `subscription`, returning whether it was attached. The
+    /// subscription is deleted with its last subscriber.
+    pub fn unsubscribe(&mut self, session: SessionId, subscription: SubscriptionId) -> bool {
+        let Some(entry) = self.subscriptions.get_mut(&subscription) else {
+            return false;
+        };
+        let len = entry.subscribers.len();
+        entry
+            .subscribers
+            .retain(|subscriber| *subscriber != session);
+        if entry.subscribers.len() == len {
+            return false;
+        }
+        if entry.subscribers.is_empty() {
+            let entry = self.subscriptions.remove(&subscription).unwrap();
+            self.trie.remove(&entry.topic, entry.policy, subscription);
+            self.ids.remove(&(entry.topic, entry.policy));
+        }
+        true
+    }
+
+    /// Detach `session` from every subscription, returning the ids it was detached from in
+    /// ascending order.
+    pub fn remove_session(&mut self, session: SessionId) -> Vec<SubscriptionId> {
+        let mut ids: Vec<SubscriptionId> = self.subscriptions.keys().copied().collect();
+        ids.retain(|id| self.unsubscribe(session, *id));
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Every subscription with its id, in ascending id order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (SubscriptionId, &Subscription)> {
+        sorted(&self.subscriptions)
+    }
+
+    /// Every subscription matching `topic`, ordered by subscription id.
+    pub fn matches(&self, topic: &str) -> Vec<MatchedSubscription> {
+        self.trie
+            .matches(topic)
+            .into_iter()
+            .filter_map(|id| {
+                let entry = self.subscriptions.get(&id)?;
+                Some(MatchedSubscription {
+                    subscription: id,
+                    policy: entry.policy,
+                    subscribers: entry.subscribers.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Number of subscriptions.
+    pub fn len(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /// Whether there is no subscription.
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Registration
+/// A registration of a [`RegistrationRegistry`].
+pub struct Registration {
+    procedure: String,
+    policy: MatchPolicy,
+    invoke: InvocationPolicy,
+    callees: Vec<SessionId>,
+    turn: u64,
+}
+
+impl Registration {
+    /// The registered procedure or pattern.
+    pub fn procedure(&self) -> &str {
+        &self.procedure
+    }
+
+    /// The match policy of the registration.
+    pub fn policy(&self) -> MatchPolicy {
+        self.policy
+    }
+
+    /// The invocation policy of the registration.
+    pub fn invoke(&self) -> InvocationPolicy {
+        self.invoke
+    }
+
+    /// The registered callees, in the order they registered.
+    pub fn callees(&self) -> &[SessionId] {
+        &self.callees
+    }
+}
+
+/// The entries of `map` in ascending key order.
+fn sorted<V>(map: &HashMap<u64, V>) -> std::vec::IntoIter<(u64, &V)> {
+    let mut entries: Vec<(u64, &V)> = map.iter().map(|(id, value)| (*id, value)).collect();
+    entries.sort_unstable_by_key(|(id, _)| *id);
+    entries.into_iter()
+}
+
+#[derive(Debug, Clone, Default)]
+/// # Registration Registry
+/// The registrations of a realm, callees registering the same procedure with the same match
+/// and invocation policy share one registration, unless the policy is
+/// [`InvocationPolicy::Single`].
+pub struct RegistrationRegistry {
+    ids: HashMap<(String, MatchPolicy), RegistrationId>,
+    registrations: HashMap<RegistrationId, Registration>,
+    trie: SubscriptionTrie,
+}
+
+impl RegistrationRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach callee `session` to the registration of `procedure` with `policy`, creating it
+    /// with the id returned by `new_id` when there is none yet.
+    ///
+    /// Fails with [`WampErrorUri::ProcedureAlreadyExists`] when the registration exists and can
+    /// not be shared with `session`.
+    pub fn register(
+        &mut self,
+        session: SessionId,
+        procedure: &str,
+        policy: MatchPolicy,
+        invoke: InvocationPolicy,
+        new_id: impl FnOnce() -> RegistrationId,
+    ) -> Result<RegistrationId, WampErrorUri> {
+        let key = (procedure.to_string(), policy);
+        if let Some(id) = self.ids.get(&key) {
+            let entry = self.registrations.get_mut(id).unwrap();
+            if invoke == InvocationPolicy::Single
+                || entry.invoke != invoke
+                || entry.callees.contains(&session)
+            {
+                return Err(WampErrorUri::ProcedureAlreadyExists);
+            }
+            entry.callees.push(session);
+            return Ok(*id);
+        }
+        let id = new_id();
+        self.ids.insert(key, id);
+        self.trie.insert(procedure, policy, id);
+        self.registrations.insert(
+            id,
+            Registration {
+                procedure: procedure.to_string(),
+                policy,
+                invoke,
+                callees: vec![session],
+                turn: 0,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Detach callee `session` from `registration`, returning whether it was attached. The
+    /// registration is deleted with its last callee.
+    pub fn unregister(&mut self, session: SessionId, registration: RegistrationId) -> bool {
+        let Some(entry) = self.registrations.get_mut(&registration) else {
+            return false;
+        };
+        let len = entry.callees.len();
+        entry.callees.retain(|callee| *callee != session);
+        if entry.callees.len() == len {
+            return false;
+        }
+        if entry.callees.is_empty() {
+            let entry = self.registrations.remove(&registration).unwrap();
+            self.trie
+                .remove(&entry.procedure, entry.policy, registration);
+            self.ids.remove(&(entry.procedure, entry.policy));
+        }
+        true
+    }
+
+    /// Detach callee `session` from every registration, returning the ids it was detached from
+    /// in ascending order.
+    pub fn remove_session(&mut self, session: SessionId) -> Vec<RegistrationId> {
+        let mut ids: Vec<RegistrationId> = self.registrations.keys().copied().collect();
+        ids.retain(|id| self.unregister(session, *id));
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Every registration with its id, in ascending id order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (RegistrationId, &Registration)> {
+        sorted(&self.registrations)
+    }
+
+    /// # Lookup
+    /// The registration a call to `procedure` is routed to.
+    ///
+    /// An exact registration wins over prefix registrations, of which the longest wins, which
+    /// win over wildcard registrations, of which the one with the most non empty components
+    /// wins. Remaining ties go to the oldest registration.
+    pub fn lookup(&self, procedure: &str) -> Option<RegistrationId> {
+        self.trie
+            .matches(procedure)
+            .into_iter()
+            .filter_map(|id| Some((id, self.registrations.get(&id)?)))
+            .min_by_key(|(id, entry)| {
+                let rank = match entry.policy {
+                    MatchPolicy::Exact => (0, 0),
+                    MatchPolicy::Prefix => (1, usize::MAX - entry.procedure.len()),
+                    MatchPolicy::Wildcard => {
+                        let fixed = entry.procedure.split('.').filter(|c| !c.is_empty());
+                        (2, usize::MAX - fixed.count())
+                    }
+                };
+                (rank, *id)
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// The callee to invoke for the next call of `registration`, applying its
+    /// [`InvocationPolicy`]. `random` is used by [`InvocationPolicy::Random`].
+    pub fn next_callee(&mut self, registration: RegistrationId, random: u64) -> Option<SessionId> {
+        self.next_callee_excluding(registration, random, &[])
+    }
+
+    /// Same as [`next_callee`](RegistrationRegistry::next_callee), but the callees in
+    /// `excluded` are not picked. `None` when every callee is excluded.
+    pub fn next_callee_excluding(
+        &mut self,
+        registration: RegistrationId,
+        random: u64,
+        excluded: &[SessionId],
+    ) -> Option<SessionId> {
+        let entry = self.registrations.get_mut(&registration)?;
+        let callees: Vec<SessionId> = entry
+            .callees
+            .iter()
+            .copied()
+            .filter(|callee| !excluded.contains(callee))
+            .collect();
+        let callee = entry.invoke.pick(&callees, entry.turn, random);
+        entry.turn = entry.turn.wrapping_add(1);
+        callee
+    }
+
+    /// Number of registrations.
+    pub fn len(&self) -> usize {
+        self.registrations.len()
+    }
+
+    /// Whether there is no registration.
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+}
+
+/// The next id of `ids`, which never runs out as no id is ever pending.
+fn next_id(ids: &mut IdGenerator) -> u64 {
+    ids.next_checked(&PendingRequests::new())
+        .expect("an empty pending set never exhausts the id space")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PendingCall {
+    caller: SessionId,
+    request_id: u64,
+    callee: SessionId,
+    registration: RegistrationId,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// # Detach Summary
+/// What [`Realm::detach_session`] removed, for emitting meta events and failing the calls left
+/// without a callee.
+pub struct DetachSummary {
+    /// The subscriptions the session was detached from, in ascending order.
+    pub removed_subscriptions: Vec<SubscriptionId>,
+    /// The registrations the session was detached from, in ascending order.
+    pub removed_registrations: Vec<RegistrationId>,
+    /// The request ids of the INVOCATIONs the session was the callee of, in ascending order.
+    pub orphaned_calls: Vec<u64>,
+    orphans: Vec<PendingCall>,
+}
+
+impl DetachSummary {
+    /// # Caller errors
+    /// The ERROR failing every orphaned call, for the caller waiting on it.
+    ///
+    /// `uri` is usually [`WampErrorUri::Canceled`] or [`WampErrorUri::Unavailable`].
+    pub fn caller_errors(&self, uri: WampErrorUri) -> Vec<(SessionId, WampError)> {
+        self.orphans
+            .iter()
+            .map(|call| {
+                let error = WampError {
+                    event: WampErrorEvent::Call,
+                    request_id: call.request_id,
+                    details: json!({}),
+                    error: uri.as_str().to_string(),
+                    args: Value::Null,
+                    kwargs: Value::Null,
+                };
+                (call.caller, error)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Action
+/// What a session asks the realm to do with a URI, for [`Authorizer::authorize`].
+pub enum Action {
+    /// SUBSCRIBE to a topic.
+    Subscribe,
+    /// PUBLISH to a topic.
+    Publish,
+    /// REGISTER a procedure.
+    Register,
+    /// CALL a procedure.
+    Call,
+}
+
+impl Action {
+    /// The action in lower case, as router configurations usually spell it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Subscribe => "subscribe",
+            Action::Publish => "publish",
+            Action::Register => "register",
+            Action::Call => "call",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Decision
+/// The outcome of [`Authorizer::authorize`].
+///
+/// Both denials reply with a `wamp.error.not_authorized` ERROR, only
+/// [`DenyWithDisclosure`](Decision::DenyWithDisclosure) tells the session why.
+pub enum Decision {
+    /// The action is allowed.
+    Allow,
+    /// The action is denied without telling the session why.
+    Deny {
+        /// Why the action is denied, for the router's own logs.
+        reason: String,
+    },
+    /// The action is denied, and the session is told why.
+    DenyWithDisclosure {
+        /// Why the action is denied, sent as the only argument of the ERROR.
+        reason: String,
+    },
+}
+
+impl Decision {
+    /// Whether the action is allowed.
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Decision::Allow)
+    }
+
+    /// The `wamp.error.not_authorized` ERROR `error` builds for the denied request, `None` when
+    /// allowed.
+    fn deny(self, error: impl FnOnce(&str) -> WampError) -> Option<WampError> {
+        let mut error = match &self {
+            Decision::Allow => return None,
+            _ => error(WampErrorUri::NotAuthorized.as_str()),
+        };
+        if let Decision::DenyWithDisclosure { reason } = self {
+            error.args = json!([reason]);
+        }
+        Some(error)
+    }
+}
+
+/// # Authorizer
+/// Decides whether a session may subscribe, publish, register or call a URI.
+///
+/// A [`Realm`] asks its authorizer before every such action, and replies to denied ones with a
+/// `wamp.error.not_authorized` ERROR. Sessions that did not [`join`](Realm::join) the realm are
+/// denied without asking.
+/// ## Examples
+/// ```
+/// use wamp_core::router::{Action, Authorizer, Decision, Realm};
+/// use wamp_core::session::SessionIdentity;
+/// use wamp_core::messages::Subscribe;
+/// use serde_json::json;
+///
+/// #[derive(Debug)]
+/// struct ReadOnly;
+///
+/// impl Authorizer for ReadOnly {
+///     fn authorize(&self, _: &SessionIdentity, action: Action, _: &str) -> Decision {
+///         match action {
+///             Action::Subscribe | Action::Call => Decision::Allow,
+///             _ => Decision::Deny { reason: "read only".to_string() },
+///         }
+///     }
+/// }
+///
+/// let mut realm = Realm::new("realm1").with_authorizer(ReadOnly);
+/// realm.join(SessionIdentity::anonymous(1));
+///
+/// let subscribe = Subscribe { request_id: 1, options: json!({}), topic: "com.myapp.topic1".into() };
+/// assert!(realm.subscribe(1, subscribe.clone()).is_ok());
+///
+/// let error = realm.subscribe(2, subscribe).unwrap_err();
+/// assert_eq!(error.error, "wamp.error.not_authorized");
+/// ```
+pub trait Authorizer: fmt::Debug + Send + Sync {
+    /// Decide whether `session` may perform `action` on `uri`.
+    fn authorize(&self, session: &SessionIdentity, action: Action, uri: &str) -> Decision;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// # Permit All
+/// The [`Authorizer`] of a new [`Realm`], allowing every action of a joined session.
+pub struct PermitAll;
+
+impl Authorizer for PermitAll {
+    fn authorize(&self, _: &SessionIdentity, _: Action, _: &str) -> Decision {
+        Decision::Allow
+    }
+}
+
+/// # Trust Level Assigner
+/// Assigns the trust level of publications and calls, the advanced profile
+/// `publication_trustlevels` and `call_trustlevels` features.
+///
+/// A [`Realm`] with an assigner stamps the level it returns for the publishing or calling
+/// session as `details.trustlevel` of every EVENT and INVOCATION it routes, and leaves the key
+/// out when it returns `None`.
+/// ## Examples
+/// ```
+/// use wamp_core::router::{Action, Realm, TrustLevelAssigner};
+/// use wamp_core::session::SessionIdentity;
+/// use wamp_core::messages::{Call, Register};
+/// use serde_json::{json, Value};
+///
+/// #[derive(Debug)]
+/// struct ByRole;
+///
+/// impl TrustLevelAssigner for ByRole {
+///     fn trust_level(&self, session: &SessionIdentity, _: Action, _: &str) -> Option<u64> {
+///         (session.authrole == "backend").then_some(1)
+///     }
+/// }
+///
+/// let mut realm = Realm::new("realm1").with_trust_level_assigner(ByRole);
+/// realm.join(SessionIdentity { authrole: "backend".to_string(), ..SessionIdentity::anonymous(1) });
+/// realm.join(SessionIdentity::anonymous(2));
+///
+/// let register = Register { request_id: 1, options: json!({}), procedure: "com.myapp.add".into() };
+/// realm.register(1, register).unwrap();
+///
+/// let call = Call {
+///     request_id: 2,
+///     options: json!({}),
+///     procedure: "com.myapp.add".into(),
+///     args: json!([1, 2]),
+///     kwargs: Value::Null
+/// };
+/// let (_, invocation) = realm.call(1, call.clone()).unwrap();
+/// assert_eq!(invocation.trust_level(), Some(1));
+///
+/// let (_, invocation) = realm.call(2, call).unwrap();
+/// assert_eq!(invocation.trust_level(), None);
+/// ```
+pub trait TrustLevelAssigner: fmt::Debug + Send + Sync {
+    /// The trust level of `action` by `session` on `uri`, `None` to assign none.
+    fn trust_level(&self, session: &SessionIdentity, action: Action, uri: &str) -> Option<u64>;
+}
+
+#[derive(Debug, Clone)]
+/// # Realm
+/// The routing state of a realm, see the [module documentation](self).
+pub struct Realm {
+    name: String,
+    sessions: HashMap<SessionId, SessionIdentity>,
+    subscriptions: SubscriptionRegistry,
+    registrations: RegistrationRegistry,
+    ids: IdGenerator,
+    seed: u64,
+    calls: HashMap<u64, PendingCall>,
+    retries: HashMap<u64, RetryPlanner>,
+    authorizer: Arc<dyn Authorizer>,
+    trust_levels: Option<Arc<dyn TrustLevelAssigner>>,
+}
+
+impl Realm {
+    /// Create an empty realm.
+    pub fn new<T: ToString>(name: T) -> Self {
+        Realm {
+            name: name.to_string(),
+            sessions: HashMap::new(),
+            subscriptions: SubscriptionRegistry::new(),
+            registrations: RegistrationRegistry::new(),
+            ids: IdGenerator::new().policy(IdPolicy::Random),
+            seed: 0,
+            calls: HashMap::new(),
+            retries: HashMap::new(),
+            authorizer: Arc::new(PermitAll),
+            trust_levels: None,
+        }
+    }
+
+    /// Decide the actions of the sessions with `authorizer` instead of [`PermitAll`].
+    pub fn with_authorizer<A: Authorizer + 'static>(mut self, authorizer: A) -> Self {
+        self.authorizer = Arc::new(authorizer);
+        self
+    }
+
+    /// Stamp the trust levels `assigner` returns on routed events and invocations.
+    pub fn with_trust_level_assigner<T: TrustLevelAssigner + 'static>(
+        mut self,
+        assigner: T,
+    ) -> Self {
+        self.trust_levels = Some(Arc::new(assigner));
+        self
+    }
+
+    /// The name of the realm.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The joined sessions, in ascending session id order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = &SessionIdentity> {
+        sorted(&self.sessions).map(|(_, identity)| identity)
+    }
+
+    /// The subscriptions of the realm.
+    pub fn subscriptions(&self) -> &SubscriptionRegistry {
+        &self.subscriptions
+    }
+
+    /// The registrations of the realm.
+    pub fn registrations(&self) -> &RegistrationRegistry {
+        &self.registrations
+    }
+
+    fn next_id(&mut self) -> u64 {
+        next_id(&mut self.ids)
+    }
+
+    /// Ask the authorizer about `action`, sessions that did not join are denied.
+    fn authorize(&self, session: SessionId, action: Action, uri: &str) -> Decision {
+        match self.sessions.get(&session) {
+            Some(identity) => self.authorizer.authorize(identity, action, uri),
+            None => Decision::Deny {
+                reason: format!("session {session} did not join realm {}", self.name),
+            },
+        }
+    }
+
+    /// The trust level of `action`, `None` without an assigner or for sessions that did not join.
+    fn trust_level(&self, session: SessionId, action: Action, uri: &str) -> Option<u64> {
+        let assigner = self.trust_levels.as_ref()?;
+        assigner.trust_level(self.sessions.get(&session)?, action, uri)
+    }
+
+    /// The next number of a splitmix64 sequence, for [`InvocationPolicy::Random`].
+    fn next_random(&mut self) -> u64 {
+        self.seed = self.seed.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Add a session to the realm, only joined sessions are authorized and receive events.
+    pub fn join(&mut self, identity: SessionIdentity) {
+        self.sessions.insert(identity.session, identity);
+    }
+
+    /// # Detach session
+    /// Remove a session that said GOODBYE or whose transport dropped, with its subscriptions,
+    /// registrations and the calls it is part of.
+    ///
+    /// Calls the session made are dropped. Calls it was invoked for are reported as orphaned,
+    /// their callers are still waiting and should be failed with
+    /// [`DetachSummary::caller_errors`].
+    pub fn detach_session(&mut self, session: SessionId) -> DetachSummary {
+        self.sessions.remove(&session);
+        let removed_subscriptions = self.subscriptions.remove_session(session);
+        let removed_registrations = self.registrations.remove_session(session);
+
+        let mut orphaned: Vec<(u64, PendingCall)> = Vec::new();
+        self.calls.retain(|id, call| {
+            if call.callee == session && call.caller != session {
+                orphaned.push((*id, *call));
+            }
+            call.caller != session && call.callee != session
+        });
+        let calls = &self.calls;
+        self.retries.retain(|id, _| calls.contains_key(id));
+        orphaned.sort_unstable_by_key(|(id, _)| *id);
+        DetachSummary {
+            removed_subscriptions,
+            removed_registrations,
+            orphaned_calls: orphaned.iter().map(|(id, _)| *id).collect(),
+            orphans: orphaned.into_iter().map(|(_, call)| call).collect(),
+        }
+    }
+
+    /// # Subscribe
+    /// Subscribe `session` to a topic.
+    ///
+    /// Fails with [`WampErrorUri::NotAuthorized`] when the [`Authorizer`] denies it, and with
+    /// [`WampErrorUri::InvalidArgument`] when the `match` option is unknown.
+    #[allow(clippy::result_large_err)]
+    pub fn subscribe(
+        &mut self,
+        session: SessionId,
+        subscribe: Subscribe,
+    ) -> Result<Subscribed, WampError> {
+        let decision = self.authorize(session, Action::Subscribe, &subscribe.topic);
+        if let Some(denied) = decision.deny(|uri| WampError::from_subscribe(&subscribe, uri)) {
+            return Err(denied);
+        }
+        let Some(policy) = option(&subscribe.options, "match") else {
+            return Err(WampError::from_subscribe(
+                &subscribe,
+                WampErrorUri::InvalidArgument.as_str(),
+            ));
+        };
+        let ids = &mut self.ids;
+        let subscription = self
+            .subscriptions
+            .subscribe(session, &subscribe.topic, policy, || next_id(ids));
+        Ok(Subscribed {
+            request_id: subscribe.request_id,
+            subscription,
+        })
+    }
+
+    /// # Unsubscribe
+    /// Unsubscribe `session` from a subscription.
+    ///
+    /// Fails with [`WampErrorUri::NoSuchSubscription`] when `session` is not subscribed.
+    #[allow(clippy::result_large_err)]
+    pub fn unsubscribe(
+        &mut self,
+        session: SessionId,
+        unsubscribe: Unsubscribe,
+    ) -> Result<Unsubscribed, WampError> {
+        if !self
+            .subscriptions
+            .unsubscribe(session, unsubscribe.subscription)
+        {
+            return Err(WampError::from_unsubscribe(
+                &unsubscribe,
+                WampErrorUri::NoSuchSubscription.as_str(),
+            ));
+        }
+        Ok(Unsubscribed {
+            request_id: unsubscribe.request_id,
+            details: Value::Null,
+        })
+    }
+
+    /// # Publish
+    /// Publish an event from `session`.
+    ///
+    /// Returns the PUBLISHED acknowledging the publication when it asked for one, and the EVENT
+    /// for every receiver, ordered by receiver and subscription id. The publisher does not
+    /// receive its own event unless the publication sets `exclude_me` to `false`. The events
+    /// carry the trust level the [`TrustLevelAssigner`] assigns as `details.trustlevel`.
+    ///
+    /// A publication the [`Authorizer`] denies is dropped. It fails with
+    /// [`WampErrorUri::NotAuthorized`] when it asked for an acknowledgement, and returns neither
+    /// PUBLISHED nor events otherwise.
+    #[allow(clippy::result_large_err)]
+    pub fn publish(
+        &mut self,
+        session: SessionId,
+        publish: Publish,
+    ) -> Result<Publication, WampError> {
+        let decision = self.authorize(session, Action::Publish, &publish.topic);
+        if let Some(denied) = decision.deny(|uri| WampError::from_publish(&publish, uri)) {
+            return match publish.expects_ack() {
+                true => Err(denied),
+                false => Ok((None, Vec::new())),
+            };
+        }
+        let publication = self.next_id();
+        let matches = self.subscriptions.matches(&publish.topic);
+        let identities = |session| self.sessions.get(&session).map(ReceiverIdentity::from);
+        let mut events = plan_shared_fanout(&publish, publication, &matches, &identities);
+        if publish.options["exclude_me"].as_bool() != Some(false) {
+            events.retain(|(receiver, _)| *receiver != session);
+        }
+        if let Some(level) = self.trust_level(session, Action::Publish, &publish.topic) {
+            events = events
+                .into_iter()
+                .map(|(receiver, event)| (receiver, event.with_trust_level(level)))
+                .collect();
+        }
+        events.sort_by_key(|(receiver, event)| (*receiver, event.subscription));
+        let published = publish.expects_ack().then_some(Published {
+            request_id: publish.request_id,
+            publication,
+        });
+        Ok((published, events))
+    }
+
+    /// # Register
+    /// Register `session` as a callee of a procedure.
+    ///
+    /// Fails with [`WampErrorUri::NotAuthorized`] when the [`Authorizer`] denies it, with
+    /// [`WampErrorUri::InvalidArgument`] when the `match` or `invoke` option is unknown, and
+    /// with [`WampErrorUri::ProcedureAlreadyExists`] when the procedure is registered and can
+    /// not be shared.
+    #[allow(clippy::result_large_err)]
+    pub fn register(
+        &mut self,
+        session: SessionId,
+        register: Register,
+    ) -> Result<Registered, WampError> {
+        let decision = self.authorize(session, Action::Register, &register.procedure);
+        if let Some(denied) = decision.deny(|uri| WampError::from_register(&register, uri)) {
+            return Err(denied);
+        }
+        let (Some(policy), Some(invoke)) = (
+            option(&register.options, "match"),
+            option(&register.options, "invoke"),
+        ) else {
+            return Err(WampError::from_register(
+                &register,
+                WampErrorUri::InvalidArgument.as_str(),
+            ));
+        };
+        let ids = &mut self.ids;
+        let registration = self
+            .registrations
+            .register(session, &register.procedure, policy, invoke, || {
+                next_id(ids)
+            })
+            .map_err(|uri| WampError::from_register(&register, uri.as_str()))?;
+        Ok(Registered {
+            request_id: register.request_id,
+            registration,
+        })
+    }
+
+    /// # Unregister
+    /// Unregister callee `session` from a registration.
+    ///
+    /// Fails with [`WampErrorUri::NoSuchRegistration`] when `session` is not registered.
+    #[allow(clippy::result_large_err)]
+    pub fn unregister(
+        &mut self,
+        session: SessionId,
+        unregister: Unregister,
+    ) -> Result<Unregistered, WampError> {
+        if !self
+            .registrations
+            .unregister(session, unregister.registration)
+        {
+            return Err(WampError::from_unregister(
+                &unregister,
+                WampErrorUri::NoSuchRegistration.as_str(),
+            ));
+        }
+        Ok(Unregistered {
+            request_id: unregister.request_id,
+        })
+    }
+
+    /// # Call
+    /// Route a call from `session` to a callee.
+    ///
+    /// Returns the callee and the INVOCATION to send it. Calls of pattern based registrations
+    /// carry the called procedure as `details.procedure`, and the [`TrustLevelAssigner`] sets
+    /// `details.trustlevel`. Fails with [`WampErrorUri::NotAuthorized`] when the
+    /// [`Authorizer`] denies it, and with [`WampErrorUri::NoSuchProcedure`] when no
+    /// registration matches.
+    #[allow(clippy::result_large_err)]
+    pub fn call(
+        &mut self,
+        session: SessionId,
+        call: Call,
+    ) -> Result<(SessionId, Invocation), WampError> {
+        let decision = self.authorize(session, Action::Call, &call.procedure);
+        if let Some(denied) = decision.deny(|uri| WampError::from_call(&call, uri)) {
+            return Err(denied);
+        }
+        let no_such_procedure =
+            || WampError::from_call(&call, WampErrorUri::NoSuchProcedure.as_str());
+        let registration = self
+            .registrations
+            .lookup(&call.procedure)
+            .ok_or_else(no_such_procedure)?;
+        let random = self.next_random();
+        let callee = self
+            .registrations
+            .next_callee(registration, random)
+            .ok_or_else(no_such_procedure)?;
+        let entry = &self.registrations.registrations[&registration];
+        let exact = entry.policy == MatchPolicy::Exact;
+        let retry = (entry.invoke != InvocationPolicy::Single).then(|| call.clone());
+
+        let request_id = self.next_id();
+        self.calls.insert(
+            request_id,
+            PendingCall {
+                caller: session,
+                request_id: call.request_id,
+                callee,
+                registration,
+            },
+        );
+        let details = if exact {
+            json!({})
+        } else {
+            json!({ "procedure": call.procedure })
+        };
+        let mut invocation = Invocation {
+            request_id,
+            registration,
+            details,
+            args: call.args,
+            kwargs: call.kwargs,
+        };
+        if let Some(level) = self.trust_level(session, Action::Call, &call.procedure) {
+            invocation = invocation.with_trust_level(level);
+        }
+        if let Some(call) = retry {
+            let planner = RetryPlanner::new(call, invocation.details.clone(), callee);
+            self.retries.insert(request_id, planner);
+        }
+        Ok((callee, invocation))
+    }
+
+    /// # Complete
+    /// Route the YIELD of callee `session` back to the caller, as a RESULT.
+    ///
+    /// A progressive YIELD keeps the call pending. Returns `None` when `session` has no such
+    /// invocation pending.
+    pub fn complete(
+        &mut self,
+        session: SessionId,
+        yielded: Yield,
+    ) -> Option<(SessionId, WampResult)> {
+        let call = *self.calls.get(&yielded.request_id)?;
+        if call.callee != session {
+            return None;
+        }
+        let progress = yielded.options["progress"].as_bool() == Some(true);
+        if !progress {
+            self.calls.remove(&yielded.request_id);
+            self.retries.remove(&yielded.request_id);
+        }
+        let details = if progress {
+            json!({ "progress": true })
+        } else {
+            json!({})
+        };
+        Some((
+            call.caller,
+            WampResult {
+                request_id: call.request_id,
+                details,
+                args: yielded.args,
+                kwargs: yielded.kwargs,
+            },
+        ))
+    }
+
+    /// # Fail
+    /// Route the ERROR callee `session` replied to an INVOCATION with back to the caller.
+    ///
+    /// A callee of a shared registration replying `wamp.error.unavailable` does not fail the
+    /// call, it is re-routed by a [`RetryPlanner`]: the INVOCATION is sent to the next callee
+    /// it was not tried on, or the caller gets `wamp.error.no_available_callee` once every
+    /// callee was tried. Returns the session to send the resulting message to, `None` when
+    /// `error` does not reply to an invocation `session` has pending.
+    pub fn fail(&mut self, session: SessionId, error: WampError) -> Option<(SessionId, Messages)> {
+        if error.event != WampErrorEvent::Invocation {
+            return None;
+        }
+        let call = *self.calls.get(&error.request_id)?;
+        if call.callee != session {
+            return None;
+        }
+        self.calls.remove(&error.request_id);
+        let planner = self.retries.remove(&error.request_id);
+        let unavailable = error.error == WampErrorUri::Unavailable.as_str();
+        if let Some(mut planner) = planner.filter(|_| unavailable) {
+            let random = self.next_random();
+            let ids = &mut self.ids;
+            let decision = planner.plan(&mut self.registrations, call.registration, random, || {
+                next_id(ids)
+            });
+            return Some(match decision {
+                RetryDecision::Retry(callee, invocation) => {
+                    self.calls
+                        .insert(invocation.request_id, PendingCall { callee, ..call });
+                    self.retries.insert(invocation.request_id, planner);
+                    (callee, Messages::from(invocation))
+                }
+                RetryDecision::GiveUp(error) => (call.caller, Messages::from(error)),
+            });
+        }
+        let error = WampError {
+            event: WampErrorEvent::Call,
+            request_id: call.request_id,
+            ..error
+        };
+        Some((call.caller, Messages::from(error)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Auth Outcome
+/// What the authenticator of the router decided about a session, see [`Handshake::resolve`].
+pub enum AuthOutcome {
+    /// Ask the client to prove its identity, sent as a CHALLENGE.
+    Challenge {
+        /// The method of the challenge, one of the methods the client offered.
+        authmethod: AuthMethod,
+        /// The challenge, sent as the CHALLENGE details.
+        extra: Value,
+    },
+    /// The session is authenticated, sent as a WELCOME.
+    Welcome(SessionIdentity),
+    /// The credentials were rejected, see [`Abort::auth_denied`].
+    Denied(Option<String>),
+    /// The router could not authenticate the session, see [`Abort::auth_failed`].
+    Failed(Option<String>),
+    /// None of the methods the client offered is accepted, see
+    /// [`Abort::no_matching_auth_method`].
+    NoMatchingMethod,
+    /// The session is authenticated but may not join the realm, see
+    /// [`Abort::authorization_denied`].
+    Unauthorized(Option<String>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// # Handshake State
+/// Where the opening handshake of a session stands, see [`Handshake`].
+pub enum HandshakeState {
+    /// Waiting for the HELLO of the client.
+    #[default]
+    AwaitingHello,
+    /// Waiting for the authenticator, see [`Handshake::resolve`].
+    Authenticating,
+    /// CHALLENGE sent, waiting for the AUTHENTICATE of the client.
+    Challenged,
+    /// WELCOME sent, the session is open.
+    Established,
+    /// ABORT sent, the session is over.
+    Aborted,
+}
+
+#[derive(Debug, Clone, Default)]
+/// # Handshake
+/// The router side of the opening handshake of a session.
+///
+/// Feed it the messages of the client with [`receive`](Handshake::receive), and the decision
+/// of the authenticator with [`resolve`](Handshake::resolve), which turns it into the
+/// CHALLENGE, WELCOME or ABORT to send.
+///
+/// The messages to send to the session are queued in an [`OutgoingQueue`], taken with
+/// [`drain_outgoing`](Handshake::drain_outgoing): the messages of the handshake, the ABORT of
+/// a protocol violation ahead of everything else, and the GOODBYE answering the one of the
+/// client. Once established, queue the other messages for the session with
+/// [`push`](Handshake::push), so the ABORT or GOODBYE closing it is ordered with them.
+/// ## Examples
+/// ```
+/// use wamp_core::router::{AuthOutcome, Handshake, HandshakeState};
+/// use wamp_core::auth::{hello_with_methods, AuthMethod};
+/// use wamp_core::messages::Messages;
+/// use serde_json::json;
+///
+/// let mut handshake = Handshake::new(json!({ "broker": {}, "dealer": {} }));
+/// let hello = hello_with_methods("realm1", &[AuthMethod::Ticket], Some("joe"));
+/// handshake.receive(&Messages::from(hello)).unwrap();
+///
+/// let abort = handshake.resolve(AuthOutcome::NoMatchingMethod).unwrap();
+///
+/// assert_eq!(handshake.state(), HandshakeState::Aborted);
+/// let Messages::Abort(abort) = abort else { panic!() };
+/// assert_eq!(abort.reason, "wamp.error.no_matching_auth_method");
+/// assert_eq!(abort.details, json!({ "authmethods": ["ticket"] }));
+/// assert_eq!(handshake.drain_outgoing(), vec![Messages::from(abort)]);
+/// ```
+pub struct Handshake {
+    state: HandshakeState,
+    roles: Value,
+    offered: Vec<AuthMethod>,
+    hello: Option<Hello>,
+    negotiated: NegotiatedFeatures,
+    outgoing: OutgoingQueue,
+}
+
+impl Handshake {
+    /// Start a handshake, `roles` are the router roles announced in the WELCOME.
+    pub fn new(roles: Value) -> Self {
+        Handshake {
+            roles,
+            ..Default::default()
+        }
+    }
+
+    /// Where the handshake stands.
+    pub fn state(&self) -> HandshakeState {
+        self.state
+    }
+
+    /// The authentication methods the client offered in its HELLO, in order of preference.
+    pub fn offered(&self) -> &[AuthMethod] {
+        &self.offered
+    }
+
+    /// The features negotiated so far, the role features being known once the WELCOME is
+    /// sent.
+    pub fn negotiated(&self) -> &NegotiatedFeatures {
+        &self.negotiated
+    }
+
+    /// # Check
+    /// Check a message of the session against the [negotiated features](Self::negotiated),
+    /// before forwarding it.
+    ///
+    /// A message requiring a feature that was not negotiated, such as a CANCEL without
+    /// `call_canceling`, is a protocol violation and fails with the ABORT, which is queued
+    /// ahead of every other message. A GOODBYE of the client queues the GOODBYE answering it,
+    /// unless the session is already closing.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::router::{AuthOutcome, Handshake, HandshakeState};
+    /// use wamp_core::session::SessionIdentity;
+    /// use wamp_core::messages::{Cancel, Hello, Messages};
+    /// use serde_json::json;
+    ///
+    /// let mut handshake = Handshake::new(json!({ "dealer": { "features": { "call_canceling": true } } }));
+    /// let hello = Hello { realm: "realm1".into(), details: json!({ "roles": { "caller": {} } }) };
+    /// handshake.receive(&Messages::from(hello)).unwrap();
+    /// handshake.resolve(AuthOutcome::Welcome(SessionIdentity::anonymous(1))).unwrap();
+    ///
+    /// let cancel = Messages::from(Cancel { request_id: 7, options: json!({}) });
+    /// let abort = handshake.check(&cancel).unwrap_err();
+    ///
+    /// assert_eq!(abort.reason, "wamp.error.protocol_violation");
+    /// assert_eq!(handshake.state(), HandshakeState::Aborted);
+    /// ```
+    pub fn check(&mut self, message: &Messages) -> Result<(), Abort> {
+        if let Err(abort) = self.negotiated.check(message) {
+            self.state = HandshakeState::Aborted;
+            return Err(self.queue_abort(abort));
+        }
+        if let Messages::Goodbye(_) = message {
+            if !self.outgoing.is_closing() {
+                self.outgoing.close_after(Goodbye {
+                    details: json!({}),
+                    reason: CLOSE_GOODBYE_AND_OUT.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// # Receive
+    /// Take a message of the client: a HELLO opening the handshake, or an AUTHENTICATE
+    /// answering the CHALLENGE. Both leave the handshake waiting for the authenticator.
+    ///
+    /// Any other message, or one out of turn, is a protocol violation and fails with the
+    /// ABORT, which is queued like the one of [`check`](Handshake::check).
+    pub fn receive(&mut self, message: &Messages) -> Result<(), Abort> {
+        match (self.state, message) {
+            (HandshakeState::AwaitingHello, Messages::Hello(hello)) => {
+                self.offered = serde_json::from_value(hello.details["authmethods"].clone())
+                    .unwrap_or_default();
+                self.negotiated = NegotiatedFeatures::from_hello(hello);
+                self.hello = Some(hello.clone());
+            }
+            (HandshakeState::Challenged, Messages::Authenticate(_)) => {}
+            _ => {
+                self.state = HandshakeState::Aborted;
+                return Err(self.queue_abort(Abort::protocol_violation(Some(
+                    "Unexpected message during the handshake.",
+                ))));
+            }
+        }
+        self.state = HandshakeState::Authenticating;
+        Ok(())
+    }
+
+    /// # Resolve
+    /// Turn the decision of the authenticator into the message to send: a CHALLENGE, the
+    /// WELCOME of the session, or the ABORT of the failure. The message is queued and returned.
+    ///
+    /// Fails with [`Error::Error`] when the handshake is not waiting for the authenticator.
+    pub fn resolve(&mut self, outcome: AuthOutcome) -> Result<Messages, Error> {
+        if self.state != HandshakeState::Authenticating {
+            return Err(Error::Error(
+                "The handshake is not waiting for an authenticator.",
+            ));
+        }
+        let (state, message) = match outcome {
+            AuthOutcome::Challenge { authmethod, extra } => (
+                HandshakeState::Challenged,
+                Messages::from(Challenge {
+                    authmethod: authmethod.as_str().to_string(),
+                    details: extra,
+                }),
+            ),
+            AuthOutcome::Welcome(identity) => {
+                let welcome = Welcome::for_identity(&identity, &self.roles);
+                if let Some(hello) = &self.hello {
+                    self.negotiated = NegotiatedFeatures::new(hello, &welcome);
+                }
+                (HandshakeState::Established, Messages::from(welcome))
+            }
+            AuthOutcome::Denied(message) => abort(Abort::auth_denied(message.as_deref())),
+            AuthOutcome::Failed(message) => abort(Abort::auth_failed(message.as_deref())),
+            AuthOutcome::NoMatchingMethod => abort(Abort::no_matching_auth_method(&self.offered)),
+            AuthOutcome::Unauthorized(message) => {
+                abort(Abort::authorization_denied(message.as_deref()))
+            }
+        };
+        self.state = state;
+        match &message {
+            Messages::Abort(abort) => {
+                self.queue_abort(abort.clone());
+            }
+            _ => self.outgoing.push(message.clone())?,
+        }
+        Ok(message)
+    }
+
+    /// # Push
+    /// Queue a message for the session, such as a RESULT or EVENT, behind the ones already
+    /// queued.
+    ///
+    /// Fails like [`OutgoingQueue::push`] once the session is closing.
+    pub fn push(&mut self, message: Messages) -> Result<(), Error> {
+        self.outgoing.push(message)
+    }
+
+    /// # Close
+    /// Queue `goodbye` behind the messages already queued, see [`OutgoingQueue::close_after`].
+    pub fn close(&mut self, goodbye: Goodbye) {
+        self.outgoing.close_after(goodbye);
+    }
+
+    /// Take the queued messages, in sending order, see [`OutgoingQueue::drain_ready`].
+    pub fn drain_outgoing(&mut self) -> Vec<Messages> {
+        self.outgoing.drain_ready()
+    }
+
+    fn queue_abort(&mut self, abort: Abort) -> Abort {
+        self.outgoing
+            .push_priority(Messages::from(abort.clone()))
+            .expect("an ABORT is always accepted");
+        abort
+    }
+}
+
+fn abort(abort: Abort) -> (HandshakeState, Messages) {
+    (HandshakeState::Aborted, Messages::from(abort))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auth::hello_with_methods,
+        messages::{Authenticate, Cancel, InvocationDetails},
+        uri::is_prefix_of,
+    };
+
+    const ALICE: SessionId = 1;
+    const BOB: SessionId = 2;
+
+    fn realm() -> Realm {
+        let mut realm = Realm::new("realm1");
+        for session in [ALICE, BOB] {
+            realm.join(SessionIdentity::anonymous(session));
+        }
+        realm
+    }
+
+    #[test]
+    fn two_clients_pubsub_and_rpc() {
+        let mut realm = realm();
+
+        // Bob subscribes to a topic and a prefix of it.
+        let exact = realm
+            .subscribe(
+                BOB,
+                Subscribe {
+                    request_id: 1,
+                    options: json!({}),
+                    topic: "com.myapp.topic1".into(),
+                },
+            )
+            .unwrap();
+        let prefix = realm
+            .subscribe(
+                BOB,
+                Subscribe {
+                    request_id: 2,
+                    options: json!({ "match": "prefix" }),
+                    topic: "com.myapp".into(),
+                },
+            )
+            .unwrap();
+        assert_eq!(exact.request_id, 1);
+        assert_ne!(exact.subscription, prefix.subscription);
+
+        // Alice publishes, Bob receives one event per subscription, Alice none.
+        let publish = Publish {
+            request_id: 1,
+            options: json!({ "acknowledge": true }),
+            topic: "com.myapp.topic1".into(),
+            args: json!(["hello"]),
+            kwargs: Value::Null,
+        };
+        let (published, events) = realm.publish(ALICE, publish.clone()).unwrap();
+        let published = published.unwrap();
+        assert_eq!(events.len(), 2);
+        for (receiver, event) in &events {
+            assert_eq!(*receiver, BOB);
+            assert_eq!(event.publication, published.publication);
+            assert_eq!(event.payload.args(), &json!(["hello"]));
+        }
+        assert!(events[0].1.payload.ptr_eq(&events[1].1.payload));
+        assert_eq!(events[0].1.subscription, exact.subscription);
+        assert_eq!(events[1].1.details, json!({ "topic": "com.myapp.topic1" }));
+
+        // Bob publishing does not receive his own event, unless he asks to.
+        assert!(realm.publish(BOB, publish.clone()).unwrap().1.is_empty());
+        let publish = Publish {
+            options: json!({ "exclude_me": false }),
+            ..publish
+        };
+        assert_eq!(realm.publish(BOB, publish.clone()).unwrap().1.len(), 2);
+
+        // Alice registers a procedure, Bob calls it.
+        let registered = realm
+            .register(
+                ALICE,
+                Register {
+                    request_id: 2,
+                    options: json!({}),
+                    procedure: "com.myapp.add".into(),
+                },
+            )
+            .unwrap();
+        let duplicate = realm
+            .register(
+                BOB,
+                Register {
+                    request_id: 3,
+                    options: json!({}),
+                    procedure: "com.myapp.add".into(),
+                },
+            )
+            .unwrap_err();
+        assert_eq!(duplicate.error, "wamp.error.procedure_already_exists");
+
+        let (callee, invocation) = realm
+            .call(
+                BOB,
+                Call {
+                    request_id: 4,
+                    options: json!({}),
+                    procedure: "com.myapp.add".into(),
+                    args: json!([1, 2]),
+                    kwargs: Value::Null,
+                },
+            )
+            .unwrap();
+        assert_eq!(callee, ALICE);
+        assert_eq!(invocation.registration, registered.registration);
+        assert_eq!(invocation.args, json!([1, 2]));
+
+        let (caller, result) = realm
+            .complete(
+                ALICE,
+                Yield {
+                    request_id: invocation.request_id,
+                    options: json!({}),
+                    args: json!([3]),
+                    kwargs: Value::Null,
+                },
+            )
+            .unwrap();
+        assert_eq!(caller, BOB);
+        assert_eq!(result.request_id, 4);
+        assert_eq!(result.args, json!([3]));
+
+        // Once Alice leaves, the procedure and her subscriptions are gone.
+        let summary = realm.detach_session(ALICE);
+        assert_eq!(summary.removed_registrations, vec![registered.registration]);
+        assert!(summary.orphaned_calls.is_empty());
+        let missing = realm
+            .call(
+                BOB,
+                Call {
+                    request_id: 5,
+                    options: json!({}),
+                    procedure: "com.myapp.add".into(),
+                    args: Value::Null,
+                    kwargs: Value::Null,
+                },
+            )
+            .unwrap_err();
+        assert_eq!(missing.event, WampErrorEvent::Call);
+        assert_eq!(missing.error, "wamp.error.no_such_procedure");
+
+        let unsubscribed = realm
+            .unsubscribe(
+                BOB,
+                Unsubscribe {
+                    request_id: 6,
+                    subscription: exact.subscription,
+                },
+            )
+            .unwrap();
+        assert_eq!(unsubscribed.request_id, 6);
+        assert_eq!(realm.subscriptions().len(), 1);
+    }
+
+    #[test]
+    fn shared_registrations() {
+        let mut realm = realm();
+        let register = |request_id, options: Value, procedure: &'static str| Register {
+            request_id,
+            options,
+            procedure: procedure.into(),
+        };
+        let call = |procedure: &'static str| Call {
+            request_id: 1,
+            options: json!({}),
+            procedure: procedure.into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+
+        let round_robin = json!({ "invoke": "roundrobin" });
+        let shared = realm
+            .register(ALICE, register(1, round_robin.clone(), "com.myapp.work"))
+            .unwrap();
+        assert_eq!(
+            realm
+                .register(BOB, register(1, round_robin, "com.myapp.work"))
+                .unwrap(),
+            shared
+        );
+        assert!(realm
+            .register(
+                BOB,
+                register(2, json!({ "invoke": "last" }), "com.myapp.work")
+            )
+            .is_err());
+        let callees: Vec<SessionId> = (0..4)
+            .map(|_| realm.call(ALICE, call("com.myapp.work")).unwrap().0)
+            .collect();
+        assert_eq!(callees, vec![ALICE, BOB, ALICE, BOB]);
+
+        let pattern = realm
+            .register(
+                ALICE,
+                register(3, json!({ "match": "prefix" }), "com.myapp"),
+            )
+            .unwrap();
+        let (_, invocation) = realm.call(BOB, call("com.myapp.other")).unwrap();
+        assert_eq!(invocation.registration, pattern.registration);
+        assert_eq!(
+            invocation.details,
+            json!({ "procedure": "com.myapp.other" })
+        );
+        assert_eq!(
+            realm.registrations().lookup("com.myapp.work"),
+            Some(shared.registration)
+        );
+
+        assert!(realm
+            .register(
+                ALICE,
+                register(4, json!({ "invoke": "fastest" }), "com.myapp.x")
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn callee_vanishes_mid_call() {
+        let mut realm = realm();
+        let subscribed = realm
+            .subscribe(
+                ALICE,
+                Subscribe {
+                    request_id: 1,
+                    options: json!({}),
+                    topic: "com.myapp.topic1".into(),
+                },
+            )
+            .unwrap();
+        let registered = realm
+            .register(
+                ALICE,
+                Register {
+                    request_id: 2,
+                    options: json!({}),
+                    procedure: "com.myapp.slow".into(),
+                },
+            )
+            .unwrap();
+        let call = |request_id| Call {
+            request_id,
+            options: json!({}),
+            procedure: "com.myapp.slow".into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+        let (_, first) = realm.call(BOB, call(10)).unwrap();
+        let (_, second) = realm.call(BOB, call(11)).unwrap();
+        // A call Alice made herself is dropped with her, nobody is left waiting on it.
+        realm.call(ALICE, call(12)).unwrap();
+
+        let summary = realm.detach_session(ALICE);
+        assert_eq!(summary.removed_subscriptions, vec![subscribed.subscription]);
+        assert_eq!(summary.removed_registrations, vec![registered.registration]);
+        assert_eq!(
+            summary.orphaned_calls,
+            vec![first.request_id, second.request_id]
+        );
+
+        let errors = summary.caller_errors(WampErrorUri::Canceled);
+        assert_eq!(errors.len(), 2);
+        for ((caller, error), request_id) in errors.iter().zip([10, 11]) {
+            assert_eq!(*caller, BOB);
+            assert_eq!(error.event, WampErrorEvent::Call);
+            assert_eq!(error.request_id, request_id);
+            assert_eq!(error.error, "wamp.error.canceled");
+        }
+
+        // A late YIELD of the vanished callee is not routed anymore.
+        let late = Yield {
+            request_id: first.request_id,
+            options: json!({}),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+        assert_eq!(realm.complete(ALICE, late), None);
+        assert!(realm.subscriptions().is_empty());
+        assert!(realm.registrations().is_empty());
+        assert_eq!(realm.detach_session(ALICE), DetachSummary::default());
+    }
+
+    #[test]
+    fn stable_ordering() {
+        let mut realm = Realm::new("realm1");
+        for session in [9, 3, 7, 1, 5] {
+            realm.join(SessionIdentity::anonymous(session));
+        }
+        let sessions: Vec<SessionId> = realm
+            .iter_sorted()
+            .map(|identity| identity.session)
+            .collect();
+        assert_eq!(sessions, vec![1, 3, 5, 7, 9]);
+
+        for (request_id, session) in (1..).zip([9, 3, 7, 1, 5]) {
+            for topic in ["com.myapp.a", "com.myapp"] {
+                let options = if topic == "com.myapp" {
+                    json!({ "match": "prefix" })
+                } else {
+                    json!({})
+                };
+                realm
+                    .subscribe(
+                        session,
+                        Subscribe {
+                            request_id,
+                            options,
+                            topic: topic.into(),
+                        },
+                    )
+                    .unwrap();
+            }
+            realm
+                .register(
+                    session,
+                    Register {
+                        request_id,
+                        options: json!({}),
+                        procedure: format!("com.myapp.proc{session}").into(),
+                    },
+                )
+                .unwrap();
+        }
+        let ids: Vec<u64> = realm
+            .subscriptions()
+            .iter_sorted()
+            .map(|(id, _)| id)
+            .collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(ids.len(), 2);
+        let procedures: Vec<&str> = realm
+            .registrations()
+            .iter_sorted()
+            .map(|(_, registration)| registration.procedure())
+            .collect();
+        assert_eq!(
+            procedures,
+            vec![
+                "com.myapp.proc9",
+                "com.myapp.proc3",
+                "com.myapp.proc7",
+                "com.myapp.proc1",
+                "com.myapp.proc5"
+            ]
+        );
+
+        let publish = Publish {
+            request_id: 1,
+            options: json!({}),
+            topic: "com.myapp.a".into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+        let (_, events) = realm.publish(5, publish).unwrap();
+        let receivers: Vec<(SessionId, u64)> = events
+            .iter()
+            .map(|(session, event)| (*session, event.subscription))
+            .collect();
+        let expected: Vec<(SessionId, u64)> = [1, 3, 7, 9]
+            .into_iter()
+            .flat_map(|session| ids.iter().map(move |id| (session, *id)))
+            .collect();
+        assert_eq!(receivers, expected);
+
+        let summary = realm.detach_session(9);
+        assert_eq!(summary.removed_subscriptions, ids);
+    }
+
+    /// Allows the first rule matching the role and action whose URI prefix matches.
+    #[derive(Debug)]
+    struct Rules(Vec<(&'static str, Action, &'static str)>);
+
+    impl Authorizer for Rules {
+        fn authorize(&self, session: &SessionIdentity, action: Action, uri: &str) -> Decision {
+            let allowed = self.0.iter().any(|(role, allowed, prefix)| {
+                *role == session.authrole && *allowed == action && is_prefix_of(prefix, uri)
+            });
+            match allowed {
+                true => Decision::Allow,
+                false if session.authrole == "anonymous" => Decision::Deny {
+                    reason: format!("anonymous {} of {uri}", action.as_str()),
+                },
+                false => Decision::DenyWithDisclosure {
+                    reason: format!("{} may not {} {uri}", session.authrole, action.as_str()),
+                },
+            }
+        }
+    }
+
+    #[test]
+    fn rule_list_authorizer() {
+        let rules = Rules(vec![
+            ("backend", Action::Register, "com.myapp.admin"),
+            ("backend", Action::Publish, "com.myapp"),
+            ("frontend", Action::Call, "com.myapp.admin.status"),
+            ("frontend", Action::Subscribe, "com.myapp"),
+            ("anonymous", Action::Subscribe, "com.myapp.public"),
+        ]);
+        let mut realm = Realm::new("realm1").with_authorizer(rules);
+        let identity = |session, authrole: &str| SessionIdentity {
+            authid: format!("user{session}"),
+            authrole: authrole.to_string(),
+            authmethod: AuthMethod::Ticket,
+            ..SessionIdentity::anonymous(session)
+        };
+        realm.join(identity(1, "backend"));
+        realm.join(identity(2, "frontend"));
+        realm.join(SessionIdentity::anonymous(3));
+
+        let subscribe = |request_id, topic: &'static str| Subscribe {
+            request_id,
+            options: json!({}),
+            topic: topic.into(),
+        };
+        let register = |procedure: &'static str| Register {
+            request_id: 1,
+            options: json!({}),
+            procedure: procedure.into(),
+        };
+        let call = |procedure: &'static str| Call {
+            request_id: 2,
+            options: json!({}),
+            procedure: procedure.into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+
+        realm
+            .register(1, register("com.myapp.admin.status"))
+            .unwrap();
+        let denied = realm
+            .register(2, register("com.myapp.admin.reset"))
+            .unwrap_err();
+        assert_eq!(denied.event, WampErrorEvent::Register);
+        assert_eq!(denied.error, "wamp.error.not_authorized");
+        assert_eq!(
+            denied.args,
+            json!(["frontend may not register com.myapp.admin.reset"])
+        );
+        assert!(realm
+            .registrations()
+            .lookup("com.myapp.admin.reset")
+            .is_none());
+
+        assert_eq!(realm.call(2, call("com.myapp.admin.status")).unwrap().0, 1);
+        let denied = realm.call(3, call("com.myapp.admin.status")).unwrap_err();
+        assert_eq!(denied.error, "wamp.error.not_authorized");
+        assert_eq!(denied.request_id, 2);
+        assert_eq!(denied.args, Value::Null);
+
+        // The prefix matcher respects URI components.
+        realm
+            .subscribe(2, subscribe(1, "com.myapp.topic1"))
+            .unwrap();
+        realm
+            .subscribe(3, subscribe(1, "com.myapp.public"))
+            .unwrap();
+        assert!(realm
+            .subscribe(3, subscribe(2, "com.myapp.publicity"))
+            .is_err());
+        assert!(realm
+            .subscribe(4, subscribe(1, "com.myapp.public"))
+            .is_err());
+
+        let publish = Publish {
+            request_id: 3,
+            options: json!({}),
+            topic: "com.myapp.topic1".into(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+        let (_, events) = realm.publish(1, publish.clone()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(realm.publish(2, publish.clone()), Ok((None, Vec::new())));
+        let denied = realm.publish(2, publish.acknowledged()).unwrap_err();
+        assert_eq!(denied.event, WampErrorEvent::Publish);
+        assert_eq!(denied.error, "wamp.error.not_authorized");
+    }
+
+    #[test]
+    fn unavailable_callee_is_retried() {
+        let mut realm = Realm::new("realm1");
+        for session in [1, 2, 3] {
+            realm.join(SessionIdentity::anonymous(session));
+        }
+        for callee in [1, 2] {
+            let register = Register {
+                request_id: 1,
+                options: json!({ "invoke": "first" }),
+                procedure: "com.myapp.add".into(),
+            };
+            realm.register(callee, register).unwrap();
+        }
+        let call = |request_id| Call {
+            request_id,
+            options: json!({}),
+            procedure: "com.myapp.add".into(),
+            args: json!([1, 2]),
+            kwargs: Value::Null,
+        };
+        let unavailable = |invocation: &Invocation| WampError {
+            event: WampErrorEvent::Invocation,
+            request_id: invocation.request_id,
+            details: json!({}),
+            error: WampErrorUri::Unavailable.as_str().to_string(),
+            args: Value::Null,
+            kwargs: Value::Null,
+        };
+
+        // The first callee is unavailable, the call moves on to the second one.
+        let (callee, first) = realm.call(3, call(7)).unwrap();
+        assert_eq!(callee, 1);
+        let (callee, retried) = realm.fail(1, unavailable(&first)).unwrap();
+        assert_eq!(callee, 2);
+        let Messages::Invocation(retried) = retried else {
+            panic!("unexpected {retried:?}");
+        };
+        assert_ne!(retried.request_id, first.request_id);
+        assert_eq!(retried.registration, first.registration);
+        assert_eq!(retried.args, json!([1, 2]));
+
+        // The first callee can not answer anymore, the second one can.
+        let yielded = |request_id| Yield {
+            request_id,
+            options: json!({}),
+            args: json!([3]),
+            kwargs: Value::Null,
+        };
+        assert!(realm.complete(1, yielded(first.request_id)).is_none());
+        let (caller, result) = realm.complete(2, yielded(retried.request_id)).unwrap();
+        assert_eq!((caller, result.request_id), (3, 7));
+        assert!(realm.retries.is_empty());
+
+        // Both callees unavailable, the caller is told no callee is available.
+        let (_, first) = realm.call(3, call(8)).unwrap();
+        let Some((2, Messages::Invocation(retried))) = realm.fail(1, unavailable(&first)) else {
+            panic!("the call was not retried on callee 2");
+        };
+        let (caller, error) = realm.fail(2, unavailable(&retried)).unwrap();
+        assert_eq!(caller, 3);
+        let Messages::Error(error) = error else {
+            panic!("unexpected {error:?}");
+        };
+        assert_eq!(error.event, WampErrorEvent::Call);
+        assert_eq!(error.request_id, 8);
+        assert_eq!(error.error, "wamp.error.no_available_callee");
+        assert!(realm.retries.is_empty());
+
+        // Other errors are routed back as they are.
+        let (_, first) = realm.call(3, call(9)).unwrap();
+        let failed = WampError {
+            error: "com.myapp.error.overflow".to_string(),
+            ..unavailable(&first)
+        };
+        let (caller, error) = realm.fail(1, failed).unwrap();
+        assert_eq!(caller, 3);
+        assert!(matches!(error, Messages::Error(e) if e.error == "com.myapp.error.overflow"));
+    }
+
+    #[derive(Debug)]
+    struct ByAuthRole;
+
+    impl TrustLevelAssigner for ByAuthRole {
+        fn trust_level(&self, session: &SessionIdentity, action: Action, _: &str) -> Option<u64> {
+            match (session.authrole.as_str(), action) {
+                ("backend", _) => Some(2),
+                ("frontend", Action::Call) => Some(1),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn assigned_trust_levels() {
+        let mut realm = Realm::new("realm1").with_trust_level_assigner(ByAuthRole);
+        let identity = |session, authrole: &str| SessionIdentity {
+            authrole: authrole.to_string(),
+            ..SessionIdentity::anonymous(session)
+        };
+        realm.join(identity(1, "backend"));
+        realm.join(identity(2, "frontend"));
+        realm.join(SessionIdentity::anonymous(3));
+
+        for session in [1, 2, 3] {
+            let subscribe = Subscribe {
+                request_id: 1,
+                options: json!({}),
+                topic: "com.myapp.topic1".into(),
+            };
+            realm.subscribe(session, subscribe).unwrap();
+        }
+        let publish = Publish {
+            request_id: 2,
+            options: json!({}),
+            topic: "com.myapp.topic1".into(),
+            args: json!(["hello"]),
+            kwargs: Value::Null,
+        };
+        let (_, events) = realm.publish(1, publish.clone()).unwrap();
+        let levels: Vec<(SessionId, Option<u64>)> = events
+            .iter()
+            .map(|(receiver, event)| (*receiver, event.trust_level()))
+            .collect();
+        assert_eq!(levels, vec![(2, Some(2)), (3, Some(2))]);
+        assert_eq!(events[0].1.details, json!({ "trustlevel": 2 }));
+
+        let (_, events) = realm.publish(2, publish).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|(_, event)| event.details == json!({})));
+
+        let register = Register {
+            request_id: 3,
+            options: json!({ "match": "prefix" }),
+            procedure: "com.myapp".into(),
+        };
+        realm.register(1, register).unwrap();
+        let call = |request_id| Call {
+            request_id,
+            options: json!({}),
+            procedure: "com.myapp.add".into(),
+            args: json!([1, 2]),
+            kwargs: Value::Null,
+        };
+        let (_, invocation) = realm.call(2, call(4)).unwrap();
+        assert_eq!(invocation.trust_level(), Some(1));
+        assert_eq!(
+            InvocationDetails::from_invocation(&invocation).unwrap(),
+            InvocationDetails {
+                procedure: Some("com.myapp.add".to_string()),
+                trust_level: Some(1),
+                ..Default::default()
+            }
+        );
+        let (_, invocation) = realm.call(3, call(5)).unwrap();
+        assert_eq!(invocation.details, json!({ "procedure": "com.myapp.add" }));
+    }
+
+    #[test]
+    fn handshake_outcomes() {
+        let roles = json!({ "broker": {}, "dealer": {} });
+        let hello = Messages::from(hello_with_methods(
+            "realm1",
+            &[AuthMethod::Ticket, AuthMethod::WampCra],
+            Some("joe"),
+        ));
+        let start = || {
+            let mut handshake = Handshake::new(roles.clone());
+            handshake.receive(&hello).unwrap();
+            handshake
+        };
+        let aborted = |outcome| {
+            let mut handshake = start();
+            match handshake.resolve(outcome).unwrap() {
+                Messages::Abort(abort) => (abort.reason, abort.details),
+                other => panic!("unexpected {other:?}"),
+            }
+        };
+
+        assert_eq!(
+            aborted(AuthOutcome::Denied(Some("Unknown ticket.".to_string()))),
+            (
+                "wamp.error.authentication_denied".to_string(),
+                json!({ "message": "Unknown ticket." })
+            )
+        );
+        assert_eq!(
+            aborted(AuthOutcome::Failed(None)),
+            ("wamp.error.authentication_failed".to_string(), json!({}))
+        );
+        assert_eq!(
+            aborted(AuthOutcome::NoMatchingMethod),
+            (
+                "wamp.error.no_matching_auth_method".to_string(),
+                json!({ "authmethods": ["ticket", "wampcra"] })
+            )
+        );
+        assert_eq!(
+            aborted(AuthOutcome::Unauthorized(Some(
+                "No role for realm1.".to_string()
+            ))),
+            (
+                "wamp.error.authorization_denied".to_string(),
+                json!({ "message": "No role for realm1." })
+            )
+        );
+
+        // A ticket challenge, answered and accepted.
+        let mut handshake = start();
+        let challenge = handshake
+            .resolve(AuthOutcome::Challenge {
+                authmethod: AuthMethod::Ticket,
+                extra: json!({}),
+            })
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&challenge).unwrap(),
+            r#"[4,"ticket",{}]"#
+        );
+        assert_eq!(handshake.state(), HandshakeState::Challenged);
+        assert!(handshake.resolve(AuthOutcome::NoMatchingMethod).is_err());
+        let authenticate = Messages::from(Authenticate {
+            signature: "secret".to_string(),
+            details: json!({}),
+        });
+        handshake.receive(&authenticate).unwrap();
+        let identity = SessionIdentity {
+            authid: "joe".to_string(),
+            authrole: "user".to_string(),
+            authmethod: AuthMethod::Ticket,
+            authprovider: Some("static".to_string()),
+            ..SessionIdentity::anonymous(9)
+        };
+        let Messages::Welcome(welcome) = handshake.resolve(AuthOutcome::Welcome(identity)).unwrap()
+        else {
+            panic!("expected a WELCOME");
+        };
+        assert_eq!(handshake.state(), HandshakeState::Established);
+        assert_eq!(welcome.session, 9);
+        assert_eq!(
+            welcome.details,
+            json!({
+                "roles": roles,
+                "authid": "joe",
+                "authrole": "user",
+                "authmethod": "ticket",
+                "authprovider": "static"
+            })
+        );
+
+        // Messages out of turn abort the handshake.
+        let violation = handshake.receive(&hello).unwrap_err();
+        assert_eq!(violation.reason, "wamp.error.protocol_violation");
+        assert_eq!(handshake.state(), HandshakeState::Aborted);
+    }
+
+    #[test]
+    fn handshake_answers_goodbye() {
+        let mut handshake = Handshake::new(json!({ "broker": {} }));
+        let hello = hello_with_methods("realm1", &[], None);
+        handshake.receive(&Messages::from(hello)).unwrap();
+        let welcome = handshake
+            .resolve(AuthOutcome::Welcome(SessionIdentity::anonymous(1)))
+            .unwrap();
+        let published = Messages::from(Published {
+            request_id: 1,
+            publication: 2,
+        });
+        handshake.push(published.clone()).unwrap();
+
+        let goodbye = Goodbye {
+            details: json!({}),
+            reason: "wamp.close.close_realm".to_string(),
+        };
+        assert_eq!(handshake.check(&Messages::from(goodbye)), Ok(()));
+        assert!(handshake.push(published.clone()).is_err());
+        let answer = Goodbye {
+            details: json!({}),
+            reason: "wamp.close.goodbye_and_out".to_string(),
+        };
+        assert_eq!(
+            handshake.drain_outgoing(),
+            vec![welcome, published, Messages::from(answer)]
+        );
+    }
+
+    #[test]
+    fn cancel_needs_negotiated_feature() {
+        let cancel = Messages::from(Cancel {
+            request_id: 7,
+            options: json!({ "mode": "kill" }),
+        });
+        let established = |caller: Value| {
+            let mut handshake =
+                Handshake::new(json!({ "dealer": { "features": { "call_canceling": true } } }));
+            let hello = Hello {
+                realm: "realm1".into(),
+                details: json!({ "roles": { "caller": caller } }),
+            };
+            handshake.receive(&Messages::from(hello)).unwrap();
+            handshake
+                .resolve(AuthOutcome::Welcome(SessionIdentity::anonymous(1)))
+                .unwrap();
+            handshake
+        };
+
+        let mut handshake = established(json!({ "features": { "call_canceling": true } }));
+        assert_eq!(handshake.check(&cancel), Ok(()));
+        assert_eq!(handshake.state(), HandshakeState::Established);
+
+        let mut handshake = established(json!({}));
+        handshake.drain_outgoing();
+        let published = Messages::from(Published {
+            request_id: 1,
+            publication: 2,
+        });
+        handshake.push(published).unwrap();
+        let abort = handshake.check(&cancel).unwrap_err();
+        assert_eq!(
+            handshake.drain_outgoing(),
+            vec![Messages::from(abort.clone())]
+        );
+        assert_eq!(abort.reason, "wamp.error.protocol_violation");
+        assert_eq!(
+            abort.details,
+            json!({ "message": "CANCEL requires the call_canceling feature of the caller, which was not negotiated." })
+        );
+        assert_eq!(handshake.state(), HandshakeState::Aborted);
+    }
+}
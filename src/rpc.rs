@@ -0,0 +1,131 @@
+//! # RPC
+//! Dealer side re-routing of calls.
+//!
+//! A callee of a shared registration may answer an INVOCATION with `wamp.error.unavailable`,
+//! for example while it shuts down. Instead of failing the call, the dealer invokes the next
+//! callee its [`InvocationPolicy`](crate::router::InvocationPolicy) picks. A [`RetryPlanner`]
+//! remembers every callee a call was tried on, so no callee is invoked twice, and gives up with
+//! `wamp.error.no_available_callee` once every callee was tried.
+//!
+//! [`Realm`](crate::router::Realm) re-routes its calls this way.
+//! ## Examples
+//! ```
+//! use wamp_core::router::{InvocationPolicy, RegistrationRegistry};
+//! use wamp_core::rpc::{RetryDecision, RetryPlanner};
+//! use wamp_core::uri::MatchPolicy;
+//! use wamp_core::messages::Call;
+//! use serde_json::json;
+//!
+//! let mut registrations = RegistrationRegistry::new();
+//! for callee in [1, 2] {
+//!     registrations
+//!         .register(callee, "com.myapp.add", MatchPolicy::Exact, InvocationPolicy::RoundRobin, || 10)
+//!         .unwrap();
+//! }
+//!
+//! let call = Call {
+//!     request_id: 7,
+//!     options: json!({}),
+//!     procedure: "com.myapp.add".into(),
+//!     args: json!([1, 2]),
+//!     kwargs: json!(null)
+//! };
+//! // Callee 1 was invoked first, and answered wamp.error.unavailable.
+//! let mut planner = RetryPlanner::new(call, json!({}), 1);
+//!
+//! match planner.plan(&mut registrations, 10, 0, || 100) {
+//!     RetryDecision::Retry(callee, invocation) => {
+//!         assert_eq!(callee, 2);
+//!         assert_eq!(invocation.request_id, 100);
+//!         assert_eq!(invocation.args, json!([1, 2]));
+//!     }
+//!     other => panic!("unexpected {other:?}"),
+//! }
+//!
+//! match planner.plan(&mut registrations, 10, 0, || 101) {
+//!     RetryDecision::GiveUp(error) => assert_eq!(error.error, "wamp.error.no_available_callee"),
+//!     other => panic!("unexpected {other:?}"),
+//! }
+//! ```
+use serde_json::Value;
+
+use crate::{
+    error::WampErrorUri,
+    messages::{Call, Invocation, WampError},
+    pubsub::SessionId,
+    router::{RegistrationId, RegistrationRegistry},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Retry Decision
+/// What to do with a call whose callee was unavailable, see [`RetryPlanner::plan`].
+pub enum RetryDecision {
+    /// Send the INVOCATION to the callee.
+    Retry(SessionId, Invocation),
+    /// Every callee was tried, send the `wamp.error.no_available_callee` ERROR to the caller.
+    GiveUp(WampError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Retry Planner
+/// Re-routes a call to the callees of its registration it was not tried on yet, see the
+/// [module documentation](self).
+pub struct RetryPlanner {
+    call: Call,
+    details: Value,
+    attempted: Vec<SessionId>,
+}
+
+impl RetryPlanner {
+    /// Plan the retries of `call`, first invoked on `callee` with the INVOCATION `details`.
+    ///
+    /// Retries carry the same details, only the request id changes.
+    pub fn new(call: Call, details: Value, callee: SessionId) -> Self {
+        RetryPlanner {
+            call,
+            details,
+            attempted: vec![callee],
+        }
+    }
+
+    /// The call being routed.
+    pub fn call(&self) -> &Call {
+        &self.call
+    }
+
+    /// The callees the call was invoked on, in order.
+    pub fn attempted(&self) -> &[SessionId] {
+        &self.attempted
+    }
+
+    /// # Plan
+    /// Pick the next callee of registration `failed` after the last one was unavailable.
+    ///
+    /// The callee is picked by the invocation policy of the registration among the callees not
+    /// tried yet, `random` being used by the random policy. The INVOCATION gets the request id
+    /// returned by `new_id`, which is only called when retrying.
+    pub fn plan(
+        &mut self,
+        registrations: &mut RegistrationRegistry,
+        failed: RegistrationId,
+        random: u64,
+        new_id: impl FnOnce() -> u64,
+    ) -> RetryDecision {
+        let Some(callee) = registrations.next_callee_excluding(failed, random, &self.attempted)
+        else {
+            return RetryDecision::GiveUp(WampError::from_call(
+                &self.call,
+                WampErrorUri::NoAvailableCallee.as_str(),
+            ));
+        };
+        self.attempted.push(callee);
+        let invocation = Invocation {
+            request_id: new_id(),
+            registration: failed,
+            details: self.details.clone(),
+            args: self.call.args.clone(),
+            kwargs: self.call.kwargs.clone(),
+        };
+        RetryDecision::Retry(callee, invocation)
+    }
+}
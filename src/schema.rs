@@ -0,0 +1,143 @@
+//! # JSON Schema
+//! JSON Schemas (draft 2020-12) describing WAMP messages, for validating traffic outside of
+//! Rust.
+//!
+//! Both schemas of a message are generated from [`MessageKind::fields`], the field table the
+//! deserializers use:
+//!
+//! - [`message_schema`] describes the positional wire form, an array whose `prefixItems` are
+//!   the message id followed by every field.
+//! - [`annotated_schema`] describes the annotated object form returned by
+//!   [`Messages::to_annotated_object`](crate::messages::Messages::to_annotated_object).
+//!
+//! The typed options and details structs implement [`JsonSchema`](schemars::JsonSchema), use
+//! [`schemars::schema_for!`] for those.
+//! ## Examples
+//! ```
+//! use wamp_core::messages::MessageKind;
+//! use wamp_core::schema::message_schema;
+//! use serde_json::{json, to_value};
+//!
+//! let schema = to_value(message_schema(MessageKind::Subscribed)).unwrap();
+//!
+//! assert_eq!(schema["title"], json!("SUBSCRIBED"));
+//! assert_eq!(schema["prefixItems"][0], json!({ "const": 33 }));
+//! assert_eq!(schema["minItems"], json!(3));
+//! ```
+use schemars::schema::RootSchema;
+use serde_json::{from_value, json, Value};
+
+use crate::messages::{MessageKind, MAX_ID};
+
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Trailing fields that may be left out of a message.
+fn is_optional(kind: MessageKind, field: &str) -> bool {
+    matches!(field, "args" | "kwargs") || (kind == MessageKind::Unsubscribed && field == "details")
+}
+
+/// The schema of a single field, by field name.
+fn field_schema(field: &str) -> Value {
+    match field {
+        "options" | "details" | "kwargs" => json!({ "type": "object" }),
+        "args" => json!({ "type": "array" }),
+        "event" => json!({ "type": "integer", "minimum": 0 }),
+        "request_id" | "session" | "subscription" | "publication" | "registration" => {
+            json!({ "type": "integer", "minimum": 0, "maximum": MAX_ID })
+        }
+        _ => json!({ "type": "string" }),
+    }
+}
+
+fn root(schema: Value) -> RootSchema {
+    from_value(schema).expect("generated schemas are valid schemars schemas")
+}
+
+/// # Message schema
+/// The schema of the positional wire form of a message.
+pub fn message_schema(kind: MessageKind) -> RootSchema {
+    let fields = kind.fields();
+    let required = fields
+        .iter()
+        .filter(|field| !is_optional(kind, field))
+        .count();
+    let items: Vec<Value> = std::iter::once(json!({ "const": kind.id() }))
+        .chain(fields.iter().map(|field| field_schema(field)))
+        .collect();
+    root(json!({
+        "$schema": DRAFT_2020_12,
+        "title": kind.name(),
+        "type": "array",
+        "prefixItems": items,
+        "minItems": 1 + required,
+        "maxItems": 1 + fields.len(),
+    }))
+}
+
+/// # Annotated schema
+/// The schema of the annotated object form of a message.
+pub fn annotated_schema(kind: MessageKind) -> RootSchema {
+    let fields = kind.fields();
+    let properties: serde_json::Map<String, Value> = fields
+        .iter()
+        .map(|field| (field.to_string(), field_schema(field)))
+        .collect();
+    let required: Vec<&str> = fields
+        .iter()
+        .copied()
+        .filter(|field| !is_optional(kind, field))
+        .collect();
+    root(json!({
+        "$schema": DRAFT_2020_12,
+        "title": kind.name(),
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonschema::JSONSchema;
+    use serde_json::{from_str, to_value};
+
+    use super::*;
+    use crate::messages::fixture_frames;
+
+    fn compile(schema: RootSchema) -> JSONSchema {
+        JSONSchema::compile(&to_value(schema).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fixture_frames_validate() {
+        for (frame, message) in fixture_frames() {
+            let kind = message.kind().unwrap();
+            let wire: Value = from_str(frame).unwrap();
+            assert!(
+                compile(message_schema(kind)).is_valid(&wire),
+                "{frame} does not match the {} schema",
+                kind.name()
+            );
+            let annotated = message.to_annotated_object().unwrap();
+            assert!(
+                compile(annotated_schema(kind)).is_valid(&annotated),
+                "{annotated} does not match the annotated {} schema",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_frames() {
+        let call = compile(message_schema(MessageKind::Call));
+        assert!(call.is_valid(&json!([48, 1, {}, "com.myapp.add"])));
+        assert!(!call.is_valid(&json!([48, 1, {}])));
+        assert!(!call.is_valid(&json!([49, 1, {}, "com.myapp.add"])));
+        assert!(!call.is_valid(&json!([48, "1", {}, "com.myapp.add"])));
+        assert!(!call.is_valid(&json!([48, 1, {}, "a", [], {}, 1])));
+
+        let annotated = compile(annotated_schema(MessageKind::Call));
+        assert!(!annotated.is_valid(&json!({ "request_id": 1, "options": {} })));
+    }
+}
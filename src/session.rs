@@ -0,0 +1,878 @@
+//! # Session
+//! Helpers for the client side of a WAMP session: the [`Session`] state and the outgoing
+//! message queue.
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    auth::AuthMethod,
+    error::Error,
+    factories::{Correlator, IdGenerator},
+    features::NegotiatedFeatures,
+    messages::{Abort, Event, Goodbye, Hello, Messages, Subscribe, Unsubscribe},
+    pending::{request_id, PendingRequests},
+    pubsub::{EventRouter, ReceiverIdentity},
+    uri::constants::CLOSE_GOODBYE_AND_OUT,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// # Session Identity
+/// Who a session authenticated as, as assigned by the router when the session joined.
+/// ## Examples
+/// ```
+/// use wamp_core::session::SessionIdentity;
+/// use wamp_core::auth::AuthMethod;
+///
+/// let identity = SessionIdentity::anonymous(7);
+///
+/// assert_eq!(identity.authrole, "anonymous");
+/// assert_eq!(identity.authmethod, AuthMethod::Anonymous);
+/// ```
+pub struct SessionIdentity {
+    /// The session id.
+    pub session: u64,
+    /// The authenticated identity of the session.
+    pub authid: String,
+    /// The role the session was granted.
+    pub authrole: String,
+    /// The method the session authenticated with.
+    pub authmethod: AuthMethod,
+    /// The provider that authenticated the session, when the router names one.
+    pub authprovider: Option<String>,
+}
+
+impl SessionIdentity {
+    /// The identity of a session that did not authenticate, its `authid` is the session id.
+    pub fn anonymous(session: u64) -> Self {
+        SessionIdentity {
+            session,
+            authid: session.to_string(),
+            authrole: "anonymous".to_string(),
+            authmethod: AuthMethod::Anonymous,
+            authprovider: None,
+        }
+    }
+}
+
+impl From<&SessionIdentity> for ReceiverIdentity {
+    fn from(identity: &SessionIdentity) -> Self {
+        ReceiverIdentity {
+            session: identity.session,
+            authid: Some(identity.authid.clone()),
+            authrole: Some(identity.authrole.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// # Outgoing Queue
+/// Messages waiting to be written to the transport, in the order the session must send them.
+///
+/// - [`push`](OutgoingQueue::push) appends in arrival order.
+/// - [`push_priority`](OutgoingQueue::push_priority) goes ahead of everything pushed normally,
+///   use it for ABORT.
+/// - [`close_after`](OutgoingQueue::close_after) sends a GOODBYE after the messages already
+///   queued, and rejects any new message other than GOODBYE or ABORT.
+///
+/// Once an ABORT is queued the session is over, so the messages queued behind it are dropped.
+/// ## Examples
+/// ```
+/// use wamp_core::session::OutgoingQueue;
+/// use wamp_core::messages::{Goodbye, Messages, Published};
+/// use serde_json::json;
+///
+/// let mut queue = OutgoingQueue::new();
+/// queue.push(Messages::from(Published { request_id: 1, publication: 2 })).unwrap();
+///
+/// let goodbye = Goodbye {
+///     details: json!({}),
+///     reason: "wamp.close.system_shutdown".to_string()
+/// };
+/// queue.close_after(goodbye.clone());
+///
+/// assert!(queue.push(Messages::from(Published { request_id: 3, publication: 4 })).is_err());
+/// assert_eq!(queue.drain_ready(), vec![
+///     Messages::from(Published { request_id: 1, publication: 2 }),
+///     Messages::from(goodbye),
+/// ]);
+/// ```
+pub struct OutgoingQueue {
+    priority: VecDeque<Messages>,
+    normal: VecDeque<Messages>,
+    goodbye: Option<Goodbye>,
+    closing: bool,
+    aborted: bool,
+}
+
+fn is_close(message: &Messages) -> bool {
+    matches!(message, Messages::Goodbye(_) | Messages::Abort(_))
+}
+
+impl OutgoingQueue {
+    /// Create an empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn accept(&mut self, message: &Messages) -> Result<(), Error> {
+        if (self.closing || self.aborted) && !is_close(message) {
+            return Err(Error::SessionClosing(Box::new(message.clone())));
+        }
+        if let Messages::Abort(_) = message {
+            self.aborted = true;
+        }
+        Ok(())
+    }
+
+    /// Queue a message behind the ones already queued.
+    ///
+    /// Fails with [`Error::SessionClosing`] once the session is closing, unless the message is
+    /// a GOODBYE or ABORT.
+    pub fn push(&mut self, message: Messages) -> Result<(), Error> {
+        self.accept(&message)?;
+        self.normal.push_back(message);
+        Ok(())
+    }
+
+    /// Queue a message ahead of every message pushed with [`push`](OutgoingQueue::push).
+    ///
+    /// Fails like [`push`](OutgoingQueue::push) does.
+    pub fn push_priority(&mut self, message: Messages) -> Result<(), Error> {
+        self.accept(&message)?;
+        self.priority.push_back(message);
+        Ok(())
+    }
+
+    /// Send `goodbye` once the messages already queued are sent, and stop accepting anything
+    /// but GOODBYE and ABORT.
+    pub fn close_after(&mut self, goodbye: Goodbye) {
+        self.closing = true;
+        self.goodbye = Some(goodbye);
+    }
+
+    /// Whether the queue stopped accepting new messages.
+    pub fn is_closing(&self) -> bool {
+        self.closing || self.aborted
+    }
+
+    /// Take every queued message, in sending order.
+    pub fn drain_ready(&mut self) -> Vec<Messages> {
+        let mut ready: Vec<Messages> = self.priority.drain(..).collect();
+        ready.extend(self.normal.drain(..));
+        ready.extend(self.goodbye.take().map(Messages::from));
+        if let Some(abort) = ready.iter().position(|m| matches!(m, Messages::Abort(_))) {
+            ready.truncate(abort + 1);
+        }
+        ready
+    }
+
+    /// Number of queued messages.
+    pub fn len(&self) -> usize {
+        self.priority.len() + self.normal.len() + usize::from(self.goodbye.is_some())
+    }
+
+    /// Whether no message is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug)]
+/// # Incoming
+/// What [`Session::handle_incoming`] made of a received message.
+pub enum Incoming<H> {
+    /// An EVENT, with the handler of its subscription.
+    Event(Event, Arc<H>),
+    /// The final response to a tracked request, or the ERROR answering it.
+    Response {
+        /// The request, as it was tracked.
+        request: Messages,
+        /// The received response.
+        response: Messages,
+    },
+    /// The broker revoked a subscription, its handler is dropped.
+    Revoked(u64),
+    /// Any other message, including responses to requests that are not tracked.
+    Other(Messages),
+}
+
+#[derive(Debug)]
+/// # Session
+/// The client side state of a session: request ids, the requests awaiting a response and the
+/// handlers of subscriptions.
+///
+/// Requests can be given a deadline, a CALL with a `timeout` option gets one of its own, other
+/// requests get the session wide [`timeout`](Session::timeout) if set. [`Session::expire`]
+/// gives up on the requests past their deadline, so their awaiters can be failed.
+///
+/// The session only produces and consumes [`Messages`], sending and receiving them is left to
+/// the transport. The GOODBYE and ABORT closing the session, including the GOODBYE answering
+/// one of the router, are queued in an [`OutgoingQueue`], see [`Session::drain_outgoing`].
+///
+/// Once given the HELLO it opened with, see [`Session::hello`], the session rejects received
+/// messages requiring a feature that was not negotiated.
+/// ## Examples
+/// ```
+/// use wamp_core::session::{Incoming, Session};
+/// use wamp_core::messages::{Messages, Subscribed};
+/// use serde_json::{from_str, json};
+///
+/// let mut session = Session::new();
+///
+/// let subscribe = session.subscribe("com.myapp.topic1", json!({}), "on_topic1").unwrap();
+/// assert_eq!(subscribe.request_id, 1);
+/// // Send `subscribe` over the transport, then hand every received message to the session
+///
+/// let subscribed = Subscribed { request_id: 1, subscription: 5512315355 };
+/// let incoming = session.handle_incoming(Messages::from(subscribed)).unwrap();
+/// assert!(matches!(incoming, Incoming::Response { .. }));
+///
+/// let event = from_str(r#"[36,5512315355,4429313566,{},["hello"]]"#).unwrap();
+/// match session.handle_incoming(event).unwrap() {
+///     Incoming::Event(event, handler) => {
+///         assert_eq!(*handler, "on_topic1");
+///         assert_eq!(event.args, json!(["hello"]));
+///     }
+///     other => panic!("unexpected {other:?}"),
+/// }
+/// ```
+pub struct Session<H> {
+    ids: IdGenerator,
+    pending: PendingRequests,
+    handlers: Correlator<H>,
+    subscriptions: EventRouter<H>,
+    timeout: Option<Duration>,
+    deadlines: HashMap<u64, Instant>,
+    outgoing: OutgoingQueue,
+    hello: Option<Hello>,
+    negotiated: Option<NegotiatedFeatures>,
+}
+
+impl<H> Default for Session<H> {
+    fn default() -> Self {
+        Session {
+            ids: IdGenerator::new(),
+            pending: PendingRequests::new(),
+            handlers: Correlator::new(),
+            subscriptions: EventRouter::new(),
+            timeout: None,
+            deadlines: HashMap::new(),
+            outgoing: OutgoingQueue::new(),
+            hello: None,
+            negotiated: None,
+        }
+    }
+}
+
+impl<H> Session<H> {
+    /// Create a session without requests or subscriptions, drawing ids from
+    /// [`IdGenerator::new`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Draw request ids from `ids`.
+    pub fn ids(mut self, ids: IdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Give up on requests without a timeout of their own after `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// # Hello
+    /// Record the HELLO the session opened with, so received messages are checked against the
+    /// [negotiated features](Session::negotiated): a CHALLENGE against the offered
+    /// authentication methods, and once the WELCOME is received, every message against the
+    /// features of both peers.
+    pub fn hello(&mut self, hello: Hello) {
+        self.negotiated = Some(NegotiatedFeatures::from_hello(&hello));
+        self.hello = Some(hello);
+    }
+
+    /// The features negotiated so far, `None` until [`Session::hello`] is called.
+    pub fn negotiated(&self) -> Option<&NegotiatedFeatures> {
+        self.negotiated.as_ref()
+    }
+
+    /// # Next id
+    /// The next request id, never one of a request still awaiting its response.
+    ///
+    /// Fails like [`IdGenerator::next_checked`].
+    pub fn next_id(&mut self) -> Result<u64, Error> {
+        self.ids.next_checked(&self.pending)
+    }
+
+    /// # Track request
+    /// Start tracking `request`, sent at `sent`, returning whether it expects a response, see
+    /// [`PendingRequests::track`].
+    ///
+    /// The request expires once its timeout passed, for a CALL the `timeout` option when not
+    /// `0`, for other requests the session wide [`timeout`](Session::timeout).
+    pub fn track_request(&mut self, request: &Messages, sent: Instant) -> bool {
+        let Some(id) = request_id(request) else {
+            return false;
+        };
+        self.pending.track(request);
+        let timeout = match request {
+            Messages::Call(call) => call.options["timeout"]
+                .as_u64()
+                .filter(|timeout| *timeout > 0)
+                .map(Duration::from_millis),
+            _ => None,
+        };
+        match timeout.or(self.timeout) {
+            Some(timeout) => self.deadlines.insert(id, sent + timeout),
+            None => self.deadlines.remove(&id),
+        };
+        true
+    }
+
+    /// # Expire
+    /// Stop tracking the requests whose deadline passed at `now`, returning their ids in
+    /// ascending order.
+    ///
+    /// The handler of an expired SUBSCRIBE is dropped, a late SUBSCRIBED for it is handed back
+    /// as [`Incoming::Other`].
+    /// ## Examples
+    /// ```
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use wamp_core::session::Session;
+    /// use wamp_core::messages::{Call, Messages};
+    /// use serde_json::{json, Value};
+    ///
+    /// let mut session: Session<()> = Session::new();
+    /// let call = Call {
+    ///     request_id: session.next_id().unwrap(),
+    ///     options: json!({ "timeout": 500 }),
+    ///     procedure: "com.myapp.add".into(),
+    ///     args: json!([1, 2]),
+    ///     kwargs: Value::Null
+    /// };
+    /// let sent = Instant::now();
+    /// session.track_request(&Messages::from(call), sent);
+    ///
+    /// assert!(session.expire(sent + Duration::from_millis(499)).is_empty());
+    /// assert_eq!(session.expire(sent + Duration::from_millis(500)), vec![1]);
+    /// assert!(session.pending().is_empty());
+    /// ```
+    pub fn expire(&mut self, now: Instant) -> Vec<u64> {
+        let mut expired: Vec<u64> = self
+            .deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(id, _)| *id)
+            .collect();
+        expired.sort_unstable();
+        for id in &expired {
+            self.deadlines.remove(id);
+            self.pending.remove(*id);
+            self.handlers.take(*id);
+        }
+        expired
+    }
+
+    /// # Subscribe
+    /// A tracked SUBSCRIBE to `topic`, events of the subscription go to `handler` once the
+    /// broker acknowledged it.
+    ///
+    /// Fails like [`Session::next_id`].
+    pub fn subscribe<T: Into<Cow<'static, str>>>(
+        &mut self,
+        topic: T,
+        options: Value,
+        handler: H,
+    ) -> Result<Subscribe, Error> {
+        let subscribe = Subscribe {
+            request_id: self.next_id()?,
+            options,
+            topic: topic.into(),
+        };
+        self.track_request(&Messages::from(subscribe.clone()), Instant::now());
+        self.handlers.register(subscribe.request_id, handler);
+        Ok(subscribe)
+    }
+
+    /// # Unsubscribe
+    /// A tracked UNSUBSCRIBE from `subscription`, its handler is dropped once the broker
+    /// acknowledged it.
+    ///
+    /// Fails like [`Session::next_id`].
+    pub fn unsubscribe(&mut self, subscription: u64) -> Result<Unsubscribe, Error> {
+        let unsubscribe = Unsubscribe {
+            request_id: self.next_id()?,
+            subscription,
+        };
+        self.track_request(&Messages::from(unsubscribe.clone()), Instant::now());
+        Ok(unsubscribe)
+    }
+
+    /// # Handle incoming
+    /// Update the session with a received message.
+    ///
+    /// - EVENTs are routed to the handler of their subscription.
+    /// - SUBSCRIBED registers the handler given to [`Session::subscribe`], an ERROR answering
+    ///   the SUBSCRIBE drops it.
+    /// - UNSUBSCRIBED drops the handler of the subscription, whether the broker answered an
+    ///   UNSUBSCRIBE or revoked the subscription.
+    ///
+    /// - GOODBYE from the router queues the GOODBYE answering it, unless the session is
+    ///   already closing.
+    /// - WELCOME completes the [negotiated features](Session::negotiated).
+    ///
+    /// A message requiring a feature that was not negotiated, such as an INTERRUPT without
+    /// `call_canceling`, is a protocol violation: its ABORT is queued, see [`Session::abort`],
+    /// and it fails with [`Error::Abort`] holding it.
+    ///
+    /// Fails with [`Error::UnknownSubscription`] for EVENTs of a subscription without handler.
+    pub fn handle_incoming(&mut self, message: Messages) -> Result<Incoming<H>, Error> {
+        if let Some(negotiated) = &self.negotiated {
+            if let Err(abort) = negotiated.check(&message) {
+                self.abort(abort.clone());
+                return Err(Error::Abort(abort));
+            }
+        }
+        if let (Messages::Welcome(welcome), Some(hello)) = (&message, &self.hello) {
+            self.negotiated = Some(NegotiatedFeatures::new(hello, welcome));
+        }
+        if let Messages::Goodbye(_) = message {
+            if !self.outgoing.is_closing() {
+                self.outgoing.close_after(Goodbye {
+                    details: json!({}),
+                    reason: CLOSE_GOODBYE_AND_OUT.to_string(),
+                });
+            }
+        }
+        if let Messages::Event(event) = message {
+            let handler = self.subscriptions.route(&event)?;
+            return Ok(Incoming::Event(event, handler));
+        }
+        if let Messages::Unsubscribed(unsubscribed) = &message {
+            if let Some(subscription) = self.subscriptions.handle_unsubscribed(unsubscribed) {
+                return Ok(Incoming::Revoked(subscription));
+            }
+        }
+        let Some(request) = self.pending.resolve(&message) else {
+            return Ok(Incoming::Other(message));
+        };
+        if let Some(id) = request_id(&request) {
+            self.deadlines.remove(&id);
+        }
+        match (&request, &message) {
+            (Messages::Subscribe(subscribe), Messages::Subscribed(subscribed)) => {
+                if let Some(handler) = self.handlers.take(subscribe.request_id) {
+                    self.subscriptions
+                        .register(subscribed.subscription, &subscribe.topic, handler);
+                }
+            }
+            (Messages::Subscribe(subscribe), _) => {
+                self.handlers.take(subscribe.request_id);
+            }
+            (Messages::Unsubscribe(unsubscribe), Messages::Unsubscribed(_)) => {
+                self.subscriptions.unregister(unsubscribe.subscription);
+            }
+            _ => {}
+        }
+        Ok(Incoming::Response {
+            request,
+            response: message,
+        })
+    }
+
+    /// # Close
+    /// Queue `goodbye` behind the messages already queued, see [`OutgoingQueue::close_after`].
+    ///
+    /// The GOODBYE the router answers with is handed back by [`Session::handle_incoming`] as
+    /// [`Incoming::Other`].
+    /// ## Examples
+    /// ```
+    /// use wamp_core::session::Session;
+    /// use wamp_core::messages::{Goodbye, Messages};
+    /// use serde_json::json;
+    ///
+    /// let mut session: Session<()> = Session::new();
+    /// let goodbye = Goodbye {
+    ///     details: json!({}),
+    ///     reason: "wamp.close.system_shutdown".to_string()
+    /// };
+    /// session.close(goodbye.clone());
+    ///
+    /// assert!(session.is_closing());
+    /// assert_eq!(session.drain_outgoing(), vec![Messages::from(goodbye)]);
+    /// ```
+    pub fn close(&mut self, goodbye: Goodbye) {
+        self.outgoing.close_after(goodbye);
+    }
+
+    /// # Abort
+    /// Queue `abort` ahead of every queued message, dropping the messages queued behind it.
+    pub fn abort(&mut self, abort: Abort) {
+        self.outgoing
+            .push_priority(Messages::from(abort))
+            .expect("an ABORT is always accepted");
+    }
+
+    /// Whether the session is closing, after [`Session::close`], [`Session::abort`] or a
+    /// GOODBYE of the router.
+    pub fn is_closing(&self) -> bool {
+        self.outgoing.is_closing()
+    }
+
+    /// Take the queued messages, in sending order, see [`OutgoingQueue::drain_ready`].
+    pub fn drain_outgoing(&mut self) -> Vec<Messages> {
+        self.outgoing.drain_ready()
+    }
+
+    /// The requests still awaiting their response.
+    pub fn pending(&self) -> &PendingRequests {
+        &self.pending
+    }
+
+    /// The handlers of the acknowledged subscriptions.
+    pub fn subscriptions(&self) -> &EventRouter<H> {
+        &self.subscriptions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::messages::{
+        Abort, Challenge, Interrupt, Published, Registered, Subscribed, Unsubscribed, WampError,
+        WampErrorEvent, Welcome,
+    };
+
+    fn published(request_id: u64) -> Messages {
+        Messages::from(Published {
+            request_id,
+            publication: 100 + request_id,
+        })
+    }
+
+    fn goodbye() -> Goodbye {
+        Goodbye {
+            details: json!({}),
+            reason: "wamp.close.system_shutdown".to_string(),
+        }
+    }
+
+    fn abort() -> Messages {
+        Messages::from(Abort {
+            details: json!({}),
+            reason: "wamp.error.protocol_violation".to_string(),
+        })
+    }
+
+    #[test]
+    fn normal_order() {
+        let mut queue = OutgoingQueue::new();
+        queue.push(published(1)).unwrap();
+        queue.push(published(2)).unwrap();
+        let registered = Messages::from(Registered {
+            request_id: 3,
+            registration: 4,
+        });
+        queue.push_priority(registered.clone()).unwrap();
+        assert_eq!(queue.len(), 3);
+        assert_eq!(
+            queue.drain_ready(),
+            vec![registered, published(1), published(2)]
+        );
+        assert!(queue.is_empty());
+        assert!(queue.drain_ready().is_empty());
+    }
+
+    #[test]
+    fn goodbye_after_in_flight_replies() {
+        let mut queue = OutgoingQueue::new();
+        queue.push(published(1)).unwrap();
+        queue.close_after(goodbye());
+        assert!(queue.is_closing());
+        match queue.push(published(2)) {
+            Err(Error::SessionClosing(message)) => assert_eq!(*message, published(2)),
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(queue.push_priority(published(3)).is_err());
+        assert_eq!(
+            queue.drain_ready(),
+            vec![published(1), Messages::from(goodbye())]
+        );
+    }
+
+    #[test]
+    fn abort_jumps_the_queue() {
+        let mut queue = OutgoingQueue::new();
+        queue.push(published(1)).unwrap();
+        queue.close_after(goodbye());
+        queue.push_priority(abort()).unwrap();
+        assert!(queue.push(published(2)).is_err());
+        assert_eq!(queue.drain_ready(), vec![abort()]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn session_close_paths() {
+        let mut session: Session<()> = Session::new();
+        let reply = session.handle_incoming(Messages::from(goodbye())).unwrap();
+        assert!(matches!(reply, Incoming::Other(Messages::Goodbye(_))));
+        assert!(session.is_closing());
+        let [Messages::Goodbye(answer)] = &session.drain_outgoing()[..] else {
+            panic!("no GOODBYE queued");
+        };
+        assert_eq!(answer.reason, "wamp.close.goodbye_and_out");
+
+        let mut session: Session<()> = Session::new();
+        session.close(goodbye());
+        assert_eq!(session.drain_outgoing(), vec![Messages::from(goodbye())]);
+        let answer = Goodbye {
+            details: json!({}),
+            reason: "wamp.close.goodbye_and_out".to_string(),
+        };
+        session.handle_incoming(Messages::from(answer)).unwrap();
+        assert!(session.drain_outgoing().is_empty());
+
+        let mut session: Session<()> = Session::new();
+        session.close(goodbye());
+        let Messages::Abort(abort) = abort() else {
+            unreachable!()
+        };
+        session.abort(abort.clone());
+        assert_eq!(session.drain_outgoing(), vec![Messages::from(abort)]);
+    }
+
+    #[test]
+    fn rejects_features_not_negotiated() {
+        let hello = |callee: Value, authmethods: Value| Hello {
+            realm: "realm1".into(),
+            details: json!({ "roles": { "callee": callee }, "authmethods": authmethods }),
+        };
+        let welcome = Messages::from(Welcome {
+            session: 1,
+            details: json!({ "roles": { "dealer": { "features": { "call_canceling": true } } } }),
+        });
+        let interrupt = Messages::from(Interrupt {
+            request_id: 7,
+            options: json!({}),
+        });
+        let challenge = Messages::from(Challenge {
+            authmethod: "ticket".to_string(),
+            details: json!({}),
+        });
+
+        let mut session: Session<()> = Session::new();
+        session.hello(hello(
+            json!({ "features": { "call_canceling": true } }),
+            json!(["ticket"]),
+        ));
+        assert!(session.handle_incoming(challenge.clone()).is_ok());
+        session.handle_incoming(welcome.clone()).unwrap();
+        assert!(session.handle_incoming(interrupt.clone()).is_ok());
+        assert!(session.drain_outgoing().is_empty());
+
+        let mut session: Session<()> = Session::new();
+        session.hello(hello(json!({}), json!([])));
+        match session.handle_incoming(challenge) {
+            Err(Error::Abort(abort)) => {
+                assert_eq!(abort.reason, "wamp.error.protocol_violation");
+                assert_eq!(session.drain_outgoing(), vec![Messages::from(abort)]);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let mut session: Session<()> = Session::new();
+        session.hello(hello(json!({}), json!([])));
+        session.handle_incoming(welcome).unwrap();
+        assert!(matches!(
+            session.handle_incoming(interrupt.clone()),
+            Err(Error::Abort(_))
+        ));
+        assert!(session.is_closing());
+
+        let mut session: Session<()> = Session::new();
+        assert!(session.handle_incoming(interrupt).is_ok());
+    }
+
+    fn event(subscription: u64) -> Messages {
+        serde_json::from_value(json!([36, subscription, 4429313566u64, {}, ["hello"]])).unwrap()
+    }
+
+    #[test]
+    fn subscribe_subscribed_event() {
+        let mut session = Session::new();
+        let first = session
+            .subscribe("com.myapp.topic1", json!({}), "on_topic1")
+            .unwrap();
+        let second = session
+            .subscribe(
+                "com.myapp.topic2",
+                json!({ "match": "prefix" }),
+                "on_topic2",
+            )
+            .unwrap();
+        assert_eq!((first.request_id, second.request_id), (1, 2));
+        assert_eq!(session.pending().outstanding(), vec![1, 2]);
+        assert!(matches!(
+            session.handle_incoming(event(5512315355)),
+            Err(Error::UnknownSubscription(5512315355))
+        ));
+
+        let subscribed = Messages::from(Subscribed {
+            request_id: 1,
+            subscription: 5512315355,
+        });
+        match session.handle_incoming(subscribed.clone()).unwrap() {
+            Incoming::Response { request, response } => {
+                assert_eq!(request, Messages::from(first));
+                assert_eq!(response, subscribed);
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert_eq!(
+            session.subscriptions().topic(5512315355).as_deref(),
+            Some("com.myapp.topic1")
+        );
+        match session.handle_incoming(event(5512315355)).unwrap() {
+            Incoming::Event(event, handler) => {
+                assert_eq!(event.subscription, 5512315355);
+                assert_eq!(*handler, "on_topic1");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        // A rejected SUBSCRIBE never gets a handler.
+        let rejected = Messages::from(WampError {
+            event: WampErrorEvent::Subscribe,
+            request_id: 2,
+            details: json!({}),
+            error: "wamp.error.not_authorized".to_string(),
+            args: json!(null),
+            kwargs: json!(null),
+        });
+        assert!(matches!(
+            session.handle_incoming(rejected).unwrap(),
+            Incoming::Response { .. }
+        ));
+        assert!(session.pending().is_empty());
+        assert_eq!(session.subscriptions().len(), 1);
+
+        // The replayed SUBSCRIBED is no longer awaited.
+        assert!(matches!(
+            session.handle_incoming(subscribed).unwrap(),
+            Incoming::Other(_)
+        ));
+    }
+
+    #[test]
+    fn unsubscribe_and_revoke() {
+        let mut session = Session::new();
+        for (request_id, topic) in [(1, "com.myapp.topic1"), (2, "com.myapp.topic2")] {
+            session.subscribe(topic, json!({}), topic).unwrap();
+            session
+                .handle_incoming(Messages::from(Subscribed {
+                    request_id,
+                    subscription: 100 + request_id,
+                }))
+                .unwrap();
+        }
+        assert_eq!(session.subscriptions().len(), 2);
+
+        let unsubscribe = session.unsubscribe(101).unwrap();
+        assert_eq!(unsubscribe.request_id, 3);
+        assert!(session.handle_incoming(event(101)).is_ok());
+        let reply = Messages::from(Unsubscribed {
+            request_id: 3,
+            details: json!(null),
+        });
+        assert!(matches!(
+            session.handle_incoming(reply).unwrap(),
+            Incoming::Response { .. }
+        ));
+        assert!(session.handle_incoming(event(101)).is_err());
+
+        let revoked = Messages::from(Unsubscribed {
+            request_id: 0,
+            details: json!({ "subscription": 102, "reason": "wamp.authentication.lost" }),
+        });
+        assert!(matches!(
+            session.handle_incoming(revoked.clone()).unwrap(),
+            Incoming::Revoked(102)
+        ));
+        assert!(session.subscriptions().is_empty());
+        assert!(matches!(
+            session.handle_incoming(revoked).unwrap(),
+            Incoming::Other(_)
+        ));
+    }
+
+    #[test]
+    fn next_id_skips_pending_requests() {
+        let mut session: Session<()> = Session::new();
+        let mut call = crate::call!("com.myapp.add2");
+        call.request_id = 1;
+        assert!(session.track_request(&Messages::from(call), Instant::now()));
+        assert_eq!(session.next_id().unwrap(), 2);
+        assert!(!session.track_request(&published(3), Instant::now()));
+    }
+
+    #[test]
+    fn expired_requests_are_removed() {
+        let mut session = Session::new().timeout(Duration::from_secs(10));
+        let sent = Instant::now();
+        let call = |request_id: u64, options: serde_json::Value| {
+            let mut call = crate::call!("com.myapp.add2", options);
+            call.request_id = request_id;
+            Messages::from(call)
+        };
+        session.track_request(&call(1, json!({ "timeout": 1000 })), sent);
+        session.track_request(&call(2, json!({ "timeout": 0 })), sent);
+        session.track_request(&call(3, json!({})), sent);
+        let subscribe = session
+            .subscribe("com.myapp.topic1", json!({}), ())
+            .unwrap();
+        assert_eq!(subscribe.request_id, 4);
+
+        assert!(session.expire(sent + Duration::from_millis(999)).is_empty());
+        assert_eq!(session.expire(sent + Duration::from_secs(1)), vec![1]);
+        assert_eq!(session.pending().outstanding(), vec![2, 3, 4]);
+        assert!(session.expire(sent + Duration::from_secs(1)).is_empty());
+
+        // A RESULT arriving in time resolves the call and clears its deadline.
+        let result = serde_json::from_str(r#"[50,3,{},[3]]"#).unwrap();
+        assert!(matches!(
+            session.handle_incoming(result).unwrap(),
+            Incoming::Response { .. }
+        ));
+        let late = sent + Duration::from_secs(60);
+        assert_eq!(session.expire(late), vec![2, 4]);
+        assert!(session.pending().is_empty());
+
+        // The answer to an expired request is no longer awaited.
+        let subscribed = Messages::from(Subscribed {
+            request_id: 4,
+            subscription: 5512315355,
+        });
+        assert!(matches!(
+            session.handle_incoming(subscribed).unwrap(),
+            Incoming::Other(_)
+        ));
+        assert!(session.subscriptions().is_empty());
+
+        // Without a session wide timeout only calls with a timeout expire.
+        let mut session: Session<()> = Session::new();
+        session.track_request(&call(1, json!({})), sent);
+        assert!(session.expire(late).is_empty());
+        assert_eq!(session.pending().outstanding(), vec![1]);
+    }
+}
@@ -0,0 +1,76 @@
+//! Instrumentation of the encode and decode paths, compiled to nothing unless the `tracing`
+//! feature is enabled.
+use crate::messages::Messages;
+
+/// The name and id of `message`, `None` for the name of extension messages.
+#[cfg(feature = "tracing")]
+fn describe(message: &Messages) -> (Option<&'static str>, Option<u64>) {
+    match message.kind() {
+        Some(kind) => (Some(kind.name()), Some(kind.id())),
+        None => (None, message.extension_id()),
+    }
+}
+
+/// Report the outcome of decoding a `size` bytes frame.
+#[cfg(feature = "tracing")]
+pub(crate) fn decoded(result: &Result<Messages, serde_json::Error>, size: usize) {
+    match result {
+        Ok(message) => {
+            let (name, id) = describe(message);
+            tracing::debug!(name, id, size, "decoded WAMP message")
+        }
+        Err(error) => tracing::warn!(reason = %error, size, "failed to decode WAMP message"),
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn decoded(_result: &Result<Messages, serde_json::Error>, _size: usize) {}
+
+/// Report a message encoded to `size` bytes.
+#[cfg(feature = "tracing")]
+pub(crate) fn encoded(message: &Messages, size: usize) {
+    let (name, id) = describe(message);
+    tracing::debug!(name, id, size, "encoded WAMP message");
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn encoded(_message: &Messages, _size: usize) {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::{codec::JsonLinesDecoder, messages::Messages};
+
+    #[test]
+    #[traced_test]
+    fn parse_failure() {
+        let message = tungstenite::Message::Text(r#"[48,1,{}"#.to_string());
+        assert!(Messages::try_from(message).is_err());
+        assert!(logs_contain("failed to decode WAMP message"));
+        assert!(logs_contain("size=8"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn batched_decode() {
+        let capture = "[33,3,5512315355]\n[33,4]\n";
+        let decoder = JsonLinesDecoder::new(capture.as_bytes())
+            .instrument(tracing::info_span!("capture", file = "capture.jsonl"));
+        assert_eq!(decoder.filter(Result::is_err).count(), 1);
+        assert!(logs_contain(
+            "decoded WAMP message name=\"SUBSCRIBED\" id=33"
+        ));
+        assert!(logs_contain("capture{file=\"capture.jsonl\"}"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn challenge_is_reported_with_its_own_id() {
+        let message = tungstenite::Message::Text(r#"[4,"ticket",{}]"#.to_string());
+        assert!(Messages::try_from(message).is_ok());
+        assert!(logs_contain("name=\"CHALLENGE\" id=4"));
+    }
+}
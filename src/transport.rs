@@ -0,0 +1,349 @@
+//! # Transports - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-transports)
+//! Encodings and the WebSocket subprotocol names they are negotiated with, and the dispatch of
+//! WebSocket frames.
+use http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue, Request};
+use tungstenite::{client::IntoClientRequest, protocol::CloseFrame, Message};
+
+use crate::{error::Error, messages::Messages};
+
+/// WebSocket subprotocol of the JSON serialization.
+pub const WAMP_JSON_SUBPROTOCOL: &str = "wamp.2.json";
+
+/// WebSocket subprotocol of the MessagePack serialization.
+pub const WAMP_MSGPACK_SUBPROTOCOL: &str = "wamp.2.msgpack";
+
+/// WebSocket subprotocol of the CBOR serialization.
+pub const WAMP_CBOR_SUBPROTOCOL: &str = "wamp.2.cbor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Encoding
+/// The serializations WAMP messages can be sent with.
+/// ## Examples
+/// ```
+/// use wamp_core::transport::{Encoding, WAMP_JSON_SUBPROTOCOL};
+///
+/// assert_eq!(Encoding::Json.subprotocol(), WAMP_JSON_SUBPROTOCOL);
+/// assert_eq!(Encoding::from_subprotocol("wamp.2.cbor"), Some(Encoding::Cbor));
+/// assert_eq!(Encoding::from_subprotocol("wamp.2.ubjson"), None);
+/// ```
+pub enum Encoding {
+    /// JSON, binary values are strings prefixed with `\0` holding base64.
+    Json,
+    /// MessagePack.
+    MessagePack,
+    /// CBOR.
+    Cbor,
+}
+
+impl Encoding {
+    /// Every encoding, in order of preference.
+    pub const ALL: [Encoding; 3] = [Encoding::Json, Encoding::MessagePack, Encoding::Cbor];
+
+    /// The encodings this crate can serialize messages with.
+    pub const SUPPORTED: [Encoding; 1] = [Encoding::Json];
+
+    /// The WebSocket subprotocol announcing this encoding.
+    pub fn subprotocol(&self) -> &'static str {
+        match self {
+            Encoding::Json => WAMP_JSON_SUBPROTOCOL,
+            Encoding::MessagePack => WAMP_MSGPACK_SUBPROTOCOL,
+            Encoding::Cbor => WAMP_CBOR_SUBPROTOCOL,
+        }
+    }
+
+    /// The encoding of a WebSocket subprotocol, `None` for unknown subprotocols.
+    pub fn from_subprotocol(subprotocol: &str) -> Option<Encoding> {
+        Encoding::ALL
+            .into_iter()
+            .find(|encoding| encoding.subprotocol() == subprotocol)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// # Frame Kind
+/// The WebSocket frames that carry no WAMP message.
+pub enum FrameKind {
+    /// A ping frame.
+    Ping,
+    /// A pong frame.
+    Pong,
+    /// A raw frame, only seen when reading frames without reassembling messages.
+    Frame,
+}
+
+/// # WebSocket upgrade request
+/// Builds the WebSocket upgrade request for `url`, offering the WAMP subprotocols of
+/// `protocols` in the given order of preference.
+///
+/// The request carries every header of a WebSocket handshake, so it can be passed to
+/// `tungstenite::connect` as is.
+/// ## Examples
+/// ```
+/// use wamp_core::transport::{ws_upgrade_request, Encoding};
+///
+/// let request = ws_upgrade_request("ws://localhost:8080/ws", &[Encoding::Json]).unwrap();
+///
+/// assert_eq!(request.headers()["Sec-WebSocket-Protocol"], "wamp.2.json");
+/// assert_eq!(request.headers()["Upgrade"], "websocket");
+/// ```
+pub fn ws_upgrade_request(url: &str, protocols: &[Encoding]) -> Result<Request<()>, Error> {
+    if protocols.is_empty() {
+        return Err(Error::Error(
+            "At least one WAMP subprotocol must be offered.",
+        ));
+    }
+    let mut request = url.into_client_request()?;
+    let offered: Vec<&str> = protocols.iter().map(Encoding::subprotocol).collect();
+    request.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_str(&offered.join(", "))?,
+    );
+    Ok(request)
+}
+
+/// # Select subprotocol
+/// Picks the encoding of a WebSocket connection from the `Sec-WebSocket-Protocol` header offered
+/// by the client, among the encodings this crate supports.
+///
+/// See [`select_subprotocol_with`] to choose among other encodings.
+/// ## Examples
+/// ```
+/// use wamp_core::transport::{select_subprotocol, Encoding};
+///
+/// assert_eq!(select_subprotocol("wamp.2.msgpack, wamp.2.json"), Some(Encoding::Json));
+/// assert_eq!(select_subprotocol("wamp.2.msgpack"), None);
+/// ```
+pub fn select_subprotocol(offered: &str) -> Option<Encoding> {
+    select_subprotocol_with(offered, &Encoding::SUPPORTED)
+}
+
+/// # Select subprotocol with
+/// Picks the first encoding of `preference` offered in a comma separated
+/// `Sec-WebSocket-Protocol` header, ignoring unknown subprotocols.
+///
+/// The answer of the router echoes [`Encoding::subprotocol`] of the selected encoding.
+/// ## Examples
+/// ```
+/// use wamp_core::transport::{select_subprotocol_with, Encoding};
+///
+/// let offered = "wamp.2.json, wamp.2.cbor";
+///
+/// assert_eq!(select_subprotocol_with(offered, &[Encoding::Cbor, Encoding::Json]), Some(Encoding::Cbor));
+/// assert_eq!(select_subprotocol_with(offered, &[Encoding::MessagePack]), None);
+/// ```
+pub fn select_subprotocol_with(offered: &str, preference: &[Encoding]) -> Option<Encoding> {
+    let offered: Vec<Encoding> = offered
+        .split(',')
+        .filter_map(|protocol| Encoding::from_subprotocol(protocol.trim()))
+        .collect();
+    preference
+        .iter()
+        .copied()
+        .find(|encoding| offered.contains(encoding))
+}
+
+/// # Frame Handler
+/// What an event loop does with the frames of a WebSocket connection, see
+/// [`dispatch_ws_frame`].
+pub trait FrameHandler {
+    /// A WAMP message arrived.
+    fn on_wamp(&mut self, message: Messages);
+
+    /// A ping arrived, returns the payload of the pong to answer with, `None` to not answer.
+    ///
+    /// Echoes the ping payload by default, as RFC 6455 requires.
+    fn on_ping(&mut self, payload: Vec<u8>) -> Option<Vec<u8>> {
+        Some(payload)
+    }
+
+    /// A pong arrived, answering a ping sent to check the peer is alive. Ignored by default.
+    fn on_pong(&mut self, _payload: Vec<u8>) {}
+
+    /// The peer closed the connection.
+    fn on_close(&mut self, frame: Option<CloseFrame<'static>>);
+}
+
+/// # Dispatch WebSocket frame
+/// Hand a frame read from a WebSocket to `handler`. Text and binary frames are decoded with the
+/// negotiated `encoding` and passed to [`FrameHandler::on_wamp`].
+///
+/// Returns the frame to send back, the pong answering a ping. Fails when a message does not
+/// decode, and with [`Error::ControlFrame`] for raw frames, which are only seen when reading
+/// frames without reassembling messages.
+/// ## Examples
+/// ```
+/// use wamp_core::transport::{dispatch_ws_frame, Encoding, FrameHandler};
+/// use wamp_core::messages::Messages;
+/// use wamp_core::tungstenite::{protocol::CloseFrame, Message};
+///
+/// #[derive(Default)]
+/// struct Inbox(Vec<Messages>);
+///
+/// impl FrameHandler for Inbox {
+///     fn on_wamp(&mut self, message: Messages) {
+///         self.0.push(message);
+///     }
+///
+///     fn on_close(&mut self, _: Option<CloseFrame<'static>>) {}
+/// }
+///
+/// let mut inbox = Inbox::default();
+/// let frame = Message::Text(r#"[33,3,5512315355]"#.to_string());
+/// assert_eq!(dispatch_ws_frame(frame, &mut inbox, Encoding::Json).unwrap(), None);
+/// assert_eq!(inbox.0.len(), 1);
+///
+/// let reply = dispatch_ws_frame(Message::Ping(b"alive?".to_vec()), &mut inbox, Encoding::Json);
+/// assert_eq!(reply.unwrap(), Some(Message::Pong(b"alive?".to_vec())));
+/// ```
+pub fn dispatch_ws_frame(
+    frame: Message,
+    handler: &mut impl FrameHandler,
+    encoding: Encoding,
+) -> Result<Option<Message>, Error> {
+    match frame {
+        Message::Text(_) | Message::Binary(_) => {
+            handler.on_wamp(Messages::from_ws_message(frame, encoding)?);
+        }
+        Message::Ping(payload) => return Ok(handler.on_ping(payload).map(Message::Pong)),
+        Message::Pong(payload) => handler.on_pong(payload),
+        Message::Close(frame) => handler.on_close(frame),
+        Message::Frame(_) => return Err(Error::ControlFrame(FrameKind::Frame)),
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subprotocol_round_trip() {
+        for encoding in Encoding::ALL {
+            assert_eq!(
+                Encoding::from_subprotocol(encoding.subprotocol()),
+                Some(encoding)
+            );
+        }
+        assert_eq!(Encoding::from_subprotocol("WAMP.2.JSON"), None);
+    }
+
+    #[test]
+    fn upgrade_request() {
+        let request = ws_upgrade_request(
+            "wss://example.com/ws",
+            &[Encoding::Json, Encoding::MessagePack],
+        )
+        .unwrap();
+        assert_eq!(
+            request.headers()[SEC_WEBSOCKET_PROTOCOL],
+            "wamp.2.json, wamp.2.msgpack"
+        );
+        assert_eq!(request.uri(), "wss://example.com/ws");
+        assert!(ws_upgrade_request("wss://example.com/ws", &[]).is_err());
+        assert!(ws_upgrade_request("not a url", &[Encoding::Json]).is_err());
+    }
+
+    #[test]
+    fn subprotocol_selection() {
+        assert_eq!(
+            select_subprotocol("mqtt, wamp.2.json"),
+            Some(Encoding::Json)
+        );
+        assert_eq!(select_subprotocol("mqtt,wamp.2.json"), Some(Encoding::Json));
+        assert_eq!(select_subprotocol("mqtt"), None);
+        assert_eq!(select_subprotocol(""), None);
+        assert_eq!(
+            select_subprotocol_with("wamp.2.json, wamp.2.msgpack", &Encoding::ALL),
+            Some(Encoding::Json)
+        );
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Recorder {
+        messages: Vec<Messages>,
+        pings: Vec<Vec<u8>>,
+        pongs: Vec<Vec<u8>>,
+        closes: Vec<Option<CloseFrame<'static>>>,
+    }
+
+    impl FrameHandler for Recorder {
+        fn on_wamp(&mut self, message: Messages) {
+            self.messages.push(message);
+        }
+
+        fn on_ping(&mut self, payload: Vec<u8>) -> Option<Vec<u8>> {
+            self.pings.push(payload);
+            (self.pings.len() == 1).then(|| b"pong".to_vec())
+        }
+
+        fn on_pong(&mut self, payload: Vec<u8>) {
+            self.pongs.push(payload);
+        }
+
+        fn on_close(&mut self, frame: Option<CloseFrame<'static>>) {
+            self.closes.push(frame);
+        }
+    }
+
+    #[test]
+    fn dispatch_every_frame_kind() {
+        use tungstenite::protocol::{frame::coding::CloseCode, frame::Frame};
+
+        let mut recorder = Recorder::default();
+        let mut dispatch = |frame| dispatch_ws_frame(frame, &mut recorder, Encoding::Json);
+
+        let text = Message::Text(r#"[33,3,5512315355]"#.to_string());
+        let binary = Message::Binary(br#"[6,{},"wamp.close.normal"]"#.to_vec());
+        assert_eq!(dispatch(text).unwrap(), None);
+        assert_eq!(dispatch(binary).unwrap(), None);
+        assert!(dispatch(Message::Binary(b"[33,".to_vec())).is_err());
+
+        let ping = |payload: &[u8]| Message::Ping(payload.to_vec());
+        assert_eq!(
+            dispatch(ping(b"1")).unwrap(),
+            Some(Message::Pong(b"pong".to_vec()))
+        );
+        assert_eq!(dispatch(ping(b"2")).unwrap(), None);
+        assert_eq!(dispatch(Message::Pong(b"3".to_vec())).unwrap(), None);
+
+        let close = CloseFrame {
+            code: CloseCode::Away,
+            reason: "bye".into(),
+        };
+        assert_eq!(dispatch(Message::Close(Some(close.clone()))).unwrap(), None);
+        assert_eq!(dispatch(Message::Close(None)).unwrap(), None);
+        assert!(matches!(
+            dispatch(Message::Frame(Frame::ping(vec![]))),
+            Err(Error::ControlFrame(FrameKind::Frame))
+        ));
+
+        let subscribed = r#"[33,3,5512315355]"#;
+        let goodbye = r#"[6,{},"wamp.close.normal"]"#;
+        assert_eq!(
+            recorder.messages,
+            vec![
+                serde_json::from_str::<Messages>(subscribed).unwrap(),
+                serde_json::from_str::<Messages>(goodbye).unwrap(),
+            ]
+        );
+        assert_eq!(recorder.pings, vec![b"1".to_vec(), b"2".to_vec()]);
+        assert_eq!(recorder.pongs, vec![b"3".to_vec()]);
+        assert_eq!(recorder.closes, vec![Some(close), None]);
+    }
+
+    #[test]
+    fn default_ping_echoes() {
+        struct Quiet;
+
+        impl FrameHandler for Quiet {
+            fn on_wamp(&mut self, _: Messages) {}
+
+            fn on_close(&mut self, _: Option<CloseFrame<'static>>) {}
+        }
+
+        let reply = dispatch_ws_frame(Message::Ping(b"x".to_vec()), &mut Quiet, Encoding::Json);
+        assert_eq!(reply.unwrap(), Some(Message::Pong(b"x".to_vec())));
+        let pong = dispatch_ws_frame(Message::Pong(b"x".to_vec()), &mut Quiet, Encoding::Json);
+        assert_eq!(pong.unwrap(), None);
+    }
+}
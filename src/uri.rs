@@ -1,3 +1,426 @@
+use std::{borrow::Cow, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Error;
+use uri_rules::{Rule, WampRules};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+/// # Match Policy - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-pattern-based-subscriptions)
+/// The `match` option used by pattern based subscriptions and registrations.
+pub enum MatchPolicy {
+    /// The URI must equal the subscribed or registered URI.
+    #[default]
+    Exact,
+    /// The subscribed or registered URI is a prefix of the URI.
+    Prefix,
+    /// The subscribed or registered URI contains empty components matching any single component.
+    Wildcard,
+}
+
+impl MatchPolicy {
+    /// The policy as spelled in the `match` option.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchPolicy::Exact => "exact",
+            MatchPolicy::Prefix => "prefix",
+            MatchPolicy::Wildcard => "wildcard",
+        }
+    }
+}
+
+/// # Is prefix of
+/// Whether the URI `prefix` matches `topic` in a [`MatchPolicy::Prefix`] subscription or
+/// registration.
+///
+/// Matching stops at component boundaries, `prefix` must equal `topic` or be followed by a
+/// `.` in it. A prefix ending with `.` matches the URIs below it, an empty prefix matches every
+/// URI.
+/// ## Examples
+/// ```
+/// use wamp_core::uri::is_prefix_of;
+///
+/// assert!(is_prefix_of("com.myapp", "com.myapp.create"));
+/// assert!(is_prefix_of("com.myapp", "com.myapp"));
+/// assert!(!is_prefix_of("com.myapp", "com.myapplication"));
+/// ```
+pub fn is_prefix_of(prefix: &str, topic: &str) -> bool {
+    match topic.strip_prefix(prefix) {
+        Some(rest) => {
+            prefix.is_empty() || prefix.ends_with('.') || rest.is_empty() || rest.starts_with('.')
+        }
+        None => false,
+    }
+}
+
+/// # Is wildcard match
+/// Whether the URI `pattern` matches `topic` in a [`MatchPolicy::Wildcard`] subscription or
+/// registration.
+///
+/// Both must have the same number of components, empty components of `pattern` match any
+/// component of `topic`, the others must be equal.
+/// ## Examples
+/// ```
+/// use wamp_core::uri::is_wildcard_match;
+///
+/// assert!(is_wildcard_match("com.myapp..create", "com.myapp.user.create"));
+/// assert!(!is_wildcard_match("com.myapp..create", "com.myapp.user.delete"));
+/// assert!(!is_wildcard_match("com.myapp.", "com.myapp.user.create"));
+/// ```
+pub fn is_wildcard_match(pattern: &str, topic: &str) -> bool {
+    let mut pattern = pattern.split('.');
+    let mut topic = topic.split('.');
+    loop {
+        match (pattern.next(), topic.next()) {
+            (None, None) => return true,
+            (Some(p), Some(t)) if p.is_empty() || p == t => {}
+            _ => return false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+/// # URI Mode - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-uris)
+/// How strictly URI components are checked.
+pub enum UriMode {
+    /// Components may hold any character except whitespace, `.` and `#`.
+    #[default]
+    Loose,
+    /// Components may only hold lower case letters, digits and `_`.
+    Strict,
+}
+
+impl UriMode {
+    /// # Is valid
+    /// Whether `uri` follows `rule` in this mode.
+    /// ## Examples
+    /// ```
+    /// use wamp_core::uri::{uri_rules::WampRules, UriMode};
+    ///
+    /// assert!(UriMode::Strict.is_valid(WampRules::URI, "com.myapp.user.new"));
+    /// assert!(!UriMode::Strict.is_valid(WampRules::URI, "com.myapp.User.new"));
+    /// assert!(UriMode::Loose.is_valid(WampRules::URI, "com.myapp.User.new"));
+    /// assert!(UriMode::Strict.is_valid(WampRules::PrefixOrWildcard, "com..user"));
+    /// ```
+    pub fn is_valid(&self, rule: WampRules, uri: &str) -> bool {
+        self.check(rule, uri)
+    }
+
+    pub(crate) fn check(&self, rule: WampRules, uri: &str) -> bool {
+        let rule = rule.rule();
+        match self {
+            UriMode::Loose => rule.loose.is_match(uri),
+            UriMode::Strict => rule.strict.is_match(uri),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # Error URI Builder
+/// Application error URIs below a namespace, such as `com.myapp.error`.
+///
+/// The namespace and every child are validated as WAMP URIs in [`UriMode::Loose`] mode, or
+/// [`UriMode::Strict`] with [`strict`](ErrorUriBuilder::strict). The `wamp` namespace is
+/// reserved for the errors of the protocol and rejected.
+/// ## Examples
+/// ```
+/// use wamp_core::uri::ErrorUriBuilder;
+/// use wamp_core::messages::{ErrorRequest, Invocation};
+/// use serde_json::json;
+///
+/// let errors = ErrorUriBuilder::new("com.myapp.error").unwrap();
+/// assert_eq!(errors.child("object_write_protected").unwrap(), "com.myapp.error.object_write_protected");
+/// assert!(errors.child("not valid").is_err());
+/// assert!(ErrorUriBuilder::new("wamp.error").is_err());
+///
+/// let invocation = Invocation {
+///     request_id: 7,
+///     registration: 1,
+///     details: json!({}),
+///     args: json!(null),
+///     kwargs: json!(null)
+/// };
+/// let error = invocation.error_app(&errors, "quota_exceeded", json!({ "limit": 10 })).unwrap();
+/// assert_eq!(error.error, "com.myapp.error.quota_exceeded");
+/// assert_eq!(error.request_id, 7);
+/// assert_eq!(error.kwargs, json!({ "limit": 10 }));
+/// ```
+pub struct ErrorUriBuilder {
+    namespace: String,
+    mode: UriMode,
+}
+
+impl ErrorUriBuilder {
+    /// Start building the error URIs below `namespace`.
+    ///
+    /// Fails with [`Error::InvalidURI`] when `namespace` is not a valid URI or is in the
+    /// reserved `wamp` namespace.
+    pub fn new<T: ToString>(namespace: T) -> Result<Self, Error> {
+        ErrorUriBuilder {
+            namespace: String::new(),
+            mode: UriMode::Loose,
+        }
+        .checked(namespace.to_string())
+    }
+
+    /// Check the namespace and every child in [`UriMode::Strict`] mode.
+    pub fn strict(mut self) -> Result<Self, Error> {
+        let namespace = std::mem::take(&mut self.namespace);
+        self.mode = UriMode::Strict;
+        self.checked(namespace)
+    }
+
+    /// The namespace of the errors.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// A builder for the namespace `name` below this one, for example `storage` to build
+    /// `com.myapp.error.storage.*` errors.
+    pub fn nested(&self, name: &str) -> Result<Self, Error> {
+        self.clone().checked(self.child(name)?)
+    }
+
+    /// # Child
+    /// The error URI `name` below the namespace, `name` may itself hold several components.
+    ///
+    /// Fails with [`Error::InvalidURI`] when the result is not a valid URI.
+    pub fn child(&self, name: &str) -> Result<String, Error> {
+        let uri = format!("{}.{name}", self.namespace);
+        if name.is_empty() || !self.mode.check(WampRules::URI, &uri) {
+            return Err(Error::InvalidURI {
+                uri,
+                rule: "application error",
+            });
+        }
+        Ok(uri)
+    }
+
+    fn checked(mut self, namespace: String) -> Result<Self, Error> {
+        if is_prefix_of("wamp", &namespace) || !self.mode.check(WampRules::URI, &namespace) {
+            return Err(Error::InvalidURI {
+                uri: namespace,
+                rule: "application error namespace",
+            });
+        }
+        self.namespace = namespace;
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// # Realm - [wamp-proto](https://wamp-proto.org/wamp_latest_ietf.html#name-realms-sessions-and-transpo)
+/// A realm name, validated as a WAMP URI.
+///
+/// Deserializing checks the realm in [`UriMode::Loose`] mode.
+/// ## Examples
+/// ```
+/// use wamp_core::uri::{Realm, UriMode};
+///
+/// let realm = Realm::new("com.myapp.realm1").unwrap();
+/// assert_eq!(realm.to_string(), "com.myapp.realm1");
+///
+/// assert!(Realm::new("").is_err());
+/// assert!(Realm::new("not a realm!!").is_err());
+/// assert!(Realm::new("MyRealm").is_ok());
+/// assert!(Realm::parse("MyRealm", UriMode::Strict).is_err());
+/// ```
+pub struct Realm(String);
+
+impl Realm {
+    /// Validate a realm in [`UriMode::Loose`] mode.
+    pub fn new<T: ToString>(realm: T) -> Result<Realm, Error> {
+        Realm::parse(realm, UriMode::Loose)
+    }
+
+    /// Validate a realm in the given mode.
+    pub fn parse<T: ToString>(realm: T, mode: UriMode) -> Result<Realm, Error> {
+        let realm = realm.to_string();
+        if mode.check(WampRules::URI, &realm) {
+            Ok(Realm(realm))
+        } else {
+            Err(Error::InvalidURI {
+                uri: realm,
+                rule: match mode {
+                    UriMode::Loose => "loose realm",
+                    UriMode::Strict => "strict realm",
+                },
+            })
+        }
+    }
+
+    /// The realm as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Realm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Realm {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Realm {
+    type Err = Error;
+
+    fn from_str(realm: &str) -> Result<Self, Error> {
+        Realm::new(realm)
+    }
+}
+
+impl From<Realm> for String {
+    fn from(realm: Realm) -> Self {
+        realm.0
+    }
+}
+
+impl From<Realm> for Cow<'static, str> {
+    fn from(realm: Realm) -> Self {
+        Cow::Owned(realm.0)
+    }
+}
+
+impl Serialize for Realm {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Realm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let realm = String::deserialize(deserializer)?;
+        Realm::new(&realm)
+            .map_err(|_| serde::de::Error::custom(format!("Invalid realm {realm:?}.")))
+    }
+}
+
+/// # Constants
+/// The canonical spelling of the URIs predefined by the protocol, as used by
+/// [`WampErrorUri`](crate::error::WampErrorUri) and [`CloseUri`](crate::error::CloseUri).
+/// ## Examples
+/// ```
+/// use wamp_core::uri::constants::{CLOSE_SYSTEM_SHUTDOWN, WAMP_PROTOCOL_VERSION};
+/// use wamp_core::messages::Goodbye;
+/// use serde_json::json;
+///
+/// let goodbye = Goodbye { details: json!({}), reason: CLOSE_SYSTEM_SHUTDOWN.to_string() };
+///
+/// assert_eq!(goodbye.reason, "wamp.close.system_shutdown");
+/// assert_eq!(WAMP_PROTOCOL_VERSION, 2);
+/// ```
+pub mod constants {
+    /// The version of the WAMP protocol, the `2` of the `wamp.2.json` subprotocol.
+    pub const WAMP_PROTOCOL_VERSION: u64 = 2;
+
+    /// The URI of `WampErrorUri::NotAuthorized`.
+    pub const ERROR_NOT_AUTHORIZED: &str = "wamp.error.not_authorized";
+
+    /// The URI of `WampErrorUri::ProcedureAlreadyExists`.
+    pub const ERROR_PROCEDURE_ALREADY_EXISTS: &str = "wamp.error.procedure_already_exists";
+
+    /// The URI of `WampErrorUri::NoSuchRealm`.
+    pub const ERROR_NO_SUCH_REALM: &str = "wamp.error.no_such_realm";
+
+    /// The URI of `WampErrorUri::ProtocolViolation`.
+    pub const ERROR_PROTOCOL_VIOLATION: &str = "wamp.error.protocol_violation";
+
+    /// The URI of `WampErrorUri::NoSuchSubscription`.
+    pub const ERROR_NO_SUCH_SUBSCRIPTION: &str = "wamp.error.no_such_subscription";
+
+    /// The URI of `WampErrorUri::NoSuchRegistration`.
+    pub const ERROR_NO_SUCH_REGISTRATION: &str = "wamp.error.no_such_registration";
+
+    /// The URI of `WampErrorUri::InvalidUri`.
+    pub const ERROR_INVALID_URI: &str = "wamp.error.invalid_uri";
+
+    /// The URI of `WampErrorUri::NoSuchProcedure`.
+    pub const ERROR_NO_SUCH_PROCEDURE: &str = "wamp.error.no_such_procedure";
+
+    /// The URI of `WampErrorUri::InvalidArgument`.
+    pub const ERROR_INVALID_ARGUMENT: &str = "wamp.error.invalid_argument";
+
+    /// The URI of `WampErrorUri::Canceled`.
+    pub const ERROR_CANCELED: &str = "wamp.error.canceled";
+
+    /// The URI of `WampErrorUri::PayloadSizeExceeded`.
+    pub const ERROR_PAYLOAD_SIZE_EXCEEDED: &str = "wamp.error.payload_size_exceeded";
+
+    /// The URI of `WampErrorUri::FeatureNotSupported`.
+    pub const ERROR_FEATURE_NOT_SUPPORTED: &str = "wamp.error.feature_not_supported";
+
+    /// The URI of `WampErrorUri::Timeout`.
+    pub const ERROR_TIMEOUT: &str = "wamp.error.timeout";
+
+    /// The URI of `WampErrorUri::Unavailable`.
+    pub const ERROR_UNAVAILABLE: &str = "wamp.error.unavailable";
+
+    /// The URI of `WampErrorUri::NoAvailableCallee`.
+    pub const ERROR_NO_AVAILABLE_CALLEE: &str = "wamp.error.no_available_callee";
+
+    /// The URI of `WampErrorUri::DiscloseMeNotAllowed`.
+    pub const ERROR_DISCLOSE_ME_NOT_ALLOWED: &str = "wamp.error.disclose_me.not_allowed";
+
+    /// The URI of `WampErrorUri::OptionDisallowedDiscloseMe`.
+    pub const ERROR_OPTION_DISALLOWED_DISCLOSE_ME: &str =
+        "wamp.error.option_disallowed.disclose_me";
+
+    /// The URI of `WampErrorUri::NoMatchingAuthMethod`.
+    pub const ERROR_NO_MATCHING_AUTH_METHOD: &str = "wamp.error.no_matching_auth_method";
+
+    /// The URI of `WampErrorUri::NoSuchRole`.
+    pub const ERROR_NO_SUCH_ROLE: &str = "wamp.error.no_such_role";
+
+    /// The URI of `WampErrorUri::NoSuchPrincipal`.
+    pub const ERROR_NO_SUCH_PRINCIPAL: &str = "wamp.error.no_such_principal";
+
+    /// The URI of `WampErrorUri::AuthenticationDenied`.
+    pub const ERROR_AUTHENTICATION_DENIED: &str = "wamp.error.authentication_denied";
+
+    /// The URI of `WampErrorUri::AuthenticationFailed`.
+    pub const ERROR_AUTHENTICATION_FAILED: &str = "wamp.error.authentication_failed";
+
+    /// The URI of `WampErrorUri::AuthenticationRequired`.
+    pub const ERROR_AUTHENTICATION_REQUIRED: &str = "wamp.error.authentication_required";
+
+    /// The URI of `WampErrorUri::AuthorizationDenied`.
+    pub const ERROR_AUTHORIZATION_DENIED: &str = "wamp.error.authorization_denied";
+
+    /// The URI of `WampErrorUri::AuthorizationFailed`.
+    pub const ERROR_AUTHORIZATION_FAILED: &str = "wamp.error.authorization_failed";
+
+    /// The URI of `WampErrorUri::AuthorizationRequired`.
+    pub const ERROR_AUTHORIZATION_REQUIRED: &str = "wamp.error.authorization_required";
+
+    /// The URI of `WampErrorUri::NetworkFailure`.
+    pub const ERROR_NETWORK_FAILURE: &str = "wamp.error.network_failure";
+
+    /// The URI of `WampErrorUri::OptionNotAllowed`.
+    pub const ERROR_OPTION_NOT_ALLOWED: &str = "wamp.error.option_not_allowed";
+
+    /// The URI of `CloseUri::Normal`.
+    pub const CLOSE_NORMAL: &str = "wamp.close.normal";
+
+    /// The URI of `CloseUri::SystemShutdown`.
+    pub const CLOSE_SYSTEM_SHUTDOWN: &str = "wamp.close.system_shutdown";
+
+    /// The URI of `CloseUri::CloseRealm`.
+    pub const CLOSE_CLOSE_REALM: &str = "wamp.close.close_realm";
+
+    /// The URI of `CloseUri::GoodbyeAndOut`.
+    pub const CLOSE_GOODBYE_AND_OUT: &str = "wamp.close.goodbye_and_out";
+
+    /// The URI of `CloseUri::Killed`.
+    pub const CLOSE_KILLED: &str = "wamp.close.killed";
+}
 
 pub mod uri_rules {
     pub use regex::Regex;
@@ -89,3 +512,95 @@ pub mod uri_rules {
 //        //let values = rule.capture
 //    }
 //}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, to_string};
+
+    use super::*;
+
+    #[test]
+    fn prefix_boundaries() {
+        assert!(is_prefix_of("com.myapp", "com.myapp"));
+        assert!(is_prefix_of("com.myapp", "com.myapp.create"));
+        assert!(is_prefix_of("com.myapp", "com.myapp.user.create"));
+        assert!(is_prefix_of("com.myapp.", "com.myapp.create"));
+        assert!(is_prefix_of("", "com.myapp"));
+        assert!(!is_prefix_of("com.myapp", "com.myapplication"));
+        assert!(!is_prefix_of("com.myapp", "com.my"));
+        assert!(!is_prefix_of("com.myapp.", "com.myapp"));
+        assert!(!is_prefix_of("com.myapp.create", "com.myapp"));
+        assert!(!is_prefix_of("myapp", "com.myapp"));
+    }
+
+    #[test]
+    fn wildcard_components() {
+        assert!(is_wildcard_match("com.myapp.create", "com.myapp.create"));
+        assert!(is_wildcard_match(".myapp.", "com.myapp.create"));
+        assert!(is_wildcard_match("..", "a.b.c"));
+        assert!(!is_wildcard_match("..", "a.b"));
+        assert!(!is_wildcard_match("com.myapp", "com.myapp.create"));
+        assert!(!is_wildcard_match("com.myapp..", "com.myapp.create"));
+    }
+
+    #[test]
+    fn realms() {
+        match Realm::new("") {
+            Err(Error::InvalidURI { uri, rule }) => {
+                assert_eq!(uri, "");
+                assert_eq!(rule, "loose realm");
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+        assert!(Realm::new("realm 1").is_err());
+        assert!(Realm::new("com..myapp").is_err());
+        assert!(Realm::new("com.MyApp").is_ok());
+        assert!(matches!(
+            Realm::parse("com.MyApp", UriMode::Strict),
+            Err(Error::InvalidURI {
+                rule: "strict realm",
+                ..
+            })
+        ));
+        assert!(Realm::parse("com.myapp_1", UriMode::Strict).is_ok());
+
+        let realm: Realm = from_str(r#""realm1""#).unwrap();
+        assert_eq!(to_string(&realm).unwrap(), r#""realm1""#);
+        assert!(from_str::<Realm>(r#""a b""#).is_err());
+    }
+
+    #[test]
+    fn error_uri_builder() {
+        let errors = ErrorUriBuilder::new("com.myapp.error").unwrap();
+        assert_eq!(
+            errors.child("object_write_protected").unwrap(),
+            "com.myapp.error.object_write_protected"
+        );
+
+        // The reserved namespace, but not URIs merely starting with the same letters.
+        for reserved in ["wamp", "wamp.error", "wamp.close.app"] {
+            assert!(ErrorUriBuilder::new(reserved).is_err(), "{reserved}");
+        }
+        assert!(ErrorUriBuilder::new("wampy.error").is_ok());
+
+        // Invalid characters and empty components.
+        for name in ["", "quota exceeded", "quota#1", "a..b", ".quota", "quota."] {
+            assert!(errors.child(name).is_err(), "{name:?}");
+        }
+        assert!(ErrorUriBuilder::new("com.myapp error").is_err());
+        assert!(errors.child("Quota").is_ok());
+        let strict = errors.clone().strict().unwrap();
+        assert!(strict.child("Quota").is_err());
+        assert!(ErrorUriBuilder::new("com.MyApp").unwrap().strict().is_err());
+
+        // Nesting.
+        let storage = errors.nested("storage").unwrap();
+        assert_eq!(storage.namespace(), "com.myapp.error.storage");
+        assert_eq!(
+            storage.child("disk.full").unwrap(),
+            "com.myapp.error.storage.disk.full"
+        );
+        assert!(strict.nested("storage").unwrap().child("Full").is_err());
+        assert!(errors.nested("bad name").is_err());
+    }
+}